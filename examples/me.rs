@@ -33,7 +33,7 @@ fn main() {
         .apply()
         .unwrap();
 
-    let mut client = screeps_api::SyncApi::new()
+    let client = screeps_api::SyncApi::new()
         .unwrap()
         .with_url(&opt_env(
             "SCREEPS_API_URL",
@@ -55,7 +55,7 @@ fn main() {
 fn perform_registration() -> Result<(), Box<dyn std::error::Error>> {
     let http_url = server_url();
 
-    let mut client = screeps_api::SyncApi::new()?.with_url(&http_url)?;
+    let client = screeps_api::SyncApi::new()?.with_url(&http_url)?;
 
     println!("New user registration! Connecting to {}", http_url);
 
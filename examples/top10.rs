@@ -39,7 +39,9 @@ fn print_ranks(result: &LeaderboardPage) {
             Some(&(_, ref details)) => {
                 println!(
                     "\t[{}] {} (GCL {})",
-                    ranked_user.rank, details.username, details.gcl_points
+                    ranked_user.rank,
+                    details.username,
+                    details.gcl().unwrap_or(1)
                 );
             }
             None => {
@@ -56,7 +58,7 @@ fn main() {
         .apply()
         .unwrap();
 
-    let mut client = screeps_api::SyncApi::new()
+    let client = screeps_api::SyncApi::new()
         .unwrap()
         .with_url(&opt_env(
             "SCREEPS_API_URL",
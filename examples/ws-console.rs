@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::Arc};
 
 use futures01::{future, stream, Future, Sink, Stream};
 use log::{debug, info, warn};
@@ -85,13 +85,13 @@ fn main() {
 
     let http_url = server_url();
 
-    let mut client = screeps_api::SyncApi::new()
+    let client = screeps_api::SyncApi::new()
         .unwrap()
         .with_url(&http_url)
         .unwrap()
         .with_token(env("SCREEPS_API_TOKEN"));
 
-    let tokens = client.token_storage().clone();
+    let tokens = client.token_storage();
 
     let my_info = client.my_info().expect("my_info call failed");
 
@@ -144,12 +144,12 @@ fn main() {
 }
 
 struct Handler {
-    tokens: TokenStorage,
+    tokens: Arc<dyn TokenStorage>,
     info: screeps_api::MyInfo,
 }
 
 impl Handler {
-    fn new(tokens: TokenStorage, info: screeps_api::MyInfo) -> Self {
+    fn new(tokens: Arc<dyn TokenStorage>, info: screeps_api::MyInfo) -> Self {
         Handler { tokens, info }
     }
 
@@ -225,6 +225,10 @@ impl Handler {
             ScreepsMessage::ServerPackage { package } => {
                 debug!("server package: {}", package);
             }
+            ScreepsMessage::Error { channel, error } => match channel {
+                Some(channel) => warn!("server error on {}: {}", channel, error),
+                None => warn!("server error: {}", error),
+            },
             ScreepsMessage::Other(other) => {
                 warn!("ScreepsMessage::Other: {}", other);
             }
@@ -1,8 +1,9 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::Arc};
 
 use futures01::{future, stream, Future, Sink, Stream};
 use log::{debug, info, warn};
 use screeps_api::{
+    transport::ClientConfig,
     websocket::{commands, Channel, ChannelUpdate, ScreepsMessage, SockjsMessage},
     RoomName, TokenStorage,
 };
@@ -52,6 +53,7 @@ struct Config {
     rooms: Vec<RoomName>,
     map_view: Vec<RoomName>,
     url: Cow<'static, str>,
+    client: ClientConfig,
 }
 
 impl Config {
@@ -85,6 +87,16 @@ impl Config {
                 .value_of("url")
                 .map(|v| v.to_owned().into())
                 .unwrap_or_else(|| screeps_api::DEFAULT_OFFICIAL_API_URL.into()),
+            client: {
+                let client = ClientConfig::new()
+                    .with_danger_accept_invalid_certs(args.is_present("accept-invalid-certs"));
+                match args.value_of("root-cert") {
+                    Some(path) => client.with_root_certificate(
+                        std::fs::read(path).expect("failed to read root certificate"),
+                    ),
+                    None => client,
+                }
+            },
         })
     }
 
@@ -212,6 +224,18 @@ fn setup() -> Config {
                 .help("Server url to connect to")
                 .takes_value(true),
         )
+        .arg(
+            clap::Arg::with_name("root-cert")
+                .long("root-cert")
+                .value_name("PEM_FILE")
+                .help("Trusts a certificate, such as a private server's self-signed one")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("accept-invalid-certs")
+                .long("accept-invalid-certs")
+                .help("Accepts any certificate from the server (insecure)"),
+        )
         .get_matches();
 
     setup_logging(cmd_arguments.occurrences_of("verbose"));
@@ -234,13 +258,13 @@ fn main() {
 
     debug!("creating client");
 
-    let mut client = screeps_api::SyncApi::new()
+    let client = screeps_api::SyncApi::new_with_connector(config.client.https_connector().unwrap())
         .unwrap()
         .with_url(&config.url)
         .unwrap()
         .with_token(env("SCREEPS_API_TOKEN"));
 
-    let tokens = client.token_storage().clone();
+    let tokens = client.token_storage();
 
     debug!("retrieving my_info");
 
@@ -254,8 +278,10 @@ fn main() {
     let ws_url = screeps_api::websocket::transform_url(&config.url)
         .expect("expected server api url to parse into websocket url.");
 
-    let connection =
-        websocket::ClientBuilder::from_url(&ws_url.as_str().parse().unwrap()).async_connect(None);
+    // the socket trusts the same certificates as the HTTP client.
+    let tls = config.client.tls_connector().unwrap();
+    let connection = websocket::ClientBuilder::from_url(&ws_url.as_str().parse().unwrap())
+        .async_connect(Some(tls));
 
     tokio01::runtime::current_thread::run(
         connection
@@ -298,13 +324,13 @@ fn main() {
 }
 
 struct Handler {
-    tokens: TokenStorage,
+    tokens: Arc<dyn TokenStorage>,
     info: screeps_api::MyInfo,
     config: Config,
 }
 
 impl Handler {
-    fn new(tokens: TokenStorage, info: screeps_api::MyInfo, config: Config) -> Self {
+    fn new(tokens: Arc<dyn TokenStorage>, info: screeps_api::MyInfo, config: Config) -> Self {
         Handler {
             tokens,
             info,
@@ -395,6 +421,10 @@ impl Handler {
             ScreepsMessage::ServerPackage { package } => {
                 info!("server package: {}", package);
             }
+            ScreepsMessage::Error { channel, error } => match channel {
+                Some(channel) => warn!("server error on {}: {}", channel, error),
+                None => warn!("server error: {}", error),
+            },
             ScreepsMessage::Other(other) => {
                 warn!("ScreepsMessage::Other: {}", other);
             }
@@ -0,0 +1,200 @@
+//! Scheduling the delays between attempts at something which keeps failing.
+use std::{fmt, sync::Arc, time::Duration};
+
+use rand::Rng;
+
+/// Decides how long to wait before each attempt at something which keeps failing, and when to give up.
+///
+/// This is used both to retry requests, through [`Api::set_retry_backoff`], and to reconnect sockets, through
+/// `SocketConfig::with_reconnect`. [`Exponential`] and [`DecorrelatedJitter`] are provided, and applications
+/// with their own scheduling needs can implement it:
+///
+/// ```
+/// # #[cfg(feature = "sockets")]
+/// # fn main() {
+/// use std::time::Duration;
+///
+/// use screeps_api::{backoff::BackoffPolicy, websocket::SocketConfig};
+///
+/// /// Tries again every 5 seconds, forever.
+/// #[derive(Debug)]
+/// struct Steady;
+///
+/// impl BackoffPolicy for Steady {
+///     fn delay(&self, _attempt: u32, _previous: Duration) -> Option<Duration> {
+///         Some(Duration::from_secs(5))
+///     }
+/// }
+///
+/// let config = SocketConfig::new().with_reconnect(Steady);
+/// # }
+/// # #[cfg(not(feature = "sockets"))] fn main() {}
+/// ```
+///
+/// [`Api::set_retry_backoff`]: ../struct.Api.html#method.set_retry_backoff
+/// [`Exponential`]: struct.Exponential.html
+/// [`DecorrelatedJitter`]: struct.DecorrelatedJitter.html
+pub trait BackoffPolicy: fmt::Debug + Send + Sync + 'static {
+    /// Gets the delay before the given attempt (starting at 0), or `None` if no further attempts should be made.
+    ///
+    /// `previous` is the delay this returned before the previous attempt, or zero before the first attempt.
+    fn delay(&self, attempt: u32, previous: Duration) -> Option<Duration>;
+}
+
+impl<B: BackoffPolicy + ?Sized> BackoffPolicy for Arc<B> {
+    fn delay(&self, attempt: u32, previous: Duration) -> Option<Duration> {
+        (**self).delay(attempt, previous)
+    }
+}
+
+/// Delays which double with each attempt, up to a maximum.
+///
+/// With jitter enabled, each delay is randomly shortened by up to half, so many clients failing at the same time do
+/// not all try again at the same time.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use screeps_api::backoff::{BackoffPolicy, Exponential};
+///
+/// let backoff = Exponential::new(Duration::from_secs(1), Duration::from_secs(60)).with_max_attempts(Some(5));
+///
+/// assert_eq!(backoff.delay(2, Duration::from_secs(2)), Some(Duration::from_secs(4)));
+/// assert_eq!(backoff.delay(5, Duration::from_secs(16)), None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Exponential {
+    initial: Duration,
+    max: Duration,
+    max_attempts: Option<u32>,
+    jitter: bool,
+}
+
+impl Exponential {
+    /// Creates a policy starting at `initial` and doubling up to `max`, without jitter or a limit on attempts.
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Exponential {
+            initial,
+            max,
+            max_attempts: None,
+            jitter: false,
+        }
+    }
+
+    /// Sets the maximum number of attempts, or `None` for unlimited, and returns the policy.
+    pub fn with_max_attempts(mut self, max_attempts: Option<u32>) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets whether delays are randomly shortened, and returns the policy.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+}
+
+impl BackoffPolicy for Exponential {
+    fn delay(&self, attempt: u32, _previous: Duration) -> Option<Duration> {
+        if self.max_attempts.is_some_and(|max| attempt >= max) {
+            return None;
+        }
+
+        let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        let delay = self
+            .initial
+            .checked_mul(factor)
+            .map_or(self.max, |delay| delay.min(self.max));
+
+        if self.jitter {
+            Some(delay.mul_f64(rand::thread_rng().gen_range(0.5, 1.0)))
+        } else {
+            Some(delay)
+        }
+    }
+}
+
+/// Random delays between `base` and three times the previous delay, up to a maximum.
+///
+/// This is the "decorrelated jitter" schedule: delays still grow quickly while failures continue, but clients
+/// spread out their attempts more than with [`Exponential`] backoff.
+///
+/// [`Exponential`]: struct.Exponential.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecorrelatedJitter {
+    base: Duration,
+    max: Duration,
+    max_attempts: Option<u32>,
+}
+
+impl DecorrelatedJitter {
+    /// Creates a policy with delays of at least `base` and at most `max`, without a limit on attempts.
+    pub fn new(base: Duration, max: Duration) -> Self {
+        DecorrelatedJitter {
+            base,
+            max,
+            max_attempts: None,
+        }
+    }
+
+    /// Sets the maximum number of attempts, or `None` for unlimited, and returns the policy.
+    pub fn with_max_attempts(mut self, max_attempts: Option<u32>) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+}
+
+impl BackoffPolicy for DecorrelatedJitter {
+    fn delay(&self, attempt: u32, previous: Duration) -> Option<Duration> {
+        if self.max_attempts.is_some_and(|max| attempt >= max) {
+            return None;
+        }
+
+        let (low, high) = (self.base.as_secs_f64(), (previous * 3).as_secs_f64());
+        let delay = if high > low {
+            Duration::from_secs_f64(rand::thread_rng().gen_range(low, high))
+        } else {
+            self.base
+        };
+
+        Some(delay.min(self.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{BackoffPolicy, DecorrelatedJitter, Exponential};
+
+    #[test]
+    fn provided_policies() {
+        let exponential = Exponential::new(Duration::from_secs(1), Duration::from_secs(10))
+            .with_max_attempts(Some(5));
+        let delays = (0..6)
+            .map(|attempt| exponential.delay(attempt, Duration::from_secs(0)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            delays,
+            vec![
+                Some(Duration::from_secs(1)),
+                Some(Duration::from_secs(2)),
+                Some(Duration::from_secs(4)),
+                Some(Duration::from_secs(8)),
+                Some(Duration::from_secs(10)),
+                None,
+            ]
+        );
+
+        let decorrelated = DecorrelatedJitter::new(Duration::from_secs(1), Duration::from_secs(20))
+            .with_max_attempts(Some(10));
+        let mut previous = Duration::from_secs(0);
+        for attempt in 0..10 {
+            let delay = decorrelated.delay(attempt, previous).unwrap();
+            assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_secs(20));
+            assert!(delay <= (previous * 3).max(Duration::from_secs(1)));
+            previous = delay;
+        }
+        assert_eq!(decorrelated.delay(10, previous), None);
+    }
+}
@@ -0,0 +1,413 @@
+//! Configuring a client in one place.
+use std::{sync::Arc, time::Duration};
+
+use http::header::{HeaderValue, InvalidHeaderValue};
+use url::Url;
+
+#[cfg(any(feature = "reqwest", feature = "sync"))]
+use crate::error::Error;
+#[cfg(feature = "sync")]
+use crate::transport::ProxyConnector;
+use crate::{
+    backoff::BackoffPolicy,
+    rate_limit::{Priority, RateLimiter},
+    request::{AuthMode, EndpointPaths, ParseOptions, ParseWarning},
+    retry::RetryPolicy,
+    server::ServerProfile,
+    tokens::{TokenStorage, TokenStore},
+    transport::{ClientConfig, HttpTransport, Proxy},
+    Api, Credentials, Token,
+};
+
+/// The token a built client starts with, or where it keeps its token.
+#[derive(Debug)]
+enum Auth {
+    Token(Token),
+    Storage(Arc<dyn TokenStorage>),
+    Store(TokenStore, String),
+}
+
+/// Settings for building an [`Api`], or a blocking `SyncApi`, from one configuration.
+///
+/// This covers the server's URL, authentication, rate limiting, retries and timeouts, and the settings of the HTTP
+/// client itself, such as a proxy. The client can then be built with any transport using [`ApiBuilder::build`], with
+/// the transports built from the settings in [`ClientConfig`], or as a `SyncApi` with the `sync` feature.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use screeps_api::{ApiBuilder, RateLimiter, RetryPolicy};
+///
+/// let builder = ApiBuilder::new()
+///     .with_url("https://screeps.com/season/api/")?
+///     .with_token("auth token")
+///     .with_rate_limiter(RateLimiter::official())
+///     .with_retry_policy(RetryPolicy::default())
+///     .with_timeout(Duration::from_secs(30));
+///
+/// let api = builder.build(hyper::Client::new());
+/// # Ok::<(), url::ParseError>(())
+/// ```
+///
+/// [`Api`]: struct.Api.html
+/// [`ApiBuilder::build`]: struct.ApiBuilder.html#method.build
+/// [`ClientConfig`]: transport/struct.ClientConfig.html
+#[derive(Debug, Default)]
+pub struct ApiBuilder {
+    url: Option<Url>,
+    server_profile: Option<ServerProfile>,
+    auth: Option<Auth>,
+    auth_mode: AuthMode,
+    credentials: Option<Credentials>,
+    rate_limiter: Option<RateLimiter>,
+    priority: Priority,
+    retry_policy: Option<RetryPolicy>,
+    retry_backoff: Option<Arc<dyn BackoffPolicy>>,
+    timeout: Option<Duration>,
+    parse_options: ParseOptions,
+    endpoint_paths: EndpointPaths,
+    user_agent: Option<HeaderValue>,
+    client_id: Option<HeaderValue>,
+    client_config: ClientConfig,
+    #[cfg(feature = "sync")]
+    runtime: Option<tokio::runtime::Runtime>,
+}
+
+impl ApiBuilder {
+    /// Creates settings for a client of the official server, without a token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the API url of the server, and returns the settings. See `Api::set_url`.
+    pub fn with_url<U: AsRef<str>>(mut self, url: U) -> Result<Self, url::ParseError> {
+        self.url = Some(Url::parse(url.as_ref())?);
+        Ok(self)
+    }
+
+    /// Sets the kind of server, and returns the settings. Unless a URL is set as well, the server's usual URL is used.
+    /// See `Api::new_for_server`.
+    pub fn with_server_profile(mut self, profile: ServerProfile) -> Self {
+        self.server_profile = Some(profile);
+        self
+    }
+
+    /// Sets the auth token to use, and returns the settings. See `Api::set_token`.
+    pub fn with_token<T: Into<Token>>(mut self, token: T) -> Self {
+        self.auth = Some(Auth::Token(token.into()));
+        self
+    }
+
+    /// Sets where the auth token is kept, and returns the settings. See `Api::set_token_storage`.
+    pub fn with_token_storage<S: TokenStorage>(mut self, storage: S) -> Self {
+        self.auth = Some(Auth::Storage(Arc::new(storage)));
+        self
+    }
+
+    /// Sets the shared token store and account to take the auth token from, and returns the settings. See
+    /// `Api::set_token_store`.
+    pub fn with_token_store<A: Into<String>>(mut self, store: TokenStore, account: A) -> Self {
+        self.auth = Some(Auth::Store(store, account.into()));
+        self
+    }
+
+    /// Sets how the auth token is sent, and returns the settings. See `Api::set_auth_mode`.
+    pub fn with_auth_mode(mut self, mode: AuthMode) -> Self {
+        self.auth_mode = mode;
+        self
+    }
+
+    /// Sets the username and password to log in with when the token expires, and returns the settings. See
+    /// `Api::set_credentials`.
+    pub fn with_credentials<U, V>(mut self, username: U, password: V) -> Self
+    where
+        U: Into<String>,
+        V: Into<String>,
+    {
+        self.credentials = Some(Credentials {
+            username: username.into(),
+            password: password.into(),
+        });
+        self
+    }
+
+    /// Sets the rate limiter requests wait on, and returns the settings. See `Api::set_rate_limiter`.
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Sets how urgently requests are sent when they have to wait for the rate limiter, and returns the settings. See
+    /// `Api::set_priority`.
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets how failed requests are retried, and returns the settings. See `Api::set_retry_policy`.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Sets the schedule retry delays are taken from instead of the retry policy's, and returns the settings. See
+    /// `Api::set_retry_backoff`.
+    pub fn with_retry_backoff<B: BackoffPolicy>(mut self, backoff: B) -> Self {
+        self.retry_backoff = Some(Arc::new(backoff));
+        self
+    }
+
+    /// Sets how long to wait for the server to respond to each request, and returns the settings. See
+    /// `Api::set_timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets whether responses with fields the result type does not parse fail, and returns the settings. See
+    /// `Api::set_strict_parsing`.
+    pub fn with_strict_parsing(mut self, strict: bool) -> Self {
+        self.parse_options = self.parse_options.with_strict(strict);
+        self
+    }
+
+    /// Sets whether successful responses are parsed straight into the result type, and returns the settings. See
+    /// `Api::set_fast_parsing`.
+    pub fn with_fast_parsing(mut self, fast: bool) -> Self {
+        self.parse_options = self.parse_options.with_fast(fast);
+        self
+    }
+
+    /// Sets whether the JSON data of the latest response from each endpoint is kept, and returns the settings. See
+    /// `Api::set_raw_json`.
+    pub fn with_raw_json(mut self, retain: bool) -> Self {
+        self.parse_options = self.parse_options.with_raw_json(retain);
+        self
+    }
+
+    /// Sets the size in bytes from which response bodies are parsed on tokio's blocking thread pool, and returns the
+    /// settings. See `Api::set_blocking_parse_threshold`.
+    pub fn with_blocking_parse_threshold(mut self, threshold: usize) -> Self {
+        self.parse_options = self.parse_options.with_blocking_threshold(Some(threshold));
+        self
+    }
+
+    /// Sets the function told about responses with fields the result type does not parse, and returns the settings.
+    /// See `Api::set_parse_warning_hook`.
+    pub fn with_parse_warning_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&ParseWarning) + Send + Sync + 'static,
+    {
+        self.parse_options = self.parse_options.with_warning_hook(hook);
+        self
+    }
+
+    /// Sets the paths to send requests for some endpoints to instead of their usual paths, and returns the settings.
+    /// See `Api::set_endpoint_path`.
+    pub fn with_endpoint_paths(mut self, paths: EndpointPaths) -> Self {
+        self.endpoint_paths = paths;
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with every request, and returns the settings. See `Api::set_user_agent`.
+    ///
+    /// Fails if the user agent is not a valid header value.
+    pub fn with_user_agent<U: AsRef<str>>(
+        mut self,
+        user_agent: U,
+    ) -> Result<Self, InvalidHeaderValue> {
+        self.user_agent = Some(HeaderValue::from_str(user_agent.as_ref())?);
+        Ok(self)
+    }
+
+    /// Sets an identifier for the application, sent in the `X-Client` header with every request, and returns the
+    /// settings. See `Api::set_client_id`.
+    ///
+    /// Fails if the identifier is not a valid header value.
+    pub fn with_client_id<U: AsRef<str>>(
+        mut self,
+        client_id: U,
+    ) -> Result<Self, InvalidHeaderValue> {
+        self.client_id = Some(HeaderValue::from_str(client_id.as_ref())?);
+        Ok(self)
+    }
+
+    /// Sets the settings for the HTTP client, replacing any previous ones, and returns the settings.
+    ///
+    /// These are only used when the client is built by [`ApiBuilder::build_reqwest`], [`ApiBuilder::build_hyper`] or
+    /// [`ApiBuilder::build_sync`], and not with [`ApiBuilder::build`].
+    ///
+    /// [`ApiBuilder::build_reqwest`]: struct.ApiBuilder.html#method.build_reqwest
+    /// [`ApiBuilder::build_hyper`]: struct.ApiBuilder.html#method.build_hyper
+    /// [`ApiBuilder::build_sync`]: struct.ApiBuilder.html#method.build_sync
+    /// [`ApiBuilder::build`]: struct.ApiBuilder.html#method.build
+    pub fn with_client_config(mut self, config: ClientConfig) -> Self {
+        self.client_config = config;
+        self
+    }
+
+    /// Sets the proxy the HTTP client sends requests through, and returns the settings. See
+    /// `ClientConfig::with_proxy`.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.client_config = self.client_config.with_proxy(proxy);
+        self
+    }
+
+    /// Sets how long to wait for a connection to the server to be established, and returns the settings. See
+    /// `ClientConfig::with_connect_timeout`.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.client_config = self.client_config.with_connect_timeout(timeout);
+        self
+    }
+
+    /// The settings for the HTTP client.
+    pub fn client_config(&self) -> &ClientConfig {
+        &self.client_config
+    }
+
+    /// Sets the tokio runtime a `SyncApi` runs requests on, and returns the settings. By default, a new runtime is
+    /// started.
+    #[cfg(feature = "sync")]
+    pub fn with_runtime(mut self, runtime: tokio::runtime::Runtime) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /// Builds a client sending requests with the given HTTP client.
+    pub fn build<T: HttpTransport>(self, transport: T) -> Api<T> {
+        let mut api = match self.server_profile {
+            Some(profile) => Api::new_for_server(transport, profile),
+            None => Api::new(transport),
+        };
+        if let Some(url) = self.url {
+            api.url = url;
+        }
+
+        match self.auth {
+            Some(Auth::Token(token)) => api.set_token(token),
+            Some(Auth::Storage(storage)) => api.set_token_storage(storage),
+            Some(Auth::Store(store, account)) => api.set_token_store(store, account),
+            None => (),
+        }
+        api.set_auth_mode(self.auth_mode);
+        if let Some(credentials) = self.credentials {
+            api.set_credentials(credentials.username, credentials.password);
+        }
+
+        api.set_rate_limiter(self.rate_limiter);
+        api.set_priority(self.priority);
+        api.set_retry_policy(self.retry_policy);
+        api.retry_backoff = self.retry_backoff;
+        api.set_timeout(self.timeout);
+        api.parse_options = self.parse_options;
+        api.set_endpoint_paths(self.endpoint_paths);
+        if let Some(user_agent) = self.user_agent {
+            api.user_agent = user_agent;
+        }
+        api.client_id = self.client_id;
+
+        api
+    }
+
+    /// Builds a client sending requests with a `reqwest::Client` built from the HTTP client settings.
+    #[cfg(feature = "reqwest")]
+    pub fn build_reqwest(self) -> Result<Api<reqwest::Client>, Error> {
+        let client = self.client_config.build_reqwest()?;
+        Ok(self.build(client))
+    }
+
+    /// Builds a client sending requests with a `hyper::Client` built from the HTTP client settings.
+    ///
+    /// Fails if the proxy's scheme isn't supported, or if any of the root certificates is invalid. See
+    /// `ClientConfig::https_connector`.
+    #[cfg(feature = "sync")]
+    pub fn build_hyper(
+        self,
+    ) -> Result<Api<hyper::Client<hyper_tls::HttpsConnector<ProxyConnector>>>, Error> {
+        let client = self.client_config.build_hyper()?;
+        Ok(self.build(client))
+    }
+
+    /// Builds a blocking client sending requests with a `hyper::Client` built from the HTTP client settings, on the
+    /// runtime set with [`ApiBuilder::with_runtime`] or a new one.
+    ///
+    /// Fails if the HTTP client can't be built, as with [`ApiBuilder::build_hyper`], or if a new runtime fails to
+    /// start.
+    ///
+    /// [`ApiBuilder::with_runtime`]: struct.ApiBuilder.html#method.with_runtime
+    /// [`ApiBuilder::build_hyper`]: struct.ApiBuilder.html#method.build_hyper
+    #[cfg(feature = "sync")]
+    pub fn build_sync(
+        mut self,
+    ) -> Result<crate::SyncApi<hyper::Client<hyper_tls::HttpsConnector<ProxyConnector>>>, Error>
+    {
+        let runtime = match self.runtime.take() {
+            Some(runtime) => runtime,
+            None => tokio::runtime::Runtime::new()?,
+        };
+        let api = self.build_hyper()?;
+        Ok(crate::SyncApi::from_api_with_runtime(api, runtime))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ApiBuilder;
+    use crate::{
+        testing::{Mock, MockTransport},
+        RateLimiter, ServerProfile, TokenStorage, DEFAULT_USER_AGENT,
+    };
+
+    #[test]
+    fn build_from_settings() {
+        let api = ApiBuilder::new()
+            .with_server_profile(ServerProfile::Private)
+            .with_token("token")
+            .with_rate_limiter(RateLimiter::new())
+            .with_timeout(Duration::from_secs(5))
+            .build(MockTransport::new());
+
+        assert_eq!(api.url.as_str(), "http://localhost:21025/api/");
+        assert_eq!(api.token_storage().get().unwrap(), "token");
+        assert!(api.rate_limiter().is_some());
+        assert_eq!(api.retry_policy(), None);
+        assert_eq!(api.timeout(), Some(Duration::from_secs(5)));
+
+        let api = ApiBuilder::new()
+            .with_server_profile(ServerProfile::Private)
+            .with_url("https://private.example.com/api/")
+            .unwrap()
+            .build(MockTransport::new());
+        assert_eq!(api.url.as_str(), "https://private.example.com/api/");
+        assert_eq!(api.server_profile(), ServerProfile::Private);
+    }
+
+    #[test]
+    fn identify_client() {
+        let transport = MockTransport::new()
+            .with_mock(Mock::get("game/shards/info").with_json(json!({ "ok": 1, "shards": [] })));
+
+        let api = ApiBuilder::new().build(transport.clone());
+        assert_eq!(api.user_agent(), DEFAULT_USER_AGENT);
+        futures::executor::block_on(api.shard_list()).unwrap();
+
+        let api = ApiBuilder::new()
+            .with_user_agent("my-tool/1.0")
+            .unwrap()
+            .with_client_id("my-tool")
+            .unwrap()
+            .build(transport.clone());
+        futures::executor::block_on(api.shard_list()).unwrap();
+
+        let requests = transport.requests();
+        assert!(DEFAULT_USER_AGENT.starts_with("screeps-api/"));
+        assert_eq!(requests[0].headers()["User-Agent"], DEFAULT_USER_AGENT);
+        assert!(!requests[0].headers().contains_key("X-Client"));
+        assert_eq!(requests[1].headers()["User-Agent"], "my-tool/1.0");
+        assert_eq!(requests[1].headers()["X-Client"], "my-tool");
+
+        assert!(ApiBuilder::new().with_user_agent("bad\nagent").is_err());
+    }
+}
@@ -0,0 +1,477 @@
+//! Caching responses to repeated requests.
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use http::{
+    header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    StatusCode,
+};
+
+use crate::middleware::{Account, Middleware};
+
+/// The number of responses `ResponseCache::new()` keeps before evicting the least recently used.
+const DEFAULT_MAX_ENTRIES: usize = 1024;
+
+#[derive(Clone, Debug)]
+struct Entry {
+    headers: HeaderMap,
+    body: Bytes,
+    stored: Instant,
+    ttl: Option<Duration>,
+    /// When this entry was last stored or served, from `State::uses`.
+    used: u64,
+}
+
+impl Entry {
+    fn response(&self) -> http::Response<Bytes> {
+        let mut response = http::Response::new(self.body.clone());
+        *response.headers_mut() = self.headers.clone();
+        response
+    }
+
+    /// Whether this entry can be served without asking the server.
+    fn is_fresh(&self) -> bool {
+        matches!(self.ttl, Some(ttl) if self.stored.elapsed() < ttl)
+    }
+
+    /// Whether this entry can be neither served nor revalidated, and is only taking up space.
+    fn is_expired(&self) -> bool {
+        !self.is_fresh()
+            && !self.headers.contains_key(ETAG)
+            && !self.headers.contains_key(LAST_MODIFIED)
+    }
+}
+
+/// Identifies the cached response for a request: its URL, and the account it was sent for.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Key {
+    url: String,
+    /// The account, or `None` for anonymous requests.
+    ///
+    /// Tokens aren't used here, as the server may replace them with every response.
+    account: Option<Account>,
+}
+
+impl Key {
+    /// Gets the key for a request, or `None` if it is authenticated without saying which account it is for.
+    fn new<B>(request: &http::Request<B>) -> Option<Self> {
+        let account = request.extensions().get::<Account>().cloned();
+        if account.is_none() && request.headers().contains_key("X-Token") {
+            return None;
+        }
+        Some(Key {
+            url: request.uri().to_string(),
+            account,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    entries: HashMap<Key, Entry>,
+    ttls: Vec<(String, Duration)>,
+    max_entries: usize,
+    /// Counts uses of entries, to find the least recently used.
+    uses: u64,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            entries: HashMap::new(),
+            ttls: Vec::new(),
+            max_entries: DEFAULT_MAX_ENTRIES,
+            uses: 0,
+        }
+    }
+}
+
+impl State {
+    fn next_use(&mut self) -> u64 {
+        self.uses += 1;
+        self.uses
+    }
+
+    /// Drops expired entries, then the least recently used ones until there are at most `max_entries`.
+    fn evict(&mut self) {
+        self.entries.retain(|_, entry| !entry.is_expired());
+
+        while self.entries.len() > self.max_entries {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|&(_, entry)| entry.used)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => {
+                    self.entries.remove(&key);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// A cache of successful `GET` responses, keyed by URL and account.
+///
+/// Add the cache to an [`Api`] as middleware, with `Api::add_middleware`. Responses which the server sent with an
+/// `ETag` or `Last-Modified` header are revalidated with conditional requests: the server can then answer with
+/// `304 Not Modified` instead of sending the same data again. Responses from endpoints with a time-to-live set with
+/// [`ResponseCache::with_ttl`] are served from the cache without any request at all until they expire, which also saves
+/// rate limit budget.
+///
+/// `ResponseCache::new()` caches terrain, which never changes, for a day, and keeps up to 1024 responses. When full,
+/// the least recently used response is evicted; responses which have expired and can't be revalidated are dropped
+/// as soon as they are found.
+///
+/// Responses to authenticated requests are only reused for requests sent for the same account, so a cache can be
+/// shared between clients logged in to different accounts without mixing up their data. Accounts are told apart by
+/// the account name given to `Api::set_token_store`, or else the username given to `Api::set_credentials`, or else
+/// by the client's token storage: clear the cache when switching a client to another account's token in the same
+/// storage.
+///
+/// When cloned, the clone will share the same underlying cache.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use screeps_api::cache::ResponseCache;
+///
+/// let cache = ResponseCache::new().with_ttl("game/shards/info", Duration::from_secs(60 * 60));
+///
+/// let client = hyper::Client::new();
+/// let api = screeps_api::Api::new(client).with_middleware(cache.clone());
+/// ```
+///
+/// [`Api`]: ../struct.Api.html
+/// [`ResponseCache::with_ttl`]: struct.ResponseCache.html#method.with_ttl
+#[derive(Clone, Debug)]
+pub struct ResponseCache {
+    state: Arc<Mutex<State>>,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        ResponseCache {
+            state: Arc::default(),
+        }
+        .with_ttl("game/room-terrain", Duration::from_secs(24 * 60 * 60))
+    }
+}
+
+impl ResponseCache {
+    /// Creates an empty cache, with terrain cached for a day.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how long responses from the given endpoint are used without asking the server, and returns the cache.
+    ///
+    /// The endpoint is the path relative to the API url, such as `"game/room-terrain"`.
+    pub fn with_ttl(self, endpoint: &str, ttl: Duration) -> Self {
+        {
+            let mut state = self.lock();
+            let endpoint = format!("/{}", endpoint.trim_start_matches('/'));
+            state.ttls.retain(|(existing, _)| *existing != endpoint);
+            state.ttls.push((endpoint, ttl));
+        }
+        self
+    }
+
+    /// Sets the most responses kept before evicting the least recently used, and returns the cache.
+    pub fn with_max_entries(self, max_entries: usize) -> Self {
+        {
+            let mut state = self.lock();
+            state.max_entries = max_entries;
+            state.evict();
+        }
+        self
+    }
+
+    /// The number of cached responses.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Whether no responses are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached responses.
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    fn ttl(&self, state: &State, path: &str) -> Option<Duration> {
+        state
+            .ttls
+            .iter()
+            .find(|(endpoint, _)| path.ends_with(endpoint.as_str()))
+            .map(|&(_, ttl)| ttl)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Middleware for ResponseCache {
+    fn on_request(&self, request: &mut http::Request<Bytes>) -> Option<http::Response<Bytes>> {
+        if *request.method() != http::Method::GET {
+            return None;
+        }
+
+        let key = Key::new(request)?;
+        let mut state = self.lock();
+        let used = state.next_use();
+        let entry = state.entries.get_mut(&key)?;
+
+        if entry.is_fresh() {
+            debug!("serving {} from cache", request.uri());
+            entry.used = used;
+            return Some(entry.response());
+        }
+        if entry.is_expired() {
+            state.entries.remove(&key);
+            return None;
+        }
+
+        let validators = [
+            (IF_NONE_MATCH, entry.headers.get(ETAG)),
+            (IF_MODIFIED_SINCE, entry.headers.get(LAST_MODIFIED)),
+        ];
+        for (name, value) in validators {
+            if let Some(value) = value {
+                request.headers_mut().insert(name, value.clone());
+            }
+        }
+
+        None
+    }
+
+    fn on_response(&self, request: &http::Request<()>, response: &mut http::Response<Bytes>) {
+        if *request.method() != http::Method::GET {
+            return;
+        }
+
+        let key = match Key::new(request) {
+            Some(key) => key,
+            None => return,
+        };
+        let mut state = self.lock();
+
+        let used = state.next_use();
+
+        match response.status() {
+            StatusCode::NOT_MODIFIED => {
+                if let Some(entry) = state.entries.get_mut(&key) {
+                    debug!("revalidated cached response for {}", key.url);
+                    entry.stored = Instant::now();
+                    entry.used = used;
+
+                    let token = response.headers().get("X-Token").cloned();
+                    *response = entry.response();
+                    if let Some(token) = token {
+                        response.headers_mut().insert("X-Token", token);
+                    }
+                }
+            }
+            StatusCode::OK => {
+                let ttl = self.ttl(&state, request.uri().path());
+                let cacheable = response.headers().contains_key(ETAG)
+                    || response.headers().contains_key(LAST_MODIFIED)
+                    || ttl.is_some();
+
+                if cacheable {
+                    let mut headers = response.headers().clone();
+                    // tokens are single-use, and must never be replayed from the cache.
+                    headers.remove("X-Token");
+                    // rate limit state would be stale when replayed.
+                    for name in &[
+                        "X-RateLimit-Limit",
+                        "X-RateLimit-Remaining",
+                        "X-RateLimit-Reset",
+                    ] {
+                        headers.remove(*name);
+                    }
+
+                    state.entries.insert(
+                        key,
+                        Entry {
+                            headers,
+                            body: response.body().clone(),
+                            stored: Instant::now(),
+                            ttl,
+                            used,
+                        },
+                    );
+                    state.evict();
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use bytes::Bytes;
+    use http::{
+        header::{ETAG, IF_NONE_MATCH},
+        HeaderValue, StatusCode,
+    };
+
+    use super::ResponseCache;
+    use crate::{
+        middleware::Middleware,
+        testing::{Mock, MockTransport},
+        tokens::TokenStore,
+        Api,
+    };
+
+    /// Serves shards with an ETag.
+    fn etag_server() -> MockTransport {
+        let etag = HeaderValue::from_static("\"v1\"");
+        MockTransport::new()
+            .with_mock(
+                Mock::get("game/shards/info")
+                    .with_request_header(IF_NONE_MATCH, etag.clone())
+                    .with_status(StatusCode::NOT_MODIFIED),
+            )
+            .with_mock(
+                Mock::get("game/shards/info")
+                    .with_header(ETAG, etag)
+                    .with_json(json!({
+                        "ok": 1,
+                        "shards": [{ "name": "shard0", "rooms": 1, "users": 2, "tick": 3000 }],
+                    })),
+            )
+    }
+
+    /// Counts the full responses a server sent, rather than `304 Not Modified` ones.
+    fn full_responses(server: &MockTransport) -> usize {
+        server
+            .requests()
+            .iter()
+            .filter(|request| !request.headers().contains_key(IF_NONE_MATCH))
+            .count()
+    }
+
+    #[test]
+    fn revalidate_with_etag() {
+        let server = etag_server();
+        let cache = ResponseCache::new();
+        let api = Api::new(server.clone()).with_middleware(cache.clone());
+
+        for _ in 0..3 {
+            let shards = futures::executor::block_on(api.shard_list()).unwrap();
+            assert_eq!(shards.len(), 1);
+        }
+
+        assert_eq!(full_responses(&server), 1);
+        assert_eq!(cache.len(), 1);
+    }
+
+    /// Replaces the token with a fresh one in every response, as some servers do.
+    struct RotateTokens(AtomicUsize);
+
+    impl Middleware for RotateTokens {
+        fn on_response(&self, _: &http::Request<()>, response: &mut http::Response<Bytes>) {
+            let n = self.0.fetch_add(1, Ordering::SeqCst);
+            let token = HeaderValue::from_str(&format!("token-{}", n)).unwrap();
+            response.headers_mut().insert("X-Token", token);
+        }
+    }
+
+    /// Sends an authenticated request for shards for each account in turn, sharing one token store.
+    fn request_as(server: &MockTransport, cache: &ResponseCache, accounts: &[&str]) {
+        let store = TokenStore::new();
+        for &account in accounts {
+            let api = Api::new(server.clone())
+                .with_middleware(cache.clone())
+                .with_token_store(store.clone(), account)
+                .with_token(account.to_owned());
+            let request = crate::request::shard_list().auth();
+            futures::executor::block_on(api.execute(request).unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn separate_responses_by_account() {
+        let server = etag_server();
+        let cache = ResponseCache::new().with_ttl("game/shards/info", Duration::from_secs(60));
+
+        request_as(&server, &cache, &["one", "two", "one"]);
+
+        assert_eq!(full_responses(&server), 2);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn revalidate_with_rotating_tokens() {
+        let server = etag_server();
+        let cache = ResponseCache::new();
+        let api = Api::new(server.clone())
+            .with_middleware(cache.clone())
+            .with_middleware(RotateTokens(AtomicUsize::new(0)))
+            .with_token("initial");
+
+        for _ in 0..3 {
+            let request = crate::request::shard_list().auth();
+            futures::executor::block_on(api.execute(request).unwrap()).unwrap();
+        }
+
+        let tokens = server
+            .requests()
+            .iter()
+            .map(|request| request.headers()["X-Token"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, ["initial", "token-0", "token-1"]);
+        assert_eq!(full_responses(&server), 1);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        let server = etag_server();
+        let cache = ResponseCache::new().with_max_entries(2);
+
+        request_as(
+            &server,
+            &cache,
+            &["one", "two", "one", "three", "one", "two"],
+        );
+
+        // "three" evicted "two", and "two" then evicted "three": "one" was used more recently each time.
+        assert_eq!(full_responses(&server), 4);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn drop_expired_responses() {
+        let server =
+            MockTransport::new().with_mock(Mock::get("game/shards/info").with_json(json!({
+                "ok": 1,
+                "shards": [{ "name": "shard0", "rooms": 1, "users": 2, "tick": 3000 }],
+            })));
+        let cache = ResponseCache::new().with_ttl("game/shards/info", Duration::from_secs(0));
+        let api = Api::new(server.clone()).with_middleware(cache.clone());
+
+        for _ in 0..2 {
+            futures::executor::block_on(api.shard_list()).unwrap();
+        }
+
+        assert_eq!(server.requests().len(), 2);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+//! Abandoning requests which are no longer needed.
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    task::{Context, Poll, Waker},
+};
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    next_id: AtomicUsize,
+    wakers: Mutex<HashMap<usize, Waker>>,
+}
+
+/// A token which cancels every request made by the clients it is set on, when cancelled.
+///
+/// Set on a client with `Api::set_cancel_token`. Once the token is cancelled, requests in flight are abandoned,
+/// closing their connections, and they and any later requests fail with `ErrorKind::Cancelled`. This lets schedulers
+/// abandon a whole batch of requests at once, such as the requests of a polling cycle which has been superseded.
+///
+/// A single request can also be abandoned by dropping its future.
+///
+/// When cloned, the clone will share the same cancellation state.
+///
+/// ```
+/// use screeps_api::{cancel::CancelToken, Api};
+///
+/// let api = Api::new(hyper::Client::new());
+///
+/// let cycle = CancelToken::new();
+/// let cycle_api = api.clone().with_cancel_token(cycle.clone());
+/// let shards = cycle_api.shard_list();
+///
+/// cycle.cancel();
+/// let err = futures::executor::block_on(shards).unwrap_err();
+/// assert!(err.is_cancelled());
+/// ```
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<Inner>,
+}
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl CancelToken {
+    /// Creates a token which isn't cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every request made with this token.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+
+        let wakers = std::mem::take(
+            &mut *self
+                .inner
+                .wakers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
+    }
+
+    /// Whether this token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// A future which resolves once this token is cancelled.
+    pub(crate) fn cancelled(&self) -> Cancelled {
+        Cancelled {
+            inner: self.inner.clone(),
+            id: self.inner.next_id.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+/// Resolves once a `CancelToken` is cancelled.
+pub(crate) struct Cancelled {
+    inner: Arc<Inner>,
+    id: usize,
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.inner.cancelled.load(Ordering::SeqCst) {
+            return Poll::Ready(());
+        }
+
+        self.inner
+            .wakers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(self.id, cx.waker().clone());
+
+        // cancelled while registering, after the wakers were taken.
+        if self.inner.cancelled.load(Ordering::SeqCst) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Cancelled {
+    fn drop(&mut self) {
+        self.inner
+            .wakers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future;
+
+    use super::CancelToken;
+    use crate::{testing::Stalled, Api};
+
+    #[test]
+    fn cancel_in_flight_requests() {
+        let token = CancelToken::new();
+        let api = Api::new(Stalled).with_cancel_token(token.clone());
+
+        let requests = future::join(api.shard_list(), api.shard_list());
+        let cancel = async {
+            token.cancel();
+        };
+        let ((first, second), ()) = futures::executor::block_on(future::join(requests, cancel));
+
+        assert!(first.unwrap_err().is_cancelled());
+        assert!(second.unwrap_err().is_cancelled());
+        assert!(token.inner.wakers.lock().unwrap().is_empty());
+    }
+}
@@ -1,16 +1,226 @@
 //! Semi-internal functionality related to networking.
-use futures::stream::TryStreamExt;
+#[cfg(feature = "http")]
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+#[cfg(feature = "http")]
+use futures::future;
+#[cfg(feature = "http")]
+use http::header::{HeaderValue, USER_AGENT};
+#[cfg(feature = "http")]
 use url::Url;
 
-use crate::{EndpointResult, Error, TokenStorage};
+#[cfg(feature = "http")]
+use crate::{
+    backoff::BackoffPolicy,
+    metrics::{Observer, RequestFinished, RequestProgress, RequestStarted},
+    middleware,
+    rate_limit::{Priority, RateLimitInfo, RateLimitLog, RateLimiter},
+    request,
+    request::{AuthMode, EndpointPaths, ParseResponse},
+    retry,
+    retry::RetryPolicy,
+    server::ServerProfile,
+    transport::BodyProgress,
+    HttpTransport, LoggedIn, Request, Token, TokenStorage,
+};
+use crate::{request::ParseOptions, EndpointResult, Error, ErrorKind};
+
+/// Everything needed to send a request, independent of the `Api` it came from.
+#[cfg(feature = "http")]
+pub(crate) struct Pipeline<T> {
+    pub base_url: Url,
+    pub client: Arc<T>,
+    pub rate_limiter: Option<RateLimiter>,
+    pub middleware: middleware::Chain,
+    pub observer: Option<Arc<dyn Observer>>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub retry_backoff: Option<Arc<dyn BackoffPolicy>>,
+    pub profile: ServerProfile,
+    pub auth_mode: AuthMode,
+    pub rate_limit_log: RateLimitLog,
+    pub timeout: Option<Duration>,
+    pub priority: Priority,
+    pub parse_options: ParseOptions,
+    pub endpoint_paths: EndpointPaths,
+    pub user_agent: HeaderValue,
+    pub client_id: Option<HeaderValue>,
+    /// The account authenticated requests are sent for.
+    pub account: middleware::Account,
+}
+
+#[cfg(feature = "http")]
+impl<T: HttpTransport> Pipeline<T> {
+    /// Sends a request through the rate limiter and middleware, retrying it according to the retry policy.
+    ///
+    /// Returns the final response, and how long the server asked to wait before retrying it, if it did.
+    pub async fn fetch<R>(
+        &self,
+        request: &Request<R>,
+        token: Option<&Token>,
+    ) -> (Result<http::Response<Bytes>, Error>, Option<Duration>) {
+        let url = request.url_with_paths(&self.base_url, &self.endpoint_paths);
+        let retry_policy = self
+            .retry_policy
+            .filter(|_| *request.method() == http::Method::GET);
+
+        // only read the clock when observed, as `Instant::now` is unavailable in browsers.
+        let started = self.observer.as_ref().map(|_| Instant::now());
+        if let Some(observer) = &self.observer {
+            observer.on_start(&RequestStarted {
+                method: request.method().clone(),
+                endpoint: request.endpoint(),
+            });
+        }
+
+        let (mut retry, mut previous_backoff) = (0, Duration::from_secs(0));
+        loop {
+            let limiter = self.rate_limiter.as_ref();
+            let priority = request.priority().unwrap_or(self.priority);
+            let wait_for_rate_limit = move || async move {
+                if let Some(limiter) = limiter {
+                    limiter
+                        .acquire(request.method(), request.endpoint(), priority)
+                        .await;
+                }
+            };
+
+            let mut http_request = request.to_http_at(&url, &self.base_url, token, self.auth_mode);
+            let headers = http_request.headers_mut();
+            headers.insert(USER_AGENT, self.user_agent.clone());
+            if let Some(client_id) = &self.client_id {
+                headers.insert("X-Client", client_id.clone());
+            }
+            if token.is_some() {
+                http_request.extensions_mut().insert(self.account.clone());
+            }
+            if let Some(observer) = &self.observer {
+                let (observer, method, endpoint) = (
+                    observer.clone(),
+                    request.method().clone(),
+                    request.endpoint(),
+                );
+                http_request
+                    .extensions_mut()
+                    .insert(BodyProgress::new(move |received, total| {
+                        observer.on_progress(&RequestProgress {
+                            method: method.clone(),
+                            endpoint,
+                            received,
+                            total,
+                        })
+                    }));
+            }
+
+            let response = self
+                .middleware
+                .send(
+                    &*self.client,
+                    http_request,
+                    wait_for_rate_limit,
+                    request.timeout().or(self.timeout),
+                )
+                .await;
+
+            if let Ok(ref response) = response {
+                if let Some(info) = RateLimitInfo::from_headers(response.headers()) {
+                    self.rate_limit_log
+                        .record(request.method(), request.endpoint(), info);
+                    if let Some(limiter) = &self.rate_limiter {
+                        limiter.observe(request.method(), request.endpoint(), &info);
+                    }
+                }
+            }
+
+            let retry_after = match response {
+                Ok(ref response) if retry::may_ask_to_wait(response.status()) => {
+                    retry::retry_after(response.headers())
+                }
+                _ => None,
+            };
 
-/// Interpret a hyper result as the result from a specific endpoint.
+            // waiting longer than retries are ever delayed for is left to the caller, who gets `retry_after`.
+            let waits_too_long =
+                |policy: RetryPolicy| retry_after.is_some_and(|after| after > policy.max_delay());
+            let backoff = match retry_policy {
+                Some(policy) if retry::is_transient(&response) && !waits_too_long(policy) => {
+                    match self.retry_backoff {
+                        Some(ref backoff) if retry < policy.max_retries() => {
+                            backoff.delay(retry, previous_backoff)
+                        }
+                        Some(_) => None,
+                        None => policy.delay(retry),
+                    }
+                }
+                _ => None,
+            };
+            match backoff {
+                Some(backoff) => {
+                    let delay = retry_after.map_or(backoff, |after| after.max(backoff));
+                    debug!(
+                        "retrying request to {} in {:?} after transient failure",
+                        url, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                    previous_backoff = backoff;
+                }
+                None => {
+                    if let (Some(observer), Some(started)) = (&self.observer, started) {
+                        observer.on_finish(&RequestFinished {
+                            method: request.method().clone(),
+                            endpoint: request.endpoint(),
+                            duration: started.elapsed(),
+                            status: response.as_ref().ok().map(|response| response.status()),
+                            retries: retry,
+                        });
+                    }
+                    return (response, retry_after);
+                }
+            }
+        }
+    }
+
+    /// Logs in again with the given credentials, storing and returning the new token.
+    pub async fn relogin(
+        &self,
+        tokens: &Arc<dyn TokenStorage>,
+        username: &str,
+        password: &str,
+    ) -> Result<Token, Error> {
+        let request = request::login(username, password);
+        let url = request.url_with_paths(&self.base_url, &self.endpoint_paths);
+
+        // logging in again is only possible where logging in is.
+        if !self.profile.supports(request.method(), request.endpoint()) {
+            return Err(Error::with_url(
+                ErrorKind::UnsupportedEndpoint(request.endpoint()),
+                Some(url),
+            ));
+        }
+
+        let (response, _) = self.fetch(&request, None).await;
+        let options = self.parse_options.for_request(request.endpoint(), &url);
+        let logged_in: LoggedIn =
+            interpret(tokens.clone(), url, future::ready(response), &options).await?;
+
+        let token = logged_in.token.clone();
+        logged_in.return_to(&**tokens);
+
+        Ok(token)
+    }
+}
+
+/// Interpret an HTTP response as the result from a specific endpoint.
 ///
 /// The returned future will:
 ///
-/// - Wait for the hyper request to finish
-/// - Wait for hyper request body, collecting it into a single chunk
-/// - Parse JSON body as the given `EndpointResult`, and return result/error.
+/// - Wait for the transport to finish the request, including collecting the body
+/// - Parse JSON body as the given `ParseResponse`, and return result/error.
 ///
 /// All errors returned will have the given `Url` contained as part of the context.
 ///
@@ -18,90 +228,133 @@ use crate::{EndpointResult, Error, TokenStorage};
 ///
 /// - `url`: url that is being queried, used only for error and warning messages
 /// - `tokens`: where to put any tokens that were returned, if any
-/// - `response`: actual hyper response that we're interpreting
-pub(crate) async fn interpret<R>(
-    tokens: TokenStorage,
+/// - `response`: actual response that we're interpreting
+/// - `options`: how to parse the response
+#[cfg(feature = "http")]
+pub(crate) async fn interpret<R, F>(
+    tokens: Arc<dyn TokenStorage>,
     url: Url,
-    response: hyper::client::ResponseFuture,
+    response: F,
+    options: &ParseOptions,
 ) -> Result<R, Error>
 where
-    R: EndpointResult,
+    R: ParseResponse,
+    F: Future<Output = Result<http::Response<bytes::Bytes>, Error>>,
 {
     let response = response
         .await
         .map_err(|e| Error::with_url(e, Some(url.clone())))?;
     if let Some(token) = response.headers().get("X-Token") {
         debug!(
-            "replacing stored auth_token with token returned from API: {:?}",
-            token.to_str()
+            "replacing stored auth token with token returned from {}",
+            url
         );
         tokens.set(token.as_bytes().to_owned().into());
     }
-    let status = response.status();
 
-    let data: Vec<u8> = response
-        .into_body()
-        .try_fold(Vec::new(), |mut data, chunk| async move {
-            data.extend_from_slice(&chunk);
-            Ok(data)
-        })
-        .await
-        .map_err(|e| Error::with_url(e, Some(url.clone())))?;
-    let data = bytes::Bytes::from(data);
-    let json_result = serde_json::from_slice(&data);
+    let (status, body) = (response.status(), response.into_body());
+    let parsed = match options.blocking_threshold {
+        // there are no threads to move parsing to in browsers.
+        Some(threshold) if body.len() >= threshold && !cfg!(target_arch = "wasm32") => {
+            let options = options.clone();
+            let parsing = tokio::task::spawn_blocking(move || {
+                R::parse_response_with(status, &body, &options)
+            });
+            match parsing.await {
+                Ok(parsed) => parsed,
+                Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                // the runtime is shutting down.
+                Err(_) => Err(ErrorKind::Cancelled.into()),
+            }
+        }
+        _ => R::parse_response_with(status, &body, options),
+    };
+
+    parsed.map_err(|e| Error::with_url(e, Some(url)))
+}
+
+/// Parses a raw response body as the result from a specific endpoint.
+///
+/// Results are deserialized straight from the body, borrowing strings from it where the result type allows. The body
+/// is never copied: errors returned share it, or contain the JSON data parsed from it, but no URL.
+pub(crate) fn parse<R>(
+    status: http::StatusCode,
+    body: &Bytes,
+    options: &ParseOptions,
+) -> Result<R, Error>
+where
+    R: EndpointResult,
+{
+    // parse straight from the body, falling back to the slower path to describe any errors.
+    if options.fast && !options.strict && options.raw_json.is_none() && status.is_success() {
+        if let Ok(parsed) = serde_json::from_slice::<R::RequestResult<'_>>(body) {
+            return R::from_raw(parsed).map_err(|e| with_data(e, body));
+        }
+    }
 
     // insert this check here so we can include response body in status errors.
     if !status.is_success() {
-        if let Ok(json) = json_result {
-            return Err(Error::with_json(status, Some(url), Some(json)));
-        } else {
-            return Err(Error::with_body(status, Some(url), Some(data)));
-        }
+        return Err(with_data(status, body));
     }
 
-    let json = match json_result {
-        Ok(v) => v,
-        Err(e) => return Err(Error::with_body(e, Some(url), Some(data))),
-    };
-    let parsed = match deserialize_with_warnings::<R>(&json, &url) {
-        Ok(v) => v,
-        Err(e) => return Err(Error::with_json(e, Some(url), Some(json))),
-    };
+    if options.raw_json.is_some() {
+        let json = serde_json::from_slice(body)
+            .map_err(|e| Error::with_body(e, None, Some(body.clone())))?;
+        options.keep_raw_json(json);
+    }
+
+    let parsed = deserialize_with_warnings::<R>(body, options)?;
+
+    R::from_raw(parsed).map_err(|e| with_data(e, body))
+}
 
-    R::from_raw(parsed).map_err(|e| Error::with_json(e, Some(url), Some(json)))
+/// Creates an error containing the JSON data in the body, or the body itself if it isn't JSON.
+///
+/// Parsing the body again is only done for errors, so that successful responses can be parsed without keeping the
+/// parsed JSON data around.
+fn with_data<E: Into<Error>>(err: E, body: &Bytes) -> Error {
+    match serde_json::from_slice(body) {
+        Ok(json) => Error::with_json(err, None, Some(json)),
+        Err(_) => Error::with_body(err, None, Some(body.clone())),
+    }
 }
 
-fn deserialize_with_warnings<T: EndpointResult>(
-    input: &serde_json::Value,
-    url: &Url,
-) -> Result<T::RequestResult, Error> {
+/// Parses the body as the result type, borrowing strings from it rather than copying them where the type allows.
+fn deserialize_with_warnings<'de, T: EndpointResult>(
+    body: &'de Bytes,
+    options: &ParseOptions,
+) -> Result<T::RequestResult<'de>, Error> {
     let mut unused = Vec::new();
 
-    let res = match serde_ignored::deserialize::<_, _, T::RequestResult>(input, |path| {
-        unused.push(path.to_string())
-    }) {
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let parsed =
+        serde_ignored::deserialize::<_, _, T::RequestResult<'de>>(&mut deserializer, |path| {
+            unused.push(path.to_string())
+        })
+        .and_then(|v| deserializer.end().map(|()| v));
+
+    let res = match parsed {
         Ok(v) => Ok(v),
         Err(e1) => {
             unused.clear();
-            match serde_ignored::deserialize::<_, _, T::ErrorResult>(input, |path| {
+            let input: serde_json::Value = serde_json::from_slice(body)
+                .map_err(|e| Error::with_body(e, None, Some(body.clone())))?;
+            match serde_ignored::deserialize::<_, _, T::ErrorResult>(&input, |path| {
                 unused.push(path.to_string())
             }) {
-                Ok(v) => Err(Error::with_json(v, Some(url.clone()), Some(input.clone()))),
+                Ok(v) => Err(Error::with_json(v, None, Some(input))),
                 // Favor the primary parsing error if one occurs parsing the error type as well.
-                Err(_) => Err(Error::with_json(e1, Some(url.clone()), Some(input.clone()))),
+                Err(_) => Err(Error::with_json(e1, None, Some(input))),
             }
         }
     };
 
+    if !unused.is_empty() && res.is_ok() && options.strict() {
+        return Err(with_data(ErrorKind::UnknownFields(unused), body));
+    }
+
     if !unused.is_empty() {
-        warn!(
-            "screeps API lib didn't parse some data retrieved from: {}\n\
-             full data: {}\n\
-             unparsed fields: {:#?}",
-            url,
-            serde_json::to_string_pretty(input).unwrap(),
-            unused
-        );
+        options.warn(std::any::type_name::<T>(), unused, body);
     }
 
     res
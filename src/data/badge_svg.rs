@@ -0,0 +1,256 @@
+//! Rendering user badges as SVG images.
+use std::{error, fmt, fmt::Write};
+
+use super::{Badge, BadgeColor, BadgeType};
+
+/// Looks up the parts of badges which are built in to the Screeps client, in order to render badges using them.
+///
+/// The built-in paths and colors are not included in this library for licensing reasons, but can be scraped from the
+/// client and provided through this trait. [`NoBadgeResources`] provides none of them, so can only be used for
+/// badges with custom paths and hex colors.
+///
+/// [`NoBadgeResources`]: struct.NoBadgeResources.html
+pub trait BadgeResources {
+    /// Gets the two SVG paths drawn for a built-in badge type from 1 to 24, given the badge's param and flip.
+    ///
+    /// Paths are drawn in a 100 by 100 box.
+    fn paths(&self, badge_type: i32, param: i32, flip: bool) -> Option<(String, String)>;
+
+    /// Gets a built-in color from 0 to 79, as an SVG color such as `"#ff0000"`.
+    fn color(&self, index: i32) -> Option<String>;
+}
+
+/// Badge resources which include no built-in paths or colors.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NoBadgeResources;
+
+impl BadgeResources for NoBadgeResources {
+    fn paths(&self, _badge_type: i32, _param: i32, _flip: bool) -> Option<(String, String)> {
+        None
+    }
+
+    fn color(&self, _index: i32) -> Option<String> {
+        None
+    }
+}
+
+/// An error rendering a badge. See [`Badge::to_svg_with`].
+///
+/// [`Badge::to_svg_with`]: struct.Badge.html#method.to_svg_with
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BadgeRenderError {
+    /// The badge uses a built-in type which the badge resources don't have.
+    UnknownType(i32),
+    /// The badge uses a built-in color which the badge resources don't have.
+    UnknownColor(i32),
+    /// The badge uses a hex color which isn't in the `#rgb` or `#rrggbb` format.
+    InvalidColor(String),
+}
+
+impl error::Error for BadgeRenderError {}
+
+impl fmt::Display for BadgeRenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BadgeRenderError::UnknownType(t) => write!(f, "no paths known for badge type {}", t),
+            BadgeRenderError::UnknownColor(c) => write!(f, "no color known for badge color {}", c),
+            BadgeRenderError::InvalidColor(c) => write!(f, "invalid badge color {:?}", c),
+        }
+    }
+}
+
+impl BadgeColor {
+    /// Gets the red, green and blue components of this color, if it is a valid hex color.
+    ///
+    /// Built-in colors return `None`, as they're only known to the client.
+    pub fn rgb(&self) -> Option<(u8, u8, u8)> {
+        let hex = match self {
+            BadgeColor::Hex(hex) => hex.strip_prefix('#')?,
+            BadgeColor::Set(_) => return None,
+        };
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let component = |s: &str| u8::from_str_radix(s, 16).ok();
+        match hex.len() {
+            3 => Some((
+                component(&hex[0..1])? * 17,
+                component(&hex[1..2])? * 17,
+                component(&hex[2..3])? * 17,
+            )),
+            6 => Some((
+                component(&hex[0..2])?,
+                component(&hex[2..4])?,
+                component(&hex[4..6])?,
+            )),
+            _ => None,
+        }
+    }
+
+    fn to_svg_color<R: BadgeResources + ?Sized>(
+        &self,
+        resources: &R,
+    ) -> Result<String, BadgeRenderError> {
+        match self {
+            BadgeColor::Set(index) => resources
+                .color(*index)
+                .ok_or(BadgeRenderError::UnknownColor(*index)),
+            BadgeColor::Hex(hex) => match self.rgb() {
+                Some((r, g, b)) => Ok(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+                None => Err(BadgeRenderError::InvalidColor(hex.clone())),
+            },
+        }
+    }
+}
+
+impl Badge {
+    /// Renders this badge as an SVG image `size` pixels wide and tall, if it only uses custom paths and hex colors.
+    ///
+    /// See [`Badge::to_svg_with`] to render badges using the client's built-in types and colors.
+    ///
+    /// ```
+    /// use screeps_api::{Badge, BadgeColor, BadgeType};
+    ///
+    /// let badge = Badge {
+    ///     badge_type: BadgeType::Dynamic {
+    ///         path1: "M0,0h50v100h-50z".to_owned(),
+    ///         path2: "M50,0h50v50h-50z".to_owned(),
+    ///     },
+    ///     color1: BadgeColor::Hex("#000000".to_owned()),
+    ///     color2: BadgeColor::Hex("#028300".to_owned()),
+    ///     color3: BadgeColor::Hex("#8b5c00".to_owned()),
+    ///     param: 0,
+    ///     flip: false,
+    /// };
+    /// let svg = badge.to_svg(64).unwrap();
+    /// assert!(svg.starts_with("<svg"));
+    /// assert!(svg.contains(r##"<path d="M0,0h50v100h-50z" fill="#028300"/>"##));
+    /// ```
+    ///
+    /// [`Badge::to_svg_with`]: #method.to_svg_with
+    pub fn to_svg(&self, size: u32) -> Result<String, BadgeRenderError> {
+        self.to_svg_with(size, &NoBadgeResources)
+    }
+
+    /// Renders this badge as an SVG image `size` pixels wide and tall, looking up built-in types and colors in the
+    /// given resources.
+    ///
+    /// As in the client, the badge is drawn in a 100 by 100 box: filled with `color1`, then the first path filled with
+    /// `color2` and the second with `color3`, all clipped to a circle. Custom paths are drawn exactly as sent, while
+    /// `param` and `flip` are passed on to the resources for built-in types.
+    pub fn to_svg_with<R: BadgeResources + ?Sized>(
+        &self,
+        size: u32,
+        resources: &R,
+    ) -> Result<String, BadgeRenderError> {
+        let (path1, path2) = match &self.badge_type {
+            BadgeType::Fixed(t) => resources
+                .paths(*t, self.param, self.flip)
+                .ok_or(BadgeRenderError::UnknownType(*t))?,
+            BadgeType::Dynamic { path1, path2 } => (path1.clone(), path2.clone()),
+        };
+        let color1 = self.color1.to_svg_color(resources)?;
+        let color2 = self.color2.to_svg_color(resources)?;
+        let color3 = self.color3.to_svg_color(resources)?;
+
+        Ok(format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 100 100">"#,
+                r#"<defs><clipPath id="badge-clip"><circle cx="50" cy="50" r="50"/></clipPath></defs>"#,
+                r#"<g clip-path="url(#badge-clip)">"#,
+                r#"<rect width="100" height="100" fill="{color1}"/>"#,
+                r#"<path d="{path1}" fill="{color2}"/>"#,
+                r#"<path d="{path2}" fill="{color3}"/>"#,
+                r#"</g></svg>"#,
+            ),
+            size = size,
+            color1 = Escaped(&color1),
+            path1 = Escaped(&path1),
+            color2 = Escaped(&color2),
+            path2 = Escaped(&path2),
+            color3 = Escaped(&color3),
+        ))
+    }
+}
+
+/// Formats a string for use in an XML attribute.
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BadgeRenderError, BadgeResources};
+    use crate::data::{Badge, BadgeColor};
+
+    struct TestResources;
+
+    impl BadgeResources for TestResources {
+        fn paths(&self, badge_type: i32, param: i32, flip: bool) -> Option<(String, String)> {
+            match badge_type {
+                1 => Some((format!("M0,0h{}", param), format!("M0,0v{}", flip))),
+                _ => None,
+            }
+        }
+
+        fn color(&self, index: i32) -> Option<String> {
+            match index {
+                0..=79 => Some(format!("#0000{:02x}", index)),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn render_badges() {
+        let badge: Badge = serde_json::from_value(json!({
+            "type": 1,
+            "color1": 37,
+            "color2": "#fff",
+            "color3": "#6B2E41",
+            "param": -100,
+            "flip": true,
+        }))
+        .unwrap();
+
+        assert_eq!(badge.color2.rgb(), Some((255, 255, 255)));
+        assert_eq!(badge.color1.rgb(), None);
+        assert_eq!(badge.to_svg(32), Err(BadgeRenderError::UnknownType(1)));
+
+        let svg = badge.to_svg_with(32, &TestResources).unwrap();
+        assert!(svg.contains(r#"width="32" height="32""#));
+        assert!(svg.contains(r##"<rect width="100" height="100" fill="#000025"/>"##));
+        assert!(svg.contains(r##"<path d="M0,0h-100" fill="#ffffff"/>"##));
+        assert!(svg.contains(r##"<path d="M0,0vtrue" fill="#6b2e41"/>"##));
+
+        let invalid = Badge {
+            color3: BadgeColor::Hex("red\"/>".to_owned()),
+            ..badge.clone()
+        };
+        assert_eq!(
+            invalid.to_svg_with(32, &TestResources),
+            Err(BadgeRenderError::InvalidColor("red\"/>".to_owned()))
+        );
+        let unknown = Badge {
+            color1: BadgeColor::Set(80),
+            ..badge
+        };
+        assert_eq!(
+            unknown.to_svg_with(32, &TestResources),
+            Err(BadgeRenderError::UnknownColor(80))
+        );
+    }
+}
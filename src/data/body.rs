@@ -0,0 +1,344 @@
+//! Structures relating to creep bodies.
+use std::fmt;
+
+/// A type of creep body part.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum BodyPart {
+    /// A move part - allows creeps to move, or move faster (max speed at 1 move part per other part).
+    Move,
+    /// A work part - allows creeps to spend energy doing things, mine sources and minerals, and damage
+    /// structures.
+    Work,
+    /// A carry part - allows creeps to carry energy and other resources.
+    Carry,
+    /// An attack part - allows creeps to damage both other creeps, and structures.
+    Attack,
+    /// A heal part - allows creeps to heal creeps (other and self).
+    Heal,
+    /// A ranged attack part - allows creeps to attack other creeps and structures from a small distance.
+    RangedAttack,
+    /// A tough part - a cheap part which has no additional functionality.
+    Tough,
+    /// A claim part - a part which allows a creep to reserve or claim a room controller.
+    Claim,
+}
+
+impl BodyPart {
+    /// The energy it costs to spawn a creep with this part.
+    pub fn cost(self) -> u32 {
+        match self {
+            BodyPart::Move => 50,
+            BodyPart::Work => 100,
+            BodyPart::Carry => 50,
+            BodyPart::Attack => 80,
+            BodyPart::Heal => 250,
+            BodyPart::RangedAttack => 150,
+            BodyPart::Tough => 10,
+            BodyPart::Claim => 600,
+        }
+    }
+
+    /// The in-game name of this part, such as `"ranged_attack"`.
+    pub fn to_part_string(self) -> &'static str {
+        match self {
+            BodyPart::Move => "move",
+            BodyPart::Work => "work",
+            BodyPart::Carry => "carry",
+            BodyPart::Attack => "attack",
+            BodyPart::Heal => "heal",
+            BodyPart::RangedAttack => "ranged_attack",
+            BodyPart::Tough => "tough",
+            BodyPart::Claim => "claim",
+        }
+    }
+}
+
+impl fmt::Display for BodyPart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.to_part_string())
+    }
+}
+
+/// A compound which can boost creep body parts.
+///
+/// Each compound boosts one type of part, at one of three tiers: the base compounds are tier 1, acids and alkalides
+/// are tier 2, and catalyzed acids and alkalides are tier 3.
+///
+/// ```
+/// use screeps_api::{BodyPart, Boost};
+///
+/// let boost: Boost = serde_json::from_str("\"XGHO2\"").unwrap();
+/// assert_eq!(boost, Boost::CatalyzedGhodiumAlkalide);
+/// assert_eq!(boost.part(), Some(BodyPart::Tough));
+/// assert_eq!(boost.tier(), Some(3));
+///
+/// let boost: Boost = serde_json::from_str("\"XQH2O\"").unwrap();
+/// assert_eq!(boost, Boost::Other("XQH2O".to_owned()));
+/// assert_eq!(boost.part(), None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Boost {
+    /// RESOURCE_UTRIUM_HYDRIDE: "UH",
+    UtriumHydride,
+    /// RESOURCE_UTRIUM_OXIDE: "UO",
+    UtriumOxide,
+    /// RESOURCE_KEANIUM_HYDRIDE: "KH",
+    KeaniumHydride,
+    /// RESOURCE_KEANIUM_OXIDE: "KO",
+    KeaniumOxide,
+    /// RESOURCE_LEMERGIUM_HYDRIDE: "LH",
+    LemergiumHydride,
+    /// RESOURCE_LEMERGIUM_OXIDE: "LO",
+    LemergiumOxide,
+    /// RESOURCE_ZYNTHIUM_HYDRIDE: "ZH",
+    ZynthiumHydride,
+    /// RESOURCE_ZYNTHIUM_OXIDE: "ZO",
+    ZynthiumOxide,
+    /// RESOURCE_GHODIUM_HYDRIDE: "GH",
+    GhodiumHydride,
+    /// RESOURCE_GHODIUM_OXIDE: "GO",
+    GhodiumOxide,
+    /// RESOURCE_UTRIUM_ACID: "UH2O",
+    UtriumAcid,
+    /// RESOURCE_UTRIUM_ALKALIDE: "UHO2",
+    UtriumAlkalide,
+    /// RESOURCE_KEANIUM_ACID: "KH2O",
+    KeaniumAcid,
+    /// RESOURCE_KEANIUM_ALKALIDE: "KHO2",
+    KeaniumAlkalide,
+    /// RESOURCE_LEMERGIUM_ACID: "LH2O",
+    LemergiumAcid,
+    /// RESOURCE_LEMERGIUM_ALKALIDE: "LHO2",
+    LemergiumAlkalide,
+    /// RESOURCE_ZYNTHIUM_ACID: "ZH2O",
+    ZynthiumAcid,
+    /// RESOURCE_ZYNTHIUM_ALKALIDE: "ZHO2",
+    ZynthiumAlkalide,
+    /// RESOURCE_GHODIUM_ACID: "GH2O",
+    GhodiumAcid,
+    /// RESOURCE_GHODIUM_ALKALIDE: "GHO2",
+    GhodiumAlkalide,
+    /// RESOURCE_CATALYZED_UTRIUM_ACID: "XUH2O",
+    CatalyzedUtriumAcid,
+    /// RESOURCE_CATALYZED_UTRIUM_ALKALIDE: "XUHO2",
+    CatalyzedUtriumAlkalide,
+    /// RESOURCE_CATALYZED_KEANIUM_ACID: "XKH2O",
+    CatalyzedKeaniumAcid,
+    /// RESOURCE_CATALYZED_KEANIUM_ALKALIDE: "XKHO2",
+    CatalyzedKeaniumAlkalide,
+    /// RESOURCE_CATALYZED_LEMERGIUM_ACID: "XLH2O",
+    CatalyzedLemergiumAcid,
+    /// RESOURCE_CATALYZED_LEMERGIUM_ALKALIDE: "XLHO2",
+    CatalyzedLemergiumAlkalide,
+    /// RESOURCE_CATALYZED_ZYNTHIUM_ACID: "XZH2O",
+    CatalyzedZynthiumAcid,
+    /// RESOURCE_CATALYZED_ZYNTHIUM_ALKALIDE: "XZHO2",
+    CatalyzedZynthiumAlkalide,
+    /// RESOURCE_CATALYZED_GHODIUM_ACID: "XGH2O",
+    CatalyzedGhodiumAcid,
+    /// RESOURCE_CATALYZED_GHODIUM_ALKALIDE: "XGHO2",
+    CatalyzedGhodiumAlkalide,
+    /// A compound which isn't known, such as one added to a private server by a mod.
+    Other(String),
+}
+
+impl Boost {
+    /// Finds the compound with the given in-game resource type string, if it is a known boost.
+    pub fn known(resource: &str) -> Option<Boost> {
+        match resource {
+            "UH" => Some(Boost::UtriumHydride),
+            "UO" => Some(Boost::UtriumOxide),
+            "KH" => Some(Boost::KeaniumHydride),
+            "KO" => Some(Boost::KeaniumOxide),
+            "LH" => Some(Boost::LemergiumHydride),
+            "LO" => Some(Boost::LemergiumOxide),
+            "ZH" => Some(Boost::ZynthiumHydride),
+            "ZO" => Some(Boost::ZynthiumOxide),
+            "GH" => Some(Boost::GhodiumHydride),
+            "GO" => Some(Boost::GhodiumOxide),
+            "UH2O" => Some(Boost::UtriumAcid),
+            "UHO2" => Some(Boost::UtriumAlkalide),
+            "KH2O" => Some(Boost::KeaniumAcid),
+            "KHO2" => Some(Boost::KeaniumAlkalide),
+            "LH2O" => Some(Boost::LemergiumAcid),
+            "LHO2" => Some(Boost::LemergiumAlkalide),
+            "ZH2O" => Some(Boost::ZynthiumAcid),
+            "ZHO2" => Some(Boost::ZynthiumAlkalide),
+            "GH2O" => Some(Boost::GhodiumAcid),
+            "GHO2" => Some(Boost::GhodiumAlkalide),
+            "XUH2O" => Some(Boost::CatalyzedUtriumAcid),
+            "XUHO2" => Some(Boost::CatalyzedUtriumAlkalide),
+            "XKH2O" => Some(Boost::CatalyzedKeaniumAcid),
+            "XKHO2" => Some(Boost::CatalyzedKeaniumAlkalide),
+            "XLH2O" => Some(Boost::CatalyzedLemergiumAcid),
+            "XLHO2" => Some(Boost::CatalyzedLemergiumAlkalide),
+            "XZH2O" => Some(Boost::CatalyzedZynthiumAcid),
+            "XZHO2" => Some(Boost::CatalyzedZynthiumAlkalide),
+            "XGH2O" => Some(Boost::CatalyzedGhodiumAcid),
+            "XGHO2" => Some(Boost::CatalyzedGhodiumAlkalide),
+            _ => None,
+        }
+    }
+
+    /// Finds the compound with the given in-game resource type string, as `Other` if it isn't known.
+    pub fn from_resource_string(resource: &str) -> Boost {
+        Boost::known(resource).unwrap_or_else(|| Boost::Other(resource.to_owned()))
+    }
+
+    /// The type of body part this compound boosts, or `None` if the compound isn't known.
+    pub fn part(&self) -> Option<BodyPart> {
+        use self::Boost::*;
+
+        Some(match *self {
+            UtriumHydride | UtriumAcid | CatalyzedUtriumAcid => BodyPart::Attack,
+            UtriumOxide | UtriumAlkalide | CatalyzedUtriumAlkalide => BodyPart::Work,
+            KeaniumHydride | KeaniumAcid | CatalyzedKeaniumAcid => BodyPart::Carry,
+            KeaniumOxide | KeaniumAlkalide | CatalyzedKeaniumAlkalide => BodyPart::RangedAttack,
+            LemergiumHydride | LemergiumAcid | CatalyzedLemergiumAcid => BodyPart::Work,
+            LemergiumOxide | LemergiumAlkalide | CatalyzedLemergiumAlkalide => BodyPart::Heal,
+            ZynthiumHydride | ZynthiumAcid | CatalyzedZynthiumAcid => BodyPart::Work,
+            ZynthiumOxide | ZynthiumAlkalide | CatalyzedZynthiumAlkalide => BodyPart::Move,
+            GhodiumHydride | GhodiumAcid | CatalyzedGhodiumAcid => BodyPart::Work,
+            GhodiumOxide | GhodiumAlkalide | CatalyzedGhodiumAlkalide => BodyPart::Tough,
+            Other(_) => return None,
+        })
+    }
+
+    /// The tier of this compound, from 1 to 3, or `None` if the compound isn't known. Higher tiers have stronger
+    /// effects.
+    pub fn tier(&self) -> Option<u8> {
+        let name = match *self {
+            Boost::Other(_) => return None,
+            _ => self.to_resource_string(),
+        };
+
+        Some(if name.starts_with('X') {
+            3
+        } else if name.len() == 4 {
+            2
+        } else {
+            1
+        })
+    }
+
+    /// Finds the in-game resource type string for this compound.
+    pub fn to_resource_string(&self) -> &str {
+        match *self {
+            Boost::UtriumHydride => "UH",
+            Boost::UtriumOxide => "UO",
+            Boost::KeaniumHydride => "KH",
+            Boost::KeaniumOxide => "KO",
+            Boost::LemergiumHydride => "LH",
+            Boost::LemergiumOxide => "LO",
+            Boost::ZynthiumHydride => "ZH",
+            Boost::ZynthiumOxide => "ZO",
+            Boost::GhodiumHydride => "GH",
+            Boost::GhodiumOxide => "GO",
+            Boost::UtriumAcid => "UH2O",
+            Boost::UtriumAlkalide => "UHO2",
+            Boost::KeaniumAcid => "KH2O",
+            Boost::KeaniumAlkalide => "KHO2",
+            Boost::LemergiumAcid => "LH2O",
+            Boost::LemergiumAlkalide => "LHO2",
+            Boost::ZynthiumAcid => "ZH2O",
+            Boost::ZynthiumAlkalide => "ZHO2",
+            Boost::GhodiumAcid => "GH2O",
+            Boost::GhodiumAlkalide => "GHO2",
+            Boost::CatalyzedUtriumAcid => "XUH2O",
+            Boost::CatalyzedUtriumAlkalide => "XUHO2",
+            Boost::CatalyzedKeaniumAcid => "XKH2O",
+            Boost::CatalyzedKeaniumAlkalide => "XKHO2",
+            Boost::CatalyzedLemergiumAcid => "XLH2O",
+            Boost::CatalyzedLemergiumAlkalide => "XLHO2",
+            Boost::CatalyzedZynthiumAcid => "XZH2O",
+            Boost::CatalyzedZynthiumAlkalide => "XZHO2",
+            Boost::CatalyzedGhodiumAcid => "XGH2O",
+            Boost::CatalyzedGhodiumAlkalide => "XGHO2",
+            Boost::Other(ref resource) => resource,
+        }
+    }
+}
+
+impl fmt::Display for Boost {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.to_resource_string())
+    }
+}
+
+mod serde {
+    use std::fmt;
+
+    use serde::de::{Deserialize, Deserializer, Error, Visitor};
+    use serde::ser::{Serialize, Serializer};
+
+    use super::Boost;
+
+    impl Serialize for Boost {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_str(self.to_resource_string())
+        }
+    }
+
+    struct BoostVisitor;
+
+    impl<'de> Visitor<'de> for BoostVisitor {
+        type Value = Boost;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a compound resource type string")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(Boost::from_resource_string(v))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Boost {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_str(BoostVisitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BodyPart, Boost};
+
+    #[test]
+    fn parts_and_boosts() {
+        let part: BodyPart = serde_json::from_value(json!("ranged_attack")).unwrap();
+        assert_eq!(part, BodyPart::RangedAttack);
+        assert_eq!(
+            serde_json::to_value(part).unwrap(),
+            json!(part.to_part_string())
+        );
+        assert_eq!(BodyPart::Claim.cost(), 600);
+
+        for (boost, part, tier) in [
+            (Boost::UtriumHydride, Some(BodyPart::Attack), Some(1)),
+            (Boost::LemergiumAlkalide, Some(BodyPart::Heal), Some(2)),
+            (
+                Boost::CatalyzedZynthiumAlkalide,
+                Some(BodyPart::Move),
+                Some(3),
+            ),
+            (Boost::GhodiumAcid, Some(BodyPart::Work), Some(2)),
+            (Boost::Other("XQH2O".to_owned()), None, None),
+        ] {
+            assert_eq!((boost.part(), boost.tier()), (part, tier));
+            let json = serde_json::to_value(&boost).unwrap();
+            assert_eq!(json, json!(boost.to_resource_string()));
+            assert_eq!(serde_json::from_value::<Boost>(json).unwrap(), boost);
+        }
+    }
+}
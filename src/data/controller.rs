@@ -0,0 +1,173 @@
+//! Structures relating to room controllers.
+use crate::decoders::null_as_default;
+
+use super::RoomSign;
+
+/// A struct describing a room's reservation.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ControllerReservation {
+    /// The user ID of the user reserving this controller.
+    pub user: String,
+    /// The game time when this reservation will end if not extended.
+    pub end_time: u32,
+}
+
+/// The state of a room's controller, as it appears in controller room objects.
+///
+/// This can be parsed directly from a controller object, ignoring the fields common to all room objects.
+/// The websocket `StructureController` embeds this as its `info` field.
+///
+/// ```
+/// use screeps_api::ControllerInfo;
+///
+/// let controller: ControllerInfo = serde_json::from_value(serde_json::json!({
+///     "type": "controller",
+///     "level": 3,
+///     "progress": 1000,
+///     "downgradeTime": 20020430,
+///     "safeModeCooldown": null,
+///     "isPowerEnabled": true,
+/// }))
+/// .unwrap();
+/// assert_eq!(controller.progress_required(), Some(135000));
+/// assert!(controller.is_power_enabled);
+/// ```
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ControllerInfo {
+    /// The current controller level (0-8 inclusive).
+    pub level: u16,
+    /// The number of upgrade points the controller has.
+    #[serde(default)]
+    pub progress: u64,
+    /// The number of upgrade points needed before the next level is reached.
+    #[serde(default)]
+    pub progress_total: u64,
+    /// The game time at which the controller downgrades, unless upgraded before then.
+    ///
+    /// None if unowned.
+    #[serde(default)]
+    pub downgrade_time: Option<u64>,
+    /// Controller reservation.
+    #[serde(default)]
+    pub reservation: Option<ControllerReservation>,
+    /// Game time at which the current safemode will end, if any.
+    #[serde(default)]
+    pub safe_mode: Option<u32>,
+    /// How many more safemodes are available.
+    #[serde(default, deserialize_with = "null_as_default::deserialize")]
+    pub safe_mode_available: u32,
+    /// The game time that must be reached before safe mode can be used on the controller.
+    #[serde(default, deserialize_with = "null_as_default::deserialize")]
+    pub safe_mode_cooldown: u32,
+    /// The room sign.
+    #[serde(default)]
+    pub sign: Option<RoomSign>,
+    /// The number of ticks until upgrading is no longer blocked.
+    #[serde(default)]
+    pub upgrade_blocked: Option<u32>,
+    /// ID of the user who owns the controller, and thus the room.
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Whether power creeps can use their powers in this room.
+    #[serde(default, deserialize_with = "null_as_default::deserialize")]
+    pub is_power_enabled: bool,
+}
+
+impl ControllerInfo {
+    /// The progress required for this controller's level (100% dependent on `level`).
+    ///
+    /// Returns `None` if level is outside of 1..=7.
+    pub fn progress_required(&self) -> Option<u32> {
+        Self::progress_required_at_level(self.level)
+    }
+
+    /// The total downgrade time for this controller's level (100% dependent on `level`).
+    ///
+    /// Returns `None` if level is outside of 1..=8.
+    pub fn total_downgrade_time(&self) -> Option<u32> {
+        Self::total_downgrade_time_at_level(self.level)
+    }
+
+    /// Whether this controller is owned by a user.
+    pub fn is_owned(&self) -> bool {
+        self.user.is_some()
+    }
+
+    /// Progress required for a controller to go from the given level to the next.
+    ///
+    /// Returns `None` if level is outside of 1..=7.
+    pub fn progress_required_at_level(level: u16) -> Option<u32> {
+        match level {
+            1 => Some(200),
+            2 => Some(45000),
+            3 => Some(135000),
+            4 => Some(405000),
+            5 => Some(1215000),
+            6 => Some(3645000),
+            7 => Some(10935000),
+            _ => None,
+        }
+    }
+
+    /// Total downgrade time for a level (game ticks since the last upgrade that a controller will loose a level).
+    ///
+    /// Returns `None` if level is outside of 1..=8.
+    pub fn total_downgrade_time_at_level(level: u16) -> Option<u32> {
+        match level {
+            1 => Some(20000),
+            2 => Some(5000),
+            3 => Some(10000),
+            4 => Some(20000),
+            5 => Some(40000),
+            6 => Some(60000),
+            7 => Some(100000),
+            8 => Some(150000),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ControllerInfo, ControllerReservation};
+
+    #[test]
+    fn parse_controller_info() {
+        let info: ControllerInfo = serde_json::from_value(json!({
+            "_id": "579fa94c0700be0674d2f15a",
+            "downgradeTime": null,
+            "level": 0,
+            "reservation": {
+                "endTime": 20158024,
+                "user": "57874d42d0ae911e3bd15bbc"
+            },
+            "room": "W12S55",
+            "safeModeAvailable": null,
+            "type": "controller",
+            "user": null,
+            "x": 22,
+            "y": 37,
+        }))
+        .unwrap();
+
+        assert_eq!(
+            info.reservation,
+            Some(ControllerReservation {
+                user: "57874d42d0ae911e3bd15bbc".to_owned(),
+                end_time: 20158024,
+            })
+        );
+        assert_eq!(info.safe_mode_available, 0);
+        assert!(!info.is_owned());
+        assert!(!info.is_power_enabled);
+        assert_eq!(info.progress_required(), None);
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(
+            serde_json::from_value::<ControllerInfo>(json).unwrap(),
+            info
+        );
+    }
+}
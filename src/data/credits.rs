@@ -0,0 +1,202 @@
+//! Structures relating to amounts of credits.
+use std::{
+    fmt, iter,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+/// The number of thousandths of a credit in each credit.
+const MILLI_PER_CREDIT: i64 = 1000;
+
+/// An amount of credits, stored exactly in thousandths of a credit.
+///
+/// The server tracks credits to 0.001 credits, but sends them as floating point numbers, so balances like
+/// `3957697.9500000584` appear. This rounds them to the nearest thousandth of a credit, so amounts can be added up and
+/// compared without rounding errors building up.
+///
+/// ```
+/// use screeps_api::Credits;
+///
+/// let price = Credits::from_f64(0.1);
+/// let total = price * 3 + Credits::from_milli(5);
+/// assert_eq!(total, Credits::from_f64(0.305));
+/// assert_eq!(total.to_string(), "0.305");
+/// ```
+#[derive(Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Credits(i64);
+
+impl Credits {
+    /// No credits.
+    pub const ZERO: Credits = Credits(0);
+
+    /// Creates an amount from a number of thousandths of a credit.
+    #[inline]
+    pub const fn from_milli(milli: i64) -> Self {
+        Credits(milli)
+    }
+
+    /// Creates an amount from a number of whole credits.
+    #[inline]
+    pub const fn from_credits(credits: i64) -> Self {
+        Credits(credits * MILLI_PER_CREDIT)
+    }
+
+    /// Creates an amount from a floating point number of credits, as the server sends them, rounding to the nearest
+    /// thousandth of a credit.
+    ///
+    /// Amounts too large to represent saturate, and NaN becomes zero.
+    #[inline]
+    pub fn from_f64(credits: f64) -> Self {
+        Credits((credits * MILLI_PER_CREDIT as f64).round() as i64)
+    }
+
+    /// The number of thousandths of a credit in this amount.
+    #[inline]
+    pub fn milli(self) -> i64 {
+        self.0
+    }
+
+    /// This amount as a floating point number of credits.
+    #[inline]
+    pub fn as_f64(self) -> f64 {
+        self.0 as f64 / MILLI_PER_CREDIT as f64
+    }
+
+    /// Adds two amounts, or returns `None` if the result would overflow.
+    #[inline]
+    pub fn checked_add(self, other: Credits) -> Option<Credits> {
+        self.0.checked_add(other.0).map(Credits)
+    }
+
+    /// Subtracts an amount from this one, or returns `None` if the result would overflow.
+    #[inline]
+    pub fn checked_sub(self, other: Credits) -> Option<Credits> {
+        self.0.checked_sub(other.0).map(Credits)
+    }
+}
+
+impl Add for Credits {
+    type Output = Credits;
+
+    #[inline]
+    fn add(self, other: Credits) -> Credits {
+        Credits(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Credits {
+    #[inline]
+    fn add_assign(&mut self, other: Credits) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Credits {
+    type Output = Credits;
+
+    #[inline]
+    fn sub(self, other: Credits) -> Credits {
+        Credits(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Credits {
+    #[inline]
+    fn sub_assign(&mut self, other: Credits) {
+        self.0 -= other.0;
+    }
+}
+
+impl Neg for Credits {
+    type Output = Credits;
+
+    #[inline]
+    fn neg(self) -> Credits {
+        Credits(-self.0)
+    }
+}
+
+impl Mul<i64> for Credits {
+    type Output = Credits;
+
+    /// Multiplies a price by an amount, such as the number of resources traded.
+    #[inline]
+    fn mul(self, amount: i64) -> Credits {
+        Credits(self.0 * amount)
+    }
+}
+
+impl iter::Sum for Credits {
+    fn sum<I: Iterator<Item = Credits>>(iter: I) -> Credits {
+        iter.fold(Credits::ZERO, Add::add)
+    }
+}
+
+impl fmt::Display for Credits {
+    /// Formats this amount with all three decimal places, such as `-12.500`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let milli = self.0.unsigned_abs();
+        let credits = milli / MILLI_PER_CREDIT as u64;
+        let fraction = milli % MILLI_PER_CREDIT as u64;
+        f.pad(&format!("{}{}.{:03}", sign, credits, fraction))
+    }
+}
+
+mod serde {
+    use serde::de::{Deserialize, Deserializer};
+    use serde::ser::{Serialize, Serializer};
+
+    use super::Credits;
+
+    impl Serialize for Credits {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_f64(self.as_f64())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Credits {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            f64::deserialize(deserializer).map(Credits::from_f64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Credits;
+
+    #[test]
+    fn exact_credit_arithmetic() {
+        let balance = serde_json::from_value::<Credits>(json!(3957697.9500000584f64)).unwrap();
+        assert_eq!(balance.milli(), 3_957_697_950);
+        assert_eq!(balance.to_string(), "3957697.950");
+        assert_eq!(serde_json::to_value(balance).unwrap(), json!(3957697.95));
+        assert_eq!(
+            serde_json::from_value::<Credits>(json!(12)).unwrap(),
+            Credits::from_credits(12)
+        );
+
+        let mut total = Credits::ZERO;
+        for _ in 0..10 {
+            total += Credits::from_f64(0.1);
+        }
+        assert_eq!(total, Credits::from_credits(1));
+        total -= Credits::from_f64(1.5);
+        assert_eq!(total.to_string(), "-0.500");
+        assert_eq!(-total, Credits::from_milli(500));
+        assert_eq!(
+            vec![Credits::from_milli(1); 3].into_iter().sum::<Credits>(),
+            Credits::from_milli(3)
+        );
+        assert_eq!(
+            Credits::from_milli(i64::MAX).checked_add(Credits::from_milli(1)),
+            None
+        );
+    }
+}
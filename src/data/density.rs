@@ -0,0 +1,116 @@
+//! Mineral densities.
+
+/// The density of a mineral, which decides how much of its resource is added each time it regenerates.
+///
+/// Sent by the server as a number from 1 for `Low` to 4 for `Ultra`.
+///
+/// ```
+/// use screeps_api::Density;
+///
+/// assert_eq!(Density::from_number(3), Some(Density::High));
+/// assert_eq!(Density::High.amount(), 70000);
+/// assert_eq!(u8::from(Density::Ultra), 4);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Density {
+    /// Low density, adding 15000 resources.
+    Low,
+    /// Moderate density, adding 35000 resources.
+    Moderate,
+    /// High density, adding 70000 resources.
+    High,
+    /// Ultra density, adding 100000 resources.
+    Ultra,
+}
+
+impl Density {
+    /// Finds the density with the given number, from 1 for `Low` to 4 for `Ultra`.
+    pub fn from_number(density: u8) -> Option<Density> {
+        match density {
+            1 => Some(Density::Low),
+            2 => Some(Density::Moderate),
+            3 => Some(Density::High),
+            4 => Some(Density::Ultra),
+            _ => None,
+        }
+    }
+
+    /// The number of this density, from 1 for `Low` to 4 for `Ultra`.
+    pub fn number(self) -> u8 {
+        match self {
+            Density::Low => 1,
+            Density::Moderate => 2,
+            Density::High => 3,
+            Density::Ultra => 4,
+        }
+    }
+
+    /// The amount of resources added when a mineral with this density regenerates.
+    pub fn amount(self) -> u32 {
+        match self {
+            Density::Low => 15000,
+            Density::Moderate => 35000,
+            Density::High => 70000,
+            Density::Ultra => 100000,
+        }
+    }
+}
+
+impl From<Density> for u8 {
+    #[inline]
+    fn from(density: Density) -> u8 {
+        density.number()
+    }
+}
+
+mod serde {
+    use serde::de::{Deserialize, Deserializer, Error, Unexpected};
+    use serde::ser::{Serialize, Serializer};
+
+    use super::Density;
+
+    impl Serialize for Density {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_u8(self.number())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Density {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let density = u8::deserialize(deserializer)?;
+            Density::from_number(density).ok_or_else(|| {
+                D::Error::invalid_value(
+                    Unexpected::Unsigned(u64::from(density)),
+                    &"a density from 1 to 4",
+                )
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Density;
+
+    #[test]
+    fn density_numbers() {
+        for number in 1..=4 {
+            let density = Density::from_number(number).unwrap();
+            assert_eq!(density.number(), number);
+            assert_eq!(
+                serde_json::from_value::<Density>(json!(number)).unwrap(),
+                density
+            );
+        }
+        assert_eq!(Density::from_number(0), None);
+        assert!(Density::Low < Density::Ultra);
+        assert!(serde_json::from_value::<Density>(json!(5)).is_err());
+        assert_eq!(serde_json::to_value(Density::Moderate).unwrap(), json!(2));
+    }
+}
@@ -0,0 +1,130 @@
+//! Structures relating to the market.
+use super::{Credits, ResourceType, RoomName};
+
+/// Whether a market order buys or sells its resource.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderType {
+    /// The order buys resources from players dealing with it.
+    Buy,
+    /// The order sells resources to players dealing with it.
+    Sell,
+}
+
+/// An order on the market, as listed by the orders and my orders endpoints.
+///
+/// The server stores prices in thousandths of a credit, and both endpoints convert them to credits, parsed here
+/// into exact [`Credits`]. The two endpoints return orders in slightly different shapes:
+///
+/// - orders of one resource, from `game/market/orders`, are listed for the requested shard, and leave `shard` as
+///   `None`.
+/// - the user's own orders, from `game/market/my-orders`, are grouped by shard on sharded servers, and have `shard`
+///   filled in from that grouping. These include inactive orders, while `game/market/orders` only lists active
+///   ones and so `active` defaults to `true` when it isn't sent.
+///
+/// The orders index, `game/market/orders-index`, lists only how many orders each resource has, as
+/// `MarketResourceSummary`s.
+///
+/// [`Credits`]: struct.Credits.html
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketOrder {
+    /// The unique ID of this order.
+    #[serde(rename = "_id")]
+    pub id: String,
+    /// Whether this order buys or sells.
+    #[serde(rename = "type")]
+    pub order_type: OrderType,
+    /// The resource this order trades.
+    pub resource_type: ResourceType,
+    /// The price of each unit of the resource.
+    pub price: Credits,
+    /// The amount which can currently be dealt, limited by the resources or credits the owner has available.
+    pub amount: u32,
+    /// The amount left to deal before this order is complete.
+    pub remaining_amount: u32,
+    /// The total amount this order was created for, including any extensions.
+    pub total_amount: u32,
+    /// The room this order was placed from, or `None` for resources not held in rooms, such as pixels.
+    #[serde(default)]
+    pub room_name: Option<RoomName>,
+    /// The user ID of the owner of this order, if listed.
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Whether this order is active, and can be dealt with.
+    #[serde(default = "default_active")]
+    pub active: bool,
+    /// The game time at which this order was created, if listed.
+    #[serde(default)]
+    pub created: Option<u32>,
+    /// The real date/time at which this order was created, in milliseconds since the unix epoch, if listed.
+    #[serde(default)]
+    pub created_timestamp: Option<i64>,
+    /// The shard this order is on, when listing the user's own orders on a sharded server.
+    #[serde(default)]
+    pub shard: Option<String>,
+    /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
+    _non_exhaustive: (),
+}
+
+fn default_active() -> bool {
+    true
+}
+
+impl MarketOrder {
+    /// The total price of dealing the given amount with this order.
+    pub fn price_of(&self, amount: u32) -> Credits {
+        self.price * i64::from(amount)
+    }
+
+    /// Whether this order has been dealt completely.
+    pub fn is_complete(&self) -> bool {
+        self.remaining_amount == 0
+    }
+
+    /// The real date/time at which this order was created, if listed.
+    #[cfg(feature = "chrono")]
+    pub fn created_datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.created_timestamp
+            .map(crate::decoders::millis_to_datetime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MarketOrder, OrderType};
+    use crate::data::{Credits, ResourceType};
+
+    #[test]
+    fn parse_market_order() {
+        let order: MarketOrder = serde_json::from_value(json!({
+            "_id": "5a9f8e7d6c5b4a3928171605",
+            "created": 23481634,
+            "createdTimestamp": 1518642154470i64,
+            "user": "57874d42d0ae911e3bd15bbc",
+            "active": true,
+            "type": "sell",
+            "amount": 500,
+            "remainingAmount": 1500,
+            "resourceType": "XGHO2",
+            "price": 1.25,
+            "totalAmount": 2000,
+            "roomName": "W41N48"
+        }))
+        .unwrap();
+
+        assert_eq!(order.order_type, OrderType::Sell);
+        assert_eq!(order.resource_type, ResourceType::CatalyzedGhodiumAlkalide);
+        assert_eq!(order.price, Credits::from_milli(1250));
+        assert_eq!(order.price_of(3), Credits::from_milli(3750));
+        assert_eq!(order.created, Some(23481634));
+        assert_eq!(order.created_timestamp, Some(1518642154470));
+        #[cfg(feature = "chrono")]
+        assert_eq!(
+            order.created_datetime().map(|date| date.timestamp_millis()),
+            Some(1518642154470)
+        );
+        assert!(!order.is_complete());
+    }
+}
@@ -1,10 +1,32 @@
 //! Data structures that appear in multiple API endpoint results.
+mod badge_svg;
+mod body;
+mod controller;
+mod credits;
+mod density;
 mod errors;
+mod market;
+mod position;
+mod power_creeps;
+mod resources;
 mod room_name;
+mod room_xy;
 mod rooms;
+mod season;
 mod users;
 
+pub use self::badge_svg::*;
+pub use self::body::*;
+pub use self::controller::*;
+pub use self::credits::*;
+pub use self::density::*;
 pub use self::errors::*;
+pub use self::market::*;
+pub use self::position::*;
+pub use self::power_creeps::*;
+pub use self::resources::*;
 pub use self::room_name::*;
+pub use self::room_xy::*;
 pub use self::rooms::*;
+pub use self::season::*;
 pub use self::users::*;
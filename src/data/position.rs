@@ -0,0 +1,107 @@
+//! Structures relating to positions anywhere in the world.
+use std::fmt;
+
+use super::{RoomName, RoomXY, ROOM_SIZE};
+
+/// A position in a specific room.
+///
+/// Positions convert to and from absolute world coordinates, which are continuous across room edges: `x` increases
+/// from west to east, and `y` from north to south, as within rooms. Tile (0, 0) of `E0S0` is at the world origin.
+///
+/// ```
+/// use screeps_api::{Position, RoomName, RoomXY};
+///
+/// let a = Position::new(RoomName::new("W0N0").unwrap(), RoomXY::new(49, 49).unwrap());
+/// let b = Position::new(RoomName::new("E0S0").unwrap(), RoomXY::new(0, 0).unwrap());
+/// assert_eq!(a.world_coords(), (-1, -1));
+/// assert_eq!(b.world_coords(), (0, 0));
+/// assert_eq!(a.distance_to(b), 1);
+/// assert_eq!(Position::from_world_coords(-1, -1), a);
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct Position {
+    /// The room the position is in.
+    pub room: RoomName,
+    /// The position within the room.
+    #[serde(flatten)]
+    pub xy: RoomXY,
+}
+
+impl Position {
+    /// Creates a position at the given coordinates in the given room.
+    #[inline]
+    pub fn new(room: RoomName, xy: RoomXY) -> Self {
+        Position { room, xy }
+    }
+
+    /// Gets the absolute world coordinates of this position, as (x, y).
+    #[inline]
+    pub fn world_coords(self) -> (i32, i32) {
+        let size = i32::from(ROOM_SIZE);
+        // room y coordinates increase to the north, so flip them to match positions within rooms.
+        (
+            self.room.x_coord * size + i32::from(self.xy.x()),
+            (-self.room.y_coord - 1) * size + i32::from(self.xy.y()),
+        )
+    }
+
+    /// Gets the position at the given absolute world coordinates.
+    pub fn from_world_coords(x: i32, y: i32) -> Self {
+        let size = i32::from(ROOM_SIZE);
+        let room = RoomName {
+            x_coord: x.div_euclid(size),
+            y_coord: -y.div_euclid(size) - 1,
+        };
+        let xy = RoomXY::new(x.rem_euclid(size) as u8, y.rem_euclid(size) as u8)
+            .expect("expected remainder of room size to be within a room");
+
+        Position { room, xy }
+    }
+
+    /// Gets the distance to another position, possibly in another room: the number of moves to get there, moving
+    /// diagonally as well as straight, and ignoring room edges and walls.
+    #[inline]
+    pub fn distance_to(self, other: Position) -> u32 {
+        let (x, y) = self.world_coords();
+        let (other_x, other_y) = other.world_coords();
+        ((x - other_x).abs()).max((y - other_y).abs()) as u32
+    }
+}
+
+impl fmt::Display for Position {
+    /// Formats this position as the game does, such as `[room E1N1 pos 25,25]`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[room {} pos {},{}]",
+            self.room,
+            self.xy.x(),
+            self.xy.y()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Position;
+    use crate::{RoomName, RoomXY};
+
+    #[test]
+    fn world_coords() {
+        let pos = Position::new(RoomName::new("W2N3").unwrap(), RoomXY::new(10, 20).unwrap());
+        assert_eq!(pos.world_coords(), (-3 * 50 + 10, -4 * 50 + 20));
+        assert_eq!(Position::from_world_coords(-140, -180), pos);
+        assert_eq!(pos.to_string(), "[room W2N3 pos 10,20]");
+
+        for &(x, y) in &[(0, 0), (-1, -1), (49, 50), (-50, 49), (1234, -5678)] {
+            assert_eq!(Position::from_world_coords(x, y).world_coords(), (x, y));
+        }
+
+        let other = Position::new(RoomName::new("W1N3").unwrap(), RoomXY::new(0, 0).unwrap());
+        assert_eq!(pos.distance_to(other), 40);
+
+        let json = serde_json::to_value(pos).unwrap();
+        assert_eq!(json, json!({ "room": "W2N3", "x": 10, "y": 20 }));
+        assert_eq!(serde_json::from_value::<Position>(json).unwrap(), pos);
+    }
+}
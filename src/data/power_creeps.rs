@@ -0,0 +1,186 @@
+//! Structures relating to power creeps.
+use std::fmt;
+
+/// A class of power creep, deciding which powers it can learn.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerClass {
+    /// An operator, the only class currently available: it generates ops and uses them to empower structures.
+    Operator,
+}
+
+/// A power which power creeps can learn and use.
+///
+/// Powers are sent by the server as numeric IDs, such as `1` for `PWR_GENERATE_OPS`.
+///
+/// ```
+/// use screeps_api::Power;
+///
+/// assert_eq!(Power::from_id(1), Some(Power::GenerateOps));
+/// assert_eq!(Power::OperateFactory.id(), 19);
+/// assert_eq!(Power::OperateFactory.to_string(), "PWR_OPERATE_FACTORY");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Power {
+    /// PWR_GENERATE_OPS: 1
+    GenerateOps,
+    /// PWR_OPERATE_SPAWN: 2
+    OperateSpawn,
+    /// PWR_OPERATE_TOWER: 3
+    OperateTower,
+    /// PWR_OPERATE_STORAGE: 4
+    OperateStorage,
+    /// PWR_OPERATE_LAB: 5
+    OperateLab,
+    /// PWR_OPERATE_EXTENSION: 6
+    OperateExtension,
+    /// PWR_OPERATE_OBSERVER: 7
+    OperateObserver,
+    /// PWR_OPERATE_TERMINAL: 8
+    OperateTerminal,
+    /// PWR_DISRUPT_SPAWN: 9
+    DisruptSpawn,
+    /// PWR_DISRUPT_TOWER: 10
+    DisruptTower,
+    /// PWR_DISRUPT_SOURCE: 11
+    DisruptSource,
+    /// PWR_SHIELD: 12
+    Shield,
+    /// PWR_REGEN_SOURCE: 13
+    RegenSource,
+    /// PWR_REGEN_MINERAL: 14
+    RegenMineral,
+    /// PWR_DISRUPT_TERMINAL: 15
+    DisruptTerminal,
+    /// PWR_OPERATE_POWER: 16
+    OperatePower,
+    /// PWR_FORTIFY: 17
+    Fortify,
+    /// PWR_OPERATE_CONTROLLER: 18
+    OperateController,
+    /// PWR_OPERATE_FACTORY: 19
+    OperateFactory,
+}
+
+/// Every power, in order of ID.
+const POWERS: [(Power, &str); 19] = [
+    (Power::GenerateOps, "PWR_GENERATE_OPS"),
+    (Power::OperateSpawn, "PWR_OPERATE_SPAWN"),
+    (Power::OperateTower, "PWR_OPERATE_TOWER"),
+    (Power::OperateStorage, "PWR_OPERATE_STORAGE"),
+    (Power::OperateLab, "PWR_OPERATE_LAB"),
+    (Power::OperateExtension, "PWR_OPERATE_EXTENSION"),
+    (Power::OperateObserver, "PWR_OPERATE_OBSERVER"),
+    (Power::OperateTerminal, "PWR_OPERATE_TERMINAL"),
+    (Power::DisruptSpawn, "PWR_DISRUPT_SPAWN"),
+    (Power::DisruptTower, "PWR_DISRUPT_TOWER"),
+    (Power::DisruptSource, "PWR_DISRUPT_SOURCE"),
+    (Power::Shield, "PWR_SHIELD"),
+    (Power::RegenSource, "PWR_REGEN_SOURCE"),
+    (Power::RegenMineral, "PWR_REGEN_MINERAL"),
+    (Power::DisruptTerminal, "PWR_DISRUPT_TERMINAL"),
+    (Power::OperatePower, "PWR_OPERATE_POWER"),
+    (Power::Fortify, "PWR_FORTIFY"),
+    (Power::OperateController, "PWR_OPERATE_CONTROLLER"),
+    (Power::OperateFactory, "PWR_OPERATE_FACTORY"),
+];
+
+impl Power {
+    /// Finds the power with the given numeric ID, as the server sends them.
+    pub fn from_id(id: u8) -> Option<Power> {
+        match id {
+            1..=19 => Some(POWERS[usize::from(id) - 1].0),
+            _ => None,
+        }
+    }
+
+    /// The numeric ID of this power.
+    pub fn id(self) -> u8 {
+        self as u8 + 1
+    }
+
+    /// The in-game constant name of this power, such as `"PWR_GENERATE_OPS"`.
+    pub fn to_constant_string(self) -> &'static str {
+        POWERS[usize::from(self.id()) - 1].1
+    }
+}
+
+impl fmt::Display for Power {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.to_constant_string())
+    }
+}
+
+/// A power creep's knowledge of one power.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerInfo {
+    /// The level the power creep has learned this power to, from 1 to 5.
+    pub level: u8,
+    /// The game time at which this power can next be used, if it has been used.
+    #[serde(default)]
+    pub cooldown_time: Option<u32>,
+}
+
+impl PowerInfo {
+    /// The number of game ticks until this power can next be used, given the current game time.
+    pub fn cooldown(&self, game_time: u32) -> u32 {
+        self.cooldown_time
+            .map_or(0, |time| time.saturating_sub(game_time))
+    }
+}
+
+mod serde {
+    use serde::de::{Deserialize, Deserializer, Error, Unexpected};
+    use serde::ser::{Serialize, Serializer};
+
+    use super::Power;
+
+    impl Serialize for Power {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_u8(self.id())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Power {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let id = u8::deserialize(deserializer)?;
+            Power::from_id(id).ok_or_else(|| {
+                D::Error::invalid_value(Unexpected::Unsigned(id.into()), &"a power ID from 1 to 19")
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{Power, PowerClass, PowerInfo};
+
+    #[test]
+    fn parse_powers() {
+        for id in 1..=19 {
+            assert_eq!(Power::from_id(id).unwrap().id(), id);
+        }
+        assert_eq!(Power::from_id(20), None);
+
+        let class: PowerClass = serde_json::from_value(json!("operator")).unwrap();
+        assert_eq!(class, PowerClass::Operator);
+
+        let powers: HashMap<Power, PowerInfo> = serde_json::from_value(json!({
+            "1": { "level": 3, "cooldownTime": 20000050 },
+            "13": { "level": 1 },
+        }))
+        .unwrap();
+        assert_eq!(powers[&Power::GenerateOps].cooldown(20000000), 50);
+        assert_eq!(powers[&Power::RegenSource].cooldown(20000000), 0);
+        assert!(serde_json::from_value::<Power>(json!(0)).is_err());
+    }
+}
@@ -0,0 +1,401 @@
+//! Structures relating to resources.
+use std::{convert::Infallible, fmt, str::FromStr};
+
+use super::Boost;
+
+macro_rules! resource_types {
+    (
+        $(
+            $( #[$attr:meta] )*
+            $name:ident => $string:literal,
+        )*
+    ) => (
+        /// All resource identifiers in the game, with `Other` for any which aren't known yet.
+        ///
+        /// Serializes to and deserializes from the in-game resource type string, such as `"energy"` or `"XGHO2"`.
+        ///
+        /// ```
+        /// use screeps_api::ResourceType;
+        ///
+        /// assert_eq!(ResourceType::from_resource_string("utrium_bar"), ResourceType::UtriumBar);
+        /// assert_eq!(ResourceType::Utrium.to_resource_string(), "U");
+        /// assert_eq!(
+        ///     ResourceType::from_resource_string("unobtainium"),
+        ///     ResourceType::Other("unobtainium".to_owned())
+        /// );
+        /// ```
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum ResourceType {
+            $(
+                $( #[$attr] )*
+                $name,
+            )*
+            /// A resource type which isn't known, such as one added to the game, or to a private server by a mod.
+            Other(String),
+        }
+
+        impl ResourceType {
+            /// Finds the resource type with the given in-game resource type string, if it is known.
+            pub fn known(resource: &str) -> Option<ResourceType> {
+                match resource {
+                    $( $string => Some(ResourceType::$name), )*
+                    _ => None,
+                }
+            }
+
+            /// Finds the in-game resource type string for this resource type.
+            pub fn to_resource_string(&self) -> &str {
+                match *self {
+                    $( ResourceType::$name => $string, )*
+                    ResourceType::Other(ref resource) => resource,
+                }
+            }
+        }
+    )
+}
+
+resource_types! {
+    /// RESOURCE_ENERGY: "energy",
+    Energy => "energy",
+    /// RESOURCE_POWER: "power",
+    Power => "power",
+    /// RESOURCE_OPS: "ops",
+    Ops => "ops",
+    /// RESOURCE_HYDROGEN: "H",
+    Hydrogen => "H",
+    /// RESOURCE_OXYGEN: "O",
+    Oxygen => "O",
+    /// RESOURCE_UTRIUM: "U",
+    Utrium => "U",
+    /// RESOURCE_LEMERGIUM: "L",
+    Lemergium => "L",
+    /// RESOURCE_KEANIUM: "K",
+    Keanium => "K",
+    /// RESOURCE_ZYNTHIUM: "Z",
+    Zynthium => "Z",
+    /// RESOURCE_CATALYST: "X",
+    Catalyst => "X",
+    /// RESOURCE_GHODIUM: "G",
+    Ghodium => "G",
+    /// RESOURCE_HYDROXIDE: "OH",
+    Hydroxide => "OH",
+    /// RESOURCE_ZYNTHIUM_KEANITE: "ZK",
+    ZynthiumKeanite => "ZK",
+    /// RESOURCE_UTRIUM_LEMERGITE: "UL",
+    UtriumLemergite => "UL",
+    /// RESOURCE_UTRIUM_HYDRIDE: "UH",
+    UtriumHydride => "UH",
+    /// RESOURCE_UTRIUM_OXIDE: "UO",
+    UtriumOxide => "UO",
+    /// RESOURCE_KEANIUM_HYDRIDE: "KH",
+    KeaniumHydride => "KH",
+    /// RESOURCE_KEANIUM_OXIDE: "KO",
+    KeaniumOxide => "KO",
+    /// RESOURCE_LEMERGIUM_HYDRIDE: "LH",
+    LemergiumHydride => "LH",
+    /// RESOURCE_LEMERGIUM_OXIDE: "LO",
+    LemergiumOxide => "LO",
+    /// RESOURCE_ZYNTHIUM_HYDRIDE: "ZH",
+    ZynthiumHydride => "ZH",
+    /// RESOURCE_ZYNTHIUM_OXIDE: "ZO",
+    ZynthiumOxide => "ZO",
+    /// RESOURCE_GHODIUM_HYDRIDE: "GH",
+    GhodiumHydride => "GH",
+    /// RESOURCE_GHODIUM_OXIDE: "GO",
+    GhodiumOxide => "GO",
+    /// RESOURCE_UTRIUM_ACID: "UH2O",
+    UtriumAcid => "UH2O",
+    /// RESOURCE_UTRIUM_ALKALIDE: "UHO2",
+    UtriumAlkalide => "UHO2",
+    /// RESOURCE_KEANIUM_ACID: "KH2O",
+    KeaniumAcid => "KH2O",
+    /// RESOURCE_KEANIUM_ALKALIDE: "KHO2",
+    KeaniumAlkalide => "KHO2",
+    /// RESOURCE_LEMERGIUM_ACID: "LH2O",
+    LemergiumAcid => "LH2O",
+    /// RESOURCE_LEMERGIUM_ALKALIDE: "LHO2",
+    LemergiumAlkalide => "LHO2",
+    /// RESOURCE_ZYNTHIUM_ACID: "ZH2O",
+    ZynthiumAcid => "ZH2O",
+    /// RESOURCE_ZYNTHIUM_ALKALIDE: "ZHO2",
+    ZynthiumAlkalide => "ZHO2",
+    /// RESOURCE_GHODIUM_ACID: "GH2O",
+    GhodiumAcid => "GH2O",
+    /// RESOURCE_GHODIUM_ALKALIDE: "GHO2",
+    GhodiumAlkalide => "GHO2",
+    /// RESOURCE_CATALYZED_UTRIUM_ACID: "XUH2O",
+    CatalyzedUtriumAcid => "XUH2O",
+    /// RESOURCE_CATALYZED_UTRIUM_ALKALIDE: "XUHO2",
+    CatalyzedUtriumAlkalide => "XUHO2",
+    /// RESOURCE_CATALYZED_KEANIUM_ACID: "XKH2O",
+    CatalyzedKeaniumAcid => "XKH2O",
+    /// RESOURCE_CATALYZED_KEANIUM_ALKALIDE: "XKHO2",
+    CatalyzedKeaniumAlkalide => "XKHO2",
+    /// RESOURCE_CATALYZED_LEMERGIUM_ACID: "XLH2O",
+    CatalyzedLemergiumAcid => "XLH2O",
+    /// RESOURCE_CATALYZED_LEMERGIUM_ALKALIDE: "XLHO2",
+    CatalyzedLemergiumAlkalide => "XLHO2",
+    /// RESOURCE_CATALYZED_ZYNTHIUM_ACID: "XZH2O",
+    CatalyzedZynthiumAcid => "XZH2O",
+    /// RESOURCE_CATALYZED_ZYNTHIUM_ALKALIDE: "XZHO2",
+    CatalyzedZynthiumAlkalide => "XZHO2",
+    /// RESOURCE_CATALYZED_GHODIUM_ACID: "XGH2O",
+    CatalyzedGhodiumAcid => "XGH2O",
+    /// RESOURCE_CATALYZED_GHODIUM_ALKALIDE: "XGHO2",
+    CatalyzedGhodiumAlkalide => "XGHO2",
+
+    // commodities
+    /// RESOURCE_SILICON: "silicon",
+    Silicon => "silicon",
+    /// RESOURCE_METAL: "metal",
+    Metal => "metal",
+    /// RESOURCE_BIOMASS: "biomass",
+    Biomass => "biomass",
+    /// RESOURCE_MIST: "mist",
+    Mist => "mist",
+    /// RESOURCE_UTRIUM_BAR: "utrium_bar",
+    UtriumBar => "utrium_bar",
+    /// RESOURCE_LEMERGIUM_BAR: "lemergium_bar",
+    LemergiumBar => "lemergium_bar",
+    /// RESOURCE_ZYNTHIUM_BAR: "zynthium_bar",
+    ZynthiumBar => "zynthium_bar",
+    /// RESOURCE_KEANIUM_BAR: "keanium_bar",
+    KeaniumBar => "keanium_bar",
+    /// RESOURCE_GHODIUM_MELT: "ghodium_melt",
+    GhodiumMelt => "ghodium_melt",
+    /// RESOURCE_OXIDANT: "oxidant",
+    Oxidant => "oxidant",
+    /// RESOURCE_REDUCTANT: "reductant",
+    Reductant => "reductant",
+    /// RESOURCE_PURIFIER: "purifier",
+    Purifier => "purifier",
+    /// RESOURCE_BATTERY: "battery",
+    Battery => "battery",
+    /// RESOURCE_COMPOSITE: "composite",
+    Composite => "composite",
+    /// RESOURCE_CRYSTAL: "crystal",
+    Crystal => "crystal",
+    /// RESOURCE_LIQUID: "liquid",
+    Liquid => "liquid",
+    /// RESOURCE_WIRE: "wire",
+    Wire => "wire",
+    /// RESOURCE_SWITCH: "switch",
+    Switch => "switch",
+    /// RESOURCE_TRANSISTOR: "transistor",
+    Transistor => "transistor",
+    /// RESOURCE_MICROCHIP: "microchip",
+    Microchip => "microchip",
+    /// RESOURCE_CIRCUIT: "circuit",
+    Circuit => "circuit",
+    /// RESOURCE_DEVICE: "device",
+    Device => "device",
+    /// RESOURCE_CELL: "cell",
+    Cell => "cell",
+    /// RESOURCE_PHLEGM: "phlegm",
+    Phlegm => "phlegm",
+    /// RESOURCE_TISSUE: "tissue",
+    Tissue => "tissue",
+    /// RESOURCE_MUSCLE: "muscle",
+    Muscle => "muscle",
+    /// RESOURCE_ORGANOID: "organoid",
+    Organoid => "organoid",
+    /// RESOURCE_ORGANISM: "organism",
+    Organism => "organism",
+    /// RESOURCE_ALLOY: "alloy",
+    Alloy => "alloy",
+    /// RESOURCE_TUBE: "tube",
+    Tube => "tube",
+    /// RESOURCE_FIXTURES: "fixtures",
+    Fixtures => "fixtures",
+    /// RESOURCE_FRAME: "frame",
+    Frame => "frame",
+    /// RESOURCE_HYDRAULICS: "hydraulics",
+    Hydraulics => "hydraulics",
+    /// RESOURCE_MACHINE: "machine",
+    Machine => "machine",
+    /// RESOURCE_CONDENSATE: "condensate",
+    Condensate => "condensate",
+    /// RESOURCE_CONCENTRATE: "concentrate",
+    Concentrate => "concentrate",
+    /// RESOURCE_EXTRACT: "extract",
+    Extract => "extract",
+    /// RESOURCE_SPIRIT: "spirit",
+    Spirit => "spirit",
+    /// RESOURCE_EMANATION: "emanation",
+    Emanation => "emanation",
+    /// RESOURCE_ESSENCE: "essence",
+    Essence => "essence",
+
+    // season resources
+    /// RESOURCE_SCORE: "score",
+    Score => "score",
+    /// RESOURCE_SYMBOL_ALEPH: "symbol_aleph",
+    SymbolAleph => "symbol_aleph",
+    /// RESOURCE_SYMBOL_BETH: "symbol_beth",
+    SymbolBeth => "symbol_beth",
+    /// RESOURCE_SYMBOL_GIMMEL: "symbol_gimmel",
+    SymbolGimmel => "symbol_gimmel",
+    /// RESOURCE_SYMBOL_DALETH: "symbol_daleth",
+    SymbolDaleth => "symbol_daleth",
+    /// RESOURCE_SYMBOL_HE: "symbol_he",
+    SymbolHe => "symbol_he",
+    /// RESOURCE_SYMBOL_WAW: "symbol_waw",
+    SymbolWaw => "symbol_waw",
+    /// RESOURCE_SYMBOL_ZAYIN: "symbol_zayin",
+    SymbolZayin => "symbol_zayin",
+    /// RESOURCE_SYMBOL_HETH: "symbol_heth",
+    SymbolHeth => "symbol_heth",
+    /// RESOURCE_SYMBOL_TETH: "symbol_teth",
+    SymbolTeth => "symbol_teth",
+    /// RESOURCE_SYMBOL_YODH: "symbol_yodh",
+    SymbolYodh => "symbol_yodh",
+    /// RESOURCE_SYMBOL_KAPH: "symbol_kaph",
+    SymbolKaph => "symbol_kaph",
+    /// RESOURCE_SYMBOL_LAMEDH: "symbol_lamedh",
+    SymbolLamedh => "symbol_lamedh",
+    /// RESOURCE_SYMBOL_MEM: "symbol_mem",
+    SymbolMem => "symbol_mem",
+    /// RESOURCE_SYMBOL_NUN: "symbol_nun",
+    SymbolNun => "symbol_nun",
+    /// RESOURCE_SYMBOL_SAMEKH: "symbol_samekh",
+    SymbolSamekh => "symbol_samekh",
+    /// RESOURCE_SYMBOL_AYIN: "symbol_ayin",
+    SymbolAyin => "symbol_ayin",
+    /// RESOURCE_SYMBOL_PE: "symbol_pe",
+    SymbolPe => "symbol_pe",
+    /// RESOURCE_SYMBOL_TSADE: "symbol_tsade",
+    SymbolTsade => "symbol_tsade",
+    /// RESOURCE_SYMBOL_QOPH: "symbol_qoph",
+    SymbolQoph => "symbol_qoph",
+    /// RESOURCE_SYMBOL_RES: "symbol_res",
+    SymbolRes => "symbol_res",
+    /// RESOURCE_SYMBOL_SIN: "symbol_sin",
+    SymbolSin => "symbol_sin",
+    /// RESOURCE_SYMBOL_TAW: "symbol_taw",
+    SymbolTaw => "symbol_taw",
+    /// RESOURCE_THORIUM: "thorium",
+    Thorium => "thorium",
+}
+
+impl ResourceType {
+    /// Finds the resource type with the given in-game resource type string, such as `"energy"`, as `Other` if it
+    /// isn't known.
+    pub fn from_resource_string(resource: &str) -> ResourceType {
+        ResourceType::known(resource).unwrap_or_else(|| ResourceType::Other(resource.to_owned()))
+    }
+
+    /// Whether this is a known resource type, rather than `Other`.
+    pub fn is_known(&self) -> bool {
+        !matches!(*self, ResourceType::Other(_))
+    }
+}
+
+impl FromStr for ResourceType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Infallible> {
+        Ok(ResourceType::from_resource_string(s))
+    }
+}
+
+impl fmt::Display for ResourceType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.to_resource_string())
+    }
+}
+
+impl From<Boost> for ResourceType {
+    fn from(boost: Boost) -> ResourceType {
+        ResourceType::from_resource_string(boost.to_resource_string())
+    }
+}
+
+mod serde {
+    use std::fmt;
+
+    use serde::de::{Deserialize, Deserializer, Error, Visitor};
+    use serde::ser::{Serialize, Serializer};
+
+    use super::ResourceType;
+
+    impl Serialize for ResourceType {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_str(self.to_resource_string())
+        }
+    }
+
+    struct ResourceTypeVisitor;
+
+    impl<'de> Visitor<'de> for ResourceTypeVisitor {
+        type Value = ResourceType;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a resource type string")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(ResourceType::from_resource_string(v))
+        }
+
+        fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(ResourceType::known(&v).unwrap_or(ResourceType::Other(v)))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ResourceType {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_str(ResourceTypeVisitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::ResourceType;
+    use crate::Boost;
+
+    #[test]
+    fn parse_resource_types() {
+        let store: HashMap<ResourceType, i32> = serde_json::from_value(json!({
+            "energy": 100,
+            "ops": 5,
+            "XGHO2": 30,
+            "switch": 2,
+            "symbol_aleph": 1,
+            "season_mystery": 7,
+        }))
+        .unwrap();
+
+        assert_eq!(store[&ResourceType::Energy], 100);
+        assert_eq!(store[&ResourceType::Ops], 5);
+        assert_eq!(store[&ResourceType::CatalyzedGhodiumAlkalide], 30);
+        assert_eq!(store[&ResourceType::Switch], 2);
+        assert_eq!(store[&ResourceType::SymbolAleph], 1);
+        assert_eq!(store[&ResourceType::Other("season_mystery".to_owned())], 7);
+        assert!(!ResourceType::Other("season_mystery".to_owned()).is_known());
+
+        assert_eq!(
+            serde_json::to_value(ResourceType::GhodiumMelt).unwrap(),
+            json!("ghodium_melt")
+        );
+        assert_eq!(
+            ResourceType::from(Boost::UtriumHydride),
+            ResourceType::UtriumHydride
+        );
+        assert_eq!("Z".parse::<ResourceType>().unwrap(), ResourceType::Zynthium);
+    }
+}
@@ -1,8 +1,11 @@
 //! Structures relating to room name parsing.
 use std::borrow::Cow;
-use std::{error, fmt, ops};
+use std::{cmp::Ordering, error, fmt, iter::FusedIterator, ops, str::FromStr};
 
 /// A structure representing a room name.
+///
+/// Room names serialize to and deserialize from the game's format, such as `"E5N39"`, so they can be used directly
+/// in JSON map keys, configuration and state files.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct RoomName {
     /// Inner x coordinate representation.
@@ -61,6 +64,26 @@ impl fmt::Display for RoomName {
     }
 }
 
+impl Ord for RoomName {
+    /// Orders room names as they are laid out on the map, a row at a time from north to south, and from west to
+    /// east within each row. This is the order [`RoomRange`] iterates rooms in.
+    ///
+    /// [`RoomRange`]: struct.RoomRange.html
+    fn cmp(&self, other: &RoomName) -> Ordering {
+        other
+            .y_coord
+            .cmp(&self.y_coord)
+            .then(self.x_coord.cmp(&other.x_coord))
+    }
+}
+
+impl PartialOrd for RoomName {
+    #[inline]
+    fn partial_cmp(&self, other: &RoomName) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl From<RoomName> for RoomNameAbsoluteCoordinates {
     fn from(other: RoomName) -> RoomNameAbsoluteCoordinates {
         RoomNameAbsoluteCoordinates {
@@ -99,8 +122,124 @@ impl RoomName {
             y_coord: if north { y_pos } else { -y_pos - 1 },
         }
     }
+
+    /// Gets the linear distance to another room: the number of rooms to cross to get there, moving diagonally as
+    /// well as straight. This is the Chebyshev distance, as in the game's `Game.map.getRoomLinearDistance`.
+    #[inline]
+    pub fn linear_distance_to(self, other: RoomName) -> u32 {
+        let (x, y) = self - other;
+        x.abs().max(y.abs()) as u32
+    }
+
+    /// Gets the Manhattan distance to another room: the number of rooms to cross to get there, moving straight only.
+    #[inline]
+    pub fn manhattan_distance_to(self, other: RoomName) -> u32 {
+        let (x, y) = self - other;
+        (x.abs() + y.abs()) as u32
+    }
+
+    /// Iterates over all rooms within the given linear distance of this room, including this room.
+    ///
+    /// ```
+    /// use screeps_api::RoomName;
+    ///
+    /// let center = RoomName::new("W10N10").unwrap();
+    /// let rooms = center.rooms_within(10);
+    /// assert_eq!(rooms.len(), 21 * 21);
+    /// assert!(rooms.into_iter().all(|room| room.linear_distance_to(center) <= 10));
+    /// ```
+    #[inline]
+    pub fn rooms_within(self, distance: u32) -> RoomRange {
+        let distance = distance as i32;
+        RoomRange::new(self - (distance, distance), self + (distance, distance))
+    }
 }
 
+impl ops::AddAssign<(i32, i32)> for RoomName {
+    /// Adds an (x, y) coordinate pair to this room name in place.
+    #[inline]
+    fn add_assign(&mut self, offset: (i32, i32)) {
+        *self = *self + offset;
+    }
+}
+
+impl ops::SubAssign<(i32, i32)> for RoomName {
+    /// Subtracts an (x, y) coordinate pair from this room name in place.
+    #[inline]
+    fn sub_assign(&mut self, offset: (i32, i32)) {
+        *self = *self - offset;
+    }
+}
+
+/// An iterator over the rooms in a rectangle of the map, including the rooms on its edges.
+///
+/// Rooms are iterated a row at a time, from north to south, and from west to east within each row. Created with
+/// [`RoomRange::new`] or [`RoomName::rooms_within`].
+///
+/// [`RoomRange::new`]: #method.new
+/// [`RoomName::rooms_within`]: struct.RoomName.html#method.rooms_within
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoomRange {
+    /// The westmost x coordinate.
+    min_x: i32,
+    /// The eastmost x coordinate.
+    max_x: i32,
+    /// The southmost y coordinate.
+    min_y: i32,
+    /// The next room, or `None` once all rooms have been iterated.
+    next: Option<RoomName>,
+}
+
+impl RoomRange {
+    /// Creates an iterator over the rectangle with the given rooms at opposite corners.
+    pub fn new(corner: RoomName, opposite: RoomName) -> Self {
+        RoomRange {
+            min_x: corner.x_coord.min(opposite.x_coord),
+            max_x: corner.x_coord.max(opposite.x_coord),
+            min_y: corner.y_coord.min(opposite.y_coord),
+            next: Some(RoomName {
+                x_coord: corner.x_coord.min(opposite.x_coord),
+                y_coord: corner.y_coord.max(opposite.y_coord),
+            }),
+        }
+    }
+}
+
+impl Iterator for RoomRange {
+    type Item = RoomName;
+
+    fn next(&mut self) -> Option<RoomName> {
+        let room = self.next?;
+        self.next = if room.x_coord < self.max_x {
+            Some(room + (1, 0))
+        } else if room.y_coord > self.min_y {
+            Some(RoomName {
+                x_coord: self.min_x,
+                y_coord: room.y_coord - 1,
+            })
+        } else {
+            None
+        };
+        Some(room)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = match self.next {
+            Some(next) => {
+                let width = (self.max_x - self.min_x) as usize + 1;
+                let rows_after = (next.y_coord - self.min_y) as usize;
+                rows_after * width + (self.max_x - next.x_coord) as usize + 1
+            }
+            None => 0,
+        };
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for RoomRange {}
+
+impl FusedIterator for RoomRange {}
+
 impl ops::Add<(i32, i32)> for RoomName {
     type Output = RoomName;
 
@@ -206,6 +345,59 @@ where
     }
 }
 
+/// A room on a specific shard, written `shard3/E15N52` as in the game's shard-aware endpoints and socket channels.
+///
+/// Serializes to and deserializes from that notation.
+///
+/// ```
+/// use screeps_api::{RoomName, ShardRoomName};
+///
+/// let room: ShardRoomName = "shard3/E15N52".parse().unwrap();
+/// assert_eq!(room.shard, "shard3");
+/// assert_eq!(room.room, RoomName::new("E15N52").unwrap());
+/// assert_eq!(room.to_string(), "shard3/E15N52");
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Debug)]
+pub struct ShardRoomName {
+    /// The name of the shard the room is on.
+    pub shard: String,
+    /// The room on that shard.
+    pub room: RoomName,
+}
+
+impl ShardRoomName {
+    /// Creates a room name on the given shard.
+    #[inline]
+    pub fn new<T: Into<String>>(shard: T, room: RoomName) -> Self {
+        ShardRoomName {
+            shard: shard.into(),
+            room,
+        }
+    }
+}
+
+impl fmt::Display for ShardRoomName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.shard, self.room)
+    }
+}
+
+impl FromStr for ShardRoomName {
+    type Err = RoomNameParseError<'static>;
+
+    /// Parses a room name on a shard, such as `shard3/E15N52`. Fails if either the shard or the room is missing.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.rfind('/') {
+            Some(idx) if idx > 0 => Ok(ShardRoomName {
+                shard: s[..idx].to_owned(),
+                room: parse_or_cheap_failure(&s[idx + 1..])
+                    .map_err(|()| RoomNameParseError::new(s.to_owned()))?,
+            }),
+            _ => Err(RoomNameParseError::new(s.to_owned())),
+        }
+    }
+}
+
 /// An error representing when a string can't be parsed into a [`RoomName`].
 ///
 /// [`RoomName`]: struct.RoomName.html
@@ -250,7 +442,7 @@ impl<'a> fmt::Display for RoomNameParseError<'a> {
 }
 
 mod serde {
-    use super::{parse_or_cheap_failure, RoomName};
+    use super::{parse_or_cheap_failure, RoomName, ShardRoomName};
 
     use std::fmt;
 
@@ -291,11 +483,49 @@ mod serde {
             deserializer.deserialize_str(RoomNameVisitor)
         }
     }
+
+    impl Serialize for ShardRoomName {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.collect_str(self)
+        }
+    }
+
+    struct ShardRoomNameVisitor;
+
+    impl<'de> Visitor<'de> for ShardRoomNameVisitor {
+        type Value = ShardRoomName;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("room name on a shard formatted `shard/(E|W)[0-9]+(N|S)[0-9]+`")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            v.parse()
+                .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ShardRoomName {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_str(ShardRoomNameVisitor)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::RoomName;
+    use std::collections::BTreeMap;
+
+    use super::{RoomName, RoomRange, ShardRoomName};
 
     #[test]
     fn parse_and_format() {
@@ -320,4 +550,80 @@ mod tests {
             assert_eq!(&RoomName::new(string).unwrap(), expected);
         }
     }
+
+    #[test]
+    fn order_and_serialize() {
+        let mut rooms = ["E0S0", "W1N0", "E0N0", "W0S0", "W1S0", "W0N0"]
+            .iter()
+            .map(|name| RoomName::new(name).unwrap())
+            .collect::<Vec<_>>();
+        rooms.sort();
+        let range = RoomRange::new(
+            RoomName::new("W1N0").unwrap(),
+            RoomName::new("E0S0").unwrap(),
+        );
+        assert_eq!(rooms, range.collect::<Vec<_>>());
+
+        let map = rooms
+            .iter()
+            .map(|&room| (room, room.x_coord))
+            .collect::<BTreeMap<_, _>>();
+        let json = serde_json::to_value(&map).unwrap();
+        assert_eq!(json["W1S0"], -2);
+        assert_eq!(
+            serde_json::from_value::<BTreeMap<RoomName, i32>>(json).unwrap(),
+            map
+        );
+        assert!(serde_json::from_value::<RoomName>(json!("N5E5")).is_err());
+    }
+
+    #[test]
+    fn parse_shard_room_names() {
+        let room = "shard3/E15N52".parse::<ShardRoomName>().unwrap();
+        assert_eq!(
+            room,
+            ShardRoomName::new("shard3", RoomName::new("E15N52").unwrap())
+        );
+        assert_eq!(room.to_string(), "shard3/E15N52");
+        assert_eq!(
+            serde_json::from_value::<ShardRoomName>(json!("shard3/E15N52")).unwrap(),
+            room
+        );
+        assert_eq!(serde_json::to_value(&room).unwrap(), "shard3/E15N52");
+
+        for invalid in &["E15N52", "/E15N52", "shard3/", "shard3/notaroom"] {
+            let err = invalid.parse::<ShardRoomName>().unwrap_err();
+            assert_eq!(err.get_failed_str(), *invalid);
+        }
+    }
+
+    #[test]
+    fn room_arithmetic() {
+        let mut room = RoomName::new("W1N0").unwrap();
+        room += (2, -1);
+        assert_eq!(room, RoomName::new("E0S0").unwrap());
+        room -= (2, -1);
+        assert_eq!(room, RoomName::new("W1N0").unwrap());
+
+        let (a, b) = (
+            RoomName::new("W2N3").unwrap(),
+            RoomName::new("E1S0").unwrap(),
+        );
+        assert_eq!(a.linear_distance_to(b), 4);
+        assert_eq!(b.linear_distance_to(a), 4);
+        assert_eq!(a.manhattan_distance_to(b), 8);
+
+        let range = RoomRange::new(
+            RoomName::new("E0S0").unwrap(),
+            RoomName::new("W1N0").unwrap(),
+        );
+        assert_eq!(range.len(), 6);
+        let rooms = range.map(|room| room.to_string()).collect::<Vec<_>>();
+        assert_eq!(rooms, ["W1N0", "W0N0", "E0N0", "W1S0", "W0S0", "E0S0"]);
+
+        let mut within = RoomName::new("E5N5").unwrap().rooms_within(1);
+        assert_eq!(within.next(), Some(RoomName::new("E4N6").unwrap()));
+        assert_eq!(within.len(), 8);
+        assert_eq!(within.last(), Some(RoomName::new("E6N4").unwrap()));
+    }
 }
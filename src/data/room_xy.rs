@@ -0,0 +1,258 @@
+//! Structures relating to positions within a room.
+use std::{cmp::Ordering, convert::TryFrom, error, fmt};
+
+/// The width and height of every room, in tiles.
+pub const ROOM_SIZE: u8 = 50;
+
+/// A position within a room, with `x` and `y` each from 0 to 49.
+///
+/// `x` increases from west to east, and `y` from north to south, as in the game. Positions are ordered a row at a
+/// time, from north to south, like the tiles of a room's terrain.
+///
+/// Serializes to and deserializes from an object with `x` and `y` fields, as positions appear in the game's data.
+///
+/// ```
+/// use screeps_api::RoomXY;
+///
+/// let spawn = RoomXY::new(25, 25).unwrap();
+/// let source = RoomXY::new(30, 22).unwrap();
+/// assert_eq!(spawn.distance_to(source), 5);
+/// assert!(RoomXY::new(50, 0).is_err());
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct RoomXY {
+    x: u8,
+    y: u8,
+}
+
+impl RoomXY {
+    /// Creates a position, failing if either coordinate is 50 or more.
+    #[inline]
+    pub fn new(x: u8, y: u8) -> Result<Self, RoomXYError> {
+        if x < ROOM_SIZE && y < ROOM_SIZE {
+            Ok(RoomXY { x, y })
+        } else {
+            Err(RoomXYError {
+                x: x.into(),
+                y: y.into(),
+            })
+        }
+    }
+
+    /// The x coordinate, from 0 on the west edge to 49 on the east edge.
+    #[inline]
+    pub fn x(self) -> u8 {
+        self.x
+    }
+
+    /// The y coordinate, from 0 on the north edge to 49 on the south edge.
+    #[inline]
+    pub fn y(self) -> u8 {
+        self.y
+    }
+
+    /// Gets the position offset by the given (x, y) amounts, or `None` if it would be outside the room.
+    #[inline]
+    pub fn checked_add(self, (x, y): (i32, i32)) -> Option<RoomXY> {
+        let x = i32::from(self.x) + x;
+        let y = i32::from(self.y) + y;
+        if (0..i32::from(ROOM_SIZE)).contains(&x) && (0..i32::from(ROOM_SIZE)).contains(&y) {
+            Some(RoomXY {
+                x: x as u8,
+                y: y as u8,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Gets the distance to another position: the number of moves to get there, moving diagonally as well as
+    /// straight. This is the Chebyshev distance, as in the game's `RoomPosition.getRangeTo`.
+    #[inline]
+    pub fn distance_to(self, other: RoomXY) -> u8 {
+        let x = (i16::from(self.x) - i16::from(other.x)).abs();
+        let y = (i16::from(self.y) - i16::from(other.y)).abs();
+        x.max(y) as u8
+    }
+
+    /// Gets the Manhattan distance to another position: the number of moves to get there, moving straight only.
+    #[inline]
+    pub fn manhattan_distance_to(self, other: RoomXY) -> u8 {
+        let x = (i16::from(self.x) - i16::from(other.x)).abs();
+        let y = (i16::from(self.y) - i16::from(other.y)).abs();
+        (x + y) as u8
+    }
+
+    /// Whether this position is on the edge of the room, where creeps move to neighboring rooms.
+    #[inline]
+    pub fn is_edge(self) -> bool {
+        self.x == 0 || self.y == 0 || self.x == ROOM_SIZE - 1 || self.y == ROOM_SIZE - 1
+    }
+
+    /// The index of this position in row-major data of a whole room, such as encoded terrain.
+    #[inline]
+    pub fn index(self) -> usize {
+        usize::from(self.y) * usize::from(ROOM_SIZE) + usize::from(self.x)
+    }
+
+    /// The position at the given index in row-major data of a whole room, or `None` if it is 2500 or more.
+    #[inline]
+    pub fn from_index(index: usize) -> Option<RoomXY> {
+        let size = usize::from(ROOM_SIZE);
+        if index < size * size {
+            Some(RoomXY {
+                x: (index % size) as u8,
+                y: (index / size) as u8,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl Ord for RoomXY {
+    fn cmp(&self, other: &RoomXY) -> Ordering {
+        self.index().cmp(&other.index())
+    }
+}
+
+impl PartialOrd for RoomXY {
+    #[inline]
+    fn partial_cmp(&self, other: &RoomXY) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for RoomXY {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl TryFrom<(u32, u32)> for RoomXY {
+    type Error = RoomXYError;
+
+    /// Converts coordinates as they are stored in room objects and flags.
+    fn try_from((x, y): (u32, u32)) -> Result<Self, RoomXYError> {
+        match (u8::try_from(x), u8::try_from(y)) {
+            (Ok(x), Ok(y)) => RoomXY::new(x, y),
+            _ => Err(RoomXYError { x, y }),
+        }
+    }
+}
+
+impl From<RoomXY> for (u8, u8) {
+    #[inline]
+    fn from(xy: RoomXY) -> (u8, u8) {
+        (xy.x, xy.y)
+    }
+}
+
+/// An error representing coordinates outside of a room. See [`RoomXY::new`].
+///
+/// [`RoomXY::new`]: struct.RoomXY.html#method.new
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct RoomXYError {
+    x: u32,
+    y: u32,
+}
+
+impl RoomXYError {
+    /// Retrieves the coordinates which were outside of the room.
+    pub fn coordinates(&self) -> (u32, u32) {
+        (self.x, self.y)
+    }
+}
+
+impl error::Error for RoomXYError {}
+
+impl fmt::Display for RoomXYError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected x and y from 0 to 49 inclusive, found ({}, {})",
+            self.x, self.y
+        )
+    }
+}
+
+mod serde {
+    use std::convert::TryFrom;
+
+    use serde::de::{Deserialize, Deserializer, Error, Unexpected};
+    use serde::ser::{Serialize, Serializer};
+
+    use super::RoomXY;
+
+    #[derive(Serialize, Deserialize)]
+    struct Coordinates {
+        x: u32,
+        y: u32,
+    }
+
+    impl Serialize for RoomXY {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            Coordinates {
+                x: self.x.into(),
+                y: self.y.into(),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for RoomXY {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let Coordinates { x, y } = Coordinates::deserialize(deserializer)?;
+            RoomXY::try_from((x, y)).map_err(|_| {
+                D::Error::invalid_value(
+                    Unexpected::Unsigned(x.max(y).into()),
+                    &"coordinates from 0 to 49 inclusive",
+                )
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::RoomXY;
+
+    #[test]
+    fn positions_in_room() {
+        let xy = RoomXY::new(0, 49).unwrap();
+        assert!(xy.is_edge());
+        assert_eq!(xy.checked_add((1, -1)), Some(RoomXY::new(1, 48).unwrap()));
+        assert_eq!(xy.checked_add((-1, 0)), None);
+        assert_eq!(xy.checked_add((0, 1)), None);
+        assert_eq!(RoomXY::from_index(xy.index()), Some(xy));
+        assert_eq!(xy.index(), 2450);
+        assert_eq!(RoomXY::from_index(2500), None);
+
+        let (a, b) = (RoomXY::new(10, 10).unwrap(), RoomXY::new(13, 8).unwrap());
+        assert_eq!(a.distance_to(b), 3);
+        assert_eq!(a.manhattan_distance_to(b), 5);
+        assert!(b < a);
+
+        assert_eq!(
+            RoomXY::try_from((49, 0)).unwrap(),
+            RoomXY::new(49, 0).unwrap()
+        );
+        assert_eq!(
+            RoomXY::try_from((300, 0)).unwrap_err().coordinates(),
+            (300, 0)
+        );
+
+        let json = serde_json::to_value(a).unwrap();
+        assert_eq!(json, json!({ "x": 10, "y": 10 }));
+        assert_eq!(serde_json::from_value::<RoomXY>(json).unwrap(), a);
+        assert!(serde_json::from_value::<RoomXY>(json!({ "x": 50, "y": 0 })).is_err());
+    }
+}
@@ -1,4 +1,9 @@
 //! Room result structures.
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+
+#[cfg(feature = "chrono")]
+use crate::decoders::millis_to_datetime;
 use crate::{decoders::timespec_seconds, error};
 
 /// A room state, returned by room status.
@@ -73,6 +78,28 @@ impl RoomState {
     pub fn closed() -> Self {
         RoomState::Closed
     }
+
+    /// The time when the novice area this room is part of expires, if it is novice.
+    #[cfg(feature = "chrono")]
+    pub fn novice_end_datetime(&self) -> Option<DateTime<Utc>> {
+        match *self {
+            RoomState::Novice { end_time } | RoomState::SecondTierNovice { end_time, .. } => {
+                Some(millis_to_datetime(end_time.sec))
+            }
+            RoomState::Nonexistant | RoomState::Closed | RoomState::Open => None,
+        }
+    }
+
+    /// The time when this room opens into its novice area, if it is second tier novice.
+    #[cfg(feature = "chrono")]
+    pub fn room_open_datetime(&self) -> Option<DateTime<Utc>> {
+        match *self {
+            RoomState::SecondTierNovice { room_open_time, .. } => {
+                Some(millis_to_datetime(room_open_time.sec))
+            }
+            _ => None,
+        }
+    }
 }
 
 /// Represents a room sign.
@@ -92,6 +119,16 @@ pub struct RoomSign {
     pub text: String,
 }
 
+#[cfg(feature = "chrono")]
+impl RoomSign {
+    /// The real date/time when the sign was set.
+    ///
+    /// The API sends this as milliseconds since the unix epoch, which `time_set` holds as its seconds.
+    pub fn datetime_set(&self) -> DateTime<Utc> {
+        millis_to_datetime(self.time_set.sec)
+    }
+}
+
 /// Represents a "hard sign" on a room, where the server has overwritten any player-placed signs for a specific period.
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Hash, Debug)]
 pub struct HardSign {
@@ -110,6 +147,19 @@ pub struct HardSign {
     pub text: String,
 }
 
+#[cfg(feature = "chrono")]
+impl HardSign {
+    /// The real date/time when the hard sign override was added.
+    pub fn start_datetime(&self) -> DateTime<Utc> {
+        millis_to_datetime(self.start.sec)
+    }
+
+    /// The real date/time when the hard sign override ends.
+    pub fn end_datetime(&self) -> DateTime<Utc> {
+        millis_to_datetime(self.end.sec)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json;
@@ -191,18 +241,23 @@ mod tests {
 
     #[test]
     fn parse_room_sign() {
-        let _: RoomSign = serde_json::from_value(json!({
+        #[cfg_attr(not(feature = "chrono"), allow(unused))]
+        let sign: RoomSign = serde_json::from_value(json!({
             "time": 16656131,
             "text": "I have plans for this block",
             "datetime": 1484071532985i64,
             "user": "57c7df771d90a0c561977377"
         }))
         .unwrap();
+
+        #[cfg(feature = "chrono")]
+        assert_eq!(sign.datetime_set().timestamp_millis(), 1484071532985);
     }
 
     #[test]
     fn parse_hard_sign() {
-        let _: HardSign = serde_json::from_value(json!({
+        #[cfg_attr(not(feature = "chrono"), allow(unused))]
+        let sign: HardSign = serde_json::from_value(json!({
             "time": 18297994,
             "datetime": 1490632558393i64,
             "text": "A new Novice Area is being planned somewhere in this sector. \
@@ -210,5 +265,8 @@ mod tests {
             "endDatetime": 1490978122587i64
         }))
         .unwrap();
+
+        #[cfg(feature = "chrono")]
+        assert_eq!(sign.end_datetime().timestamp_millis(), 1490978122587);
     }
 }
@@ -0,0 +1,224 @@
+//! Leaderboard season identifiers.
+use std::{borrow::Cow, error, fmt, str::FromStr};
+
+/// A leaderboard season: one calendar month, identified like `"2017-02"`.
+///
+/// Seasons are ordered by date, and can be passed anywhere a season is accepted as a string.
+///
+/// ```
+/// use screeps_api::SeasonId;
+///
+/// let season: SeasonId = "2017-12".parse().unwrap();
+/// assert_eq!(season.next(), SeasonId::new(2018, 1));
+/// assert_eq!(season.next().unwrap().to_string(), "2018-01");
+/// assert!(Some(season) < season.next());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SeasonId {
+    year: u16,
+    month: u8,
+}
+
+impl SeasonId {
+    /// Creates a season from its year and month, from 1 for January to 12 for December.
+    ///
+    /// Returns `None` if the month is out of range, or the year has more than 4 digits.
+    pub fn new(year: u16, month: u8) -> Option<Self> {
+        if year <= 9999 && (1..=12).contains(&month) {
+            Some(SeasonId { year, month })
+        } else {
+            None
+        }
+    }
+
+    /// The season in progress now, according to the system clock.
+    pub fn current() -> Self {
+        let now = time::now_utc();
+        SeasonId {
+            year: (now.tm_year + 1900) as u16,
+            month: now.tm_mon as u8 + 1,
+        }
+    }
+
+    /// The year of this season.
+    pub fn year(self) -> u16 {
+        self.year
+    }
+
+    /// The month of this season, from 1 for January to 12 for December.
+    pub fn month(self) -> u8 {
+        self.month
+    }
+
+    /// The season the given number of months after this one, or before it if negative.
+    ///
+    /// Returns `None` if that season's year would be before year 0 or after year 9999.
+    pub fn add_months(self, months: i32) -> Option<Self> {
+        let index = self.month_index().checked_add(months)?;
+        if !(0..=9999 * 12 + 11).contains(&index) {
+            return None;
+        }
+        Some(SeasonId {
+            year: (index / 12) as u16,
+            month: (index % 12) as u8 + 1,
+        })
+    }
+
+    /// The season after this one, or `None` if this is the last season of year 9999.
+    pub fn next(self) -> Option<Self> {
+        self.add_months(1)
+    }
+
+    /// The season before this one, or `None` if this is the first season of year 0.
+    pub fn previous(self) -> Option<Self> {
+        self.add_months(-1)
+    }
+
+    /// The number of months from the given season until this one, negative if the given season is later.
+    pub fn months_since(self, other: SeasonId) -> i32 {
+        self.month_index() - other.month_index()
+    }
+
+    fn month_index(self) -> i32 {
+        i32::from(self.year) * 12 + i32::from(self.month) - 1
+    }
+}
+
+impl fmt::Display for SeasonId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+impl FromStr for SeasonId {
+    type Err = SeasonIdParseError;
+
+    /// Parses a season like `"2017-02"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || SeasonIdParseError(s.to_owned());
+        let bytes = s.as_bytes();
+        if bytes.len() != 7 || bytes[4] != b'-' {
+            return Err(err());
+        }
+        let digits = |part: &str| -> Option<u16> {
+            if part.bytes().all(|b| b.is_ascii_digit()) {
+                part.parse().ok()
+            } else {
+                None
+            }
+        };
+        let year = digits(&s[..4]).ok_or_else(err)?;
+        let month = digits(&s[5..]).ok_or_else(err)?;
+        SeasonId::new(year, month as u8).ok_or_else(err)
+    }
+}
+
+impl<'a> From<SeasonId> for Cow<'a, str> {
+    fn from(season: SeasonId) -> Self {
+        Cow::Owned(season.to_string())
+    }
+}
+
+impl<'a> From<&'a SeasonId> for Cow<'a, str> {
+    fn from(season: &'a SeasonId) -> Self {
+        Cow::Owned(season.to_string())
+    }
+}
+
+/// An error parsing a [`SeasonId`], holding the string which failed to parse.
+///
+/// [`SeasonId`]: struct.SeasonId.html
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SeasonIdParseError(String);
+
+impl SeasonIdParseError {
+    /// Retrieves the string which failed to parse into a [`SeasonId`].
+    ///
+    /// [`SeasonId`]: struct.SeasonId.html
+    pub fn invalid_season(&self) -> &str {
+        &self.0
+    }
+}
+
+impl error::Error for SeasonIdParseError {}
+
+impl fmt::Display for SeasonIdParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected season in the format YYYY-MM, found {:?}",
+            self.0
+        )
+    }
+}
+
+mod serde {
+    use serde::de::{Deserialize, Deserializer, Error};
+    use serde::ser::{Serialize, Serializer};
+
+    use super::SeasonId;
+
+    impl Serialize for SeasonId {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for SeasonId {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let season = String::deserialize(deserializer)?;
+            season.parse().map_err(D::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SeasonId;
+
+    #[test]
+    fn season_arithmetic() {
+        let season: SeasonId = "2017-02".parse().unwrap();
+        assert_eq!((season.year(), season.month()), (2017, 2));
+        assert_eq!(
+            season
+                .previous()
+                .and_then(SeasonId::previous)
+                .unwrap()
+                .to_string(),
+            "2016-12"
+        );
+        assert_eq!(season.add_months(-14), SeasonId::new(2015, 12));
+        assert_eq!(season.add_months(23).unwrap().to_string(), "2019-01");
+        assert_eq!(
+            season
+                .next()
+                .unwrap()
+                .months_since(season.previous().unwrap()),
+            2
+        );
+        assert_eq!(SeasonId::new(9999, 12).unwrap().next(), None);
+        assert_eq!(SeasonId::new(0, 1).unwrap().previous(), None);
+
+        for invalid in &[
+            "2017-13", "2017-00", "2017-2", "17-02", "2017/02", "+201-02",
+        ] {
+            assert!(invalid.parse::<SeasonId>().is_err(), "{}", invalid);
+        }
+
+        let mut seasons: Vec<SeasonId> =
+            serde_json::from_value(json!(["2017-03", "2016-12"])).unwrap();
+        seasons.sort();
+        assert_eq!(
+            serde_json::to_value(&seasons).unwrap(),
+            json!(["2016-12", "2017-03"])
+        );
+        assert!(SeasonId::current() > season);
+    }
+}
@@ -35,7 +35,9 @@ pub enum BadgeColor {
 ///
 /// Badges can be rendered as SVG by first filling the entire image with `color1`, filling the first path (defined by
 /// badge type) with `color2`, filling the second path with `color3`, and then cutting off corners to make the badge
-/// a round circle.
+/// a round circle. [`Badge::to_svg`] does this.
+///
+/// [`Badge::to_svg`]: #method.to_svg
 #[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct Badge {
     /// Badge type, used for different badge formats
@@ -53,9 +55,71 @@ pub struct Badge {
     pub flip: bool,
 }
 
+/// Public information on a user, as included in the results of several endpoints.
+///
+/// Each endpoint sends a different subset of this information:
+///
+/// - `user/find` sends the ID, username, badge, GCL points and power points
+/// - leaderboard pages send the ID, username, badge and GCL points of each listed user
+/// - map stats send the ID, username and badge of each room owner and signer
+/// - room overviews send only the username and badge of the room's owner
+///
+/// Private servers may also send whether the user is active, where inactive users are those who have not spawned
+/// or whose account has been disabled.
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct UserInfo {
+    /// The user's ID, if the endpoint sent it.
+    #[serde(rename = "_id", default)]
+    pub user_id: Option<String>,
+    /// The user's username.
+    pub username: String,
+    /// The user's badge, if they have set one.
+    #[serde(default)]
+    pub badge: Option<Badge>,
+    /// The user's GCL points (calculate to get GCL), if the endpoint sent them.
+    #[serde(rename = "gcl", default)]
+    pub gcl_points: Option<u64>,
+    /// The user's power points, if the endpoint sent them.
+    #[serde(rename = "power", default)]
+    pub power_points: Option<u64>,
+    /// Whether the user is active, if the server sent it.
+    #[serde(default, deserialize_with = "active_flag")]
+    pub active: Option<bool>,
+    /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
+    _non_exhaustive: (),
+}
+
+impl UserInfo {
+    /// The user's GCL, if the endpoint sent their GCL points.
+    pub fn gcl(&self) -> Option<u64> {
+        self.gcl_points.map(crate::gcl_calc)
+    }
+}
+
+/// Parses the `active` flag, which private servers store either as a boolean or as a number which is 0 for
+/// inactive users.
+fn active_flag<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ActiveFlag {
+        Bool(bool),
+        Number(f64),
+    }
+
+    let flag: Option<ActiveFlag> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(flag.map(|flag| match flag {
+        ActiveFlag::Bool(active) => active,
+        ActiveFlag::Number(n) => n != 0.0,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Badge;
+    use super::{Badge, UserInfo};
     use serde_json;
 
     #[test]
@@ -110,4 +174,39 @@ mod tests {
             "flip": false,
         })).unwrap();
     }
+
+    #[test]
+    fn parse_user_info() {
+        let user: UserInfo = serde_json::from_value(json!({
+            "_id": "57874d42d0ae911e3bd15bbc",
+            "username": "daboross",
+            "badge": {
+                "type": 19,
+                "color1": 37,
+                "color2": 57,
+                "color3": 1,
+                "param": 0,
+                "flip": false,
+            },
+            "gcl": 5278032,
+            "power": 1200,
+            "active": 10000,
+        }))
+        .unwrap();
+
+        assert_eq!(user.user_id.as_deref(), Some("57874d42d0ae911e3bd15bbc"));
+        assert_eq!(user.gcl(), Some(3));
+        assert_eq!(user.power_points, Some(1200));
+        assert_eq!(user.active, Some(true));
+
+        let round_trip: UserInfo =
+            serde_json::from_value(serde_json::to_value(&user).unwrap()).unwrap();
+        assert_eq!(round_trip, user);
+
+        let owner: UserInfo = serde_json::from_value(json!({ "username": "Invader" })).unwrap();
+        assert_eq!(owner.user_id, None);
+        assert_eq!(owner.badge, None);
+        assert_eq!(owner.gcl(), None);
+        assert_eq!(owner.active, None);
+    }
 }
@@ -0,0 +1,42 @@
+//! Conversions of the API's timestamps into `chrono` types.
+use chrono::{DateTime, TimeZone, Utc};
+
+/// Interprets a timestamp sent as a number of milliseconds since the unix epoch.
+///
+/// Timestamps too far in the past or future to be represented are clamped to the earliest or latest date.
+pub fn millis_to_datetime(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .unwrap_or(if millis < 0 {
+            DateTime::<Utc>::MIN_UTC
+        } else {
+            DateTime::<Utc>::MAX_UTC
+        })
+}
+
+/// Parses a date string such as `"2017-03-04T05:38:04.012Z"`.
+pub fn parse_iso_datetime(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(date)
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::SecondsFormat;
+
+    use super::{millis_to_datetime, parse_iso_datetime};
+
+    #[test]
+    fn convert_timestamps() {
+        assert_eq!(
+            millis_to_datetime(1484071532985).to_rfc3339_opts(SecondsFormat::Millis, true),
+            "2017-01-10T18:05:32.985Z"
+        );
+        assert_eq!(
+            parse_iso_datetime("2017-03-04T05:38:04.012Z").map(|date| date.timestamp_millis()),
+            Some(1488605884012)
+        );
+        assert_eq!(parse_iso_datetime("2017-03"), None);
+    }
+}
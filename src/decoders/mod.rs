@@ -1,7 +1,13 @@
+#[cfg(feature = "chrono")]
+mod datetime;
 mod numbers;
 mod timespec;
 
 pub mod null_as_default;
 
+#[cfg(feature = "chrono")]
+pub use datetime::*;
+// the number decoders are only used by websocket room objects.
+#[cfg_attr(not(feature = "sockets"), allow(unused_imports))]
 pub use numbers::*;
 pub use timespec::*;
@@ -73,7 +73,7 @@ macro_rules! str_mod {
             }
 
             /// Serializes an integer directly.
-            pub fn serialize<S>(data: &i64, serializer: S) -> Result<S::Ok, S::Error>
+            pub fn serialize<S>(data: &$ty, serializer: S) -> Result<S::Ok, S::Error>
             where
                 S: Serializer,
             {
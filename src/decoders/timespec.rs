@@ -146,6 +146,7 @@ pub mod optional_timespec_seconds {
 /// A non-existent value will be None, but a JSON null will always deserialize into `Some(None)`.
 ///
 /// Useful for updating structs.
+#[cfg_attr(not(feature = "sockets"), allow(dead_code))]
 pub mod double_optional_timespec_seconds {
     use serde::{Deserializer, Serializer};
     use time::Timespec;
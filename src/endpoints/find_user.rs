@@ -0,0 +1,66 @@
+//! Interpreting user lookup results.
+use crate::{
+    data::{self, UserInfo},
+    error::{ApiError, Result},
+    EndpointResult,
+};
+
+/// User lookup raw result.
+#[derive(serde_derive::Deserialize, Clone, Debug)]
+#[doc(hidden)]
+pub(crate) struct Response {
+    ok: i32,
+    user: UserInfo,
+}
+
+impl EndpointResult for UserInfo {
+    type RequestResult<'de> = Response;
+    type ErrorResult = data::ApiError;
+
+    fn from_raw(raw: Response) -> Result<UserInfo> {
+        let Response { ok, user } = raw;
+
+        if ok != 1 {
+            return Err(ApiError::NotOk(ok).into());
+        }
+
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UserInfo;
+    use crate::EndpointResult;
+
+    #[test]
+    fn parse_sample_found_user() {
+        let response = serde_json::from_value(json!({
+            "ok": 1,
+            "user": {
+                "_id": "57874d42d0ae911e3bd15bbc",
+                "steam": {
+                    "id": "76561198033802814"
+                },
+                "username": "daboross",
+                "badge": {
+                    "type": 21,
+                    "color1": "#260d0d",
+                    "color2": "#6b2e41",
+                    "color3": "#ffe56d",
+                    "param": -100,
+                    "flip": false
+                },
+                "gcl": 657254041,
+                "power": 8412
+            }
+        }))
+        .unwrap();
+
+        let user = UserInfo::from_raw(response).unwrap();
+        assert_eq!(user.username, "daboross");
+        assert_eq!(user.gcl(), Some(15));
+        assert_eq!(user.power_points, Some(8412));
+        assert!(user.badge.is_some());
+    }
+}
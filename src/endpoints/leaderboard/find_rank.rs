@@ -38,7 +38,11 @@ struct InnerAllSeasonsResponse {
 /// Result from a lookup for a user's rank on the leaderboard.
 #[derive(Serialize, Deserialize, Clone, Hash, Debug)]
 pub struct FoundUserRank {
-    /// The season ID which this rank is for
+    /// The season ID which this rank is for, usually like `"2017-02"`.
+    ///
+    /// Kept as sent, as private servers may use other season IDs. See [`FoundUserRank::season`].
+    ///
+    /// [`FoundUserRank::season`]: #method.season
     pub season_id: String,
     /// The user's ID
     pub user_id: String,
@@ -53,9 +57,16 @@ pub struct FoundUserRank {
     pub(super) _non_exhaustive: (),
 }
 
+impl FoundUserRank {
+    /// The season which this rank is for, or `None` if its ID isn't a month like `"2017-02"`.
+    pub fn season(&self) -> Option<data::SeasonId> {
+        self.season_id.parse().ok()
+    }
+}
+
 // This is the result when specifying one season.
 impl EndpointResult for FoundUserRank {
-    type RequestResult = SingleResponse;
+    type RequestResult<'de> = SingleResponse;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: SingleResponse) -> Result<FoundUserRank> {
@@ -83,7 +94,7 @@ impl EndpointResult for FoundUserRank {
 
 // This is the result when requesting without specifying one season.
 impl EndpointResult for Vec<FoundUserRank> {
-    type RequestResult = AllSeasonRanksResponse;
+    type RequestResult<'de> = AllSeasonRanksResponse;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: AllSeasonRanksResponse) -> Result<Vec<FoundUserRank>> {
@@ -120,13 +131,13 @@ impl EndpointResult for Vec<FoundUserRank> {
 #[cfg(test)]
 mod tests {
     use super::FoundUserRank;
-    use crate::EndpointResult;
+    use crate::{EndpointResult, SeasonId};
     use serde_json;
 
-    fn test_parse_single(json: serde_json::Value) {
+    fn test_parse_single(json: serde_json::Value) -> FoundUserRank {
         let response = serde_json::from_value(json).unwrap();
 
-        let _ = FoundUserRank::from_raw(response).unwrap();
+        FoundUserRank::from_raw(response).unwrap()
     }
     fn test_parse_multi(json: serde_json::Value) {
         let response = serde_json::from_value(json).unwrap();
@@ -136,7 +147,7 @@ mod tests {
 
     #[test]
     fn parse_sample_single_season() {
-        test_parse_single(json! ({
+        let rank = test_parse_single(json! ({
             "_id": "58b60f2f75a8e42a5c0923f9",
             "ok": 1,
             "rank": 68,
@@ -144,6 +155,22 @@ mod tests {
             "season": "2017-03",
             "user": "57874d42d0ae911e3bd15bbc"
         }));
+
+        assert_eq!(rank.season(), SeasonId::new(2017, 3));
+    }
+
+    #[test]
+    fn parse_nonstandard_season() {
+        let rank = test_parse_single(json! ({
+            "ok": 1,
+            "rank": 3,
+            "score": 120000,
+            "season": "private-1",
+            "user": "57874d42d0ae911e3bd15bbc"
+        }));
+
+        assert_eq!(rank.season_id, "private-1");
+        assert_eq!(rank.season(), None);
     }
 
     #[test]
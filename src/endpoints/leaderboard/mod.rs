@@ -6,7 +6,7 @@ pub mod season_list;
 pub use self::{find_rank::*, page::*, season_list::*};
 
 /// Type of leaderboards that are available for each season.
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum LeaderboardType {
     /// Global control point leaderboard.
     GlobalControl,
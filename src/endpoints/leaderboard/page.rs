@@ -13,7 +13,7 @@ pub(crate) struct Response {
     ok: i32,
     count: u64,
     list: Vec<ResponseRankedUser>,
-    users: HashMap<String, ExtendedUserInfo>,
+    users: HashMap<String, data::UserInfo>,
 }
 
 #[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
@@ -25,14 +25,6 @@ struct ResponseRankedUser {
     user: String,
 }
 
-#[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
-struct ExtendedUserInfo {
-    _id: String,
-    username: String,
-    gcl: u64,
-    badge: data::Badge,
-}
-
 /// Single leaderboard page of users.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct LeaderboardPage {
@@ -45,29 +37,19 @@ pub struct LeaderboardPage {
     #[serde(with = "::tuple_vec_map")]
     pub user_details: Vec<(String, UserDetails)>,
     /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
     _non_exhaustive: (),
 }
 
 /// Alias since the format is the same for the inner user ranks and found user ranks.
 pub type RankedUser = find_rank::FoundUserRank;
 
-/// Details on any user in a given leaderboard page result.
-#[derive(Serialize, Deserialize, Clone, Hash, Debug)]
-pub struct UserDetails {
-    /// The user's id.
-    pub user_id: String,
-    /// The user's badge.
-    pub badge: data::Badge,
-    /// The user's GCL points (calculate to get GCL)
-    pub gcl_points: u64,
-    /// The user's username.
-    pub username: String,
-    /// Phantom data in order to allow adding any additional fields in the future.
-    _non_exhaustive: (),
-}
+/// Alias since leaderboard pages use the same user information as other endpoints. Leaderboard pages always include
+/// the user's ID, badge and GCL points.
+pub type UserDetails = data::UserInfo;
 
 impl EndpointResult for LeaderboardPage {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: Response) -> Result<LeaderboardPage> {
@@ -96,17 +78,11 @@ impl EndpointResult for LeaderboardPage {
                 .collect(),
             user_details: user_details
                 .into_iter()
-                .map(|(user_id, data)| {
-                    (
-                        user_id,
-                        UserDetails {
-                            user_id: data._id,
-                            badge: data.badge,
-                            gcl_points: data.gcl,
-                            username: data.username,
-                            _non_exhaustive: (),
-                        },
-                    )
+                .map(|(user_id, mut details)| {
+                    if details.user_id.is_none() {
+                        details.user_id = Some(user_id.clone());
+                    }
+                    (user_id, details)
                 })
                 .collect(),
             _non_exhaustive: (),
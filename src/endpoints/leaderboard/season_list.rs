@@ -28,15 +28,36 @@ pub struct LeaderboardSeason {
     /// The display name of the season.
     pub name: String,
     /// The season "id", usable by passing to a leaderboard list call.
+    ///
+    /// Kept as sent, as private servers may use IDs other than months like `"2017-02"`. See
+    /// [`LeaderboardSeason::season`].
+    ///
+    /// [`LeaderboardSeason::season`]: #method.season
     pub season_id: String,
     /// The date when the leaderboard season ended, in the format like 2017-03-04T05:38:04.012Z.
     pub end_date: String,
     /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
     _non_exhaustive: (),
 }
 
+impl LeaderboardSeason {
+    /// The season this describes, or `None` if its ID isn't a month like `"2017-02"`.
+    pub fn season(&self) -> Option<data::SeasonId> {
+        self.season_id.parse().ok()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl LeaderboardSeason {
+    /// The date when the leaderboard season ended, or `None` if it isn't in the expected format.
+    pub fn end_datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        crate::decoders::parse_iso_datetime(&self.end_date)
+    }
+}
+
 impl EndpointResult for Vec<LeaderboardSeason> {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: Response) -> Result<Vec<LeaderboardSeason>> {
@@ -61,19 +82,19 @@ impl EndpointResult for Vec<LeaderboardSeason> {
 #[cfg(test)]
 mod tests {
     use super::LeaderboardSeason;
-    use crate::EndpointResult;
+    use crate::{EndpointResult, SeasonId};
     use serde_json;
 
-    fn test_parse(json: serde_json::Value) {
+    fn test_parse(json: serde_json::Value) -> Vec<LeaderboardSeason> {
         let response = serde_json::from_value(json).unwrap();
 
-        let _ = Vec::<LeaderboardSeason>::from_raw(response).unwrap();
+        Vec::<LeaderboardSeason>::from_raw(response).unwrap()
     }
 
     #[test]
     fn parse_sample_leaderboard_season_list() {
         // This is not an actual call, but rather just a truncated call containing the top 5 results.
-        test_parse(json! ({
+        let seasons = test_parse(json! ({
             "ok": 1,
             "seasons": [
                 {
@@ -108,5 +129,12 @@ mod tests {
                 }
             ]
         }));
+
+        assert_eq!(seasons[0].season_id, "2017-03");
+        assert_eq!(seasons[0].season(), SeasonId::new(2017, 3));
+        assert_eq!(
+            seasons[1].season(),
+            seasons[0].season().and_then(SeasonId::previous)
+        );
     }
 }
@@ -1,13 +1,13 @@
 //! Interpreting login responses.
-use std::borrow::Cow;
+use std::{borrow::Cow, fmt};
 
 use crate::data;
 use crate::error::{ApiError, Result};
 
-use crate::{EndpointResult, Token, TokenStorage};
+use crate::{redact, EndpointResult, Token, TokenStorage};
 
 /// Login details
-#[derive(Serialize, Clone, Hash, Debug)]
+#[derive(Serialize, Clone, Hash)]
 pub struct LoginArgs<'a> {
     /// The email or username to log in with (either works)
     email: Cow<'a, str>,
@@ -29,16 +29,27 @@ impl<'a> LoginArgs<'a> {
     }
 }
 
+impl fmt::Debug for LoginArgs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LoginArgs")
+            .field("email", &self.email)
+            .field("password", &redact::REDACTED)
+            .finish()
+    }
+}
+
 /// Login raw result.
-#[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
+#[derive(serde_derive::Deserialize, Clone, Hash)]
 pub(crate) struct Response {
     ok: i32,
     token: Option<String>,
 }
 
 /// The result of a call to log in.
+///
+/// The token is never included in debug output.
 #[must_use = "LoggedIn does not do anything unless registered in a token store"]
-#[derive(Clone, Hash, Debug)]
+#[derive(Clone, Hash)]
 pub struct LoggedIn {
     /// The token which can be used to make future authenticated API calls.
     pub token: Token,
@@ -46,15 +57,23 @@ pub struct LoggedIn {
     _non_exhaustive: (),
 }
 
+impl fmt::Debug for LoggedIn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LoggedIn")
+            .field("token", &redact::REDACTED)
+            .finish()
+    }
+}
+
 impl LoggedIn {
     /// Stores the token into the given token storage.
-    pub fn return_to(self, storage: &TokenStorage) {
+    pub fn return_to(self, storage: &dyn TokenStorage) {
         storage.set(self.token);
     }
 }
 
 impl EndpointResult for LoggedIn {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: Response) -> Result<LoggedIn> {
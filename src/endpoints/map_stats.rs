@@ -1,8 +1,8 @@
 //! Interpreting bulk room statistics (map stats).
-//!
-//! Note: currently only supports "owner0" stats, not any other statistic that can also be retrieved with the same API.
 use std::convert::AsRef;
+use std::fmt;
 
+use serde::de::{Deserialize, Deserializer, Unexpected, Visitor};
 use serde::{Serialize, Serializer};
 
 use crate::data::{self, RoomName};
@@ -11,18 +11,117 @@ use crate::error::ApiError;
 use crate::error::Result as ScapiResult;
 use crate::EndpointResult;
 
-/// Stat name argument to the map stats call. Only one possible argument implemented, more to come!
-#[derive(Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+/// Stat name argument to the map stats call.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub enum MapStatName {
     /// Gets the room owner (always gotten even if other stats are requested).
-    #[serde(rename = "owner0")]
     RoomOwner,
+    /// Gets a statistic of each room, totalled over the given interval in game ticks: 8, 180 or 1440.
+    Stat(MapStat, u32),
     /// A marker variant that tells the compiler that users of this enum cannot match it exhaustively.
     #[doc(hidden)]
-    #[serde(rename = "owner0")]
     __Nonexhaustive,
 }
 
+/// A statistic which can be retrieved for each room with a map stats call.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MapStat {
+    /// Number of creep parts lost.
+    CreepsLost,
+    /// Number of creep parts produced.
+    CreepsProduced,
+    /// Energy spent on construction.
+    EnergyConstruction,
+    /// Energy spent on upgrading controllers.
+    EnergyControl,
+    /// Energy spent on creeps.
+    EnergyCreeps,
+    /// Energy harvested.
+    EnergyHarvested,
+    /// Power processed.
+    PowerProcessed,
+}
+
+impl MapStat {
+    fn api_name(self) -> &'static str {
+        match self {
+            MapStat::CreepsLost => "creepsLost",
+            MapStat::CreepsProduced => "creepsProduced",
+            MapStat::EnergyConstruction => "energyConstruction",
+            MapStat::EnergyControl => "energyControl",
+            MapStat::EnergyCreeps => "energyCreeps",
+            MapStat::EnergyHarvested => "energyHarvested",
+            MapStat::PowerProcessed => "powerProcessed",
+        }
+    }
+
+    fn from_api_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "creepsLost" => MapStat::CreepsLost,
+            "creepsProduced" => MapStat::CreepsProduced,
+            "energyConstruction" => MapStat::EnergyConstruction,
+            "energyControl" => MapStat::EnergyControl,
+            "energyCreeps" => MapStat::EnergyCreeps,
+            "energyHarvested" => MapStat::EnergyHarvested,
+            "powerProcessed" => MapStat::PowerProcessed,
+            _ => return None,
+        })
+    }
+}
+
+impl Serialize for MapStatName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            MapStatName::Stat(stat, interval) => {
+                serializer.collect_str(&format_args!("{}{}", stat.api_name(), interval))
+            }
+            MapStatName::RoomOwner | MapStatName::__Nonexhaustive => {
+                serializer.serialize_str("owner0")
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MapStatName {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct StatNameVisitor;
+
+        impl<'de> Visitor<'de> for StatNameVisitor {
+            type Value = MapStatName;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str(
+                    "a map stat name followed by an interval, such as \"energyHarvested180\"",
+                )
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<MapStatName, E>
+            where
+                E: serde::de::Error,
+            {
+                if value == "owner0" {
+                    return Ok(MapStatName::RoomOwner);
+                }
+
+                let split = value.find(char::is_numeric).unwrap_or(value.len());
+                let (name, interval) = value.split_at(split);
+                match (MapStat::from_api_name(name), interval.parse()) {
+                    (Some(stat), Ok(interval)) => Ok(MapStatName::Stat(stat, interval)),
+                    _ => Err(E::invalid_value(Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(StatNameVisitor)
+    }
+}
+
 /// Arguments to a map stats call, holds a single value which can be iterated to get rooms.
 #[derive(Serialize, Clone, Debug)]
 #[serde(bound = "")]
@@ -88,38 +187,99 @@ where
 }
 
 /// Map stats raw result.
-#[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
+#[derive(serde_derive::Deserialize, Clone, Debug)]
 #[doc(hidden)]
 pub(crate) struct Response {
     ok: i32,
     #[serde(with = "::tuple_vec_map")]
     stats: Vec<(String, RoomResponse)>,
     #[serde(with = "::tuple_vec_map")]
-    users: Vec<(String, UserResponse)>,
+    users: Vec<(String, data::UserInfo)>,
 }
 
-#[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
-#[serde(rename_all = "camelCase")]
-struct RoomResponse {
-    status: String,
-    own: Option<RoomOwner>,
-    /// The end time for the novice area this room is or was last in.
-    #[serde(with = "optional_timespec_seconds")]
-    #[serde(default)]
-    novice: Option<time::Timespec>,
-    /// The time this room will open or did open into the novice area as a second tier novice room.
-    #[serde(with = "optional_timespec_seconds")]
-    #[serde(default)]
-    open_time: Option<time::Timespec>,
-    sign: Option<data::RoomSign>,
-    hard_sign: Option<data::HardSign>,
+/// Declares the raw room result, with a field for each statistic the server can send, and a way to take them all.
+///
+/// Each statistic is sent under its own key, such as `energyHarvested180`, so listing them keeps any other field
+/// reported as unknown.
+macro_rules! room_response {
+    ($($field:ident: $key:literal => $stat:ident, $interval:expr;)*) => {
+        #[derive(serde_derive::Deserialize, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        struct RoomResponse {
+            status: String,
+            own: Option<RoomOwner>,
+            /// The end time for the novice area this room is or was last in.
+            #[serde(with = "optional_timespec_seconds")]
+            #[serde(default)]
+            novice: Option<time::Timespec>,
+            /// The time this room will open or did open into the novice area as a second tier novice room.
+            #[serde(with = "optional_timespec_seconds")]
+            #[serde(default)]
+            open_time: Option<time::Timespec>,
+            /// The end time for the respawn area this room is or was last in.
+            #[serde(with = "optional_timespec_seconds")]
+            #[serde(default)]
+            respawn_area: Option<time::Timespec>,
+            sign: Option<data::RoomSign>,
+            hard_sign: Option<data::HardSign>,
+            #[serde(default)]
+            safe_mode: bool,
+            $(
+                #[serde(rename = $key, default)]
+                $field: Option<Vec<MapStatValue>>,
+            )*
+        }
+
+        impl RoomResponse {
+            /// Takes the values of each statistic sent for this room.
+            fn take_stats(&mut self) -> Vec<(MapStatName, Vec<MapStatValue>)> {
+                let mut stats = Vec::new();
+                $(
+                    if let Some(values) = self.$field.take() {
+                        stats.push((MapStatName::Stat(MapStat::$stat, $interval), values));
+                    }
+                )*
+                stats
+            }
+        }
+    };
+}
+
+room_response! {
+    creeps_lost_8: "creepsLost8" => CreepsLost, 8;
+    creeps_lost_180: "creepsLost180" => CreepsLost, 180;
+    creeps_lost_1440: "creepsLost1440" => CreepsLost, 1440;
+    creeps_produced_8: "creepsProduced8" => CreepsProduced, 8;
+    creeps_produced_180: "creepsProduced180" => CreepsProduced, 180;
+    creeps_produced_1440: "creepsProduced1440" => CreepsProduced, 1440;
+    energy_construction_8: "energyConstruction8" => EnergyConstruction, 8;
+    energy_construction_180: "energyConstruction180" => EnergyConstruction, 180;
+    energy_construction_1440: "energyConstruction1440" => EnergyConstruction, 1440;
+    energy_control_8: "energyControl8" => EnergyControl, 8;
+    energy_control_180: "energyControl180" => EnergyControl, 180;
+    energy_control_1440: "energyControl1440" => EnergyControl, 1440;
+    energy_creeps_8: "energyCreeps8" => EnergyCreeps, 8;
+    energy_creeps_180: "energyCreeps180" => EnergyCreeps, 180;
+    energy_creeps_1440: "energyCreeps1440" => EnergyCreeps, 1440;
+    energy_harvested_8: "energyHarvested8" => EnergyHarvested, 8;
+    energy_harvested_180: "energyHarvested180" => EnergyHarvested, 180;
+    energy_harvested_1440: "energyHarvested1440" => EnergyHarvested, 1440;
+    power_processed_8: "powerProcessed8" => PowerProcessed, 8;
+    power_processed_180: "powerProcessed180" => PowerProcessed, 180;
+    power_processed_1440: "powerProcessed1440" => PowerProcessed, 1440;
 }
 
-#[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
-struct UserResponse {
-    badge: data::Badge,
-    _id: String,
-    username: String,
+/// One user's value of the requested statistic in a room.
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MapStatValue {
+    /// The user ID of the user this value is for.
+    #[serde(rename = "user")]
+    pub user_id: String,
+    /// The value of the statistic.
+    pub value: u64,
+    /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
+    _non_exhaustive: (),
 }
 
 /// Description of the owner of an owned room.
@@ -137,7 +297,7 @@ pub struct RoomOwner {
 }
 
 /// Statistics on a number of rooms.
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct MapStats {
     /// A list of results retrieved from this map stats call. Note: Invalid or non-existent room names will simply just
     /// not appear in this result!
@@ -146,8 +306,9 @@ pub struct MapStats {
     /// not exist.
     pub rooms: Vec<RoomInfo>,
     /// A list of user information for each user who either owns or signed a room that was requested.
-    pub users: Vec<UserInfo>,
+    pub users: Vec<data::UserInfo>,
     /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
     _non_exhaustive: (),
 }
 
@@ -160,31 +321,46 @@ pub struct RoomInfo {
     pub state: data::RoomState,
     /// Info on the room's owner, if any.
     pub owner: Option<RoomOwner>,
+    /// The user ID of the user reserving the room, if it is reserved rather than owned.
+    pub reserved_by: Option<String>,
+    /// Whether the room's owner has safe mode active.
+    pub safe_mode: bool,
+    /// The time the novice area this room is or was last in ends or ended, if it has ever been in one.
+    ///
+    /// Unlike `state`, this is reported even after the novice area has ended.
+    #[serde(with = "optional_timespec_seconds")]
+    pub novice_area_end: Option<time::Timespec>,
+    /// The time the respawn area this room is or was last in ends or ended, if it has ever been in one.
+    #[serde(with = "optional_timespec_seconds")]
+    pub respawn_area_end: Option<time::Timespec>,
     /// The room's player-set sign, if any.
     pub sign: Option<data::RoomSign>,
     /// The room's system-set sign, if any.
     pub hard_sign: Option<data::HardSign>,
+    /// Each user's values of each statistic sent for this room, or empty when only room owners were requested.
+    ///
+    /// See also [`RoomInfo::stat_values`].
+    ///
+    /// [`RoomInfo::stat_values`]: struct.RoomInfo.html#method.stat_values
+    pub stats: Vec<(MapStatName, Vec<MapStatValue>)>,
     /// Phantom data in order to allow adding any additional fields in the future.
     #[serde(skip)]
     _non_exhaustive: (),
 }
 
-/// Information on a user.
-#[derive(Serialize, Deserialize, Clone, Hash, Debug)]
-pub struct UserInfo {
-    /// The user's ID.
-    pub user_id: String,
-    /// The user's username.
-    pub username: String,
-    /// The user's badge.
-    pub badge: data::Badge,
-    /// Phantom data in order to allow adding any additional fields in the future.
-    #[serde(skip)]
-    _non_exhaustive: (),
+impl RoomInfo {
+    /// Gets each user's value of the given statistic in this room, or nothing if it wasn't sent.
+    pub fn stat_values(&self, stat: &MapStatName) -> &[MapStatValue] {
+        self.stats
+            .iter()
+            .find(|(name, _)| name == stat)
+            .map(|(_, values)| &**values)
+            .unwrap_or(&[])
+    }
 }
 
 impl EndpointResult for MapStats {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: Response) -> ScapiResult<MapStats> {
@@ -197,14 +373,18 @@ impl EndpointResult for MapStats {
         Ok(MapStats {
             rooms: stats
                 .into_iter()
-                .map(|(room_name, room_data)| {
+                .map(|(room_name, mut room_data)| {
+                    let stats = room_data.take_stats();
                     let RoomResponse {
                         status,
-                        own: owner,
+                        own,
                         novice,
                         open_time,
+                        respawn_area,
                         sign,
                         hard_sign,
+                        safe_mode,
+                        ..
                     } = room_data;
                     if status == "out of borders" {
                         // Oddity in Screeps: for shard0, all rooms which are out of bounds are simply left out of
@@ -220,13 +400,22 @@ impl EndpointResult for MapStats {
                         .into());
                     }
 
+                    // Reserved rooms are listed as owned at level 0.
+                    let (owner, reserved_by) = match own {
+                        Some(own) if own.room_controller_level == 0 => (None, Some(own.user_id)),
+                        own => (own, None),
+                    };
                     let info = RoomInfo {
                         name: RoomName::new(&room_name)?,
                         state: data::RoomState::from_data(time::get_time(), novice, open_time)?,
                         owner: owner,
-                        // turn Option<Result<A, B>> into Result<Option<A>, B>
+                        reserved_by,
+                        safe_mode,
+                        novice_area_end: novice,
+                        respawn_area_end: respawn_area,
                         sign: sign,
                         hard_sign: hard_sign,
+                        stats,
                         _non_exhaustive: (),
                     };
 
@@ -240,27 +429,18 @@ impl EndpointResult for MapStats {
                 .collect::<ScapiResult<_>>()?,
             users: users
                 .into_iter()
-                .map(|(user_id, user_data)| {
-                    let UserResponse {
-                        badge,
-                        _id: user_id2,
-                        username,
-                    } = user_data;
-                    if user_id != user_id2 {
-                        return Err(ApiError::MalformedResponse(format!(
-                            "expected user id object key to match user \
-                             id, {} != {}",
-                            user_id, user_id2
-                        ))
-                        .into());
+                .map(|(user_id, mut info)| {
+                    if let Some(ref user_id2) = info.user_id {
+                        if *user_id2 != user_id {
+                            return Err(ApiError::MalformedResponse(format!(
+                                "expected user id object key to match user \
+                                 id, {} != {}",
+                                user_id, user_id2
+                            ))
+                            .into());
+                        }
                     }
-
-                    let info = UserInfo {
-                        user_id: user_id,
-                        username: username,
-                        badge: badge,
-                        _non_exhaustive: (),
-                    };
+                    info.user_id = Some(user_id);
 
                     Ok(info)
                 })
@@ -272,8 +452,8 @@ impl EndpointResult for MapStats {
 
 #[cfg(test)]
 mod tests {
-    use super::MapStats;
-    use crate::EndpointResult;
+    use super::{MapStat, MapStatName, MapStats};
+    use crate::{EndpointResult, RoomName};
     use serde_json;
 
     fn test_parse(json: serde_json::Value) {
@@ -400,4 +580,124 @@ mod tests {
             }
         }));
     }
+
+    #[test]
+    fn parse_sample_reservations_and_stats() {
+        let response = serde_json::from_value(json!({
+            "ok": 1,
+            "stats": {
+                "W13S21": {
+                    "status": "normal",
+                    "novice": 1485278202869i64,
+                    "respawnArea": 1583107200000i64,
+                    "own": {
+                        "user": "57874d42d0ae911e3bd15bbc",
+                        "level": 0
+                    },
+                    "energyHarvested180": [
+                        {
+                            "user": "57874d42d0ae911e3bd15bbc",
+                            "value": 48000
+                        }
+                    ]
+                },
+                "W12S20": {
+                    "status": "normal",
+                    "own": {
+                        "user": "57874d42d0ae911e3bd15bbc",
+                        "level": 7
+                    },
+                    "safeMode": true,
+                    "energyHarvested180": []
+                }
+            },
+            "users": {
+                "57874d42d0ae911e3bd15bbc": {
+                    "username": "daboross",
+                    "_id": "57874d42d0ae911e3bd15bbc",
+                    "badge": {
+                        "color1": "#260d0d",
+                        "flip": false,
+                        "param": -100,
+                        "color3": "#ffe56d",
+                        "type": 21,
+                        "color2": "#6b2e41"
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        let stats = MapStats::from_raw(response).unwrap();
+        let room = |name: &str| {
+            let name = RoomName::new(name).unwrap();
+            stats.rooms.iter().find(|room| room.name == name).unwrap()
+        };
+
+        let reserved = room("W13S21");
+        assert!(reserved.owner.is_none());
+        assert_eq!(
+            reserved.reserved_by.as_deref(),
+            Some("57874d42d0ae911e3bd15bbc")
+        );
+        assert_eq!(
+            reserved.respawn_area_end,
+            Some(time::Timespec::new(1583107200000, 0))
+        );
+        let harvested = MapStatName::Stat(MapStat::EnergyHarvested, 180);
+        assert_eq!(reserved.stat_values(&harvested)[0].value, 48000);
+        assert!(reserved
+            .stat_values(&MapStatName::Stat(MapStat::EnergyHarvested, 8))
+            .is_empty());
+
+        let owned = room("W12S20");
+        assert_eq!(owned.owner.as_ref().unwrap().room_controller_level, 7);
+        assert_eq!(owned.reserved_by, None);
+        assert!(owned.safe_mode);
+        assert!(owned.stat_values(&harvested).is_empty());
+
+        assert_eq!(
+            serde_json::to_value(MapStatName::Stat(MapStat::CreepsLost, 8)).unwrap(),
+            "creepsLost8"
+        );
+        assert_eq!(
+            serde_json::from_value::<MapStatName>(json!("energyControl1440")).unwrap(),
+            MapStatName::Stat(MapStat::EnergyControl, 1440)
+        );
+        assert!(serde_json::from_value::<MapStatName>(json!("energy180")).is_err());
+    }
+
+    #[test]
+    fn parse_stat_values_strictly() {
+        let request = crate::request::map_stats("shard0", &["W12S20"]);
+        let strict = crate::request::ParseOptions::new().with_strict(true);
+        let parse = |stats: serde_json::Value| {
+            let body = json!({ "ok": 1, "stats": { "W12S20": stats }, "users": {} });
+            request.parse_response_with(http::StatusCode::OK, &body.to_string().into(), &strict)
+        };
+
+        let stats = parse(json!({
+            "status": "normal",
+            "creepsLost8": [{ "user": "57874d42d0ae911e3bd15bbc", "value": 4 }]
+        }))
+        .unwrap();
+        let stat = MapStatName::Stat(MapStat::CreepsLost, 8);
+        assert_eq!(stats.rooms[0].stat_values(&stat)[0].value, 4);
+
+        assert!(parse(json!({
+            "status": "normal",
+            "creepsLost8": [{ "user": "57874d42d0ae911e3bd15bbc" }]
+        }))
+        .is_err());
+
+        match *parse(json!({ "status": "normal", "creepsLost7": [] }))
+            .unwrap_err()
+            .kind()
+        {
+            crate::ErrorKind::UnknownFields(ref paths) => {
+                assert_eq!(*paths, ["stats.W12S20.creepsLost7"])
+            }
+            ref other => panic!("expected unknown fields error, found {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,282 @@
+//! Interpreting market order listings.
+use std::collections::HashMap;
+
+use crate::{
+    data::{self, Credits, MarketOrder, ResourceType},
+    error::{ApiError, Result},
+    EndpointResult,
+};
+
+/// Orders index raw result.
+#[derive(serde_derive::Deserialize, Clone, Debug)]
+#[doc(hidden)]
+pub(crate) struct IndexResponse {
+    ok: i32,
+    list: Vec<ResourceSummaryResponse>,
+}
+
+#[derive(serde_derive::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ResourceSummaryResponse {
+    #[serde(rename = "_id")]
+    resource_type: ResourceType,
+    count: u32,
+    #[serde(default)]
+    avg_price: Option<Credits>,
+    #[serde(default)]
+    stddev_price: Option<f64>,
+}
+
+/// Orders of one resource raw result.
+#[derive(serde_derive::Deserialize, Clone, Debug)]
+#[doc(hidden)]
+pub(crate) struct OrdersResponse {
+    ok: i32,
+    list: Vec<MarketOrder>,
+}
+
+/// The user's own orders raw result.
+#[derive(serde_derive::Deserialize, Clone, Debug)]
+#[doc(hidden)]
+pub(crate) struct MyOrdersResponse {
+    ok: i32,
+    #[serde(default)]
+    list: Option<Vec<MarketOrder>>,
+    #[serde(default)]
+    shards: Option<HashMap<String, Vec<MarketOrder>>>,
+}
+
+/// A summary of the orders trading one resource, part of the orders index.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MarketResourceSummary {
+    /// The resource.
+    pub resource_type: ResourceType,
+    /// The number of active orders trading this resource.
+    pub count: u32,
+    /// The average price of these orders, if reported.
+    pub avg_price: Option<Credits>,
+    /// The standard deviation of the price of these orders, in credits, if reported.
+    pub stddev_price: Option<f64>,
+    /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
+    _non_exhaustive: (),
+}
+
+/// Result of listing how many orders each resource has on the market.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MarketOrdersIndex {
+    /// A summary of each resource with orders.
+    pub resources: Vec<MarketResourceSummary>,
+    /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
+    _non_exhaustive: (),
+}
+
+/// Result of listing all active orders of one resource.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MarketOrders {
+    /// The orders.
+    pub orders: Vec<MarketOrder>,
+    /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
+    _non_exhaustive: (),
+}
+
+impl MarketOrders {
+    /// Iterates over the orders buying the resource.
+    pub fn buy_orders(&self) -> impl Iterator<Item = &MarketOrder> {
+        self.orders
+            .iter()
+            .filter(|order| order.order_type == data::OrderType::Buy)
+    }
+
+    /// Iterates over the orders selling the resource.
+    pub fn sell_orders(&self) -> impl Iterator<Item = &MarketOrder> {
+        self.orders
+            .iter()
+            .filter(|order| order.order_type == data::OrderType::Sell)
+    }
+}
+
+/// Result of listing the logged in user's orders.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MyMarketOrders {
+    /// The orders, with their shards filled in on sharded servers.
+    pub orders: Vec<MarketOrder>,
+    /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
+    _non_exhaustive: (),
+}
+
+impl EndpointResult for MarketOrdersIndex {
+    type RequestResult<'de> = IndexResponse;
+    type ErrorResult = data::ApiError;
+
+    fn from_raw(raw: IndexResponse) -> Result<MarketOrdersIndex> {
+        let IndexResponse { ok, list } = raw;
+
+        if ok != 1 {
+            return Err(ApiError::NotOk(ok).into());
+        }
+
+        Ok(MarketOrdersIndex {
+            resources: list
+                .into_iter()
+                .map(|summary| MarketResourceSummary {
+                    resource_type: summary.resource_type,
+                    count: summary.count,
+                    avg_price: summary.avg_price,
+                    stddev_price: summary.stddev_price,
+                    _non_exhaustive: (),
+                })
+                .collect(),
+            _non_exhaustive: (),
+        })
+    }
+}
+
+impl EndpointResult for MarketOrders {
+    type RequestResult<'de> = OrdersResponse;
+    type ErrorResult = data::ApiError;
+
+    fn from_raw(raw: OrdersResponse) -> Result<MarketOrders> {
+        let OrdersResponse { ok, list } = raw;
+
+        if ok != 1 {
+            return Err(ApiError::NotOk(ok).into());
+        }
+
+        Ok(MarketOrders {
+            orders: list,
+            _non_exhaustive: (),
+        })
+    }
+}
+
+impl EndpointResult for MyMarketOrders {
+    type RequestResult<'de> = MyOrdersResponse;
+    type ErrorResult = data::ApiError;
+
+    fn from_raw(raw: MyOrdersResponse) -> Result<MyMarketOrders> {
+        let MyOrdersResponse { ok, list, shards } = raw;
+
+        if ok != 1 {
+            return Err(ApiError::NotOk(ok).into());
+        }
+
+        let mut orders = list.unwrap_or_default();
+        for (shard, shard_orders) in shards.into_iter().flatten() {
+            orders.extend(shard_orders.into_iter().map(|mut order| {
+                order.shard = Some(shard.clone());
+                order
+            }));
+        }
+
+        Ok(MyMarketOrders {
+            orders,
+            _non_exhaustive: (),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MarketOrders, MarketOrdersIndex, MyMarketOrders};
+    use crate::{Credits, EndpointResult, ResourceType};
+
+    #[test]
+    fn parse_sample_orders_index() {
+        let response = serde_json::from_value(json!({
+            "ok": 1,
+            "list": [
+                { "_id": "energy", "count": 312, "avgPrice": 0.05, "stddevPrice": 0.01 },
+                { "_id": "pixel", "count": 40 }
+            ]
+        }))
+        .unwrap();
+
+        let index = MarketOrdersIndex::from_raw(response).unwrap();
+        assert_eq!(index.resources[0].resource_type, ResourceType::Energy);
+        assert_eq!(index.resources[0].avg_price, Some(Credits::from_milli(50)));
+        assert_eq!(index.resources[1].count, 40);
+    }
+
+    #[test]
+    fn parse_sample_orders() {
+        let response = serde_json::from_value(json!({
+            "ok": 1,
+            "list": [
+                {
+                    "_id": "5a9f8e7d6c5b4a3928171605",
+                    "created": 23481634,
+                    "user": "57874d42d0ae911e3bd15bbc",
+                    "active": true,
+                    "type": "sell",
+                    "amount": 500,
+                    "remainingAmount": 1500,
+                    "resourceType": "energy",
+                    "price": 0.051,
+                    "totalAmount": 2000,
+                    "roomName": "W41N48"
+                },
+                {
+                    "_id": "5a9f8e7d6c5b4a3928171606",
+                    "created": 23481000,
+                    "type": "buy",
+                    "amount": 10000,
+                    "remainingAmount": 10000,
+                    "resourceType": "energy",
+                    "price": 0.04,
+                    "totalAmount": 10000,
+                    "roomName": "E1N1"
+                }
+            ]
+        }))
+        .unwrap();
+
+        let orders = MarketOrders::from_raw(response).unwrap();
+        assert_eq!(orders.sell_orders().count(), 1);
+        let buy = orders.buy_orders().next().unwrap();
+        assert_eq!(buy.price_of(100), Credits::from_credits(4));
+        assert!(buy.active);
+        assert_eq!(buy.user, None);
+    }
+
+    #[test]
+    fn parse_sample_my_orders() {
+        let order = json!({
+            "_id": "5a9f8e7d6c5b4a3928171605",
+            "created": 23481634,
+            "user": "57874d42d0ae911e3bd15bbc",
+            "active": false,
+            "type": "sell",
+            "amount": 0,
+            "remainingAmount": 1500,
+            "resourceType": "energy",
+            "price": 0.051,
+            "totalAmount": 2000,
+            "roomName": "W41N48"
+        });
+
+        let sharded = MyMarketOrders::from_raw(
+            serde_json::from_value(json!({
+                "ok": 1,
+                "shards": { "shard2": [order.clone()] }
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(sharded.orders[0].shard.as_deref(), Some("shard2"));
+        assert!(!sharded.orders[0].active);
+
+        let unsharded = MyMarketOrders::from_raw(
+            serde_json::from_value(json!({
+                "ok": 1,
+                "list": [order]
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(unsharded.orders[0].shard, None);
+    }
+}
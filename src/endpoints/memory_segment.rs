@@ -1,4 +1,6 @@
 //! Interpreting memory calls.
+use std::borrow::Cow;
+
 use crate::{
     data,
     error::{ApiError, Result},
@@ -8,24 +10,28 @@ use crate::{
 /// Call raw result.
 #[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
 #[doc(hidden)]
-pub(crate) struct Response {
+pub(crate) struct Response<'a> {
     ok: i32,
-    data: String,
+    /// Borrowed from the response body unless it contains escapes.
+    #[serde(borrow)]
+    data: Cow<'a, str>,
 }
 
 /// Memory segment retrieval result
-#[derive(Clone, Hash, Debug)]
-pub(crate) struct MemorySegment {
+#[derive(Serialize, Deserialize, Clone, Hash, Debug)]
+pub struct MemorySegment {
+    /// The contents of the segment.
     pub data: String,
     /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
     _non_exhaustive: (),
 }
 
 impl EndpointResult for MemorySegment {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response<'de>;
     type ErrorResult = data::ApiError;
 
-    fn from_raw(raw: Response) -> Result<Self> {
+    fn from_raw(raw: Response<'_>) -> Result<Self> {
         let Response { ok, data } = raw;
 
         if ok != 1 {
@@ -33,7 +39,7 @@ impl EndpointResult for MemorySegment {
         }
 
         Ok(MemorySegment {
-            data,
+            data: data.into_owned(),
             _non_exhaustive: (),
         })
     }
@@ -46,7 +52,8 @@ mod tests {
     use serde_json;
 
     fn test_parse(json: serde_json::Value) {
-        let response = serde_json::from_value(json).unwrap();
+        let body = json.to_string();
+        let response = serde_json::from_str(&body).unwrap();
 
         let _ = MemorySegment::from_raw(response).unwrap();
     }
@@ -58,4 +65,15 @@ mod tests {
             "data": "asdf"
         }));
     }
+
+    #[test]
+    fn borrow_data_from_body() {
+        let body = br#"{"ok":1,"data":"asdf"}"#;
+        let response: Response<'_> = serde_json::from_slice(body).unwrap();
+        assert!(matches!(response.data, Cow::Borrowed("asdf")));
+
+        let body = br#"{"ok":1,"data":"{\"a\":1}"}"#;
+        let response: Response<'_> = serde_json::from_slice(body).unwrap();
+        assert_eq!(response.data, r#"{"a":1}"#);
+    }
 }
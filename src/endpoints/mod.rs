@@ -1,18 +1,24 @@
 //! Parsing code for each individual API endpoint.
 //!
 //! Each sub-module contains code for interpreting the result of calling a specific API endpoint.
+mod find_user;
 mod leaderboard;
 mod login;
 mod map_stats;
+mod market;
 mod memory_segment;
+mod money_history;
 mod my_info;
 mod recent_pvp;
 mod register;
+#[cfg(feature = "sockets")]
+mod room_objects;
 mod room_overview;
 mod room_status;
 mod room_terrain;
 mod set_memory_segment;
 mod shards;
+mod version;
 mod world_start_room;
 
 // don't compile this endpoint template file with regular output, but still compile w/ tests to test for correctness.
@@ -20,9 +26,64 @@ mod world_start_room;
 pub mod template;
 
 pub use self::{
-    leaderboard::*, login::*, map_stats::*, my_info::*, recent_pvp::*, register::*,
-    room_overview::*, room_status::*, room_terrain::*, set_memory_segment::*, shards::*,
-    world_start_room::*,
+    leaderboard::*, login::*, map_stats::*, market::*, memory_segment::*, money_history::*,
+    my_info::*, recent_pvp::*, register::*, room_overview::*, room_status::*, room_terrain::*,
+    set_memory_segment::*, shards::*, version::*, world_start_room::*,
 };
 
-pub(crate) use self::memory_segment::*;
+#[cfg(feature = "sockets")]
+pub use self::room_objects::*;
+
+use crate::data::UserInfo;
+
+macro_rules! impl_parse_response {
+    ($($result:ty),* $(,)*) => {
+        $(
+            impl crate::request::ParseResponse for $result {
+                fn parse_response(
+                    status: http::StatusCode,
+                    body: &[u8],
+                ) -> crate::error::Result<Self> {
+                    let body = bytes::Bytes::copy_from_slice(body);
+                    crate::connecting::parse(status, &body, &Default::default())
+                }
+
+                fn parse_response_with(
+                    status: http::StatusCode,
+                    body: &bytes::Bytes,
+                    options: &crate::request::ParseOptions,
+                ) -> crate::error::Result<Self> {
+                    crate::connecting::parse(status, body, options)
+                }
+            }
+        )*
+    };
+}
+
+impl_parse_response!(
+    LoggedIn,
+    RegistrationSuccess,
+    MyInfo,
+    UserInfo,
+    MoneyHistory,
+    WorldStartRoom,
+    MapStats,
+    RoomOverview,
+    RoomTerrain,
+    Vec<ShardInfo>,
+    RoomStatus,
+    RecentPvp,
+    Vec<LeaderboardSeason>,
+    FoundUserRank,
+    Vec<FoundUserRank>,
+    LeaderboardPage,
+    MarketOrdersIndex,
+    MarketOrders,
+    MyMarketOrders,
+    MemorySegment,
+    SetMemorySegment,
+    ServerVersion,
+);
+
+#[cfg(feature = "sockets")]
+impl_parse_response!(RoomObjects);
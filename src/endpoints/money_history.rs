@@ -0,0 +1,387 @@
+//! Interpreting the logged in user's credit transaction history.
+
+use crate::{
+    data::{self, Credits, OrderType, ResourceType, RoomName},
+    error::{ApiError, Result},
+    EndpointResult,
+};
+
+/// Money history raw result.
+#[derive(serde_derive::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Response {
+    ok: i32,
+    #[serde(default)]
+    page: u32,
+    #[serde(default)]
+    has_more: bool,
+    list: Vec<TransactionResponse>,
+}
+
+#[derive(serde_derive::Deserialize, Clone, Debug)]
+struct TransactionResponse {
+    _id: String,
+    date: String,
+    tick: u32,
+    #[serde(rename = "type")]
+    transaction_type: String,
+    balance: Credits,
+    change: Credits,
+    #[serde(default)]
+    market: Option<serde_json::Value>,
+}
+
+/// One page of the logged in user's credit transactions, most recent first.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MoneyHistory {
+    /// The page number of this page, starting at 0 for the most recent transactions.
+    pub page: u32,
+    /// Whether there are older transactions on later pages.
+    pub has_more: bool,
+    /// The transactions on this page.
+    pub transactions: Vec<MoneyTransaction>,
+    /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
+    _non_exhaustive: (),
+}
+
+/// A single change to the user's credit balance.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MoneyTransaction {
+    /// The unique ID of this transaction.
+    pub id: String,
+    /// The date of this transaction, in the format like 2017-03-04T05:38:04.012Z.
+    pub date: String,
+    /// The game time of this transaction.
+    pub tick: u32,
+    /// The user's credit balance after this transaction.
+    pub balance: Credits,
+    /// The change to the user's credit balance, negative when credits were spent.
+    pub change: Credits,
+    /// What the credits were spent or earned on.
+    pub kind: TransactionKind,
+    /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
+    _non_exhaustive: (),
+}
+
+#[cfg(feature = "chrono")]
+impl MoneyTransaction {
+    /// The date of this transaction, or `None` if it isn't in the expected format.
+    pub fn datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        crate::decoders::parse_iso_datetime(&self.date)
+    }
+}
+
+/// What a transaction spent or earned credits on, from its `type` and `market` fields.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum TransactionKind {
+    /// Buying resources by dealing with a sell order, or having a buy order dealt with: `"market.buy"`.
+    MarketBuy(MarketDeal),
+    /// Selling resources by dealing with a buy order, or having a sell order dealt with: `"market.sell"`.
+    MarketSell(MarketDeal),
+    /// A fee for creating or changing a market order: `"market.fee"`.
+    MarketFee(MarketFee),
+    /// A subscription purchase: `"user.subscription"`.
+    Subscription,
+    /// Any transaction type not known to this library, or a known one whose market details are missing or in an
+    /// unexpected format.
+    Other {
+        /// The transaction's `type`, such as `"market.buy"`.
+        transaction_type: String,
+        /// The transaction's `market` field, if it had one.
+        market: Option<serde_json::Value>,
+    },
+}
+
+impl TransactionKind {
+    fn from_raw(transaction_type: String, market: Option<serde_json::Value>) -> Self {
+        fn parse<T>(market: &Option<serde_json::Value>) -> Option<T>
+        where
+            T: serde::de::DeserializeOwned,
+        {
+            T::deserialize(market.as_ref()?).ok()
+        }
+
+        let kind = match &*transaction_type {
+            "market.buy" => parse(&market).map(TransactionKind::MarketBuy),
+            "market.sell" => parse(&market).map(TransactionKind::MarketSell),
+            "market.fee" => parse(&market).map(TransactionKind::MarketFee),
+            "user.subscription" => Some(TransactionKind::Subscription),
+            _ => None,
+        };
+
+        kind.unwrap_or(TransactionKind::Other {
+            transaction_type,
+            market,
+        })
+    }
+}
+
+/// A deal made on the market, bought or sold by the user.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketDeal {
+    /// The resource traded.
+    pub resource_type: ResourceType,
+    /// The amount of the resource traded.
+    pub amount: u32,
+    /// The price of each unit of the resource.
+    pub price: Credits,
+    /// The room the order was placed from, if it was for a resource held in rooms.
+    #[serde(default)]
+    pub room_name: Option<RoomName>,
+    /// The room which dealt with the order, if it was for a resource held in rooms.
+    #[serde(default)]
+    pub target_room_name: Option<RoomName>,
+    /// The user ID of the owner of the order, if it wasn't an NPC's.
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// The user ID of the user who dealt with the order.
+    #[serde(default)]
+    pub dealer: Option<String>,
+    /// Whether the order belonged to an NPC terminal.
+    #[serde(default)]
+    pub npc: bool,
+}
+
+/// A fee paid for creating or changing a market order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum MarketFee {
+    /// The fee for creating an order.
+    #[serde(rename_all = "camelCase")]
+    Order {
+        /// Whether the order buys or sells.
+        #[serde(rename = "type")]
+        order_type: OrderType,
+        /// The resource the order trades.
+        resource_type: ResourceType,
+        /// The price of each unit of the resource.
+        price: Credits,
+        /// The total amount of the resource the order trades.
+        total_amount: u32,
+        /// The room the order was placed from, if it is for a resource held in rooms.
+        #[serde(default)]
+        room_name: Option<RoomName>,
+    },
+    /// The fee for adding to the amount of an order.
+    #[serde(rename_all = "camelCase")]
+    ExtendOrder {
+        /// The ID of the order.
+        order_id: String,
+        /// The amount added to the order.
+        add_amount: u32,
+    },
+    /// The fee for raising the price of an order.
+    #[serde(rename_all = "camelCase")]
+    ChangeOrderPrice {
+        /// The ID of the order.
+        order_id: String,
+        /// The price of the order before the change.
+        old_price: Credits,
+        /// The price of the order after the change.
+        new_price: Credits,
+    },
+}
+
+impl EndpointResult for MoneyHistory {
+    type RequestResult<'de> = Response;
+    type ErrorResult = data::ApiError;
+
+    fn from_raw(raw: Response) -> Result<MoneyHistory> {
+        let Response {
+            ok,
+            page,
+            has_more,
+            list,
+        } = raw;
+
+        if ok != 1 {
+            return Err(ApiError::NotOk(ok).into());
+        }
+
+        Ok(MoneyHistory {
+            page,
+            has_more,
+            transactions: list
+                .into_iter()
+                .map(|transaction| {
+                    let TransactionResponse {
+                        _id: id,
+                        date,
+                        tick,
+                        transaction_type,
+                        balance,
+                        change,
+                        market,
+                    } = transaction;
+
+                    MoneyTransaction {
+                        id,
+                        date,
+                        tick,
+                        balance,
+                        change,
+                        kind: TransactionKind::from_raw(transaction_type, market),
+                        _non_exhaustive: (),
+                    }
+                })
+                .collect(),
+            _non_exhaustive: (),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MarketFee, MoneyHistory, TransactionKind};
+    use crate::{Credits, EndpointResult, OrderType, ResourceType, RoomName};
+
+    #[test]
+    fn parse_sample_money_history() {
+        let response = serde_json::from_value(json!({
+            "ok": 1,
+            "page": 0,
+            "list": [
+                {
+                    "_id": "5a84a2a8b3e1f4c2c9f6a1b2",
+                    "date": "2018-02-14T21:02:34.470Z",
+                    "tick": 23481634,
+                    "user": "57874d42d0ae911e3bd15bbc",
+                    "type": "market.sell",
+                    "balance": 1020.5,
+                    "change": 20.5,
+                    "market": {
+                        "resourceType": "energy",
+                        "roomName": "W41N48",
+                        "targetRoomName": "E1N1",
+                        "price": 0.0205,
+                        "npc": false,
+                        "owner": "57874d42d0ae911e3bd15bbc",
+                        "dealer": "5a0e1d2c3b4a5f6e7d8c9b0a",
+                        "amount": 1000
+                    }
+                },
+                {
+                    "_id": "5a84a2a8b3e1f4c2c9f6a1b1",
+                    "date": "2018-02-14T20:00:00.000Z",
+                    "tick": 23481000,
+                    "user": "57874d42d0ae911e3bd15bbc",
+                    "type": "market.fee",
+                    "balance": 1000,
+                    "change": -2.5,
+                    "market": {
+                        "order": {
+                            "type": "sell",
+                            "resourceType": "energy",
+                            "price": 0.025,
+                            "totalAmount": 2000,
+                            "roomName": "W41N48"
+                        }
+                    }
+                },
+                {
+                    "_id": "5a84a2a8b3e1f4c2c9f6a1b0",
+                    "date": "2018-02-14T19:00:00.000Z",
+                    "tick": 23480000,
+                    "user": "57874d42d0ae911e3bd15bbc",
+                    "type": "power.purchase",
+                    "balance": 1002.5,
+                    "change": -100
+                }
+            ],
+            "hasMore": true
+        }))
+        .unwrap();
+
+        let history = MoneyHistory::from_raw(response).unwrap();
+        assert!(history.has_more);
+        assert_eq!(history.transactions.len(), 3);
+
+        let sale = &history.transactions[0];
+        assert_eq!(sale.change, Credits::from_milli(20_500));
+        match sale.kind {
+            TransactionKind::MarketSell(ref deal) => {
+                assert_eq!(deal.resource_type, ResourceType::Energy);
+                assert_eq!(deal.amount, 1000);
+                assert_eq!(deal.room_name, Some(RoomName::new("W41N48").unwrap()));
+            }
+            ref other => panic!("expected market sale, found {:?}", other),
+        }
+
+        match history.transactions[1].kind {
+            TransactionKind::MarketFee(MarketFee::Order {
+                order_type,
+                total_amount,
+                ..
+            }) => {
+                assert_eq!(order_type, OrderType::Sell);
+                assert_eq!(total_amount, 2000);
+            }
+            ref other => panic!("expected order fee, found {:?}", other),
+        }
+
+        match history.transactions[2].kind {
+            TransactionKind::Other {
+                ref transaction_type,
+                ref market,
+            } => {
+                assert_eq!(transaction_type, "power.purchase");
+                assert_eq!(market, &None);
+            }
+            ref other => panic!("expected unknown transaction, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_malformed_market_transaction() {
+        let response = serde_json::from_value(json!({
+            "ok": 1,
+            "page": 0,
+            "list": [
+                {
+                    "_id": "5a84a2a8b3e1f4c2c9f6a1b2",
+                    "date": "2018-02-14T21:02:34.470Z",
+                    "tick": 23481634,
+                    "type": "market.buy",
+                    "balance": 1000,
+                    "change": -20
+                },
+                {
+                    "_id": "5a84a2a8b3e1f4c2c9f6a1b1",
+                    "date": "2018-02-14T20:00:00.000Z",
+                    "tick": 23481000,
+                    "type": "market.sell",
+                    "balance": 1020,
+                    "change": 20,
+                    "market": { "resourceType": 5 }
+                }
+            ],
+            "hasMore": false
+        }))
+        .unwrap();
+
+        let history = MoneyHistory::from_raw(response).unwrap();
+        match history.transactions[0].kind {
+            TransactionKind::Other {
+                ref transaction_type,
+                ref market,
+            } => {
+                assert_eq!(transaction_type, "market.buy");
+                assert_eq!(market, &None);
+            }
+            ref other => panic!("expected unparsed transaction, found {:?}", other),
+        }
+        match history.transactions[1].kind {
+            TransactionKind::Other {
+                ref transaction_type,
+                ref market,
+            } => {
+                assert_eq!(transaction_type, "market.sell");
+                assert_eq!(market, &Some(json!({ "resourceType": 5 })));
+            }
+            ref other => panic!("expected unparsed transaction, found {:?}", other),
+        }
+    }
+}
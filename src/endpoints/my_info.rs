@@ -4,7 +4,7 @@ use std::collections::HashMap;
 use time::Timespec;
 
 use crate::{
-    data::{self, Badge},
+    data::{self, Badge, Credits},
     decoders::{optional_timespec_seconds, timespec_seconds},
     error::{ApiError, Result},
     EndpointResult,
@@ -23,7 +23,7 @@ pub(crate) struct Response {
     password: bool,
     cpu: i32,
     gcl: u64,
-    money: f64,
+    money: Credits,
     // These can be added if needed
     // lastChargeTime: Option<String>,
     // lastTweetTime: Option<String>,
@@ -53,7 +53,7 @@ pub struct MyInfo {
     /// This user's current total count of GCL points (perform calculation to find actual gcl level).
     pub gcl_points: u64,
     /// This user's current credit balance.
-    pub credits: f64,
+    pub credits: Credits,
     /// Information on per-shard allocation. Unavailable on non-sharded servers.
     pub shard_allocations: Option<UserCpuShardAllocation>,
     /// Phantom data in order to allow adding any additional fields in the future.
@@ -71,8 +71,16 @@ pub struct UserCpuShardAllocation {
     pub last_update: Timespec,
 }
 
+#[cfg(feature = "chrono")]
+impl UserCpuShardAllocation {
+    /// The last time the CPU allocation was updated, interpreting `last_update` as milliseconds since the unix epoch.
+    pub fn last_update_datetime(&self) -> chrono::DateTime<chrono::Utc> {
+        crate::decoders::millis_to_datetime(self.last_update.sec)
+    }
+}
+
 impl EndpointResult for MyInfo {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: Response) -> Result<MyInfo> {
@@ -113,18 +121,18 @@ impl EndpointResult for MyInfo {
 #[cfg(test)]
 mod tests {
     use super::MyInfo;
-    use crate::EndpointResult;
+    use crate::{Credits, EndpointResult};
     use serde_json;
 
-    fn test_parse(json: serde_json::Value) {
+    fn test_parse(json: serde_json::Value) -> MyInfo {
         let response = serde_json::from_value(json).unwrap();
 
-        let _ = MyInfo::from_raw(response).unwrap();
+        MyInfo::from_raw(response).unwrap()
     }
 
     #[test]
     fn parse_sample_info() {
-        test_parse(json! ({
+        let info = test_parse(json! ({
             "_id": "57874d42d0ae911e3bd15bbc",
             "badge": {
                 "color1": "#260d0d",
@@ -161,5 +169,6 @@ mod tests {
             "subscriptionTokens": 0,
             "username": "daboross"
         }));
+        assert_eq!(info.credits, Credits::from_milli(3_957_697_950));
     }
 }
@@ -54,26 +54,29 @@ struct InnerRoom {
 }
 
 /// Result storing recent pvp matches for the entire world.
+#[derive(Serialize, Deserialize, Clone, Hash, Debug)]
 pub struct RecentPvp {
     /// A list of shard names and the recent pvp within that shard.
     pub shards: Vec<(String, ShardRecentPvp)>,
     /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
     _non_exhaustive: (),
 }
 
 /// Result storing recent pvp matches for a particular shard.
-#[derive(Clone, Hash, Debug)]
+#[derive(Serialize, Deserialize, Clone, Hash, Debug)]
 pub struct ShardRecentPvp {
     /// A list of room names in which pvp has recently occurred, and the time at which pvp last occurred.
     pub rooms: Vec<(data::RoomName, u32)>,
     /// The current game time of the server when the call was completed, the tick up to which pvp has been reported.
     pub reported_up_to: u32,
     /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
     _non_exhaustive: (),
 }
 
 impl EndpointResult for RecentPvp {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: Response) -> Result<RecentPvp> {
@@ -1,14 +1,14 @@
 //! Creating registration calls and interpreting registration results.
-use std::borrow::Cow;
+use std::{borrow::Cow, fmt};
 
 use crate::{
     data,
     error::{ApiError, Result},
-    EndpointResult,
+    redact, EndpointResult,
 };
 
 /// Registration details
-#[derive(Serialize, Clone, Hash, Debug)]
+#[derive(Serialize, Clone, Hash)]
 pub struct RegistrationArgs<'a> {
     /// The username to register.
     username: Cow<'a, str>,
@@ -46,6 +46,16 @@ impl<'a> RegistrationArgs<'a> {
     }
 }
 
+impl fmt::Debug for RegistrationArgs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RegistrationArgs")
+            .field("username", &self.username)
+            .field("email", &self.email)
+            .field("password", &redact::REDACTED)
+            .finish()
+    }
+}
+
 /// Raw registration response.
 #[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
 pub(crate) struct Response {
@@ -53,14 +63,15 @@ pub(crate) struct Response {
 }
 
 /// Registration success response.
-#[derive(Clone, Hash, Debug)]
+#[derive(Serialize, Deserialize, Clone, Hash, Debug)]
 pub struct RegistrationSuccess {
     /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
     _non_exhaustive: (),
 }
 
 impl EndpointResult for RegistrationSuccess {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: Response) -> Result<RegistrationSuccess> {
@@ -0,0 +1,136 @@
+//! Interpreting room objects results.
+use std::collections::HashMap;
+
+use crate::{
+    data,
+    error::{ApiError, Result},
+    websocket::{objects::RoomObject, RoomUserInfo},
+    EndpointResult,
+};
+
+/// Room objects raw result.
+#[derive(serde_derive::Deserialize, Clone, Debug)]
+pub(crate) struct Response {
+    ok: i32,
+    objects: Option<Vec<RoomObject>>,
+    #[serde(default)]
+    users: HashMap<String, RoomUserInfo>,
+}
+
+/// All objects in a room, along with the users who own them.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RoomObjects {
+    /// Every object in the room.
+    pub objects: Vec<RoomObject>,
+    /// Information on all users who have objects in this room, keyed by user ID.
+    pub users: HashMap<String, RoomUserInfo>,
+    /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
+    _non_exhaustive: (),
+}
+
+impl RoomObjects {
+    /// Gets the username of the given user ID, if they have objects in this room.
+    pub fn username(&self, user_id: &str) -> Option<&str> {
+        self.users
+            .get(user_id)
+            .and_then(|info| info.username.as_ref())
+            .map(String::as_str)
+    }
+}
+
+impl EndpointResult for RoomObjects {
+    type RequestResult<'de> = Response;
+    type ErrorResult = data::ApiError;
+
+    fn from_raw(raw: Response) -> Result<RoomObjects> {
+        let Response { ok, objects, users } = raw;
+
+        if ok != 1 {
+            return Err(ApiError::NotOk(ok).into());
+        }
+
+        let objects = match objects {
+            Some(v) => v,
+            None => return Err(ApiError::MissingField("objects").into()),
+        };
+
+        Ok(RoomObjects {
+            objects,
+            users,
+            _non_exhaustive: (),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoomObjects;
+    use crate::{websocket::objects::RoomObject, EndpointResult};
+
+    fn test_parse(json: serde_json::Value) -> RoomObjects {
+        let response = serde_json::from_value(json).unwrap();
+
+        RoomObjects::from_raw(response).unwrap()
+    }
+
+    #[test]
+    fn parse_sample_objects() {
+        let result = test_parse(json!({
+            "ok": 1,
+            "objects": [
+                {
+                    "_id": "594cac66e1dd5c8d2eb7df9d",
+                    "energy": 200,
+                    "energyCapacity": 200,
+                    "hits": 1000,
+                    "hitsMax": 1000,
+                    "notifyWhenAttacked": true,
+                    "off": false,
+                    "room": "E4S61",
+                    "type": "extension",
+                    "user": "57874d42d0ae911e3bd15bbc",
+                    "x": 27,
+                    "y": 3
+                },
+                {
+                    "_id": "5f0b7b6e0a1b2c3d4e5f6a7b",
+                    "room": "E4S61",
+                    "type": "factory",
+                    "user": "57874d42d0ae911e3bd15bbc",
+                    "x": 20,
+                    "y": 30,
+                    "level": 2
+                }
+            ],
+            "users": {
+                "57874d42d0ae911e3bd15bbc": {
+                    "_id": "57874d42d0ae911e3bd15bbc",
+                    "username": "daboross"
+                }
+            }
+        }));
+
+        assert_eq!(result.objects.len(), 2);
+        match result.objects[0] {
+            RoomObject::Extension(ref extension) => assert_eq!(extension.energy, 200),
+            ref other => panic!("expected an extension, found {:?}", other),
+        }
+        assert_eq!(result.objects[1].object_type(), "factory");
+        assert_eq!(
+            result.username("57874d42d0ae911e3bd15bbc"),
+            Some("daboross")
+        );
+
+        let serialized = serde_json::to_value(&result).unwrap();
+        assert_eq!(serialized["objects"][0]["type"], "extension");
+        assert_eq!(serialized["objects"][0]["energy"], 200);
+        assert_eq!(serialized["objects"][1]["level"], 2);
+        let reparsed: RoomObjects = serde_json::from_value(serialized.clone()).unwrap();
+        match reparsed.objects[0] {
+            RoomObject::Extension(ref extension) => assert_eq!(extension.energy, 200),
+            ref other => panic!("expected an extension, found {:?}", other),
+        }
+        assert_eq!(serde_json::to_value(&reparsed).unwrap(), serialized);
+    }
+}
@@ -1,7 +1,7 @@
 //! Interpreting room overview results.
 
 use crate::{
-    data::{self, Badge},
+    data::{self, UserInfo},
     error::{ApiError, Result},
     EndpointResult,
 };
@@ -11,17 +11,11 @@ use crate::{
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Response {
     ok: i32,
-    owner: Option<OwnerResponse>,
+    owner: Option<UserInfo>,
     stats: Option<RoomStatsResponse>,
     stats_max: Option<RoomTotalStatsResponse>,
 }
 
-#[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
-struct OwnerResponse {
-    username: String,
-    badge: Badge,
-}
-
 #[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
 #[serde(rename_all = "camelCase")]
 struct RoomStatsResponse {
@@ -110,10 +104,8 @@ pub struct TotalStats {
 /// Various statistics about a single room, returned as a result from `room_overview` calls.
 #[derive(Serialize, Deserialize, Clone, Hash, Debug)]
 pub struct RoomOverview {
-    /// The username of the owner of the room.
-    pub owner: Option<String>,
-    /// The owner's badge
-    pub owner_badge: Option<Badge>,
+    /// The owner of the room, if any. Only the owner's username and badge are included.
+    pub owner: Option<UserInfo>,
     /// Energy harvested during each interval of the requested time.
     pub energy_harvested: Vec<StatPoint>,
     /// Energy spent on creeps during each interval of the requested time.
@@ -134,7 +126,7 @@ pub struct RoomOverview {
 }
 
 impl EndpointResult for RoomOverview {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: Response) -> Result<RoomOverview> {
@@ -148,10 +140,6 @@ impl EndpointResult for RoomOverview {
         if ok != 1 {
             return Err(ApiError::NotOk(ok).into());
         }
-        let (username, badge) = match owner {
-            Some(v) => (Some(v.username), Some(v.badge)),
-            None => (None, None),
-        };
         let stats = match stats {
             Some(v) => v,
             None => return Err(ApiError::MissingField("stats").into()),
@@ -162,8 +150,7 @@ impl EndpointResult for RoomOverview {
         };
 
         Ok(RoomOverview {
-            owner: username,
-            owner_badge: badge,
+            owner,
             energy_harvested: stats.energy_harvested.into_iter().map(Into::into).collect(),
             energy_spent_construction: stats
                 .energy_construction
@@ -217,15 +204,15 @@ mod tests {
     use crate::EndpointResult;
     use serde_json;
 
-    fn test_parse(json: serde_json::Value) {
+    fn test_parse(json: serde_json::Value) -> RoomOverview {
         let response = serde_json::from_value(json).unwrap();
 
-        let _ = RoomOverview::from_raw(response).unwrap();
+        RoomOverview::from_raw(response).unwrap()
     }
 
     #[test]
     fn parse_sample_overview_e15n52() {
-        test_parse(json! ({
+        let overview = test_parse(json! ({
             "ok": 1,
             "owner": {
                 "badge": {
@@ -515,5 +502,7 @@ mod tests {
                 "energyHarvested": 18960
             }
         }));
+
+        assert_eq!(overview.owner.unwrap().username, "daboross");
     }
 }
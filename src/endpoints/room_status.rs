@@ -1,7 +1,7 @@
 //! Interpreting room status results.
 
 use crate::{
-    data::{self, RoomName, RoomState},
+    data::{self, RoomName, RoomSign, RoomState},
     decoders::optional_timespec_seconds,
     error::{ApiError, Result},
     EndpointResult,
@@ -29,6 +29,14 @@ struct InnerRoom {
     #[serde(with = "optional_timespec_seconds")]
     #[serde(default)]
     open_time: Option<time::Timespec>,
+    /// The end time for the respawn area this room is or was last in.
+    #[serde(rename = "respawnArea")]
+    #[serde(with = "optional_timespec_seconds")]
+    #[serde(default)]
+    respawn_area: Option<time::Timespec>,
+    /// The room's player-set sign.
+    #[serde(default)]
+    sign: Option<RoomSign>,
 }
 
 /// Struct describing the status of a room
@@ -39,13 +47,38 @@ pub struct RoomStatus {
     /// The state of the room, determined by comparing the API response timestamps with the current UTC time, as
     /// retrieved from the system.
     pub state: RoomState,
+    /// The time the novice area this room is or was last in ends or ended, if it has ever been in one.
+    ///
+    /// Unlike `state`, this is reported even after the novice area has ended.
+    #[serde(with = "optional_timespec_seconds")]
+    pub novice_area_end: Option<time::Timespec>,
+    /// The time the respawn area this room is or was last in ends or ended, if it has ever been in one.
+    #[serde(with = "optional_timespec_seconds")]
+    pub respawn_area_end: Option<time::Timespec>,
+    /// The room's player-set sign, if any.
+    pub sign: Option<RoomSign>,
     /// Phantom data in order to allow adding any additional fields in the future.
     #[serde(skip)]
     _non_exhaustive: (),
 }
 
+#[cfg(feature = "chrono")]
+impl RoomStatus {
+    /// The time the novice area this room is or was last in ends or ended, if it has ever been in one.
+    pub fn novice_area_end_datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.novice_area_end
+            .map(|time| crate::decoders::millis_to_datetime(time.sec))
+    }
+
+    /// The time the respawn area this room is or was last in ends or ended, if it has ever been in one.
+    pub fn respawn_area_end_datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.respawn_area_end
+            .map(|time| crate::decoders::millis_to_datetime(time.sec))
+    }
+}
+
 impl EndpointResult for RoomStatus {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: Response) -> Result<RoomStatus> {
@@ -60,12 +93,17 @@ impl EndpointResult for RoomStatus {
             status,
             novice,
             open_time,
+            respawn_area,
+            sign,
         } = match room {
             Some(v) => v,
             None => {
                 return Ok(RoomStatus {
                     room_name: None,
                     state: RoomState::non_existant(),
+                    novice_area_end: None,
+                    respawn_area_end: None,
+                    sign: None,
                     _non_exhaustive: (),
                 });
             }
@@ -85,6 +123,9 @@ impl EndpointResult for RoomStatus {
         Ok(RoomStatus {
             room_name: Some(RoomName::new(&room_name)?),
             state: state,
+            novice_area_end: novice,
+            respawn_area_end: respawn_area,
+            sign,
             _non_exhaustive: (),
         })
     }
@@ -95,11 +136,12 @@ mod tests {
     use super::RoomStatus;
     use crate::EndpointResult;
     use serde_json;
+    use time::Timespec;
 
-    fn test_parse(json: serde_json::Value) {
+    fn test_parse(json: serde_json::Value) -> RoomStatus {
         let response = serde_json::from_value(json).unwrap();
 
-        let _ = RoomStatus::from_raw(response).unwrap();
+        RoomStatus::from_raw(response).unwrap()
     }
 
     #[test]
@@ -137,4 +179,36 @@ mod tests {
             }
         }));
     }
+
+    #[test]
+    fn parse_sample_signed_respawn_room() {
+        let status = test_parse(json! ({
+            "ok": 1,
+            "room": {
+                "_id": "W41N48",
+                "status": "normal",
+                "novice": 1475538699273i64,
+                "respawnArea": 1583107200000i64,
+                "sign": {
+                    "time": 18318966,
+                    "text": "Outer reach settlement",
+                    "user": "57e0dde6adafdf710cc02af0",
+                    "datetime": 1490723256463i64
+                }
+            }
+        }));
+
+        assert_eq!(
+            status.novice_area_end,
+            Some(Timespec::new(1475538699273, 0))
+        );
+        assert_eq!(
+            status.respawn_area_end,
+            Some(Timespec::new(1583107200000, 0))
+        );
+        let sign = status.sign.unwrap();
+        assert_eq!(sign.user_id, "57e0dde6adafdf710cc02af0");
+        assert_eq!(sign.text, "Outer reach settlement");
+        assert_eq!(sign.game_time_set, 18318966);
+    }
 }
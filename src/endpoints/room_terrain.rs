@@ -1,32 +1,56 @@
 //! Interpreting room terrain results.
-
-use arrayvec::ArrayVec;
+use std::{borrow::Cow, convert::TryFrom, fmt, ops::Index};
 
 use crate::{
     data,
     error::{ApiError, Result},
-    EndpointResult,
+    EndpointResult, RoomXY,
 };
 
 /// Room overview raw result.
 #[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
-pub(crate) struct Response {
+pub(crate) struct Response<'a> {
     ok: i32,
-    terrain: Option<Vec<InnerResponse>>,
+    #[serde(borrow)]
+    terrain: Option<TerrainResponse<'a>>,
+}
+
+/// Terrain in either of the formats the server returns it in.
+#[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
+#[serde(untagged)]
+enum TerrainResponse<'a> {
+    /// Requested with `encoded=true`: a single object containing the whole room.
+    #[serde(borrow)]
+    Encoded(Vec<InnerResponse<'a>>),
+    /// Otherwise: an object for each tile which isn't plains.
+    Verbose(Vec<VerboseTile>),
 }
 
 #[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
-struct InnerResponse {
+struct InnerResponse<'a> {
     // this is returned as part of the data, but what the heck is it even for?
     /// A cache key maybe?
     _id: String,
     /// Should be `terrain`.
-    #[serde(rename = "type")]
-    response_type: String,
+    #[serde(rename = "type", borrow)]
+    response_type: Cow<'a, str>,
+    /// room name
+    #[serde(borrow)]
+    room: Cow<'a, str>,
+    /// encoded data, borrowed from the response body.
+    #[serde(borrow)]
+    terrain: Cow<'a, str>,
+}
+
+#[derive(serde_derive::Deserialize, Clone, Hash, Debug)]
+struct VerboseTile {
     /// room name
     room: String,
-    /// encoded data
-    terrain: String,
+    x: u32,
+    y: u32,
+    /// `wall` or `swamp`.
+    #[serde(rename = "type")]
+    terrain_type: String,
 }
 
 /// Type of terrain
@@ -42,35 +66,224 @@ pub enum TerrainType {
     SwampyWall,
 }
 
-/// A type alias for a single row in the terrain grid.
-pub type TerrainRow = ArrayVec<[TerrainType; 50]>;
+/// Terrain types by their 2-bit code, which is also their digit in encoded terrain.
+static TERRAIN_CODES: [TerrainType; 4] = [
+    TerrainType::Plains,
+    TerrainType::Wall,
+    TerrainType::Swamp,
+    TerrainType::SwampyWall,
+];
+
+impl TerrainType {
+    /// The 2-bit code of this terrain type: a bit for walls, and a bit for swamps.
+    fn code(self) -> u8 {
+        match self {
+            TerrainType::Plains => 0,
+            TerrainType::Wall => 1,
+            TerrainType::Swamp => 2,
+            TerrainType::SwampyWall => 3,
+        }
+    }
+}
+
+/// The terrain of a whole room, stored in 2 bits per tile.
+///
+/// Index it with a [`RoomXY`] to get the terrain at a position. Parsed from the terrain endpoint's responses, or
+/// built from terrain in the encoded string format with [`TerrainGrid::from_encoded`].
+///
+/// ```
+/// use screeps_api::{RoomXY, TerrainGrid, TerrainType};
+///
+/// let mut encoded = "0".repeat(2500);
+/// encoded.replace_range(51..52, "1");
+/// let terrain = TerrainGrid::from_encoded(&encoded).unwrap();
+///
+/// let wall = RoomXY::new(1, 1).unwrap();
+/// assert_eq!(terrain[wall], TerrainType::Wall);
+/// assert_eq!(terrain.tiles_of(TerrainType::Wall).collect::<Vec<_>>(), [wall]);
+/// ```
+///
+/// [`RoomXY`]: struct.RoomXY.html
+/// [`TerrainGrid::from_encoded`]: #method.from_encoded
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct TerrainGrid {
+    /// 4 tiles per byte, in row-major order, with the first tile in the lowest bits.
+    bits: Box<[u8]>,
+}
+
+impl TerrainGrid {
+    /// Creates a grid which is all plains.
+    pub fn new() -> Self {
+        TerrainGrid {
+            bits: vec![0; 2500 / 4].into_boxed_slice(),
+        }
+    }
 
-/// A type alias for a 50x50 terrain grid. `terrain[y_pos][x_pos]` can be used to access any terrain
-/// from x 0-49, y 0-49.
-pub type TerrainGrid = ArrayVec<[TerrainRow; 50]>;
+    /// Parses terrain in the encoded format: 2500 digits, a row at a time, where `0` is plains, `1` is a wall, `2`
+    /// is a swamp and `3` is a wall on a swamp.
+    pub fn from_encoded(encoded: &str) -> Result<Self> {
+        if encoded.len() != 2500 {
+            return Err(ApiError::MalformedResponse(format!(
+                "expected encoded terrain to be a 2500 byte string, found a {} byte string.",
+                encoded.len()
+            ))
+            .into());
+        }
+
+        let mut grid = TerrainGrid::new();
+        for (index, byte) in encoded.bytes().enumerate() {
+            let code = match byte {
+                b'0'..=b'3' => byte - b'0',
+                other => {
+                    let xy = RoomXY::from_index(index).expect("expected index within room");
+                    return Err(ApiError::MalformedResponse(format!(
+                        "expected terrain data to contain only characters 0,1,2,3, found byte {} at x,y {},{}.",
+                        other,
+                        xy.x(),
+                        xy.y()
+                    ))
+                    .into());
+                }
+            };
+            grid.set_code(index, code);
+        }
+
+        Ok(grid)
+    }
+
+    /// Creates a grid from the terrain of the given tiles, with any other tiles being plains.
+    pub fn from_tiles<I>(tiles: I) -> Self
+    where
+        I: IntoIterator<Item = (RoomXY, TerrainType)>,
+    {
+        let mut grid = TerrainGrid::new();
+        for (xy, terrain) in tiles {
+            grid.set(xy, terrain);
+        }
+        grid
+    }
+
+    /// Gets the terrain at the given position.
+    #[inline]
+    pub fn get(&self, xy: RoomXY) -> TerrainType {
+        self[xy]
+    }
+
+    /// Sets the terrain at the given position.
+    #[inline]
+    pub fn set(&mut self, xy: RoomXY, terrain: TerrainType) {
+        self.set_code(xy.index(), terrain.code());
+    }
+
+    /// Iterates over every tile of the room and its terrain, a row at a time.
+    pub fn iter(&self) -> impl Iterator<Item = (RoomXY, TerrainType)> + '_ {
+        (0..2500).map(move |index| {
+            let xy = RoomXY::from_index(index).expect("expected index within room");
+            (xy, self[xy])
+        })
+    }
+
+    /// Iterates over the positions of every tile with the given terrain, a row at a time.
+    pub fn tiles_of(&self, terrain: TerrainType) -> impl Iterator<Item = RoomXY> + '_ {
+        self.iter()
+            .filter(move |&(_, tile)| tile == terrain)
+            .map(|(xy, _)| xy)
+    }
+
+    /// Formats this terrain in the encoded format read by [`TerrainGrid::from_encoded`].
+    ///
+    /// [`TerrainGrid::from_encoded`]: #method.from_encoded
+    pub fn to_encoded(&self) -> String {
+        (0..2500)
+            .map(|index| char::from(b'0' + self.code(index)))
+            .collect()
+    }
+
+    fn code(&self, index: usize) -> u8 {
+        (self.bits[index / 4] >> (index % 4 * 2)) & 0b11
+    }
+
+    fn set_code(&mut self, index: usize, code: u8) {
+        let shift = index % 4 * 2;
+        let byte = &mut self.bits[index / 4];
+        *byte = (*byte & !(0b11 << shift)) | (code << shift);
+    }
+}
+
+impl Default for TerrainGrid {
+    fn default() -> Self {
+        TerrainGrid::new()
+    }
+}
+
+impl Index<RoomXY> for TerrainGrid {
+    type Output = TerrainType;
+
+    fn index(&self, xy: RoomXY) -> &TerrainType {
+        &TERRAIN_CODES[usize::from(self.code(xy.index()))]
+    }
+}
+
+impl fmt::Debug for TerrainGrid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TerrainGrid")
+            .field(&self.to_encoded())
+            .finish()
+    }
+}
+
+mod serde {
+    use serde::de::{Deserialize, Deserializer, Error};
+    use serde::ser::{Serialize, Serializer};
+
+    use super::TerrainGrid;
+
+    impl Serialize for TerrainGrid {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_str(&self.to_encoded())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for TerrainGrid {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let encoded = String::deserialize(deserializer)?;
+            TerrainGrid::from_encoded(&encoded).map_err(D::Error::custom)
+        }
+    }
+}
 
 /// Structure describing the terrain of a room
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct RoomTerrain {
     /// The name of the room
     pub room_name: data::RoomName,
-    /// Cache ID? Not sure exactly...
+    /// Cache ID? Not sure exactly... This is empty when the terrain was requested in the verbose format.
     pub response_id: String,
-    /// A 50x50 grid of terrain squares. When coming from the API, this is guaranteed to be
-    /// completely filled, and accessing any square between `(0, 0)` and `(49, 49)` inclusive will
-    /// succeed.
-    ///
-    /// You can use `terrain[y_pos][x_pos]` to get any individual terrain square.
+    /// The terrain of every tile of the room.
     pub terrain: TerrainGrid,
     /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
     _non_exhaustive: (),
 }
 
+impl RoomTerrain {
+    /// Gets the terrain at the given position.
+    pub fn get(&self, xy: RoomXY) -> TerrainType {
+        self.terrain[xy]
+    }
+}
+
 impl EndpointResult for RoomTerrain {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response<'de>;
     type ErrorResult = data::ApiError;
 
-    fn from_raw(raw: Response) -> Result<RoomTerrain> {
+    fn from_raw(raw: Response<'_>) -> Result<RoomTerrain> {
         let Response {
             ok,
             terrain: terrain_array,
@@ -80,11 +293,12 @@ impl EndpointResult for RoomTerrain {
             return Err(ApiError::NotOk(ok).into());
         }
 
-        let terrain_data = match terrain_array {
-            Some(v) => match v.into_iter().next() {
+        let tiles = match terrain_array {
+            Some(TerrainResponse::Encoded(v)) => match v.into_iter().next() {
                 Some(v) => v,
                 None => return Err(ApiError::MissingField("terrain.0").into()),
             },
+            Some(TerrainResponse::Verbose(tiles)) => return from_verbose(tiles),
             None => return Err(ApiError::MissingField("terrain").into()),
         };
 
@@ -93,16 +307,7 @@ impl EndpointResult for RoomTerrain {
             room: room_string,
             _id: response_id,
             terrain,
-        } = terrain_data;
-
-        if terrain.len() != 2500 {
-            return Err(ApiError::MalformedResponse(format!(
-                "expected response.terrain[0].\
-                 terrain to be a 2500 byte string, found a {} byte string.",
-                terrain.len()
-            ))
-            .into());
-        }
+        } = tiles;
 
         if response_type != "terrain" {
             return Err(ApiError::MalformedResponse(format!(
@@ -116,49 +321,105 @@ impl EndpointResult for RoomTerrain {
         Ok(RoomTerrain {
             room_name: data::RoomName::new(&room_string)?,
             response_id: response_id,
-            terrain: terrain
-                .into_bytes()
-                .chunks(50)
-                .enumerate()
-                .map(|(y, chunk)| {
-                    chunk
-                        .iter()
-                        .enumerate()
-                        .map(|(x, byte)| match *byte {
-                            b'0' => Ok(TerrainType::Plains),
-                            b'1' => Ok(TerrainType::Wall),
-                            b'2' => Ok(TerrainType::Swamp),
-                            b'3' => Ok(TerrainType::SwampyWall),
-                            other => Err(ApiError::MalformedResponse(format!(
-                                "expected terrain data to contain \
-                                 only characters 0,1,2,3, found byte {} at x,y {},{}.",
-                                other, x, y
-                            ))
-                            .into()),
-                        })
-                        .collect::<Result<_>>()
-                })
-                .collect::<Result<_>>()?,
+            terrain: TerrainGrid::from_encoded(&terrain)?,
             _non_exhaustive: (),
         })
     }
 }
 
+/// Interprets terrain in the verbose format, listing each tile which isn't plains.
+fn from_verbose(tiles: Vec<VerboseTile>) -> Result<RoomTerrain> {
+    let room_name = match tiles.first() {
+        Some(tile) => data::RoomName::new(&tile.room)?,
+        None => return Err(ApiError::MissingField("terrain.0.room").into()),
+    };
+
+    let mut terrain = TerrainGrid::new();
+    for tile in tiles {
+        let xy = RoomXY::try_from((tile.x, tile.y)).map_err(|_| {
+            ApiError::MalformedResponse(format!(
+                "expected terrain tile within room, found x,y {},{}.",
+                tile.x, tile.y
+            ))
+        })?;
+        let code = match &*tile.terrain_type {
+            "wall" => TerrainType::Wall.code(),
+            "swamp" => TerrainType::Swamp.code(),
+            "plain" | "plains" => continue,
+            other => {
+                return Err(ApiError::MalformedResponse(format!(
+                    "expected terrain type to be 'wall' or 'swamp', found {:?}",
+                    other
+                ))
+                .into())
+            }
+        };
+        // a tile listed as both a wall and a swamp is a wall on a swamp.
+        let index = xy.index();
+        let existing = terrain.code(index);
+        terrain.set_code(index, existing | code);
+    }
+
+    Ok(RoomTerrain {
+        room_name,
+        response_id: String::new(),
+        terrain,
+        _non_exhaustive: (),
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use super::RoomTerrain;
-    use crate::EndpointResult;
+    use super::{RoomTerrain, TerrainGrid, TerrainType};
+    use crate::{EndpointResult, RoomName, RoomXY};
     use serde_json;
 
-    fn test_parse(json: serde_json::Value) {
-        let response = serde_json::from_value(json).unwrap();
+    fn test_parse(json: serde_json::Value) -> RoomTerrain {
+        let body = json.to_string();
+        let response = serde_json::from_str(&body).unwrap();
+
+        let terrain = RoomTerrain::from_raw(response).unwrap();
+        assert_eq!(terrain.get(RoomXY::new(0, 0).unwrap()), TerrainType::Wall);
+        terrain
+    }
+
+    #[test]
+    fn parse_verbose_terrain() {
+        let terrain = test_parse(json!({
+            "ok": 1,
+            "terrain": [
+                { "room": "W1N1", "x": 0, "y": 0, "type": "wall" },
+                { "room": "W1N1", "x": 10, "y": 3, "type": "swamp" },
+                { "room": "W1N1", "x": 10, "y": 3, "type": "wall" },
+                { "room": "W1N1", "x": 49, "y": 49, "type": "swamp" },
+            ]
+        }));
+        assert_eq!(terrain.room_name, RoomName::new("W1N1").unwrap());
+        assert!(terrain.response_id.is_empty());
+
+        let grid = &terrain.terrain;
+        assert_eq!(grid[RoomXY::new(10, 3).unwrap()], TerrainType::SwampyWall);
+        assert_eq!(grid[RoomXY::new(1, 0).unwrap()], TerrainType::Plains);
+        assert_eq!(
+            grid.tiles_of(TerrainType::Swamp).collect::<Vec<_>>(),
+            [RoomXY::new(49, 49).unwrap()]
+        );
+        assert_eq!(grid.iter().count(), 2500);
+
+        let reencoded = TerrainGrid::from_encoded(&grid.to_encoded()).unwrap();
+        assert_eq!(&reencoded, grid);
+        assert!(TerrainGrid::from_encoded("0123").is_err());
+        assert!(TerrainGrid::from_encoded(&"4".repeat(2500)).is_err());
 
-        let _ = RoomTerrain::from_raw(response).unwrap();
+        let json = serde_json::to_value(&terrain).unwrap();
+        assert_eq!(json["terrain"], json!(grid.to_encoded()));
+        let reparsed: RoomTerrain = serde_json::from_value(json).unwrap();
+        assert_eq!(&reparsed.terrain, grid);
     }
 
     #[test]
     fn parse_sample_terrain() {
-        test_parse(json! ({
+        let terrain = test_parse(json! ({
             "ok": 1,
             "terrain": [
                 {
@@ -220,5 +481,11 @@ mod tests {
                 }
             ]
         }));
+        let xy = RoomXY::new(34, 4).unwrap();
+        assert_eq!(terrain.get(xy), TerrainType::Swamp);
+        assert_eq!(
+            terrain.terrain.tiles_of(TerrainType::SwampyWall).count(),
+            36
+        );
     }
 }
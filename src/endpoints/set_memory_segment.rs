@@ -26,14 +26,15 @@ pub struct SetMemorySegmentArgs<'a> {
 }
 
 /// Memory segment set result
-#[derive(Clone, Hash, Debug)]
-pub(crate) struct SetMemorySegment {
+#[derive(Serialize, Deserialize, Clone, Hash, Debug)]
+pub struct SetMemorySegment {
     /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
     _non_exhaustive: (),
 }
 
 impl EndpointResult for SetMemorySegment {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: Response) -> Result<Self> {
@@ -22,7 +22,7 @@ struct ShardResponse {
 }
 
 /// Structure describing information about a single game shard.
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ShardInfo {
     /// The name of this shard, useful for all shard-specific API calls.
     pub name: String,
@@ -33,6 +33,7 @@ pub struct ShardInfo {
     /// The average millisecond tick this shard has for some past period of time (TODO: more detail).
     pub tick_avg_milliseconds: f64,
     /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
     _non_exhaustive: (),
 }
 
@@ -43,7 +44,7 @@ impl AsRef<str> for ShardInfo {
 }
 
 impl EndpointResult for Vec<ShardInfo> {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: Response) -> Result<Vec<ShardInfo>> {
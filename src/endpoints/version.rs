@@ -0,0 +1,188 @@
+//! Interpreting server version calls.
+use crate::{
+    data,
+    error::{ApiError, Result},
+    server::Feature,
+    EndpointResult,
+};
+
+/// Server version raw result.
+#[derive(serde_derive::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+#[doc(hidden)]
+pub(crate) struct Response {
+    ok: i32,
+    protocol: u32,
+    #[serde(default)]
+    package: Option<u32>,
+    #[serde(default)]
+    package_version: Option<String>,
+    #[serde(default)]
+    use_native_auth: Option<bool>,
+    #[serde(default)]
+    users: Option<u64>,
+    server_data: ServerData,
+}
+
+#[derive(serde_derive::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ServerData {
+    #[serde(default)]
+    history_chunk_size: Option<u32>,
+    #[serde(default)]
+    shards: Vec<String>,
+    #[serde(default)]
+    features: Vec<ServerFeature>,
+    // private server client settings, which aren't interpreted.
+    #[allow(dead_code)]
+    #[serde(default)]
+    welcome_text: serde_json::Value,
+    #[allow(dead_code)]
+    #[serde(default)]
+    custom_object_types: serde_json::Value,
+    #[allow(dead_code)]
+    #[serde(default)]
+    socket_update_throttle: serde_json::Value,
+    #[allow(dead_code)]
+    #[serde(default)]
+    renderer: serde_json::Value,
+}
+
+#[derive(serde_derive::Deserialize, Clone, Debug)]
+struct ServerFeature {
+    name: String,
+    #[allow(dead_code)]
+    #[serde(default)]
+    version: serde_json::Value,
+}
+
+/// Information about the server's version and the features it has.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ServerVersion {
+    /// The version of the API protocol the server speaks.
+    pub protocol: u32,
+    /// The version of the game on the official servers.
+    pub package: Option<u32>,
+    /// The version of the server package on private servers.
+    pub package_version: Option<String>,
+    /// The number of registered users, if reported.
+    pub users: Option<u64>,
+    /// The number of ticks in each chunk of room history.
+    pub history_chunk_size: Option<u32>,
+    /// The names of the server's shards, if it has any.
+    pub shards: Vec<String>,
+    /// The names of the features mods have reported on a private server, such as `"screepsmod-auth"`.
+    pub mods: Vec<String>,
+    /// Whether a private server reports using its built-in authentication through Steam.
+    pub native_auth: bool,
+    /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
+    _non_exhaustive: (),
+}
+
+impl ServerVersion {
+    /// Whether the server has the given feature.
+    pub fn supports(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Shards => !self.shards.is_empty(),
+            Feature::Season => self
+                .shards
+                .iter()
+                .any(|name| name.starts_with("shardSeason")),
+            Feature::PasswordAuth => self.mods.iter().any(|name| name == "screepsmod-auth"),
+        }
+    }
+}
+
+impl EndpointResult for ServerVersion {
+    type RequestResult<'de> = Response;
+    type ErrorResult = data::ApiError;
+
+    fn from_raw(raw: Response) -> Result<ServerVersion> {
+        let Response {
+            ok,
+            protocol,
+            package,
+            package_version,
+            use_native_auth,
+            users,
+            server_data,
+        } = raw;
+
+        if ok != 1 {
+            return Err(ApiError::NotOk(ok).into());
+        }
+
+        Ok(ServerVersion {
+            protocol,
+            package,
+            package_version,
+            users,
+            history_chunk_size: server_data.history_chunk_size,
+            shards: server_data.shards,
+            mods: server_data
+                .features
+                .into_iter()
+                .map(|feature| feature.name)
+                .collect(),
+            native_auth: use_native_auth.unwrap_or(false),
+            _non_exhaustive: (),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ServerVersion;
+    use crate::{server::Feature, EndpointResult};
+
+    fn test_parse(json: serde_json::Value) -> ServerVersion {
+        let response = serde_json::from_value(json).unwrap();
+
+        ServerVersion::from_raw(response).unwrap()
+    }
+
+    #[test]
+    fn parse_sample_official() {
+        let version = test_parse(json! ({
+            "ok": 1,
+            "package": 204,
+            "protocol": 14,
+            "serverData": {
+                "historyChunkSize": 100,
+                "shards": ["shard0", "shard1", "shard2", "shard3"],
+            },
+            "users": 84517,
+        }));
+
+        assert_eq!(version.protocol, 14);
+        assert!(version.supports(Feature::Shards));
+        assert!(!version.supports(Feature::Season));
+        assert!(!version.supports(Feature::PasswordAuth));
+    }
+
+    #[test]
+    fn parse_sample_private() {
+        let version = test_parse(json! ({
+            "ok": 1,
+            "protocol": 14,
+            "useNativeAuth": false,
+            "users": 3,
+            "serverData": {
+                "welcomeText": "<h4>Welcome</h4>",
+                "customObjectTypes": {},
+                "historyChunkSize": 20,
+                "socketUpdateThrottle": 100,
+                "renderer": { "resources": {}, "metadata": {} },
+                "features": [
+                    { "name": "screepsmod-auth", "version": 1 },
+                ],
+            },
+            "packageVersion": "4.2.16",
+        }));
+
+        assert_eq!(version.package_version.as_ref().unwrap(), "4.2.16");
+        assert!(!version.supports(Feature::Shards));
+        assert!(version.supports(Feature::PasswordAuth));
+    }
+}
@@ -3,7 +3,7 @@
 use crate::{
     data,
     error::{ApiError, Result},
-    EndpointResult,
+    EndpointResult, RoomName, ShardRoomName,
 };
 
 /// World start room raw result.
@@ -14,18 +14,28 @@ pub(crate) struct Response {
 }
 
 /// Structure describing the shard and room the client should start at.
-#[derive(Clone, Hash, Debug)]
+#[derive(Serialize, Deserialize, Clone, Hash, Debug)]
 pub struct WorldStartRoom {
     /// The room name to start viewing.
     pub room_name: String,
     /// The shard name to start viewing, or None if a shard was provided for the query or the server is out of date.
     pub shard: Option<String>,
     /// Phantom data in order to allow adding any additional fields in the future.
+    #[serde(skip)]
     _non_exhaustive: (),
 }
 
+impl WorldStartRoom {
+    /// Gets the shard and room to start viewing, if a shard was returned and the room name is valid.
+    pub fn shard_room_name(&self) -> Option<ShardRoomName> {
+        let shard = self.shard.as_ref()?;
+        let room = RoomName::new(&self.room_name).ok()?;
+        Some(ShardRoomName::new(&**shard, room))
+    }
+}
+
 impl EndpointResult for WorldStartRoom {
-    type RequestResult = Response;
+    type RequestResult<'de> = Response;
     type ErrorResult = data::ApiError;
 
     fn from_raw(raw: Response) -> Result<WorldStartRoom> {
@@ -74,6 +84,10 @@ mod tests {
         let result = WorldStartRoom::from_raw(response).unwrap();
         assert_eq!(&result.room_name, expected_room);
         assert_eq!(result.shard.as_ref().map(AsRef::as_ref), expected_shard);
+        assert_eq!(
+            result.shard_room_name().map(|room| room.to_string()),
+            expected_shard.map(|shard| format!("{}/{}", shard, expected_room))
+        );
     }
 
     #[test]
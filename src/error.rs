@@ -1,5 +1,5 @@
 //! Error types for the screeps api.
-use std::{error::Error as StdError, fmt, io, str};
+use std::{error::Error as StdError, fmt, io, str, time::Duration};
 
 use crate::data::RoomNameParseError;
 
@@ -7,6 +7,11 @@ use self::ErrorKind::*;
 
 #[derive(Debug)]
 /// Possible error types for library errors.
+///
+/// For handling errors without matching on every detailed kind, see [`Error::category`].
+///
+/// [`Error::category`]: struct.Error.html#method.category
+#[non_exhaustive]
 pub enum ErrorKind {
     /// Unauthorized access. This is caused by either attempting to access a login-only endpoint without a token,
     /// attempting to access a login-only endpoint with an expired token, or providing incorrect login details to the
@@ -18,19 +23,59 @@ pub enum ErrorKind {
     /// URL parsing error.
     Url(url::ParseError),
     /// Error connecting to the server, or error parsing a URL provided.
-    Hyper(hyper::error::Error),
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    Hyper(hyper::Error),
+    /// Error connecting to the server, when using the `reqwest` transport.
+    #[cfg(feature = "reqwest")]
+    Reqwest(reqwest::Error),
+    /// Error connecting to the server, when using a custom `HttpTransport`.
+    Transport(Box<dyn StdError + Send + Sync>),
     /// IO error.
     Io(io::Error),
     /// Error for when the server responds with a non-success HTTP status code.
-    StatusCode(hyper::StatusCode),
+    StatusCode(http::StatusCode),
+    /// The server did not respond within the timeout, which is included.
+    Timeout(Duration),
+    /// The request was not sent, as the server does not have the endpoint, which is included. See
+    /// `ServerProfile::supports`.
+    UnsupportedEndpoint(&'static str),
+    /// The request was abandoned, as the `CancelToken` set on the client was cancelled.
+    Cancelled,
+    /// The response had fields which the result type does not parse, listed by their paths, and strict parsing is
+    /// enabled. See `Api::set_strict_parsing`.
+    UnknownFields(Vec<String>),
     /// API Error: when the server responds with a successful HTTP response, but the returned format is not what we
     /// expected.
     Api(ApiError),
     /// Error parsing a room name.
     RoomNameParse(RoomNameParseError<'static>),
-    /// A marker variant that tells the compiler that users of this enum cannot match it exhaustively.
-    #[doc(hidden)]
-    __Nonexhaustive,
+}
+
+/// A broad category of errors, which can be matched on without depending on the details of each `ErrorKind`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCategory {
+    /// The request was not authorized: no token was stored, the token was rejected, or the login details were
+    /// incorrect.
+    Unauthorized,
+    /// The server rejected the request with `429 Too Many Requests`. See `Error::retry_after`.
+    RateLimited,
+    /// The server responded with `404 Not Found`.
+    NotFound,
+    /// The server responded with another `4xx` status code.
+    BadRequest,
+    /// The server responded with a `5xx` status code.
+    Server,
+    /// The server responded successfully, but with data which could not be parsed.
+    Parse,
+    /// The server could not be reached, or did not respond in time.
+    Network,
+    /// The server responded with an error, or an unexpected result, in the response body. See `ErrorKind::Api`.
+    Api,
+    /// The request could not be made: its URL was invalid, or the server does not have the endpoint.
+    Request,
+    /// The request was cancelled.
+    Cancelled,
 }
 
 /// Error deriving from some API call.
@@ -38,17 +83,71 @@ pub enum ErrorKind {
 pub struct Error {
     /// The type specifying what kind of error, and a detailed description if available.
     err: ErrorKind,
+    /// Where the error occurred and what the server responded, if known. Boxed to keep `Result`s small.
+    context: Option<Box<Context>>,
+    /// The request which resulted in this error, if it was made through an `Api`.
+    request: Option<Box<RequestContext>>,
+}
+
+/// The details of an `Error` beyond its kind.
+#[derive(Debug, Default)]
+struct Context {
     /// The whole URL which was being accessed when this error occurred (not included for URL parsing errors).
     url: Option<url::Url>,
     /// The json or body data from the request which resulted in this error
     /// (not included for URL parsing errors).
     data: AdditionalData,
+    /// The status code of the response which resulted in this error, if it was not successful.
+    status: Option<http::StatusCode>,
+    /// How long the server asked to wait before retrying, if it did.
+    retry_after: Option<Duration>,
 }
 
-#[derive(Debug)]
+/// The request which resulted in an error, for describing failures without any other context. See `Error::request`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RequestContext {
+    /// The HTTP method of the request.
+    pub method: http::Method,
+    /// The endpoint requested, relative to the API url, such as `"game/room-terrain"`.
+    pub endpoint: &'static str,
+    /// The JSON body of the request, if it had one, with any auth tokens or passwords hidden.
+    pub body: Option<bytes::Bytes>,
+    /// How long the request took to fail, including any retries and logging in again, if known. This is never known
+    /// in browsers.
+    pub elapsed: Option<Duration>,
+}
+
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const MAX_BODY_CHARS: usize = 200;
+
+        write!(f, "{} {}", self.method, self.endpoint)?;
+        if let Some(ref body) = self.body {
+            let body = crate::redact::body(body.clone());
+            let body = shorten(String::from_utf8_lossy(&body).into_owned(), MAX_BODY_CHARS);
+            write!(f, " with body '{}'", body)?;
+        }
+        if let Some(elapsed) = self.elapsed {
+            write!(f, " failed after {:?}", elapsed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shortens text to at most the given number of characters, followed by `...` if anything was cut off.
+fn shorten(text: String, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+#[derive(Debug, Default)]
 enum AdditionalData {
     Json(serde_json::Value),
     Body(bytes::Bytes),
+    #[default]
     None,
 }
 
@@ -70,12 +169,8 @@ impl From<Option<bytes::Bytes>> for AdditionalData {
 }
 
 impl AdditionalData {
-    fn or(self, other: AdditionalData) -> Self {
-        match self {
-            AdditionalData::Json(v) => AdditionalData::Json(v),
-            AdditionalData::Body(v) => AdditionalData::Body(v),
-            AdditionalData::None => other,
-        }
+    fn is_none(&self) -> bool {
+        matches!(*self, AdditionalData::None)
     }
     fn json(&self) -> Option<&serde_json::Value> {
         match *self {
@@ -97,31 +192,73 @@ impl Error {
         Error::with_json(err, url, None)
     }
     /// Creates a new error from the given error, the given possible url, and the given possible JSON data.
+    ///
+    /// Any auth tokens or passwords in the data are hidden.
     pub fn with_json<T: Into<Error>>(
         err: T,
         url: Option<url::Url>,
         json: Option<serde_json::Value>,
     ) -> Error {
-        let err = err.into();
-        Error {
-            err: err.err,
-            url: url.or(err.url),
-            data: AdditionalData::from(json).or(err.data),
-        }
+        let json = json.map(|mut json| {
+            crate::redact::json(&mut json);
+            json
+        });
+        err.into().with_context(url, AdditionalData::from(json))
     }
 
     /// Creates a new error from the given error, the given possible url, and the given possible body.
+    ///
+    /// Any auth tokens or passwords in the body are hidden, if it is JSON.
     pub fn with_body<T: Into<Error>>(
         err: T,
         url: Option<url::Url>,
         body: Option<bytes::Bytes>,
     ) -> Error {
-        let err = err.into();
-        Error {
-            err: err.err,
-            url: url.or(err.url),
-            data: AdditionalData::from(body).or(err.data),
+        err.into()
+            .with_context(url, AdditionalData::from(body.map(crate::redact::body)))
+    }
+
+    /// Sets the url and data associated with this error, keeping those already set where these are missing.
+    fn with_context(mut self, url: Option<url::Url>, data: AdditionalData) -> Error {
+        if url.is_none() && data.is_none() {
+            return self;
+        }
+        let context = self.context_mut();
+        if url.is_some() {
+            context.url = url;
+        }
+        if !data.is_none() {
+            context.data = data;
         }
+        self
+    }
+
+    fn context_mut(&mut self) -> &mut Context {
+        self.context.get_or_insert_with(Default::default)
+    }
+
+    /// Creates a new error from an error which occurred sending a request using a custom `HttpTransport`.
+    pub fn transport<E>(err: E) -> Error
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        ErrorKind::Transport(err.into()).into()
+    }
+
+    /// Sets how long the server asked to wait before retrying.
+    #[cfg(feature = "http")]
+    pub(crate) fn with_retry_after(mut self, retry_after: Option<Duration>) -> Error {
+        if retry_after.is_some() {
+            self.context_mut().retry_after = retry_after;
+        }
+        self
+    }
+
+    /// Sets the request which resulted in this error, unless one is already set.
+    #[cfg(feature = "http")]
+    pub(crate) fn with_request(mut self, request: RequestContext) -> Error {
+        self.request = self.request.or_else(|| Some(Box::new(request)));
+        self
     }
 
     /// Retrieves the type specifying what kind of error, and a detailed description if available.
@@ -131,17 +268,114 @@ impl Error {
 
     /// Retrieves the URL associated with this error, if any.
     pub fn url(&self) -> Option<&url::Url> {
-        self.url.as_ref()
+        self.context.as_ref()?.url.as_ref()
+    }
+
+    /// Retrieves the request which resulted in this error: its method, endpoint, body and how long it took to fail.
+    ///
+    /// This is set for errors returned by requests made through an `Api`, and included when the error is displayed.
+    pub fn request(&self) -> Option<&RequestContext> {
+        self.request.as_deref()
+    }
+
+    /// Retrieves the broad category of this error.
+    pub fn category(&self) -> ErrorCategory {
+        match self.err {
+            Unauthorized => ErrorCategory::Unauthorized,
+            StatusCode(status) => match status {
+                http::StatusCode::TOO_MANY_REQUESTS => ErrorCategory::RateLimited,
+                http::StatusCode::NOT_FOUND => ErrorCategory::NotFound,
+                status if status.is_server_error() => ErrorCategory::Server,
+                _ => ErrorCategory::BadRequest,
+            },
+            SerdeJson(_) | RoomNameParse(_) | UnknownFields(_) => ErrorCategory::Parse,
+            Transport(_) | Io(_) | Timeout(_) => ErrorCategory::Network,
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+            Hyper(_) => ErrorCategory::Network,
+            #[cfg(feature = "reqwest")]
+            Reqwest(_) => ErrorCategory::Network,
+            Api(_) => ErrorCategory::Api,
+            Url(_) | UnsupportedEndpoint(_) => ErrorCategory::Request,
+            Cancelled => ErrorCategory::Cancelled,
+        }
+    }
+
+    /// Retrieves the error the server reported in the response body, if it reported one.
+    ///
+    /// This recognizes messages both in successful responses and in error responses such as `401 Unauthorized`,
+    /// using the endpoint from this error's URL. See [`ApiError::from_message`].
+    ///
+    /// [`ApiError::from_message`]: enum.ApiError.html#method.from_message
+    pub fn api_error(&self) -> Option<ApiError> {
+        let message = match self.err {
+            Api(ApiError::NotOk(_))
+            | Api(ApiError::MissingField(_))
+            | Api(ApiError::MalformedResponse(_)) => return None,
+            Api(ApiError::GenericError(ref message)) => message,
+            Api(ref other) => return Some(other.clone()),
+            _ => self.json()?.get("error")?.as_str()?,
+        };
+        let endpoint = self.url().map_or("", |url| url.path());
+
+        Some(ApiError::from_message(endpoint, message))
+    }
+
+    /// Retrieves the status code of the response which resulted in this error, if it was not successful.
+    pub fn status(&self) -> Option<http::StatusCode> {
+        self.context.as_ref()?.status
+    }
+
+    /// Retrieves the start of the response body or JSON data associated with this error, if any, shortened to at
+    /// most 200 characters.
+    pub fn snippet(&self) -> Option<String> {
+        const MAX_CHARS: usize = 200;
+
+        let text = match self.context.as_ref()?.data {
+            AdditionalData::Json(ref json) => json.to_string(),
+            AdditionalData::Body(ref body) => String::from_utf8_lossy(body).into_owned(),
+            AdditionalData::None => return None,
+        };
+
+        Some(shorten(text, MAX_CHARS))
     }
 
     /// Retrieves the JSON data associated with this error, if any.
     pub fn json(&self) -> Option<&serde_json::Value> {
-        self.data.json()
+        self.context.as_ref()?.data.json()
     }
 
     /// Retrieves the body data associated with this error, if any.
     pub fn body(&self) -> Option<&bytes::Bytes> {
-        self.data.body()
+        self.context.as_ref()?.data.body()
+    }
+
+    /// Whether this error was caused by a request timing out.
+    ///
+    /// This is true for `ErrorKind::Timeout`, and for timeouts reported by the HTTP client, such as a connect timeout
+    /// set with `ClientConfig::with_connect_timeout`.
+    pub fn is_timeout(&self) -> bool {
+        match self.err {
+            Timeout(_) => true,
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+            Hyper(ref err) => err.is_timeout(),
+            #[cfg(feature = "reqwest")]
+            Reqwest(ref err) => err.is_timeout(),
+            Io(ref err) => err.kind() == io::ErrorKind::TimedOut,
+            _ => false,
+        }
+    }
+
+    /// Whether this error was caused by the request being cancelled with a `CancelToken`.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.err, Cancelled)
+    }
+
+    /// Retrieves how long the server asked to wait before retrying, if it did.
+    ///
+    /// This is parsed from the `Retry-After` or `X-RateLimit-Reset` headers of `429 Too Many Requests` and
+    /// `503 Service Unavailable` responses. See [`retry::retry_after`](../retry/fn.retry_after.html).
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.context.as_ref()?.retry_after
     }
 }
 
@@ -152,8 +386,8 @@ impl From<ErrorKind> for Error {
     fn from(err: ErrorKind) -> Error {
         Error {
             err: err,
-            url: None,
-            data: AdditionalData::None,
+            context: None,
+            request: None,
         }
     }
 }
@@ -164,12 +398,20 @@ impl From<serde_json::error::Error> for Error {
     }
 }
 
-impl From<hyper::error::Error> for Error {
-    fn from(err: hyper::error::Error) -> Error {
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+impl From<hyper::Error> for Error {
+    fn from(err: hyper::Error) -> Error {
         ErrorKind::Hyper(err).into()
     }
 }
 
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Error {
+        ErrorKind::Reqwest(err).into()
+    }
+}
+
 impl From<url::ParseError> for Error {
     fn from(err: url::ParseError) -> Error {
         ErrorKind::Url(err).into()
@@ -182,13 +424,15 @@ impl From<io::Error> for Error {
     }
 }
 
-impl From<hyper::StatusCode> for Error {
-    fn from(code: hyper::StatusCode) -> Error {
-        if code == hyper::StatusCode::UNAUTHORIZED {
+impl From<http::StatusCode> for Error {
+    fn from(code: http::StatusCode) -> Error {
+        let mut err: Error = if code == http::StatusCode::UNAUTHORIZED {
             ErrorKind::Unauthorized.into()
         } else {
             ErrorKind::StatusCode(code).into()
-        }
+        };
+        err.context_mut().status = Some(code);
+        err
     }
 }
 
@@ -216,10 +460,22 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.err {
             SerdeJson(ref err) => err.fmt(f)?,
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
             Hyper(ref err) => err.fmt(f)?,
+            #[cfg(feature = "reqwest")]
+            Reqwest(ref err) => err.fmt(f)?,
+            Transport(ref err) => err.fmt(f)?,
             Url(ref err) => err.fmt(f)?,
             Io(ref err) => err.fmt(f)?,
             StatusCode(ref status) => status.fmt(f)?,
+            Timeout(ref timeout) => write!(f, "no response received within {:?}", timeout)?,
+            Cancelled => "the request was cancelled".fmt(f)?,
+            UnsupportedEndpoint(endpoint) => {
+                write!(f, "the server does not have the endpoint '{}'", endpoint)?
+            }
+            UnknownFields(ref paths) => {
+                write!(f, "the response had unknown fields: {}", paths.join(", "))?
+            }
             Api(ref err) => err.fmt(f)?,
             RoomNameParse(ref err) => err.fmt(f)?,
             Unauthorized => {
@@ -229,18 +485,21 @@ impl fmt::Display for Error {
                        incorrect or no login provided"
                 )?;
             }
-            ErrorKind::__Nonexhaustive => unreachable!(),
         }
-        if let Some(ref url) = self.url {
+        if let Some(url) = self.url() {
             write!(f, " | at url '{}'", url)?;
         }
-        match self.data {
-            AdditionalData::Json(ref json) => write!(f, " | return json: '{}'", json)?,
-            AdditionalData::Body(ref body) => match str::from_utf8(body) {
+        if let Some(ref request) = self.request {
+            write!(f, " | request: {}", request)?;
+        }
+        if let Some(json) = self.json() {
+            write!(f, " | return json: '{}'", json)?;
+        }
+        if let Some(body) = self.body() {
+            match str::from_utf8(body) {
                 Ok(v) => write!(f, " | return body: '{}'", v)?,
                 Err(_) => write!(f, " | return body: '{:?}'", &*body)?,
-            },
-            AdditionalData::None => (),
+            }
         }
         Ok(())
     }
@@ -250,13 +509,21 @@ impl StdError for Error {
     fn cause(&self) -> Option<&dyn StdError> {
         match self.err {
             SerdeJson(ref err) => Some(err),
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
             Hyper(ref err) => Some(err),
+            #[cfg(feature = "reqwest")]
+            Reqwest(ref err) => Some(err),
+            Transport(ref err) => Some(&**err),
             Url(ref err) => Some(err),
             Io(ref err) => Some(err),
             Api(ref err) => Some(err),
             RoomNameParse(ref err) => Some(err),
-            StatusCode(_) | Unauthorized => None,
-            __Nonexhaustive => unreachable!(),
+            StatusCode(_)
+            | Timeout(_)
+            | UnsupportedEndpoint(_)
+            | Cancelled
+            | UnknownFields(_)
+            | Unauthorized => None,
         }
     }
 }
@@ -276,16 +543,20 @@ impl fmt::Display for NoToken {
 impl StdError for NoToken {}
 
 /// Error representing some abnormal response from the API.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ApiError {
     /// The server responded with an "ok" code which was not `1`.
     NotOk(i32),
+    /// The endpoint requires a valid auth token.
+    AuthRequired,
     /// The server is offline.
     ServerDown,
     /// A known response to a query about an invalid room.
     InvalidRoom,
     /// A known response to a query about an invalid shard.
     InvalidShard,
+    /// The room whose data was being requested was not found.
+    RoomNotFound,
     /// The data being requested was not found.
     ResultNotFound,
     /// The user whose data was being requested was not found.
@@ -318,6 +589,8 @@ impl fmt::Display for ApiError {
             ApiError::GenericError(ref err) => write!(f, "api call resulted in error: {}", err),
             ApiError::InvalidRoom => "malformed api call: invalid room".fmt(f),
             ApiError::InvalidShard => "malformed apic all: invalid shard".fmt(f),
+            ApiError::AuthRequired => "the endpoint requires authorization".fmt(f),
+            ApiError::RoomNotFound => "the room requested was not found".fmt(f),
             ApiError::ResultNotFound => "specific data requested was not found".fmt(f),
             ApiError::UserNotFound => "the user requested was not found".fmt(f),
             ApiError::RegistrationNotAllowed => "registering users via the API is disabled: \
@@ -334,3 +607,149 @@ impl fmt::Display for ApiError {
 }
 
 impl StdError for ApiError {}
+
+/// Endpoints where `"not found"` refers to a room.
+const ROOM_ENDPOINTS: &[&str] = &[
+    "game/room-overview",
+    "game/room-objects",
+    "game/room-terrain",
+    "game/room-status",
+];
+
+/// Endpoints where `"not found"` refers to a user.
+const USER_ENDPOINTS: &[&str] = &["user/find", "user/rooms", "user/badge-svg"];
+
+impl ApiError {
+    /// Recognizes an error message reported by the given endpoint.
+    ///
+    /// Messages are recognized per endpoint, as some endpoints use the same message for different things:
+    /// `"not found"` from `game/room-overview` means the room was not found, and from `user/find` that the user was
+    /// not found. Messages which aren't known are kept in `GenericError`.
+    ///
+    /// The endpoint is the path relative to the API url, such as `"game/room-overview"`. A full URL path, such as
+    /// `"/api/game/room-overview"`, is also recognized.
+    pub fn from_message(endpoint: &str, message: &str) -> ApiError {
+        let is_any = |endpoints: &[&str]| endpoints.iter().any(|e| endpoint.ends_with(e));
+
+        match message {
+            "unauthorized" | "not authorized" | "Not authorized" => ApiError::AuthRequired,
+            "server down" => ApiError::ServerDown,
+            "invalid room" => ApiError::InvalidRoom,
+            "invalid shard" => ApiError::InvalidShard,
+            "user not found" => ApiError::UserNotFound,
+            "result not found" => ApiError::ResultNotFound,
+            "not found" if is_any(ROOM_ENDPOINTS) => ApiError::RoomNotFound,
+            "not found" if is_any(USER_ENDPOINTS) => ApiError::UserNotFound,
+            "not found" => ApiError::ResultNotFound,
+            "invalid params" => ApiError::InvalidParameters,
+            "User already exists" => ApiError::UsernameAlreadyExists,
+            "Registration is automatically disabled. \
+             A server password has been set." => ApiError::RegistrationNotAllowed,
+            other => ApiError::GenericError(other.to_owned()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiError, Error, ErrorCategory, ErrorKind, RequestContext};
+
+    #[test]
+    fn categorize_errors() {
+        let err = Error::with_body(
+            http::StatusCode::TOO_MANY_REQUESTS,
+            None,
+            Some("x".repeat(300).into()),
+        );
+        assert_eq!(err.category(), ErrorCategory::RateLimited);
+        assert_eq!(err.status(), Some(http::StatusCode::TOO_MANY_REQUESTS));
+        assert_eq!(err.snippet().unwrap().len(), 203);
+
+        let err = Error::from(http::StatusCode::UNAUTHORIZED);
+        assert_eq!(err.category(), ErrorCategory::Unauthorized);
+        assert_eq!(err.status(), Some(http::StatusCode::UNAUTHORIZED));
+
+        let err = Error::from(http::StatusCode::BAD_GATEWAY);
+        assert_eq!(err.category(), ErrorCategory::Server);
+
+        let err = Error::from(ErrorKind::UnsupportedEndpoint("auth/signin"));
+        assert_eq!(err.category(), ErrorCategory::Request);
+        assert_eq!(err.status(), None);
+        assert_eq!(err.snippet(), None);
+    }
+
+    #[test]
+    fn typed_api_errors() {
+        let url = |path| {
+            Some(
+                url::Url::parse("https://screeps.com/api/")
+                    .unwrap()
+                    .join(path)
+                    .unwrap(),
+            )
+        };
+
+        let err = Error::with_json(
+            http::StatusCode::UNAUTHORIZED,
+            url("auth/me"),
+            Some(json!({ "error": "unauthorized" })),
+        );
+        assert_eq!(err.api_error(), Some(ApiError::AuthRequired));
+
+        let err = Error::with_url(
+            ApiError::GenericError("not found".to_owned()),
+            url("game/room-overview"),
+        );
+        assert_eq!(err.api_error(), Some(ApiError::RoomNotFound));
+
+        let err = Error::with_url(
+            ApiError::GenericError("not found".to_owned()),
+            url("user/find"),
+        );
+        assert_eq!(err.api_error(), Some(ApiError::UserNotFound));
+
+        let err = Error::with_url(ApiError::InvalidRoom, url("game/room-terrain"));
+        assert_eq!(err.api_error(), Some(ApiError::InvalidRoom));
+
+        let err = Error::from(ApiError::GenericError("tick too far".to_owned()));
+        assert_eq!(
+            err.api_error(),
+            Some(ApiError::GenericError("tick too far".to_owned()))
+        );
+
+        assert_eq!(Error::from(ApiError::NotOk(0)).api_error(), None);
+        assert_eq!(Error::from(http::StatusCode::BAD_GATEWAY).api_error(), None);
+    }
+
+    #[test]
+    fn hide_tokens() {
+        let err = Error::with_json(
+            ErrorKind::Api(ApiError::MissingField("ok")),
+            None,
+            Some(json!({ "token": "secret" })),
+        );
+        assert_eq!(err.json().unwrap()["token"], "<redacted>");
+        assert!(!err.to_string().contains("secret"));
+
+        let err = Error::with_body(
+            http::StatusCode::BAD_REQUEST,
+            None,
+            Some(r#"{"password":"secret"}"#.into()),
+        );
+        assert!(!err.to_string().contains("secret"));
+
+        let request = |body: String| RequestContext {
+            method: http::Method::POST,
+            endpoint: "auth/signin",
+            body: Some(body.into()),
+            elapsed: None,
+        };
+        let formatted = request(r#"{"email":"user","password":"secret"}"#.to_owned()).to_string();
+        assert!(formatted.starts_with("POST auth/signin with body '"));
+        assert!(!formatted.contains("secret"));
+
+        let formatted = request("x".repeat(300)).to_string();
+        assert!(formatted.ends_with("...'"));
+        assert!(formatted.len() < 250);
+    }
+}
@@ -6,7 +6,18 @@
 //! `rust-screeps-api` can connect to the [official server][screeps], and any
 //! [private server][screeps-os] instances run by users.
 //!
-//! `rust-screeps-api` uses [hyper] to run http requests and [serde] to parse json results.
+//! `rust-screeps-api` uses [hyper] to run http requests and [serde] to parse json results. With the `reqwest`
+//! feature, [reqwest] can be used to run requests instead. In browsers, with the `wasm` feature and without default
+//! features, `transport::FetchTransport` runs requests with `fetch`.
+//!
+//! The HTTP client is built with the `http` feature, the blocking `SyncApi` with `sync`, and the websocket protocol in
+//! `websocket` with `sockets`, all enabled by default. Without default features, only the request descriptions in
+//! `request` and the typed endpoint results are built, for parsing responses fetched some other way without
+//! depending on hyper or tokio. The room objects endpoint also needs `sockets`, since its results are the room object
+//! types in `websocket`.
+//!
+//! With the `chrono` feature, timestamps the API sends as milliseconds or date strings can also be retrieved as
+//! `chrono::DateTime<Utc>`, through methods such as `RoomSign::datetime_set`.
 //!
 //! # Usage
 //!
@@ -22,14 +33,13 @@
 //! # fn main() {
 //! use screeps_api::SyncApi;
 //!
-//! let mut api = SyncApi::new().expect("expected starting screeps http client to suceed");
+//! let api = SyncApi::new().expect("expected starting screeps http client to suceed");
 //! # }
 //! # #[cfg(not(feature = "sync"))] fn main() {}
 //! ```
 //!
 //! This API object can then be used to make any number of API calls. Each will return a `Result`
-//! with a typed response or an error. All calls require mutable access to manage tokens and the
-//! underlying tokio instance:
+//! with a typed response or an error. The client can be cloned and shared between threads:
 //!
 //! ```no_run
 //! # extern crate screeps_api;
@@ -49,11 +59,33 @@
 //! # #[cfg(not(feature = "sync"))] fn main() {}
 //! ```
 //!
+//! The asynchronous [`Api`] returns `std::future::Future`s, and can be used with `async`/`await` from within any
+//! tokio application:
+//!
+//! ```no_run
+//! # #[cfg(feature = "sync")]
+//! async fn print_info() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = hyper::Client::builder().build(hyper_tls::HttpsConnector::new());
+//!     let api = screeps_api::Api::new(client).with_token("auth token");
+//!
+//!     let my_info = api.my_info()?.await?;
+//!
+//!     println!("Logged in with user ID {}!", my_info.user_id);
+//!     Ok(())
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! Both can be configured in one place, including the URL, auth, rate limits, timeouts and HTTP client settings, with
+//! an [`ApiBuilder`].
+//!
 //! [`Api`]: struct.Api.html
+//! [`ApiBuilder`]: struct.ApiBuilder.html
 //! [`SyncApi`]: sync/struct.SyncApi.html
 //! [screeps]: https://screeps.com
 //! [screeps-os]: https://github.com/screeps/screeps/
 //! [hyper]: https://github.com/hyperium/hyper/
+//! [reqwest]: https://github.com/seanmonstar/reqwest/
 //! [serde]: https://github.com/serde-rs/json/
 #![doc(html_root_url = "https://docs.rs/screeps-api/0.6.0")]
 #![deny(missing_docs)]
@@ -68,6 +100,13 @@ extern crate serde_derive;
 #[cfg_attr(test, macro_use)]
 extern crate serde_json;
 
+pub mod backoff;
+#[cfg(feature = "http")]
+mod builder;
+#[cfg(feature = "http")]
+pub mod cache;
+#[cfg(feature = "http")]
+pub mod cancel;
 mod connecting;
 mod data;
 mod decoders;
@@ -75,84 +114,216 @@ mod decoders;
 pub mod docs;
 mod endpoints;
 pub mod error;
+#[cfg(feature = "http")]
+pub mod metrics;
+#[cfg(feature = "http")]
+pub mod middleware;
+pub mod rate_limit;
+mod redact;
+pub mod request;
+#[cfg(feature = "http")]
+pub mod retry;
+pub mod server;
+#[cfg(feature = "http")]
+pub mod session;
 #[cfg(feature = "sync")]
 pub mod sync;
+#[cfg(feature = "http")]
+pub mod testing;
+pub mod tokens;
+#[cfg(feature = "http")]
+pub mod transport;
+#[cfg(all(feature = "http", any(feature = "sync", feature = "sockets")))]
+mod tunnel;
+#[cfg(feature = "sockets")]
 pub mod websocket;
 
 #[cfg(feature = "sync")]
 pub use crate::sync::SyncApi;
+#[cfg(feature = "http")]
+pub use crate::{
+    builder::ApiBuilder,
+    cache::ResponseCache,
+    cancel::CancelToken,
+    metrics::Observer,
+    middleware::{Middleware, RequestLog},
+    retry::RetryPolicy,
+    transport::HttpTransport,
+};
 pub use crate::{
     data::*,
     endpoints::*,
-    error::{Error, ErrorKind, NoToken},
+    error::{Error, ErrorCategory, ErrorKind, NoToken, RequestContext},
+    rate_limit::{Priority, RateLimitInfo, RateLimiter},
+    request::{
+        AuthMode, Endpoint, EndpointPaths, ParseOptions, ParseResponse, ParseWarning, Request,
+    },
+    server::{Feature, ServerProfile},
+    tokens::{MemoryTokenStorage, TokenStorage, TokenStore},
 };
 
+#[cfg(feature = "http")]
 use std::{
     borrow::Cow,
     convert::AsRef,
+    fmt,
     future::Future,
-    marker::PhantomData,
-    sync::{Arc, PoisonError, RwLock},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
 };
 
-use futures::future::{BoxFuture, FutureExt, TryFutureExt};
-use hyper::header::{HeaderValue, CONTENT_TYPE};
+#[cfg(feature = "http")]
+use futures::{
+    future::{self, BoxFuture, Either, FutureExt, TryFutureExt},
+    stream::{self, BoxStream, StreamExt},
+};
+#[cfg(feature = "http")]
+use http::header::{HeaderValue, InvalidHeaderValue};
+#[cfg(feature = "http")]
 use url::Url;
 
+#[cfg(feature = "http")]
+use crate::{backoff::BackoffPolicy, rate_limit::RateLimitLog};
+
 /// A trait for each endpoint
 pub(crate) trait EndpointResult: Sized + 'static {
-    type RequestResult: for<'de> serde::Deserialize<'de>;
+    /// The raw result, which may borrow strings from the response body it is parsed from.
+    type RequestResult<'de>: serde::Deserialize<'de>;
     type ErrorResult: for<'de> serde::Deserialize<'de> + Into<Error>;
 
-    fn from_raw(data: Self::RequestResult) -> Result<Self, Error>;
+    fn from_raw(data: Self::RequestResult<'_>) -> Result<Self, Error>;
 }
 
 /// An API token that allows for one-time authentication. Each use of an API token with the screeps
 /// API will cause the API to return a new token which should be stored in its place.
 pub type Token = bytes::Bytes;
 
-/// Storage for the token inside the client, so that async requests can update the client's token if
-/// an updated token is returned.
+/// API Object, stores the current API token and allows access to making requests.
+///
+/// Requests are sent using the HTTP client `T`, which is usually a `hyper::Client`. See [`HttpTransport`].
 ///
-/// When cloned, the clone will share the same underlying synchronized token storage.
-#[derive(Clone, Debug, Default)]
-pub struct TokenStorage(Arc<RwLock<Option<Token>>>);
+/// [`HttpTransport`]: transport/trait.HttpTransport.html
+#[cfg(feature = "http")]
+pub struct Api<T> {
+    /// The base URL for this API instance.
+    pub url: Url,
+    /// The authentication token.
+    auth_token: Arc<dyn TokenStorage>,
+    /// The shared store and account name to take the token from instead of `auth_token`, if any.
+    token_store: Option<(TokenStore, String)>,
+    /// The HTTP client.
+    client: Arc<T>,
+    /// The rate limiter requests wait on, if any.
+    rate_limiter: Option<RateLimiter>,
+    /// How urgently requests are sent when waiting on the rate limiter.
+    priority: Priority,
+    /// The latest rate limit state the server reported for each endpoint.
+    rate_limit_log: RateLimitLog,
+    /// How failed requests are retried, if at all.
+    retry_policy: Option<RetryPolicy>,
+    /// The schedule retry delays are taken from instead of the retry policy's, if any.
+    retry_backoff: Option<Arc<dyn BackoffPolicy>>,
+    /// How long to wait for responses, if limited.
+    timeout: Option<Duration>,
+    /// Hooks run around every request.
+    middleware: middleware::Chain,
+    /// What is told about each request, if anything.
+    observer: Option<Arc<dyn Observer>>,
+    /// How the auth token is sent.
+    auth_mode: AuthMode,
+    /// The kind of server, if not detected from the URL.
+    server_profile: Option<ServerProfile>,
+    /// The version information of the server at a URL, once requested.
+    capabilities: Arc<Mutex<Option<(Url, ServerVersion)>>>,
+    /// Login details used to log in again when the token expires, if any.
+    credentials: Option<Arc<Credentials>>,
+    /// Held while logging in again, so other requests wait for the new token.
+    relogin: Arc<futures::lock::Mutex<()>>,
+    /// The token which abandons this client's requests when cancelled, if any.
+    cancel_token: Option<CancelToken>,
+    /// How responses are parsed.
+    parse_options: ParseOptions,
+    /// Paths to send requests for some endpoints to.
+    endpoint_paths: EndpointPaths,
+    /// The `User-Agent` header sent with every request.
+    user_agent: HeaderValue,
+    /// The `X-Client` header sent with every request, if any.
+    client_id: Option<HeaderValue>,
+}
 
-impl TokenStorage {
-    /// Overwrites the previously stored token with the given token.
-    pub fn set(&self, token: Token) {
-        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Some(token);
-    }
+/// Login details, which are never printed.
+#[cfg(feature = "http")]
+struct Credentials {
+    username: String,
+    password: String,
+}
 
-    /// Gets the current stored token.
-    pub fn get(&self) -> Option<Token> {
-        self.0
-            .read()
-            .unwrap_or_else(PoisonError::into_inner)
-            .clone()
+#[cfg(feature = "http")]
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &redact::REDACTED)
+            .finish()
     }
 }
 
-/// API Object, stores the current API token and allows access to making requests.
-#[derive(Debug)]
-pub struct Api<C> {
-    /// The base URL for this API instance.
-    pub url: Url,
-    /// The authentication token.
-    auth_token: TokenStorage,
-    /// The hyper client.
-    client: hyper::Client<C>,
+#[cfg(feature = "http")]
+impl<T: fmt::Debug> fmt::Debug for Api<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Api")
+            .field("url", &self.url)
+            .field("auth_token", &self.auth_token)
+            .field("token_store", &self.token_store)
+            .field("client", &self.client)
+            .field("rate_limiter", &self.rate_limiter)
+            .field("priority", &self.priority)
+            .field("rate_limit_log", &self.rate_limit_log)
+            .field("retry_policy", &self.retry_policy)
+            .field("retry_backoff", &self.retry_backoff)
+            .field("timeout", &self.timeout)
+            .field("middleware", &self.middleware)
+            .field("observer", &self.observer.as_ref().map(|_| "<observer>"))
+            .field("auth_mode", &self.auth_mode)
+            .field("server_profile", &self.server_profile)
+            .field("capabilities", &self.capabilities)
+            .field("credentials", &self.credentials)
+            .field("relogin", &self.relogin)
+            .field("cancel_token", &self.cancel_token)
+            .field("parse_options", &self.parse_options)
+            .field("endpoint_paths", &self.endpoint_paths)
+            .field("user_agent", &self.user_agent)
+            .field("client_id", &self.client_id)
+            .finish()
+    }
 }
 
-impl<C> Clone for Api<C>
-where
-    C: Clone,
-{
+#[cfg(feature = "http")]
+impl<T> Clone for Api<T> {
     fn clone(&self) -> Self {
         Api {
             url: self.url.clone(),
             auth_token: self.auth_token.clone(),
+            token_store: self.token_store.clone(),
             client: self.client.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            priority: self.priority,
+            rate_limit_log: self.rate_limit_log.clone(),
+            retry_policy: self.retry_policy,
+            retry_backoff: self.retry_backoff.clone(),
+            timeout: self.timeout,
+            middleware: self.middleware.clone(),
+            observer: self.observer.clone(),
+            auth_mode: self.auth_mode,
+            server_profile: self.server_profile,
+            capabilities: self.capabilities.clone(),
+            credentials: self.credentials.clone(),
+            relogin: self.relogin.clone(),
+            cancel_token: self.cancel_token.clone(),
+            parse_options: self.parse_options.clone(),
+            endpoint_paths: self.endpoint_paths.clone(),
+            user_agent: self.user_agent.clone(),
+            client_id: self.client_id.clone(),
         }
     }
 }
@@ -160,11 +331,17 @@ where
 /// The official server's default api url`
 pub static DEFAULT_OFFICIAL_API_URL: &'static str = "https://screeps.com/api/";
 
+/// The `User-Agent` header sent by default: this crate's name and version.
+pub const DEFAULT_USER_AGENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+#[cfg(feature = "http")]
 fn default_url() -> Url {
     Url::parse(DEFAULT_OFFICIAL_API_URL).expect("expected pre-set url to parse, parsing failed")
 }
 
-impl<C> Api<C> {
+#[cfg(feature = "http")]
+impl<T> Api<T> {
     /// Creates a new API instance for the official server with the `"https://screeps.com/api/"` base
     /// url.
     ///
@@ -173,14 +350,57 @@ impl<C> Api<C> {
     /// The returned instance can be used to make anonymous calls. Use [`Api::with_token`] or
     /// [`Api::set_token`] to enable authenticated access.
     #[inline]
-    pub fn new(client: hyper::Client<C>) -> Self {
+    pub fn new(client: T) -> Self {
         Api {
             url: default_url(),
-            client: client,
-            auth_token: TokenStorage::default(),
+            client: Arc::new(client),
+            auth_token: Arc::new(MemoryTokenStorage::default()),
+            token_store: None,
+            rate_limiter: None,
+            priority: Priority::Normal,
+            rate_limit_log: RateLimitLog::default(),
+            retry_policy: None,
+            retry_backoff: None,
+            timeout: None,
+            middleware: middleware::Chain::default(),
+            observer: None,
+            auth_mode: AuthMode::Auto,
+            server_profile: None,
+            capabilities: Arc::default(),
+            credentials: None,
+            relogin: Arc::default(),
+            cancel_token: None,
+            parse_options: ParseOptions::default(),
+            endpoint_paths: EndpointPaths::default(),
+            user_agent: HeaderValue::from_static(DEFAULT_USER_AGENT),
+            client_id: None,
         }
     }
 
+    /// Creates a new API instance for the official server which authenticates with an existing auth token.
+    ///
+    /// The token is used as is, and the login endpoint is never called, so this works for accounts which sign in
+    /// through Steam and have no password. Tokens can be generated in the account settings of the official server:
+    /// see [the screeps docs page](https://docs.screeps.com/auth-tokens.html).
+    ///
+    /// This is the same as `Api::new(client).with_token(token)`.
+    #[inline]
+    pub fn new_with_token<U: Into<Token>>(client: T, token: U) -> Self {
+        Api::new(client).with_token(token)
+    }
+
+    /// Creates a new API instance for the given kind of server, using its usual URL.
+    ///
+    /// For private servers, this is a server running locally on the default port: use [`Api::with_url`] or
+    /// [`Api::set_url`] to change to another.
+    #[inline]
+    pub fn new_for_server(client: T, profile: ServerProfile) -> Self {
+        let mut api = Api::new(client);
+        api.url = profile.default_url();
+        api.server_profile = Some(profile);
+        api
+    }
+
     /// Sets the server url this api client will use.
     ///
     /// See also [`Api::with_url`].
@@ -199,80 +419,853 @@ impl<C> Api<C> {
         Ok(self)
     }
 
-    /// Sets the auth token this api client will use.
+    /// Sends requests for the endpoint to the given path, relative to the server url, instead of its usual path.
+    ///
+    /// Endpoints are named by their usual path, such as `"game/room-terrain"`. This copes with private servers whose
+    /// mods move endpoints, without changing the crate. Fails if the path can never be joined to a url. See
+    /// [`EndpointPaths`].
+    ///
+    /// See also [`Api::with_endpoint_path`].
+    ///
+    /// [`EndpointPaths`]: request/struct.EndpointPaths.html
+    #[inline]
+    pub fn set_endpoint_path<E, P>(&mut self, endpoint: E, path: P) -> Result<(), url::ParseError>
+    where
+        E: Into<String>,
+        P: Into<String>,
+    {
+        self.endpoint_paths.set_path(endpoint, path)
+    }
+
+    /// Sends requests for the endpoint to the given path instead of its usual path, and returns the client.
+    ///
+    /// See also [`Api::set_endpoint_path`].
+    #[inline]
+    pub fn with_endpoint_path<E, P>(mut self, endpoint: E, path: P) -> Result<Self, url::ParseError>
+    where
+        E: Into<String>,
+        P: Into<String>,
+    {
+        self.set_endpoint_path(endpoint, path)?;
+        Ok(self)
+    }
+
+    /// Sets the paths to send requests for some endpoints to, replacing any previously set.
+    #[inline]
+    pub fn set_endpoint_paths(&mut self, paths: EndpointPaths) {
+        self.endpoint_paths = paths;
+    }
+
+    /// Retrieves the paths requests for some endpoints are sent to instead of their usual paths.
+    #[inline]
+    pub fn endpoint_paths(&self) -> &EndpointPaths {
+        &self.endpoint_paths
+    }
+
+    /// Sets the auth token this api client will use.
+    ///
+    /// See [the screeps docs page](https://docs.screeps.com/auth-tokens.html) for information on tokens.
+    ///
+    /// See also [`Api::with_token`].
+    #[inline]
+    pub fn set_token<U: Into<Token>>(&mut self, token: U) {
+        self.token_storage().set(token.into());
+    }
+
+    /// Sets the auth token this api client will use, and returns the client.
+    ///
+    /// See [the screeps docs page](https://docs.screeps.com/auth-tokens.html) for information on tokens.
+    ///
+    /// See also [`Api::set_token`].
+    #[inline]
+    pub fn with_token<U: Into<Token>>(mut self, token: U) -> Self {
+        self.set_token(token);
+        self
+    }
+
+    /// Retrieves the token storage for this client.
+    ///
+    /// With a [`TokenStore`] set, this is the storage for the client's current URL and account.
+    ///
+    /// [`TokenStore`]: tokens/struct.TokenStore.html
+    #[inline]
+    pub fn token_storage(&self) -> Arc<dyn TokenStorage> {
+        match self.token_store {
+            Some((ref store, ref account)) => Arc::new(store.account(&self.url, account)),
+            None => self.auth_token.clone(),
+        }
+    }
+
+    /// Sets where this client keeps its auth token. Any token in the previous storage is not carried over.
+    ///
+    /// Tokens are kept in memory by default. See [`TokenStorage`] for other options.
+    ///
+    /// See also [`Api::with_token_storage`].
+    ///
+    /// [`TokenStorage`]: tokens/trait.TokenStorage.html
+    #[inline]
+    pub fn set_token_storage<S: TokenStorage>(&mut self, storage: S) {
+        self.auth_token = Arc::new(storage);
+        self.token_store = None;
+    }
+
+    /// Sets where this client keeps its auth token, and returns the client.
+    ///
+    /// See also [`Api::set_token_storage`].
+    #[inline]
+    pub fn with_token_storage<S: TokenStorage>(mut self, storage: S) -> Self {
+        self.set_token_storage(storage);
+        self
+    }
+
+    /// Sets the kind of server this client talks to, or `None` to detect it from the client's URL.
+    ///
+    /// The profile decides which endpoints can be requested, and which headers the auth token is sent in when using
+    /// `AuthMode::Auto`. The URL is not changed.
+    ///
+    /// See also [`Api::with_server_profile`].
+    #[inline]
+    pub fn set_server_profile(&mut self, profile: Option<ServerProfile>) {
+        self.server_profile = profile;
+    }
+
+    /// Sets the kind of server this client talks to, and returns the client.
+    ///
+    /// See also [`Api::set_server_profile`].
+    #[inline]
+    pub fn with_server_profile(mut self, profile: ServerProfile) -> Self {
+        self.set_server_profile(Some(profile));
+        self
+    }
+
+    /// Retrieves the kind of server this client talks to, as set or detected from its URL.
+    #[inline]
+    pub fn server_profile(&self) -> ServerProfile {
+        self.server_profile
+            .unwrap_or_else(|| ServerProfile::detect(&self.url))
+    }
+
+    /// Sets which headers the auth token is sent in.
+    ///
+    /// By default, this is `AuthMode::Auto`: the official servers are sent the token in `X-Token`, and private servers
+    /// in both `X-Token` and `X-Username`, as decided by the client's server profile. Either way, new tokens the server returns in `X-Token` are stored.
+    ///
+    /// See also [`Api::with_auth_mode`].
+    #[inline]
+    pub fn set_auth_mode(&mut self, mode: AuthMode) {
+        self.auth_mode = mode;
+    }
+
+    /// Sets which headers the auth token is sent in, and returns the client.
+    ///
+    /// See also [`Api::set_auth_mode`].
+    #[inline]
+    pub fn with_auth_mode(mut self, mode: AuthMode) -> Self {
+        self.set_auth_mode(mode);
+        self
+    }
+
+    /// Retrieves which headers the auth token is sent in.
+    #[inline]
+    pub fn auth_mode(&self) -> AuthMode {
+        self.auth_mode
+    }
+
+    /// Sets the `User-Agent` header sent with every request, so server operators can tell which tool is making them.
+    ///
+    /// By default, this is [`DEFAULT_USER_AGENT`], this crate's name and version. Fails if the user agent is not a
+    /// valid header value. Browsers may not send it.
+    ///
+    /// See also [`Api::with_user_agent`].
+    ///
+    /// [`DEFAULT_USER_AGENT`]: constant.DEFAULT_USER_AGENT.html
+    #[inline]
+    pub fn set_user_agent<U: AsRef<str>>(
+        &mut self,
+        user_agent: U,
+    ) -> Result<(), InvalidHeaderValue> {
+        self.user_agent = HeaderValue::from_str(user_agent.as_ref())?;
+        Ok(())
+    }
+
+    /// Sets the `User-Agent` header sent with every request, and returns the client.
+    ///
+    /// See also [`Api::set_user_agent`].
+    #[inline]
+    pub fn with_user_agent<U: AsRef<str>>(
+        mut self,
+        user_agent: U,
+    ) -> Result<Self, InvalidHeaderValue> {
+        self.set_user_agent(user_agent)?;
+        Ok(self)
+    }
+
+    /// Retrieves the `User-Agent` header sent with every request.
+    #[inline]
+    pub fn user_agent(&self) -> &HeaderValue {
+        &self.user_agent
+    }
+
+    /// Sets an identifier for the application, sent in the `X-Client` header with every request, or `None` to send
+    /// no such header, as by default.
+    ///
+    /// Fails if the identifier is not a valid header value.
+    ///
+    /// See also [`Api::with_client_id`].
+    #[inline]
+    pub fn set_client_id<U: AsRef<str>>(
+        &mut self,
+        client_id: Option<U>,
+    ) -> Result<(), InvalidHeaderValue> {
+        self.client_id = match client_id {
+            Some(id) => Some(HeaderValue::from_str(id.as_ref())?),
+            None => None,
+        };
+        Ok(())
+    }
+
+    /// Sets an identifier for the application, sent in the `X-Client` header with every request, and returns the
+    /// client.
+    ///
+    /// See also [`Api::set_client_id`].
+    #[inline]
+    pub fn with_client_id<U: AsRef<str>>(
+        mut self,
+        client_id: U,
+    ) -> Result<Self, InvalidHeaderValue> {
+        self.set_client_id(Some(client_id))?;
+        Ok(self)
+    }
+
+    /// Retrieves the identifier sent in the `X-Client` header with every request, if any.
+    #[inline]
+    pub fn client_id(&self) -> Option<&HeaderValue> {
+        self.client_id.as_ref()
+    }
+
+    /// Sets a store of tokens for several accounts and servers to use, and the account this client acts as.
+    ///
+    /// The client then uses the token stored for its current URL and the given account, including after its URL is
+    /// changed. This replaces any storage set with [`Api::set_token_storage`].
+    ///
+    /// See also [`Api::with_token_store`].
+    #[inline]
+    pub fn set_token_store<A: Into<String>>(&mut self, store: TokenStore, account: A) {
+        self.token_store = Some((store, account.into()));
+    }
+
+    /// Sets a store of tokens for several accounts and servers to use, and the account this client acts as, and
+    /// returns the client.
+    ///
+    /// See also [`Api::set_token_store`].
+    #[inline]
+    pub fn with_token_store<A: Into<String>>(mut self, store: TokenStore, account: A) -> Self {
+        self.set_token_store(store, account);
+        self
+    }
+
+    /// Sets the rate limiter requests made by this client wait on, or `None` to send requests immediately.
+    ///
+    /// Rate limiting is disabled by default. Use `RateLimiter::official()` for the limits of the official server.
+    ///
+    /// See also [`Api::with_rate_limiter`].
+    #[inline]
+    pub fn set_rate_limiter(&mut self, limiter: Option<RateLimiter>) {
+        self.rate_limiter = limiter;
+    }
+
+    /// Sets the rate limiter requests made by this client wait on, and returns the client.
+    ///
+    /// See also [`Api::set_rate_limiter`].
+    #[inline]
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.set_rate_limiter(Some(limiter));
+        self
+    }
+
+    /// Retrieves the rate limiter for this client, if any.
+    #[inline]
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_ref()
+    }
+
+    /// Sets how urgently requests made by this client are sent when they have to wait for the rate limiter.
+    ///
+    /// By default, this is `Priority::Normal`. Clones of a client sharing a rate limiter can be given different
+    /// priorities, so that a clone scanning the map in the background doesn't hold up another sending console
+    /// commands. Requests given a priority with `Request::with_priority` keep it. See also [`Api::with_priority`].
+    #[inline]
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+    }
+
+    /// Sets how urgently requests made by this client are sent when they have to wait for the rate limiter, and
+    /// returns the client.
+    ///
+    /// See also [`Api::set_priority`].
+    #[inline]
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.set_priority(priority);
+        self
+    }
+
+    /// Retrieves how urgently requests made by this client are sent when they have to wait for the rate limiter.
+    #[inline]
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Gets the latest rate limit state the server reported for requests with the given method to the given endpoint,
+    /// if it has reported any.
+    ///
+    /// The endpoint is the path relative to the API url, such as `"user/code"`. The official server reports the
+    /// state of endpoints with their own limits in the `X-RateLimit-*` headers of each response.
+    ///
+    /// When a rate limiter is set, its limit for the endpoint is also updated with the reported state.
+    #[inline]
+    pub fn rate_limit_info(&self, method: &http::Method, endpoint: &str) -> Option<RateLimitInfo> {
+        self.rate_limit_log.get(method, endpoint)
+    }
+
+    /// Sets how `GET` requests which fail for transient reasons are retried, or `None` to never retry.
+    ///
+    /// Retrying is disabled by default.
+    ///
+    /// See also [`Api::with_retry_policy`].
+    #[inline]
+    pub fn set_retry_policy(&mut self, policy: Option<RetryPolicy>) {
+        self.retry_policy = policy;
+    }
+
+    /// Sets how `GET` requests which fail for transient reasons are retried, and returns the client.
+    ///
+    /// See also [`Api::set_retry_policy`].
+    #[inline]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.set_retry_policy(Some(policy));
+        self
+    }
+
+    /// Retrieves how failed requests are retried, if at all.
+    #[inline]
+    pub fn retry_policy(&self) -> Option<RetryPolicy> {
+        self.retry_policy
+    }
+
+    /// Sets the schedule retry delays are taken from instead of the retry policy's exponential backoff, such as a
+    /// [`DecorrelatedJitter`] schedule or one of the application's own.
+    ///
+    /// Requests are still only retried as the retry policy allows, and at most `max_retries` times. The schedule is
+    /// given the previous delay it returned for the same request.
+    ///
+    /// See also [`Api::with_retry_backoff`].
+    ///
+    /// [`DecorrelatedJitter`]: backoff/struct.DecorrelatedJitter.html
+    #[inline]
+    pub fn set_retry_backoff<B: BackoffPolicy>(&mut self, backoff: B) {
+        self.retry_backoff = Some(Arc::new(backoff));
+    }
+
+    /// Sets the schedule retry delays are taken from instead of the retry policy's, and returns the client.
+    ///
+    /// See also [`Api::set_retry_backoff`].
+    #[inline]
+    pub fn with_retry_backoff<B: BackoffPolicy>(mut self, backoff: B) -> Self {
+        self.set_retry_backoff(backoff);
+        self
+    }
+
+    /// Removes the schedule set with [`Api::set_retry_backoff`].
+    #[inline]
+    pub fn clear_retry_backoff(&mut self) {
+        self.retry_backoff = None;
+    }
+
+    /// Sets how long to wait for the server to respond to each request, or `None` to wait indefinitely.
+    ///
+    /// Requests which time out fail with `ErrorKind::Timeout`. The timeout can be overridden for a single request
+    /// with `Request::with_timeout`. There is no timeout by default. Timing out uses `tokio::time`, so requests
+    /// must be run on a tokio runtime.
+    ///
+    /// See also [`Api::with_timeout`].
+    #[inline]
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Sets how long to wait for the server to respond to each request, and returns the client.
+    ///
+    /// See also [`Api::set_timeout`].
+    #[inline]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.set_timeout(Some(timeout));
+        self
+    }
+
+    /// Retrieves how long to wait for the server to respond to each request, if limited.
+    #[inline]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Adds a hook which is run around every request this client makes, after any previously added ones.
+    ///
+    /// See [`Middleware`] for the order hooks run in. See also [`Api::with_middleware`].
+    ///
+    /// [`Middleware`]: middleware/trait.Middleware.html
+    #[inline]
+    pub fn add_middleware<M: Middleware>(&mut self, middleware: M) {
+        self.middleware.push(middleware);
+    }
+
+    /// Sets the username and password to log in with whenever an authenticated request fails with
+    /// `401 Unauthorized`.
+    ///
+    /// When set, such requests log in again, store the new token, and are sent once more with it, rather than
+    /// failing with `ErrorKind::Unauthorized`. This only works for servers supporting [`Api::login`]: with other
+    /// server profiles, such requests fail with `ErrorKind::UnsupportedEndpoint` without trying to log in.
+    ///
+    /// Only one request logs in at a time, for this client and its clones. Authenticated requests made meanwhile wait
+    /// for the new token and are sent with it, and requests which were rejected with the old token are sent again
+    /// with the new one, rather than each logging in again.
+    ///
+    /// See also [`Api::with_credentials`].
+    #[inline]
+    pub fn set_credentials<U, V>(&mut self, username: U, password: V)
+    where
+        U: Into<String>,
+        V: Into<String>,
+    {
+        self.credentials = Some(Arc::new(Credentials {
+            username: username.into(),
+            password: password.into(),
+        }));
+    }
+
+    /// Sets the username and password to log in with whenever an authenticated request fails with
+    /// `401 Unauthorized`, and returns the client.
+    ///
+    /// See also [`Api::set_credentials`].
+    #[inline]
+    pub fn with_credentials<U, V>(mut self, username: U, password: V) -> Self
+    where
+        U: Into<String>,
+        V: Into<String>,
+    {
+        self.set_credentials(username, password);
+        self
+    }
+
+    /// Forgets the username and password set with [`Api::set_credentials`].
+    #[inline]
+    pub fn clear_credentials(&mut self) {
+        self.credentials = None;
+    }
+
+    /// Adds a hook which is run around every request this client makes, and returns the client.
+    ///
+    /// See also [`Api::add_middleware`].
+    #[inline]
+    pub fn with_middleware<M: Middleware>(mut self, middleware: M) -> Self {
+        self.add_middleware(middleware);
+        self
+    }
+
+    /// Sets the token which abandons every request this client makes when it is cancelled, or removes it.
+    ///
+    /// See [`CancelToken`]. See also [`Api::with_cancel_token`].
+    ///
+    /// [`CancelToken`]: cancel/struct.CancelToken.html
+    #[inline]
+    pub fn set_cancel_token(&mut self, token: Option<CancelToken>) {
+        self.cancel_token = token;
+    }
+
+    /// Sets the token which abandons every request this client makes when it is cancelled, and returns the client.
+    ///
+    /// See also [`Api::set_cancel_token`].
+    #[inline]
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        self.set_cancel_token(Some(token));
+        self
+    }
+
+    /// Retrieves the token which abandons this client's requests when cancelled, if any.
+    #[inline]
+    pub fn cancel_token(&self) -> Option<&CancelToken> {
+        self.cancel_token.as_ref()
+    }
+
+    /// Sets the observer which is told when each request this client makes starts and finishes, replacing any
+    /// previous one.
+    ///
+    /// See [`Observer`] for the events. See also [`Api::with_observer`].
+    ///
+    /// [`Observer`]: metrics/trait.Observer.html
+    #[inline]
+    pub fn set_observer<O: Observer>(&mut self, observer: O) {
+        self.observer = Some(Arc::new(observer));
+    }
+
+    /// Sets the observer which is told when each request this client makes starts and finishes, and returns the
+    /// client.
+    ///
+    /// See also [`Api::set_observer`].
+    #[inline]
+    pub fn with_observer<O: Observer>(mut self, observer: O) -> Self {
+        self.set_observer(observer);
+        self
+    }
+
+    /// Removes the observer set with [`Api::set_observer`].
+    #[inline]
+    pub fn clear_observer(&mut self) {
+        self.observer = None;
+    }
+
+    /// Sets whether responses with fields the result type does not parse fail, with `ErrorKind::UnknownFields`.
+    ///
+    /// By default, such fields are only logged as a warning. Enabling this in tests catches changes to the server's
+    /// API early. See also [`Api::with_strict_parsing`].
+    #[inline]
+    pub fn set_strict_parsing(&mut self, strict: bool) {
+        self.parse_options.strict = strict;
+    }
+
+    /// Sets whether responses with fields the result type does not parse fail, and returns the client.
+    ///
+    /// See also [`Api::set_strict_parsing`].
+    #[inline]
+    pub fn with_strict_parsing(mut self, strict: bool) -> Self {
+        self.set_strict_parsing(strict);
+        self
+    }
+
+    /// Sets whether successful responses are parsed straight into the result type, skipping the intermediate
+    /// `serde_json::Value` and the tracking of fields which are not parsed.
+    ///
+    /// This is faster for clients polling large endpoints often, but unparsed fields are no longer logged or reported
+    /// to the warning hook. It has no effect with strict parsing. See also [`Api::with_fast_parsing`].
+    #[inline]
+    pub fn set_fast_parsing(&mut self, fast: bool) {
+        self.parse_options.fast = fast;
+    }
+
+    /// Sets whether successful responses are parsed straight into the result type, and returns the client.
+    ///
+    /// See also [`Api::set_fast_parsing`].
+    #[inline]
+    pub fn with_fast_parsing(mut self, fast: bool) -> Self {
+        self.set_fast_parsing(fast);
+        self
+    }
+
+    /// Sets the size in bytes from which response bodies are parsed on tokio's blocking thread pool, with
+    /// `tokio::task::spawn_blocking`, or `None` to always parse them where they are received.
+    ///
+    /// This keeps multi-megabyte responses, such as room objects, room history or large leaderboard pages, from
+    /// stalling other tasks on the runtime while they are parsed. Requests must then be run on a tokio runtime. By
+    /// default, responses are never moved to the pool. See also [`Api::with_blocking_parse_threshold`].
+    #[inline]
+    pub fn set_blocking_parse_threshold(&mut self, threshold: Option<usize>) {
+        self.parse_options.blocking_threshold = threshold;
+    }
+
+    /// Sets the size in bytes from which response bodies are parsed on tokio's blocking thread pool, and returns the
+    /// client.
+    ///
+    /// See also [`Api::set_blocking_parse_threshold`].
+    #[inline]
+    pub fn with_blocking_parse_threshold(mut self, threshold: usize) -> Self {
+        self.set_blocking_parse_threshold(Some(threshold));
+        self
+    }
+
+    /// Sets the function told about responses with fields the result type does not parse, instead of logging them as
+    /// a warning.
+    ///
+    /// The function is told the endpoint, the URL, and the paths of the fields which were not parsed, so
+    /// applications can collect these into their own telemetry. See also [`Api::with_parse_warning_hook`].
+    #[inline]
+    pub fn set_parse_warning_hook<F>(&mut self, hook: F)
+    where
+        F: Fn(&ParseWarning) + Send + Sync + 'static,
+    {
+        self.parse_options.warning_hook = Some(Arc::new(hook));
+    }
+
+    /// Sets the function told about responses with fields the result type does not parse, and returns the client.
+    ///
+    /// See also [`Api::set_parse_warning_hook`].
+    #[inline]
+    pub fn with_parse_warning_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&ParseWarning) + Send + Sync + 'static,
+    {
+        self.set_parse_warning_hook(hook);
+        self
+    }
+
+    /// Removes the function set with [`Api::set_parse_warning_hook`], logging unparsed fields as a warning again.
+    #[inline]
+    pub fn clear_parse_warning_hook(&mut self) {
+        self.parse_options.warning_hook = None;
+    }
+
+    /// Sets whether the JSON data of the latest response from each endpoint is kept, for debugging.
     ///
-    /// See [the screeps docs page](https://docs.screeps.com/auth-tokens.html) for information on tokens.
+    /// This lets fields the result types don't expose yet be read with [`Api::raw_json`], without making the request
+    /// again. Responses are then never parsed straight into the result type. Clones of this client share the kept
+    /// data. See also [`Api::with_raw_json`].
+    #[inline]
+    pub fn set_raw_json(&mut self, retain: bool) {
+        self.parse_options = self.parse_options.clone().with_raw_json(retain);
+    }
+
+    /// Sets whether the JSON data of the latest response from each endpoint is kept, and returns the client.
     ///
-    /// See also [`Api::with_token`].
+    /// See also [`Api::set_raw_json`].
     #[inline]
-    pub fn set_token<T: Into<Token>>(&mut self, token: T) {
-        self.auth_token.set(token.into());
+    pub fn with_raw_json(mut self, retain: bool) -> Self {
+        self.set_raw_json(retain);
+        self
     }
 
-    /// Sets the auth token this api client will use, and returns the client.
+    /// Gets the JSON data of the latest response from the given endpoint, if [`Api::set_raw_json`] is enabled.
     ///
-    /// See [the screeps docs page](https://docs.screeps.com/auth-tokens.html) for information on tokens.
+    /// The endpoint is the path relative to the API url, such as `"game/shards/info"`. Any auth tokens or passwords
+    /// are hidden.
     ///
-    /// See also [`Api::set_token`].
+    /// ```
+    /// use screeps_api::{
+    ///     testing::{Mock, MockTransport},
+    ///     Api,
+    /// };
+    ///
+    /// let transport = MockTransport::new().with_mock(
+    ///     Mock::get("game/shards/info")
+    ///         .with_json(serde_json::json!({ "ok": 1, "shards": [], "new_field": "value" })),
+    /// );
+    /// let api = Api::new(transport).with_raw_json(true);
+    ///
+    /// let shards = futures::executor::block_on(api.shard_list()).unwrap();
+    /// assert!(shards.is_empty());
+    /// assert_eq!(api.raw_json("game/shards/info").unwrap()["new_field"], "value");
+    /// ```
     #[inline]
-    pub fn with_token<T: Into<Token>>(mut self, token: T) -> Self {
-        self.set_token(token);
-        self
+    pub fn raw_json(&self, endpoint: &str) -> Option<serde_json::Value> {
+        self.parse_options.raw_json(endpoint)
     }
 
-    /// Retrieves the token storage for this client.
+    /// Retrieves how responses are parsed.
     #[inline]
-    pub fn token_storage(&self) -> &TokenStorage {
-        &self.auth_token
+    pub fn parse_options(&self) -> &ParseOptions {
+        &self.parse_options
     }
 }
 
-impl<C> Api<C>
+#[cfg(feature = "http")]
+impl<T> Api<T>
 where
-    C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
+    T: HttpTransport,
 {
-    /// Starts preparing a POST or GET request to the given endpoint URL
-    #[inline]
-    fn request<'a, R, S>(&'a self, endpoint: &'a str) -> PartialRequest<'a, C, R, NoAuthRequired, S>
+    /// Sends a request described by one of the functions in the [`request`] module.
+    ///
+    /// Fails with `NoToken` if the request requires authentication and no token is stored.
+    ///
+    /// Dropping the returned future abandons the request, closing its connection if it is still in flight. To
+    /// abandon many requests at once, see [`Api::set_cancel_token`].
+    ///
+    /// [`request`]: request/index.html
+    pub fn execute<R>(
+        &self,
+        request: Request<R>,
+    ) -> Result<BoxFuture<'static, Result<R, Error>>, NoToken>
     where
-        R: EndpointResult,
-        S: serde::Serialize,
+        R: ParseResponse,
     {
-        PartialRequest {
-            client: self,
-            endpoint,
-            post_body: None,
-            query_params: None,
-            _phantom: PhantomData,
-        }
+        let token = if request.requires_auth() {
+            Some(self.token_storage().get().ok_or(NoToken)?)
+        } else {
+            None
+        };
+
+        Ok(self.send(request, token))
     }
 
-    /// Makes a new GET request to the given endpoint URL, with given the query parameters added to
-    /// the end.
-    #[inline]
-    fn get<'a, R>(
-        &'a self,
-        endpoint: &'a str,
-    ) -> PartialRequest<'a, C, R, NoAuthRequired, &'static str>
+    /// Sends many requests, at most `concurrency` at a time, and collects their results in the same order as the
+    /// requests.
+    ///
+    /// Each request succeeds or fails on its own: a request which fails, or requires authentication while no token
+    /// is stored, results in an error for that request alone. Requests still go through the client's rate limiter
+    /// and retry policy, so a large batch is paced by the rate limiter rather than rejected by the server.
+    ///
+    /// ```no_run
+    /// # async fn scan(api: screeps_api::Api<hyper::Client<hyper::client::HttpConnector>>) {
+    /// use screeps_api::request;
+    ///
+    /// let rooms = ["E0N0", "E0N1", "E1N0", "E1N1"];
+    /// let terrain = api
+    ///     .batch(rooms.iter().map(|room| request::room_terrain(Some("shard0"), *room)), 8)
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn batch<R, I>(
+        &self,
+        requests: I,
+        concurrency: usize,
+    ) -> BoxFuture<'static, Vec<Result<R, Error>>>
     where
-        R: EndpointResult,
+        R: ParseResponse,
+        I: IntoIterator<Item = Request<R>>,
     {
-        self.request(endpoint)
+        let responses = requests
+            .into_iter()
+            .map(|request| match self.execute(request) {
+                Ok(response) => response,
+                Err(e) => future::ready(Err(e.into())).boxed(),
+            })
+            .collect::<Vec<_>>();
+
+        stream::iter(responses)
+            .buffered(concurrency.max(1))
+            .collect()
+            .boxed()
     }
 
-    /// Makes a POST request to the given endpoint URL, with the given data encoded as JSON in the
-    /// body of the request.
-    #[inline]
-    fn post<'a, U, R>(
-        &'a self,
-        endpoint: &'a str,
-        request_text: U,
-    ) -> PartialRequest<'a, C, R, NoAuthRequired, U>
+    /// Sends a request which does not require authentication.
+    fn execute_anonymous<R>(&self, request: Request<R>) -> BoxFuture<'static, Result<R, Error>>
+    where
+        R: ParseResponse,
+    {
+        self.send(request, None)
+    }
+
+    fn send<R>(
+        &self,
+        request: Request<R>,
+        token: Option<Token>,
+    ) -> BoxFuture<'static, Result<R, Error>>
     where
-        U: serde::Serialize,
-        R: EndpointResult,
+        R: ParseResponse,
     {
-        self.request(endpoint).post(request_text)
+        // only read the clock where there is one, as `Instant::now` is unavailable in browsers.
+        let started = if cfg!(target_arch = "wasm32") {
+            None
+        } else {
+            Some(Instant::now())
+        };
+        let mut context = RequestContext {
+            method: request.method().clone(),
+            endpoint: request.endpoint(),
+            body: request.body().cloned(),
+            elapsed: None,
+        };
+        let with_context = move |err: Error| {
+            context.body = context.body.map(redact::body);
+            context.elapsed = started.map(|started| started.elapsed());
+            err.with_request(context)
+        };
+
+        let profile = self.server_profile();
+        if !profile.supports(request.method(), request.endpoint()) {
+            let err = Error::with_url(
+                ErrorKind::UnsupportedEndpoint(request.endpoint()),
+                Some(request.url_with_paths(&self.url, &self.endpoint_paths)),
+            );
+            return future::ready(Err(with_context(err))).boxed();
+        }
+
+        let auth_mode = match self.auth_mode {
+            AuthMode::Auto => profile.auth_mode(),
+            mode => mode,
+        };
+        let pipeline = connecting::Pipeline {
+            base_url: self.url.clone(),
+            client: self.client.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            middleware: self.middleware.clone(),
+            observer: self.observer.clone(),
+            retry_policy: self.retry_policy,
+            retry_backoff: self.retry_backoff.clone(),
+            profile,
+            auth_mode,
+            rate_limit_log: self.rate_limit_log.clone(),
+            timeout: self.timeout,
+            priority: self.priority,
+            parse_options: self.parse_options.clone(),
+            endpoint_paths: self.endpoint_paths.clone(),
+            user_agent: self.user_agent.clone(),
+            client_id: self.client_id.clone(),
+            account: match (&self.token_store, &self.credentials) {
+                (Some((_, account)), _) => middleware::Account::Stored(account.clone()),
+                (None, Some(credentials)) => {
+                    middleware::Account::Username(credentials.username.clone())
+                }
+                (None, None) => middleware::Account::Storage(self.auth_token.clone()),
+            },
+        };
+        let url = request.url_with_paths(&self.url, &self.endpoint_paths);
+        let tokens = self.token_storage();
+        let credentials = self.credentials.clone();
+        let relogin = self.relogin.clone();
+        let cancel_url = url.clone();
+
+        let response = async move {
+            let mut token = token;
+            if token.is_some() && credentials.is_some() {
+                // wait for any login in progress, and send the token it stored.
+                drop(relogin.lock().await);
+                token = tokens.get().or(token);
+            }
+
+            let (mut response, mut retry_after) = pipeline.fetch(&request, token.as_ref()).await;
+
+            let unauthorized = match response {
+                Ok(ref response) => response.status() == http::StatusCode::UNAUTHORIZED,
+                Err(_) => false,
+            };
+            if let (true, Some(rejected), Some(credentials)) = (unauthorized, &token, credentials) {
+                let token = {
+                    let _logging_in = relogin.lock().await;
+                    match tokens.get() {
+                        // another request logged in while this one was in flight.
+                        Some(stored) if stored != *rejected => stored,
+                        _ => {
+                            debug!("token rejected at {}, logging in again", url);
+                            pipeline
+                                .relogin(&tokens, &credentials.username, &credentials.password)
+                                .await?
+                        }
+                    }
+                };
+
+                let (replayed, replayed_retry_after) = pipeline.fetch(&request, Some(&token)).await;
+                response = replayed;
+                retry_after = replayed_retry_after;
+            }
+
+            let options = pipeline.parse_options.for_request(request.endpoint(), &url);
+            connecting::interpret(tokens, url, future::ready(response), &options)
+                .await
+                .map_err(|e| e.with_retry_after(retry_after))
+        }
+        .boxed();
+
+        let response = match self.cancel_token {
+            // dropping the request when cancelled abandons it, along with its connection.
+            Some(ref cancel) => future::select(cancel.cancelled(), response)
+                .map(move |either| match either {
+                    Either::Left(((), _)) => {
+                        Err(Error::with_url(ErrorKind::Cancelled, Some(cancel_url)))
+                    }
+                    Either::Right((result, _)) => result,
+                })
+                .boxed(),
+            None => response,
+        };
+
+        response.map_err(with_context).boxed()
     }
 
     /// Logs in with the given username and password and stores the authenticated token in self.
@@ -293,8 +1286,7 @@ where
         U: Into<Cow<'b, str>>,
         V: Into<Cow<'b, str>>,
     {
-        self.post("auth/signin", LoginArgs::new(username, password))
-            .send()
+        self.execute_anonymous(request::login(username, password))
     }
 
     /// Registers a new account with the given username, password and optional email and returns a
@@ -308,19 +1300,54 @@ where
         &self,
         details: RegistrationArgs,
     ) -> impl Future<Output = Result<RegistrationSuccess, Error>> {
-        self.post("register/submit", details).send()
+        self.execute_anonymous(request::register(details))
     }
 
     /// Gets user information on the user currently logged in, including username and user id.
     pub fn my_info(&self) -> Result<impl Future<Output = Result<MyInfo, Error>>, NoToken> {
-        self.get("auth/me").auth().send()
+        self.execute(request::my_info())
+    }
+
+    /// Gets a page of the logged in user's credit transactions, starting with page 0 for the most recent.
+    pub fn money_history(
+        &self,
+        page: u32,
+    ) -> Result<impl Future<Output = Result<MoneyHistory, Error>>, NoToken> {
+        self.execute(request::money_history(page))
+    }
+
+    /// Looks up a user's public information by username.
+    ///
+    /// Does not require authentication. Errors with [`ApiError::UserNotFound`] if no user has the username.
+    ///
+    /// [`ApiError::UserNotFound`]: enum.ApiError.html#variant.UserNotFound
+    pub fn find_user<'b, U>(&self, username: U) -> impl Future<Output = Result<UserInfo, Error>>
+    where
+        U: Into<Cow<'b, str>>,
+    {
+        self.execute_anonymous(request::find_user(username))
+    }
+
+    /// Looks up a user's public information by user ID.
+    ///
+    /// Does not require authentication. Errors with [`ApiError::UserNotFound`] if no user has the ID.
+    ///
+    /// [`ApiError::UserNotFound`]: enum.ApiError.html#variant.UserNotFound
+    pub fn find_user_by_id<'b, U>(
+        &self,
+        user_id: U,
+    ) -> impl Future<Output = Result<UserInfo, Error>>
+    where
+        U: Into<Cow<'b, str>>,
+    {
+        self.execute_anonymous(request::find_user_by_id(user_id))
     }
 
     /// Gets the world shard and room name the server thinks the client should start with viewing.
     pub fn world_start_room(
         &self,
     ) -> Result<impl Future<Output = Result<WorldStartRoom, Error>>, NoToken> {
-        self.get("user/world-start-room").auth().send()
+        self.execute(request::world_start_room())
     }
 
     /// Gets the room name the server thinks the client should start with viewing for a particular
@@ -332,10 +1359,7 @@ where
     where
         U: Into<Cow<'b, str>>,
     {
-        self.get("user/world-start-room")
-            .params(&[("shard", shard.into().into_owned())])
-            .auth()
-            .send()
+        self.execute(request::shard_start_room(shard))
     }
 
     /// Get information on a number of rooms.
@@ -348,10 +1372,47 @@ where
         U: AsRef<str>,
         &'a V: IntoIterator<Item = U>,
     {
-        // TODO: interpret for different stats.
-        let args = MapStatsArgs::new(shard, rooms, MapStatName::RoomOwner);
+        self.execute(request::map_stats(shard, rooms))
+    }
+
+    /// Get information on a number of rooms, along with the given statistic of each room.
+    ///
+    /// The statistic's values can be found with each room's `RoomInfo::stat_values`.
+    pub fn map_stats_with_stat<'a, U, V>(
+        &self,
+        shard: &'a str,
+        rooms: &'a V,
+        stat: MapStatName,
+    ) -> Result<impl Future<Output = Result<MapStats, Error>>, NoToken>
+    where
+        U: AsRef<str>,
+        &'a V: IntoIterator<Item = U>,
+    {
+        self.execute(request::map_stats_with_stat(shard, rooms, stat))
+    }
 
-        self.post("game/map-stats", args).auth().send()
+    /// Get information on a number of rooms on any shards, along with the given statistic of each room.
+    ///
+    /// One request is sent for each shard the rooms are on, and each shard's result is returned paired with the
+    /// shard's name, in the order each shard first appears in `rooms`.
+    pub fn map_stats_on<'a, V>(
+        &self,
+        rooms: &'a V,
+        stat: MapStatName,
+    ) -> Result<impl Future<Output = Result<Vec<(String, MapStats)>, Error>>, NoToken>
+    where
+        &'a V: IntoIterator<Item = &'a ShardRoomName>,
+    {
+        let shards = request::map_stats_on(rooms, stat)
+            .into_iter()
+            .map(|(shard, request)| {
+                let shard = shard.to_owned();
+                self.execute(request)
+                    .map(|fut| fut.map_ok(move |stats| (shard, stats)))
+            })
+            .collect::<Result<Vec<_>, NoToken>>()?;
+
+        Ok(future::try_join_all(shards))
     }
 
     /// Gets the overview of a room, returning totals for usually 3 intervals, 8, 180 and 1440,
@@ -371,14 +1432,16 @@ where
         U: Into<Cow<'b, str>>,
         V: Into<Cow<'b, str>>,
     {
-        self.get("game/room-overview")
-            .params(&[
-                ("shard", shard.into().into_owned()),
-                ("room", room_name.into().into_owned()),
-                ("interval", request_interval.to_string()),
-            ])
-            .auth()
-            .send()
+        self.execute(request::room_overview(shard, room_name, request_interval))
+    }
+
+    /// Gets the overview of a room on a shard. See [`Api::room_overview`] for the allowed intervals.
+    pub fn room_overview_on(
+        &self,
+        room: &ShardRoomName,
+        request_interval: u32,
+    ) -> Result<impl Future<Output = Result<RoomOverview, Error>>, NoToken> {
+        self.execute(request::room_overview_on(room, request_interval))
     }
 
     /// Gets the terrain of a room, returning a 2d array of 50x50 points.
@@ -393,23 +1456,36 @@ where
         U: Into<Cow<'b, str>>,
         V: Into<Cow<'b, str>>,
     {
-        match shard {
-            Some(shard) => self
-                .get("game/room-terrain")
-                .params(&[
-                    ("shard", shard.into().into_owned()),
-                    ("room", room_name.into().into_owned()),
-                    ("encoded", true.to_string()),
-                ])
-                .send(),
-            None => self
-                .get("game/room-terrain")
-                .params(&[
-                    ("room", room_name.into().into_owned()),
-                    ("encoded", true.to_string()),
-                ])
-                .send(),
-        }
+        self.execute_anonymous(request::room_terrain(shard, room_name))
+    }
+
+    /// Gets the terrain of a room on a shard, returning a 2d array of 50x50 points.
+    ///
+    /// Does not require authentication.
+    pub fn room_terrain_on(
+        &self,
+        room: &ShardRoomName,
+    ) -> impl Future<Output = Result<RoomTerrain, Error>> {
+        self.execute_anonymous(request::room_terrain_on(room))
+    }
+
+    /// Gets every object in a room, such as structures, creeps and sources, along with the users who own them.
+    ///
+    /// Does not require authentication. To follow a room's objects as they change, subscribe to the room's socket
+    /// channel instead, and apply each update with `RoomUpdate::apply_to`.
+    ///
+    /// Only available with the `sockets` feature, which has the room object types.
+    #[cfg(feature = "sockets")]
+    pub fn room_objects<'b, U, V>(
+        &self,
+        shard: Option<U>,
+        room_name: V,
+    ) -> impl Future<Output = Result<RoomObjects, Error>>
+    where
+        U: Into<Cow<'b, str>>,
+        V: Into<Cow<'b, str>>,
+    {
+        self.execute_anonymous(request::room_objects(shard, room_name))
     }
 
     /// Gets a list of shards available on this server. Errors with a `404` error when connected to
@@ -417,7 +1493,54 @@ where
     ///
     /// Does not require authentication.
     pub fn shard_list(&self) -> impl Future<Output = Result<Vec<ShardInfo>, Error>> {
-        self.get("game/shards/info").send()
+        self.execute_anonymous(request::shard_list())
+    }
+
+    /// Gets the server's version, and which features it has.
+    ///
+    /// Does not require authentication. See also [`Api::capabilities`], which only requests this once.
+    pub fn version(&self) -> impl Future<Output = Result<ServerVersion, Error>> {
+        self.execute_anonymous(request::version())
+    }
+
+    /// Gets the server's version, and which features it has, requesting it the first time only.
+    ///
+    /// The result is kept until the client's URL changes, and shared with clones of this client. Once it has been
+    /// retrieved, [`Api::supports`] tells whether the server has a given feature.
+    ///
+    /// Does not require authentication.
+    pub fn capabilities(&self) -> BoxFuture<'static, Result<ServerVersion, Error>> {
+        if let Some(version) = self.cached_capabilities() {
+            return future::ready(Ok(version)).boxed();
+        }
+
+        let (cache, url) = (self.capabilities.clone(), self.url.clone());
+        self.version()
+            .map_ok(move |version| {
+                *cache.lock().unwrap_or_else(PoisonError::into_inner) =
+                    Some((url, version.clone()));
+                version
+            })
+            .boxed()
+    }
+
+    /// Whether the server has the given feature, or `None` if not known yet.
+    ///
+    /// This is known once [`Api::capabilities`] has completed for the client's current URL.
+    pub fn supports(&self, feature: Feature) -> Option<bool> {
+        self.cached_capabilities()
+            .map(|version| version.supports(feature))
+    }
+
+    fn cached_capabilities(&self) -> Option<ServerVersion> {
+        match *self
+            .capabilities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            Some((ref url, ref version)) if *url == self.url => Some(version.clone()),
+            _ => None,
+        }
     }
 
     /// Gets the "status" of a room: if it is open, if it is in a novice area, if it exists.
@@ -428,10 +1551,7 @@ where
     where
         U: Into<Cow<'b, str>>,
     {
-        self.get("game/room-status")
-            .params(&[("room", room_name.into().into_owned())])
-            .auth()
-            .send()
+        self.execute(request::room_status(room_name))
     }
 
     /// Experimental endpoint to get all rooms in which PvP has recently occurred, or where PvP has
@@ -440,12 +1560,7 @@ where
         &self,
         details: RecentPvpArgs,
     ) -> impl Future<Output = Result<RecentPvp, Error>> {
-        let args = match details {
-            RecentPvpArgs::WithinLast { ticks } => [("interval", ticks.to_string())],
-            RecentPvpArgs::Since { time } => [("start", time.to_string())],
-        };
-
-        self.get("experimental/pvp").params(&args).send()
+        self.execute_anonymous(request::recent_pvp(details))
     }
 
     /// Gets a list of all past leaderboard seasons, with end dates, display names, and season ids
@@ -460,7 +1575,7 @@ where
     pub fn leaderboard_season_list(
         &self,
     ) -> Result<impl Future<Output = Result<Vec<LeaderboardSeason>, Error>>, NoToken> {
-        self.get("leaderboard/seasons").auth().send()
+        self.execute(request::leaderboard_season_list())
     }
 
     /// Finds the rank of a user in a specific season for a specific leaderboard type.
@@ -473,6 +1588,8 @@ where
     ///
     /// This is technically the same API endpoint as find_leaderboard_rank, but the result format
     /// differs when requesting a specific season from when requesting all season ranks.
+    ///
+    /// The season can be given as a `SeasonId`, or as a string like `"2017-02"`.
     pub fn find_season_leaderboard_rank<'b, U, V>(
         &self,
         leaderboard_type: LeaderboardType,
@@ -483,14 +1600,11 @@ where
         U: Into<Cow<'b, str>>,
         V: Into<Cow<'b, str>>,
     {
-        self.get("leaderboard/find")
-            .auth()
-            .params(&[
-                ("mode", leaderboard_type.api_representation().to_string()),
-                ("season", season.into().into_owned()),
-                ("username", username.into().into_owned()),
-            ])
-            .send()
+        self.execute(request::find_season_leaderboard_rank(
+            leaderboard_type,
+            username,
+            season,
+        ))
     }
 
     /// Finds the rank of a user for all seasons for a specific leaderboard type.
@@ -507,13 +1621,7 @@ where
     where
         U: Into<Cow<'b, str>>,
     {
-        self.get("leaderboard/find")
-            .auth()
-            .params(&[
-                ("mode", leaderboard_type.api_representation().to_string()),
-                ("username", username.into().into_owned()),
-            ])
-            .send()
+        self.execute(request::find_leaderboard_ranks(leaderboard_type, username))
     }
 
     /// Gets a page of the leaderboard for a given season.
@@ -523,6 +1631,8 @@ where
     ///
     /// Offset doesn't have to be a multiple of limit, but it's most likely most useful that it is.
     /// Offset 0 will get you the start/top of the ranked list.
+    ///
+    /// The season can be given as a `SeasonId`, or as a string like `"2017-02"`.
     pub fn leaderboard_page<'b, U>(
         &self,
         leaderboard_type: LeaderboardType,
@@ -533,15 +1643,91 @@ where
     where
         U: Into<Cow<'b, str>>,
     {
-        self.get("leaderboard/list")
-            .auth()
-            .params(&[
-                ("mode", leaderboard_type.api_representation().to_string()),
-                ("season", season.into().into_owned()),
-                ("limit", limit.to_string()),
-                ("offset", offset.to_string()),
-            ])
-            .send()
+        self.execute(request::leaderboard_page(
+            leaderboard_type,
+            season,
+            limit,
+            offset,
+        ))
+    }
+
+    /// Walks the leaderboard for a given season from the top, one page at a time, as a stream of pages.
+    ///
+    /// Each page holds up to `limit` users, at most 20. The next page is only requested once the stream is polled
+    /// for it, and the stream ends after the last page, or after the first error.
+    ///
+    /// Pages are requested through the client's rate limiter and retry policy like any other request: pages rejected
+    /// with `429 Too Many Requests` are requested again according to the [`RetryPolicy`], waiting at least as long as
+    /// the server asks, and fail the stream if the client has no retry policy.
+    ///
+    /// [`RetryPolicy`]: retry/struct.RetryPolicy.html
+    pub fn leaderboard_pages<'b, U>(
+        &self,
+        leaderboard_type: LeaderboardType,
+        season: U,
+        limit: u32,
+    ) -> Result<BoxStream<'static, Result<LeaderboardPage, Error>>, NoToken>
+    where
+        U: Into<Cow<'b, str>>,
+    {
+        if self.token_storage().get().is_none() {
+            return Err(NoToken);
+        }
+
+        let state = (self.clone(), season.into().into_owned(), 0);
+        Ok(stream::unfold(Some(state), move |state| async move {
+            let (api, season, offset) = state?;
+
+            let result = match api.leaderboard_page(leaderboard_type, &*season, limit, offset) {
+                Ok(page) => page.await,
+                Err(e) => Err(e.into()),
+            };
+
+            match result {
+                Ok(page) => {
+                    let next = offset + page.ranks.len() as u32;
+                    let more = !page.ranks.is_empty() && u64::from(next) < page.total_count;
+                    let state = if more {
+                        Some((api, season, next))
+                    } else {
+                        None
+                    };
+                    Some((Ok(page), state))
+                }
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+        .boxed())
+    }
+
+    /// Gets how many orders each resource has on the market, on the given shard.
+    pub fn market_orders_index<'b, U>(
+        &self,
+        shard: Option<U>,
+    ) -> Result<impl Future<Output = Result<MarketOrdersIndex, Error>>, NoToken>
+    where
+        U: Into<Cow<'b, str>>,
+    {
+        self.execute(request::market_orders_index(shard))
+    }
+
+    /// Gets all active orders buying or selling the given resource, on the given shard.
+    pub fn market_orders<'b, U>(
+        &self,
+        shard: Option<U>,
+        resource_type: &ResourceType,
+    ) -> Result<impl Future<Output = Result<MarketOrders, Error>>, NoToken>
+    where
+        U: Into<Cow<'b, str>>,
+    {
+        self.execute(request::market_orders(shard, resource_type))
+    }
+
+    /// Gets the logged in user's market orders, on all shards.
+    pub fn my_market_orders(
+        &self,
+    ) -> Result<impl Future<Output = Result<MyMarketOrders, Error>>, NoToken> {
+        self.execute(request::my_market_orders())
     }
 
     /// Gets the player's memory segment on a given shard
@@ -553,22 +1739,18 @@ where
     where
         U: Into<Cow<'b, str>>,
     {
-        match shard {
-            Some(shard) => self
-                .get::<MemorySegment>("user/memory-segment")
-                .params(&[
-                    ("segment", segment.to_string()),
-                    ("shard", shard.into().into_owned()),
-                ])
-                .auth()
-                .send(),
-            None => self
-                .get::<MemorySegment>("user/memory-segment")
-                .params(&[("segment", segment.to_string())])
-                .auth()
-                .send(),
-        }
-        .map(|fut| fut.map_ok(|res| res.data))
+        self.execute(request::memory_segment(shard, segment))
+            .map(|fut| fut.map_ok(|res| res.data))
+    }
+
+    /// Gets the player's memory segment on the shard a room is on.
+    pub fn memory_segment_on(
+        &self,
+        room: &ShardRoomName,
+        segment: u32,
+    ) -> Result<impl Future<Output = Result<String, Error>>, NoToken> {
+        self.execute(request::memory_segment_on(room, segment))
+            .map(|fut| fut.map_ok(|res| res.data))
     }
 
     /// Sets the player's memory segment on a given shard
@@ -582,225 +1764,406 @@ where
         U: Into<Cow<'b, str>>,
         V: Into<Cow<'b, str>>,
     {
-        let args = SetMemorySegmentArgs {
-            segment,
-            shard: shard.map(Into::into),
-            data: data.into(),
-        };
+        self.execute(request::set_memory_segment(shard, segment, data))
+            .map(|fut| fut.map_ok(|_| ()))
+    }
 
-        self.post("user/memory-segment", args)
-            .auth()
-            .send()
-            .map(|fut| fut.map_ok(|_: SetMemorySegment| ()))
+    /// Sets the player's memory segment on the shard a room is on.
+    pub fn set_memory_segment_on<'b, V>(
+        &self,
+        room: &ShardRoomName,
+        segment: u32,
+        data: V,
+    ) -> Result<impl Future<Output = Result<(), Error>>, NoToken>
+    where
+        V: Into<Cow<'b, str>>,
+    {
+        self.execute(request::set_memory_segment_on(room, segment, data))
+            .map(|fut| fut.map_ok(|_| ()))
     }
 }
 
-trait PartialRequestAuth<T> {
-    type Result;
-
-    fn token_or_result(token: Option<Token>) -> Result<Option<Token>, Self::Result>;
+/// Calculates GCL, given GCL points.
+#[inline]
+pub fn gcl_calc(gcl_points: u64) -> u64 {
+    const GCL_INV_MULTIPLY: f64 = 1.0 / 1_000_000f64;
+    const GCL_INV_POW: f64 = 1.0 / 2.4f64;
 
-    fn successful_result(success: T) -> Self::Result;
+    ((gcl_points as f64) * GCL_INV_MULTIPLY)
+        .powf(GCL_INV_POW)
+        .floor() as u64
+        + 1
 }
 
-struct NoAuthRequired;
+#[cfg(all(test, feature = "http"))]
+mod api_tests {
+    use std::sync::Arc;
+
+    use futures::future;
+    use http::{header::HeaderName, HeaderValue, StatusCode};
+
+    use super::{Api, LeaderboardType, ServerProfile, TokenStorage};
+    use crate::testing::{Mock, MockTransport};
 
-impl<T> PartialRequestAuth<T> for NoAuthRequired {
-    type Result = T;
+    /// Accepts only the token handed out by logging in.
+    fn expiring_tokens() -> MockTransport {
+        let unauthorized = |endpoint| {
+            Mock::any(endpoint)
+                .with_status(StatusCode::UNAUTHORIZED)
+                .with_body("Unauthorized")
+        };
 
-    fn token_or_result(_token: Option<Token>) -> Result<Option<Token>, T> {
-        Ok(None)
+        MockTransport::new()
+            .with_mock(Mock::post("auth/signin").with_json(json!({ "ok": 1, "token": "fresh" })))
+            .with_mock(
+                Mock::get("user/world-start-room")
+                    .with_request_header(
+                        HeaderName::from_static("x-token"),
+                        HeaderValue::from_static("fresh"),
+                    )
+                    .with_json(json!({ "ok": 1, "room": ["shard0/E1N1"] })),
+            )
+            .with_mock(unauthorized("user/world-start-room"))
+            .with_mock(unauthorized("user/memory-segment"))
+            .with_mock(unauthorized("register/submit"))
     }
 
-    fn successful_result(success: T) -> T {
-        success
+    /// Counts the logins sent to a transport.
+    fn logins(transport: &MockTransport) -> usize {
+        transport
+            .requests()
+            .iter()
+            .filter(|request| request.uri().path().ends_with("auth/signin"))
+            .count()
     }
-}
 
-struct AuthRequired;
+    #[test]
+    fn relogin_on_unauthorized() {
+        let api =
+            Api::new_for_server(expiring_tokens(), ServerProfile::Private).with_token("expired");
+        let err = futures::executor::block_on(api.world_start_room().unwrap()).unwrap_err();
+        match *err.kind() {
+            super::ErrorKind::Unauthorized => (),
+            ref other => panic!("expected unauthorized error, found {:?}", other),
+        }
 
-impl<T> PartialRequestAuth<T> for AuthRequired {
-    type Result = Result<T, NoToken>;
+        let api = api.with_credentials("username", "password");
+        let start = futures::executor::block_on(api.world_start_room().unwrap()).unwrap();
+        assert_eq!(start.room_name, "E1N1");
+        assert_eq!(api.token_storage().get().unwrap(), "fresh");
+    }
 
-    fn token_or_result(token: Option<Token>) -> Result<Option<Token>, Result<T, NoToken>> {
-        match token {
-            Some(v) => Ok(Some(v)),
-            None => Err(Err(NoToken)),
+    #[test]
+    fn relogin_once_for_concurrent_requests() {
+        let transport = expiring_tokens();
+        let api = Api::new_for_server(transport.clone(), ServerProfile::Private)
+            .with_token("expired")
+            .with_credentials("username", "password");
+
+        let requests = (0..4)
+            .map(|_| api.world_start_room().unwrap())
+            .collect::<Vec<_>>();
+        for start in futures::executor::block_on(future::join_all(requests)) {
+            assert_eq!(start.unwrap().room_name, "E1N1");
         }
+        assert_eq!(logins(&transport), 1);
     }
 
-    fn successful_result(success: T) -> Result<T, NoToken> {
-        Ok(success)
+    #[test]
+    fn keep_raw_json_without_tokens() {
+        let api = Api::new_for_server(expiring_tokens(), ServerProfile::Private)
+            .with_token("expired")
+            .with_credentials("username", "password")
+            .with_raw_json(true);
+        let start = futures::executor::block_on(api.world_start_room().unwrap()).unwrap();
+        assert_eq!(start.room_name, "E1N1");
+
+        let start = api.raw_json("user/world-start-room").unwrap();
+        assert_eq!(start, json!({ "ok": 1, "room": ["shard0/E1N1"] }));
+        let login = api.raw_json("auth/signin").unwrap();
+        assert_ne!(login["token"], "fresh");
+        assert!(api.clone().raw_json("user/world-start-room").is_some());
+        assert!(api.raw_json("game/shards/info").is_none());
     }
-}
 
-struct PartialRequest<'a, C, R, A = NoAuthRequired, S = &'static str>
-where
-    C: hyper::client::connect::Connect,
-    R: EndpointResult,
-    S: serde::Serialize + 'a,
-{
-    client: &'a Api<C>,
-    endpoint: &'a str,
-    query_params: Option<&'a [(&'static str, String)]>,
-    post_body: Option<S>,
-    _phantom: PhantomData<(R, A)>,
-}
+    #[test]
+    fn attach_request_context() {
+        let api = Api::new(expiring_tokens()).with_token("expired");
+        let err =
+            futures::executor::block_on(api.set_memory_segment(Some("shard0"), 1, "data").unwrap())
+                .unwrap_err();
+
+        let request = err.request().unwrap();
+        assert_eq!(request.method, http::Method::POST);
+        assert_eq!(request.endpoint, "user/memory-segment");
+        assert!(String::from_utf8_lossy(request.body.as_ref().unwrap()).contains("\"data\""));
+        assert!(request.elapsed.is_some());
+        assert!(err
+            .to_string()
+            .contains("request: POST user/memory-segment"));
+
+        let api = Api::new(expiring_tokens());
+        let err = futures::executor::block_on(
+            api.register(crate::RegistrationArgs::new("user", "password")),
+        )
+        .unwrap_err();
+        let body = err.request().unwrap().body.as_ref().unwrap();
+        assert!(!String::from_utf8_lossy(body).contains("\"password\":\"password\""));
+    }
 
-impl<'a, C, R, S> PartialRequest<'a, C, R, NoAuthRequired, S>
-where
-    C: hyper::client::connect::Connect + 'static,
-    R: EndpointResult,
-    S: serde::Serialize,
-{
-    #[inline]
-    fn auth(self) -> PartialRequest<'a, C, R, AuthRequired, S> {
-        PartialRequest {
-            client: self.client,
-            endpoint: self.endpoint,
-            query_params: self.query_params,
-            post_body: self.post_body,
-            _phantom: PhantomData,
-        }
+    #[test]
+    fn existing_token() {
+        let api = Api::new_with_token(expiring_tokens(), "fresh");
+        let start = futures::executor::block_on(api.world_start_room().unwrap()).unwrap();
+        assert_eq!(start.room_name, "E1N1");
     }
-}
 
-impl<'a, C, R, S> PartialRequest<'a, C, R, AuthRequired, S>
-where
-    C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
-    R: EndpointResult,
-    S: serde::Serialize,
-{
-    // This particular method should be a useful one to have around, even if just for completeness.
-    #[allow(dead_code)]
-    #[inline]
-    fn no_auth(self) -> PartialRequest<'a, C, R, NoAuthRequired, S> {
-        PartialRequest {
-            client: self.client,
-            endpoint: self.endpoint,
-            query_params: self.query_params,
-            post_body: self.post_body,
-            _phantom: PhantomData,
+    /// Serves a leaderboard of 3 users, in pages of 1 or 2.
+    fn leaderboard() -> MockTransport {
+        let transport = MockTransport::new();
+        for &(offset, limit) in &[(0, 1), (1, 1), (2, 1), (0, 2), (2, 2)] {
+            let list = (offset..(offset + limit).min(3))
+                .map(|rank| {
+                    json!({
+                        "rank": rank,
+                        "score": 1000 - rank,
+                        "season": "2018-01",
+                        "user": format!("user{}", rank),
+                    })
+                })
+                .collect::<Vec<_>>();
+            transport.add_mock(
+                Mock::get("leaderboard/list")
+                    .with_query("offset", offset.to_string())
+                    .with_query("limit", limit.to_string())
+                    .with_json(json!({ "ok": 1, "count": 3, "list": list, "users": {} })),
+            );
         }
+        transport
     }
-}
 
-impl<'a, C, R, A, S> PartialRequest<'a, C, R, A, S>
-where
-    C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
-    R: EndpointResult,
-    A: PartialRequestAuth<BoxFuture<'static, Result<R, Error>>>,
-    S: serde::Serialize,
-{
-    #[inline]
-    fn params(mut self, params: &'a [(&'static str, String)]) -> Self {
-        self.query_params = Some(params);
-        self
+    #[test]
+    fn walk_leaderboard_pages() {
+        use futures::stream::TryStreamExt;
+
+        let api = Api::new_with_token(leaderboard(), "token");
+        let pages = api
+            .leaderboard_pages(LeaderboardType::GlobalControl, "2018-01", 2)
+            .unwrap();
+        let pages = futures::executor::block_on(pages.try_collect::<Vec<_>>()).unwrap();
+
+        let ranks = pages
+            .iter()
+            .map(|page| page.ranks.iter().map(|user| user.rank).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(ranks, vec![vec![0, 1], vec![2]]);
+
+        assert!(Api::new(leaderboard())
+            .leaderboard_pages(LeaderboardType::GlobalControl, "2018-01", 2)
+            .is_err());
     }
 
-    #[inline]
-    fn post(mut self, body: S) -> Self {
-        self.post_body = Some(body);
-        self
+    #[cfg(feature = "sync")]
+    #[test]
+    fn retry_rate_limited_leaderboard_pages() {
+        use std::time::Duration;
+
+        use futures::stream::StreamExt;
+
+        use crate::testing::{Mock, MockTransport};
+
+        let transport = MockTransport::new().with_mock(
+            Mock::get("leaderboard/list").with_status(http::StatusCode::TOO_MANY_REQUESTS),
+        );
+        let api = Api::new_with_token(transport.clone(), "token").with_retry_policy(
+            crate::RetryPolicy::new()
+                .with_max_retries(2)
+                .with_initial_delay(Duration::from_millis(1))
+                .with_jitter(false),
+        );
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let mut pages = api
+            .leaderboard_pages(LeaderboardType::GlobalControl, "2018-01", 2)
+            .unwrap();
+        let first = runtime.block_on(pages.next()).unwrap();
+        assert_eq!(
+            first.unwrap_err().category(),
+            crate::ErrorCategory::RateLimited
+        );
+        assert_eq!(transport.requests().len(), 3);
     }
 
-    /// Result type here _so hacky!_ Glad this is an internal API.
-    ///
-    /// Returns either `connecting::impl Future<Output=Result< Error=Error>` or `Result<connecting::FutureResponse<R>, NoToken>`
-    /// depending on if auth() has been called.
-    fn send(self) -> A::Result {
-        let PartialRequest {
-            client,
-            endpoint,
-            query_params,
-            post_body,
-            _phantom: _,
-        } = self;
-
-        // this checks if authentication is required.
-        //
-        // like:
-        // ```
-        // let auth_token = if auth_required {
-        //     match client.tokens.take_token() {
-        //         Some(token) => Some(token),
-        //         None => return Err(NoToken),
-        //     }
-        // } else {
-        //     None
-        // };
-        // ```
-        //
-        // but this way we can return without a Result if authentication isn't required.
-        let auth_token = match A::token_or_result(client.auth_token.get()) {
-            Ok(token_option) => token_option,
-            Err(return_value) => return return_value,
-        };
+    #[test]
+    fn batch_requests() {
+        let api = Api::new_with_token(leaderboard(), "token");
+        let requests = (0..3).map(|offset| {
+            crate::request::leaderboard_page(LeaderboardType::GlobalControl, "2018-01", 1, offset)
+        });
+
+        let results = futures::executor::block_on(api.batch(requests, 2));
+        let first_ranks = results
+            .into_iter()
+            .map(|page| page.unwrap().ranks[0].rank)
+            .collect::<Vec<_>>();
+        assert_eq!(first_ranks, vec![0, 1, 2]);
+
+        let results = futures::executor::block_on(Api::new(leaderboard()).batch(
+            vec![crate::request::leaderboard_page(
+                LeaderboardType::GlobalControl,
+                "2018-01",
+                1,
+                0,
+            )],
+            2,
+        ));
+        assert!(results[0].is_err());
+    }
 
-        let method = match post_body {
-            Some(_) => hyper::Method::POST,
-            None => hyper::Method::GET,
-        };
+    #[cfg(feature = "sync")]
+    #[test]
+    fn time_out_stalled_requests() {
+        use std::time::Duration;
 
-        let url = {
-            let mut temp = client
-                .url
-                .join(endpoint)
-                .expect("expected pre-set endpoint url text to succeed, but it failed.");
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let api = Api::new(crate::testing::Stalled).with_timeout(Duration::from_secs(60));
 
-            if let Some(pairs) = query_params {
-                temp.query_pairs_mut().extend_pairs(pairs).finish();
-            }
+        let request = crate::request::shard_list().with_timeout(Duration::from_millis(10));
+        let err = runtime.block_on(api.execute(request).unwrap()).unwrap_err();
+        match *err.kind() {
+            super::ErrorKind::Timeout(timeout) => assert_eq!(timeout, Duration::from_millis(10)),
+            ref other => panic!("expected timeout error, found {:?}", other),
+        }
+        assert!(err.is_timeout());
+    }
 
-            temp
+    #[cfg(feature = "sync")]
+    #[test]
+    fn parse_large_responses_on_blocking_pool() {
+        use std::{sync::Mutex, thread};
+
+        use crate::testing::{Mock, MockTransport};
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let transport = MockTransport::new().with_mock(
+            Mock::get("game/shards/info").with_json(json!({ "ok": 1, "shards": [], "extra": 1 })),
+        );
+        let parsed_on = Arc::new(Mutex::new(None));
+        let api = {
+            let parsed_on = parsed_on.clone();
+            Api::new(transport).with_parse_warning_hook(move |_| {
+                *parsed_on.lock().unwrap() = Some(thread::current().id());
+            })
         };
 
-        let mut request = hyper::Request::builder();
+        // block_on runs requests on this thread, so only parsing on the pool moves it elsewhere.
+        runtime.block_on(api.shard_list()).unwrap();
+        assert_eq!(*parsed_on.lock().unwrap(), Some(thread::current().id()));
 
-        request = request.method(method).uri(url.as_str());
+        let api = api.with_blocking_parse_threshold(16);
+        runtime.block_on(api.shard_list()).unwrap();
+        assert_ne!(*parsed_on.lock().unwrap(), Some(thread::current().id()));
+    }
+
+    #[test]
+    fn retry_after_only_when_rate_limited_or_unavailable() {
+        use http::{header::RETRY_AFTER, HeaderValue, StatusCode};
 
-        // headers
-        request = request.header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        use crate::testing::{Mock, MockTransport};
 
-        if let Some(token) = auth_token {
-            request = request.header(
-                "X-Token",
-                HeaderValue::from_maybe_shared(token.clone())
-                    // TODO: turn this into a non-expect error (how the heck does this function return errors?)
-                    .expect("tokens should always be valid headers"),
+        let wait_with = |status| {
+            let transport = MockTransport::new().with_mock(
+                Mock::get("game/shards/info")
+                    .with_status(status)
+                    .with_header(RETRY_AFTER, HeaderValue::from_static("120")),
             );
-        }
+            let api = Api::new(transport);
+            futures::executor::block_on(api.shard_list())
+                .unwrap_err()
+                .retry_after()
+        };
 
-        let request = if let Some(ref serializable) = post_body {
-            request.body(hyper::Body::from(
-                serde_json::to_string(serializable).expect(
-                    "expected serde_json::to_string to unfailingly succeed, but it failed.",
-                ),
-            ))
-        } else {
-            request.body(hyper::Body::empty())
+        let two_minutes = Some(std::time::Duration::from_secs(120));
+        assert_eq!(wait_with(StatusCode::TOO_MANY_REQUESTS), two_minutes);
+        assert_eq!(wait_with(StatusCode::SERVICE_UNAVAILABLE), two_minutes);
+        assert_eq!(wait_with(StatusCode::INTERNAL_SERVER_ERROR), None);
+        assert_eq!(wait_with(StatusCode::NOT_FOUND), None);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn retry_with_custom_backoff() {
+        use std::{sync::Mutex, time::Duration};
+
+        use crate::{
+            backoff::BackoffPolicy,
+            testing::{Mock, MockTransport},
         };
-        let request = request.expect("building http request should never fail");
 
-        let hyper_future = client.client.request(request);
-        let finished = connecting::interpret(client.auth_token.clone(), url, hyper_future);
+        /// Records what it is asked, and waits one millisecond longer each time.
+        #[derive(Debug, Default)]
+        struct Recording(Mutex<Vec<(u32, Duration)>>);
 
-        // turns into either `Result<FutureResponse<..>>` or `FutureResponse<..>` depending on
-        // if we required auth.
-        A::successful_result(finished.boxed())
-    }
-}
+        impl BackoffPolicy for Arc<Recording> {
+            fn delay(&self, attempt: u32, previous: Duration) -> Option<Duration> {
+                self.0.lock().unwrap().push((attempt, previous));
+                Some(previous + Duration::from_millis(1))
+            }
+        }
 
-/// Calculates GCL, given GCL points.
-#[inline]
-pub fn gcl_calc(gcl_points: u64) -> u64 {
-    const GCL_INV_MULTIPLY: f64 = 1.0 / 1_000_000f64;
-    const GCL_INV_POW: f64 = 1.0 / 2.4f64;
+        let transport = MockTransport::new()
+            .with_mock(Mock::get("game/shards/info").with_status(http::StatusCode::BAD_GATEWAY));
+        let recording = Arc::new(Recording::default());
+        let api = Api::new(transport.clone())
+            .with_retry_policy(crate::RetryPolicy::new().with_max_retries(2))
+            .with_retry_backoff(recording.clone());
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        assert!(runtime.block_on(api.shard_list()).is_err());
+        assert_eq!(transport.requests().len(), 3);
+        assert_eq!(
+            *recording.0.lock().unwrap(),
+            [(0, Duration::from_secs(0)), (1, Duration::from_millis(1))]
+        );
+    }
 
-    ((gcl_points as f64) * GCL_INV_MULTIPLY)
-        .powf(GCL_INV_POW)
-        .floor() as u64
-        + 1
+    #[cfg(feature = "sync")]
+    #[test]
+    fn no_retry_when_asked_to_wait_too_long() {
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        use http::HeaderValue;
+
+        use crate::testing::{Mock, MockTransport};
+
+        // a daily limit, reset a day from now.
+        let reset = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 86_400;
+        let transport = MockTransport::new().with_mock(
+            Mock::get("game/shards/info")
+                .with_status(http::StatusCode::TOO_MANY_REQUESTS)
+                .with_header(
+                    http::header::HeaderName::from_static("x-ratelimit-reset"),
+                    HeaderValue::from_str(&reset.to_string()).unwrap(),
+                ),
+        );
+        let api = Api::new(transport.clone()).with_retry_policy(
+            crate::RetryPolicy::new()
+                .with_max_retries(2)
+                .with_initial_delay(Duration::from_millis(1)),
+        );
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let err = runtime.block_on(api.shard_list()).unwrap_err();
+        assert_eq!(transport.requests().len(), 1);
+        let retry_after = err.retry_after().unwrap();
+        assert!(
+            retry_after > Duration::from_secs(86_000) && retry_after <= Duration::from_secs(86_400)
+        );
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,140 @@
+//! Observing the requests a client makes, for metrics and logging.
+use std::time::Duration;
+
+/// Receives an event when each request an [`Api`] sends starts and finishes.
+///
+/// Set on a client with `Api::set_observer`, to export metrics such as request counts and latencies per endpoint
+/// without wrapping every call. Requests answered by middleware, such as a [`ResponseCache`], are observed too.
+///
+/// Observers are called on the task sending the request, and should not block.
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use screeps_api::metrics::{Observer, RequestFinished};
+///
+/// /// Counts requests which did not succeed.
+/// #[derive(Default)]
+/// struct FailureCount(AtomicUsize);
+///
+/// impl Observer for FailureCount {
+///     fn on_finish(&self, event: &RequestFinished) {
+///         if !event.status.is_some_and(|status| status.is_success()) {
+///             self.0.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+/// }
+///
+/// let api = screeps_api::Api::new(hyper::Client::new()).with_observer(FailureCount::default());
+/// ```
+///
+/// [`Api`]: ../struct.Api.html
+/// [`ResponseCache`]: ../cache/struct.ResponseCache.html
+pub trait Observer: Send + Sync + 'static {
+    /// Called before a request is first sent.
+    fn on_start(&self, event: &RequestStarted) {
+        let _ = event;
+    }
+
+    /// Called as the body of a response is received, after each chunk.
+    ///
+    /// This is reported by the [`HttpTransport`] through a [`BodyProgress`]: transports which don't report progress
+    /// don't call it. When a request is retried, each response's body is reported from the start.
+    ///
+    /// [`HttpTransport`]: ../transport/trait.HttpTransport.html
+    /// [`BodyProgress`]: ../transport/struct.BodyProgress.html
+    fn on_progress(&self, event: &RequestProgress) {
+        let _ = event;
+    }
+
+    /// Called when a request has finished, after any retries.
+    fn on_finish(&self, event: &RequestFinished) {
+        let _ = event;
+    }
+}
+
+/// A request which is about to be sent.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RequestStarted {
+    /// The request's method.
+    pub method: http::Method,
+    /// The endpoint, relative to the API url, such as `"game/room-terrain"`.
+    pub endpoint: &'static str,
+}
+
+/// A request whose response body is being received.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RequestProgress {
+    /// The request's method.
+    pub method: http::Method,
+    /// The endpoint, relative to the API url, such as `"game/room-terrain"`.
+    pub endpoint: &'static str,
+    /// How many bytes of the body have been received so far.
+    pub received: u64,
+    /// The size of the whole body in bytes, if known.
+    pub total: Option<u64>,
+}
+
+/// A request which has finished.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RequestFinished {
+    /// The request's method.
+    pub method: http::Method,
+    /// The endpoint, relative to the API url, such as `"game/room-terrain"`.
+    pub endpoint: &'static str,
+    /// How long the request took, including waiting for the rate limiter and between retries.
+    pub duration: Duration,
+    /// The status code of the final response, or `None` if no response was received.
+    pub status: Option<http::StatusCode>,
+    /// How many times the request was retried.
+    pub retries: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{Observer, RequestFinished, RequestStarted};
+    use crate::{
+        testing::{Mock, MockTransport},
+        Api,
+    };
+
+    #[derive(Default)]
+    struct Events(Mutex<Vec<String>>);
+
+    impl Observer for Arc<Events> {
+        fn on_start(&self, event: &RequestStarted) {
+            let mut events = self.0.lock().unwrap();
+            events.push(format!("start {} {}", event.method, event.endpoint));
+        }
+
+        fn on_finish(&self, event: &RequestFinished) {
+            let mut events = self.0.lock().unwrap();
+            events.push(format!(
+                "finish {} {} {:?} {}",
+                event.method, event.endpoint, event.status, event.retries
+            ));
+        }
+    }
+
+    #[test]
+    fn observe_requests() {
+        let events = Arc::new(Events::default());
+        let transport = MockTransport::new()
+            .with_mock(Mock::get("game/shards/info").with_json(json!({ "ok": 1, "shards": [] })));
+        let api = Api::new(transport).with_observer(events.clone());
+
+        futures::executor::block_on(api.shard_list()).unwrap();
+        assert_eq!(
+            *events.0.lock().unwrap(),
+            [
+                "start GET game/shards/info",
+                "finish GET game/shards/info Some(200) 0",
+            ]
+        );
+    }
+}
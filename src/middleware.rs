@@ -0,0 +1,374 @@
+//! Hooks which see and modify every request and response.
+use std::{
+    fmt,
+    future::Future,
+    hash::{Hash, Hasher},
+    mem,
+    sync::Arc,
+    time::Duration,
+};
+
+use bytes::Bytes;
+
+use crate::{
+    error::{Error, ErrorKind},
+    redact,
+    transport::HttpTransport,
+    TokenStorage,
+};
+
+/// A hook run around every request an [`Api`] sends.
+///
+/// Middleware is added with `Api::add_middleware`, and applies to every endpoint method alike. It can be used to add
+/// custom headers, to trace requests, to serve responses from a cache, or to make assertions in tests.
+///
+/// When several are added, `on_request` runs in the order they were added, and `on_response` and `on_error` in the
+/// reverse order. Each attempt of a retried request goes through the whole chain.
+///
+/// ```
+/// use bytes::Bytes;
+/// use screeps_api::middleware::Middleware;
+///
+/// /// Identifies requests made by this bot.
+/// struct BotName;
+///
+/// impl Middleware for BotName {
+///     fn on_request(&self, request: &mut http::Request<Bytes>) -> Option<http::Response<Bytes>> {
+///         request
+///             .headers_mut()
+///             .insert("X-Bot-Name", http::HeaderValue::from_static("harvester"));
+///         None
+///     }
+/// }
+/// ```
+///
+/// [`Api`]: ../struct.Api.html
+pub trait Middleware: Send + Sync + 'static {
+    /// Called before a request is sent, and may modify it.
+    ///
+    /// Returning a response skips sending the request: the response is used as if the server had returned it, and
+    /// middleware added after this one is not run.
+    fn on_request(&self, request: &mut http::Request<Bytes>) -> Option<http::Response<Bytes>> {
+        let _ = request;
+        None
+    }
+
+    /// Called when a response is received, before it is parsed, and may modify it.
+    ///
+    /// `request` is the request the response is for, without its body.
+    fn on_response(&self, request: &http::Request<()>, response: &mut http::Response<Bytes>) {
+        let _ = (request, response);
+    }
+
+    /// Called when sending a request fails without any response.
+    fn on_error(&self, request: &http::Request<()>, error: &Error) {
+        let _ = (request, error);
+    }
+}
+
+/// Middleware which logs every request and response, without ever logging auth tokens or passwords.
+///
+/// Requests and responses are logged through the `log` crate, at `debug` level by default, along with their headers.
+/// The values of headers carrying credentials, such as `X-Token`, are always hidden. With
+/// [`RequestLog::with_bodies`], bodies are logged too, with the values of any `token` or `password` fields in JSON
+/// bodies hidden.
+///
+/// ```
+/// use screeps_api::middleware::RequestLog;
+///
+/// let client = hyper::Client::new();
+/// let api = screeps_api::Api::new(client).with_middleware(RequestLog::new().with_bodies(true));
+/// ```
+///
+/// [`RequestLog::with_bodies`]: struct.RequestLog.html#method.with_bodies
+#[derive(Clone, Copy, Debug)]
+pub struct RequestLog {
+    level: log::Level,
+    bodies: bool,
+}
+
+impl Default for RequestLog {
+    fn default() -> Self {
+        RequestLog {
+            level: log::Level::Debug,
+            bodies: false,
+        }
+    }
+}
+
+impl RequestLog {
+    /// Creates middleware logging requests and responses at `debug` level, without their bodies.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the level to log at, and returns the middleware.
+    pub fn with_level(mut self, level: log::Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets whether bodies are logged, and returns the middleware.
+    pub fn with_bodies(mut self, bodies: bool) -> Self {
+        self.bodies = bodies;
+        self
+    }
+}
+
+impl Middleware for RequestLog {
+    fn on_request(&self, request: &mut http::Request<Bytes>) -> Option<http::Response<Bytes>> {
+        log!(
+            self.level,
+            "sending {} {} with headers {:?}",
+            request.method(),
+            request.uri(),
+            redact::Headers(request.headers())
+        );
+        if self.bodies && !request.body().is_empty() {
+            log!(
+                self.level,
+                "request body: {:?}",
+                redact::Body(request.body())
+            );
+        }
+        None
+    }
+
+    fn on_response(&self, request: &http::Request<()>, response: &mut http::Response<Bytes>) {
+        log!(
+            self.level,
+            "received {} for {} {} with headers {:?}",
+            response.status(),
+            request.method(),
+            request.uri(),
+            redact::Headers(response.headers())
+        );
+        if self.bodies {
+            log!(
+                self.level,
+                "response body: {:?}",
+                redact::Body(response.body())
+            );
+        }
+    }
+
+    fn on_error(&self, request: &http::Request<()>, error: &Error) {
+        log!(
+            self.level,
+            "failed sending {} {}: {}",
+            request.method(),
+            request.uri(),
+            error
+        );
+    }
+}
+
+/// The middleware added to an `Api`, in order.
+#[derive(Clone, Default)]
+pub(crate) struct Chain {
+    hooks: Vec<Arc<dyn Middleware>>,
+}
+
+impl fmt::Debug for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Chain")
+            .field("len", &self.hooks.len())
+            .finish()
+    }
+}
+
+impl Chain {
+    pub(crate) fn push<M: Middleware>(&mut self, middleware: M) {
+        self.hooks.push(Arc::new(middleware));
+    }
+
+    /// Sends a request through this middleware and the given transport.
+    ///
+    /// `before_send` is awaited right before the request is actually sent, and not when middleware answers the
+    /// request itself. Sending fails with `ErrorKind::Timeout` if the client takes longer than `timeout`.
+    pub(crate) async fn send<T, F, Fut>(
+        &self,
+        client: &T,
+        mut request: http::Request<Bytes>,
+        before_send: F,
+        timeout: Option<Duration>,
+    ) -> Result<http::Response<Bytes>, Error>
+    where
+        T: HttpTransport + ?Sized,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let middleware = &self.hooks[..];
+        let mut ran = middleware.len();
+        let mut response = None;
+        for (idx, hook) in middleware.iter().enumerate() {
+            if let Some(early) = hook.on_request(&mut request) {
+                ran = idx;
+                response = Some(early);
+                break;
+            }
+        }
+
+        let head = head(&request);
+        let mut response = match response {
+            Some(response) => Ok(response),
+            None => {
+                before_send().await;
+                match timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, client.send(request))
+                        .await
+                        .unwrap_or_else(|_| Err(ErrorKind::Timeout(timeout).into())),
+                    None => client.send(request).await,
+                }
+            }
+        };
+
+        for hook in middleware[..ran].iter().rev() {
+            match response {
+                Ok(ref mut response) => hook.on_response(&head, response),
+                Err(ref error) => hook.on_error(&head, error),
+            }
+        }
+
+        response
+    }
+}
+
+/// The account an authenticated request is sent for, attached to the request as an extension.
+///
+/// Unlike the token, which the server may replace with every response, this stays the same for as long as a client
+/// is used with the same account.
+#[derive(Clone, Debug)]
+pub(crate) enum Account {
+    /// An account in a shared `TokenStore`, by name.
+    Stored(String),
+    /// The account the client logs in to again with its credentials, by username.
+    Username(String),
+    /// Whichever account the token kept in this storage is for.
+    Storage(Arc<dyn TokenStorage>),
+}
+
+impl PartialEq for Account {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Account::Stored(a), Account::Stored(b)) => a == b,
+            (Account::Username(a), Account::Username(b)) => a == b,
+            (Account::Storage(a), Account::Storage(b)) => {
+                Arc::as_ptr(a) as *const () == Arc::as_ptr(b) as *const ()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Account {}
+
+impl Hash for Account {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Account::Stored(name) | Account::Username(name) => name.hash(state),
+            Account::Storage(storage) => (Arc::as_ptr(storage) as *const ()).hash(state),
+        }
+    }
+}
+
+/// Copies everything but the body of a request, keeping the account it is sent for.
+fn head(request: &http::Request<Bytes>) -> http::Request<()> {
+    let mut head = http::Request::new(());
+    *head.method_mut() = request.method().clone();
+    *head.uri_mut() = request.uri().clone();
+    *head.version_mut() = request.version();
+    *head.headers_mut() = request.headers().clone();
+    if let Some(account) = request.extensions().get::<Account>() {
+        head.extensions_mut().insert(account.clone());
+    }
+    head
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use bytes::Bytes;
+    use http::{header::HeaderName, HeaderValue};
+
+    use super::Middleware;
+    use crate::{
+        testing::{Mock, MockTransport},
+        Api,
+    };
+
+    /// Responds successfully only to requests with the `X-Test` header.
+    fn require_header() -> MockTransport {
+        MockTransport::new()
+            .with_mock(
+                Mock::get("game/shards/info")
+                    .with_request_header(
+                        HeaderName::from_static("x-test"),
+                        HeaderValue::from_static("1"),
+                    )
+                    .with_json(json!({ "ok": 1, "shards": [] })),
+            )
+            .with_mock(Mock::get("game/shards/info").with_status(http::StatusCode::BAD_REQUEST))
+    }
+
+    struct AddHeader;
+
+    impl Middleware for AddHeader {
+        fn on_request(&self, request: &mut http::Request<Bytes>) -> Option<http::Response<Bytes>> {
+            request
+                .headers_mut()
+                .insert("X-Test", http::HeaderValue::from_static("1"));
+            None
+        }
+    }
+
+    #[derive(Default)]
+    struct CountResponses(AtomicUsize);
+
+    impl Middleware for Arc<CountResponses> {
+        fn on_response(&self, request: &http::Request<()>, response: &mut http::Response<Bytes>) {
+            assert!(request.headers().contains_key("X-Test"));
+            assert!(response.status().is_success());
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    struct ShortCircuit;
+
+    impl Middleware for ShortCircuit {
+        fn on_request(&self, _: &mut http::Request<Bytes>) -> Option<http::Response<Bytes>> {
+            Some(http::Response::new(Bytes::from_static(
+                br#"{"ok":1,"shards":[]}"#,
+            )))
+        }
+    }
+
+    #[test]
+    fn modify_requests() {
+        let count = Arc::new(CountResponses::default());
+        let api = Api::new(require_header())
+            .with_middleware(count.clone())
+            .with_middleware(AddHeader);
+
+        let shards = futures::executor::block_on(api.shard_list()).unwrap();
+        assert!(shards.is_empty());
+        assert_eq!(count.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn skip_sending() {
+        let count = Arc::new(CountResponses::default());
+        let api = Api::new(require_header())
+            .with_middleware(ShortCircuit)
+            .with_middleware(count.clone());
+
+        let shards = futures::executor::block_on(api.shard_list()).unwrap();
+        assert!(shards.is_empty());
+        assert_eq!(count.0.load(Ordering::SeqCst), 0);
+    }
+}
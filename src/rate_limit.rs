@@ -0,0 +1,608 @@
+//! Client-side rate limiting.
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use http::header::HeaderMap;
+
+/// A number of requests allowed per period of time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RateLimit {
+    /// The number of requests allowed in each period.
+    pub requests: u32,
+    /// The length of the period.
+    pub per: Duration,
+}
+
+impl RateLimit {
+    /// Creates a limit of `requests` requests per `per`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests` is 0 or `per` is zero.
+    pub fn new(requests: u32, per: Duration) -> Self {
+        assert!(
+            requests > 0,
+            "expected rate limit to allow at least 1 request"
+        );
+        assert!(
+            per > Duration::from_secs(0),
+            "expected non-zero rate limit period"
+        );
+
+        RateLimit { requests, per }
+    }
+
+    /// Creates a limit of `requests` requests per minute.
+    pub fn per_minute(requests: u32) -> Self {
+        RateLimit::new(requests, Duration::from_secs(60))
+    }
+
+    /// Creates a limit of `requests` requests per hour.
+    pub fn per_hour(requests: u32) -> Self {
+        RateLimit::new(requests, Duration::from_secs(60 * 60))
+    }
+
+    /// Creates a limit of `requests` requests per day.
+    pub fn per_day(requests: u32) -> Self {
+        RateLimit::new(requests, Duration::from_secs(24 * 60 * 60))
+    }
+
+    /// Requests regained per second.
+    fn rate(&self) -> f64 {
+        f64::from(self.requests) / self.per.as_secs_f64()
+    }
+}
+
+/// The current state of one rate limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    /// The limit this budget is for.
+    pub limit: RateLimit,
+    /// The number of requests which can be made right now without waiting.
+    pub remaining: u32,
+    /// How long until the next request can be made: zero if `remaining` is not 0.
+    pub next_in: Duration,
+}
+
+/// The rate limit state the server reported in the `X-RateLimit-Limit`, `X-RateLimit-Remaining` and
+/// `X-RateLimit-Reset` headers of a response.
+///
+/// The official server sends these with every response to a rate limited endpoint. The latest state for each
+/// endpoint is available from `Api::rate_limit_info`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RateLimitInfo {
+    /// The number of requests allowed in the current period.
+    pub limit: u32,
+    /// The number of requests left in the current period.
+    pub remaining: u32,
+    /// When the current period ends, and `remaining` is reset to `limit`.
+    pub reset: SystemTime,
+}
+
+impl RateLimitInfo {
+    /// Reads the rate limit state from response headers, if all three headers are present and valid.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        fn header<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+            headers.get(name)?.to_str().ok()?.trim().parse().ok()
+        }
+
+        Some(RateLimitInfo {
+            limit: header(headers, "X-RateLimit-Limit")?,
+            remaining: header(headers, "X-RateLimit-Remaining")?,
+            reset: UNIX_EPOCH + Duration::from_secs(header(headers, "X-RateLimit-Reset")?),
+        })
+    }
+}
+
+/// The latest rate limit state reported for each endpoint, shared between clones of an `Api`.
+#[cfg(feature = "http")]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RateLimitLog {
+    endpoints: Arc<Mutex<HashMap<(http::Method, String), RateLimitInfo>>>,
+}
+
+#[cfg(feature = "http")]
+impl RateLimitLog {
+    pub(crate) fn record(&self, method: &http::Method, endpoint: &str, info: RateLimitInfo) {
+        self.endpoints
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((method.clone(), endpoint.to_owned()), info);
+    }
+
+    pub(crate) fn get(&self, method: &http::Method, endpoint: &str) -> Option<RateLimitInfo> {
+        self.endpoints
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&(method.clone(), endpoint.to_owned()))
+            .cloned()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Bucket {
+    limit: RateLimit,
+    /// Available requests. Negative when requests have been reserved ahead of time.
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Bucket {
+            limit,
+            tokens: f64::from(limit.requests),
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * self.limit.rate()).min(f64::from(self.limit.requests));
+        self.updated = now;
+    }
+
+    fn wait(&self) -> Duration {
+        self.wait_for(1)
+    }
+
+    /// How long until `count` requests can be made, if none are made in the meantime.
+    fn wait_for(&self, count: u32) -> Duration {
+        let count = f64::from(count);
+        if self.tokens >= count {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs_f64((count - self.tokens) / self.limit.rate())
+        }
+    }
+
+    fn budget(&mut self, now: Instant) -> Budget {
+        self.refill(now);
+
+        Budget {
+            limit: self.limit,
+            remaining: self.tokens.max(0.0) as u32,
+            next_in: self.wait(),
+        }
+    }
+}
+
+/// How urgently a request is sent, when it has to wait for a [`RateLimiter`].
+///
+/// Requests waiting for the same limit are sent in order of priority, and in the order they started waiting within
+/// each priority, so interactive requests such as console commands and memory writes can overtake bulk scans made
+/// with the same limiter. Set for all requests a client makes with `Api::set_priority`, or for a single request with
+/// `Request::with_priority`.
+///
+/// [`RateLimiter`]: struct.RateLimiter.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Priority {
+    /// Bulk requests, which are sent after any others waiting.
+    Background,
+    /// The priority of most requests.
+    #[default]
+    Normal,
+    /// Requests someone is waiting for, which are sent before any others waiting.
+    Interactive,
+}
+
+/// Requests waiting for their turn, in the order they are sent: by priority, then by when they started waiting.
+type Waiters = BTreeMap<(Reverse<Priority>, u64), (http::Method, String)>;
+
+#[derive(Debug, Default)]
+struct Limits {
+    global: Option<Bucket>,
+    endpoints: HashMap<(http::Method, String), Bucket>,
+    waiters: Waiters,
+    next_waiter: u64,
+}
+
+// waiting for a turn is only done by `RateLimiter::acquire`, with the HTTP client.
+#[cfg_attr(not(feature = "http"), allow(dead_code))]
+impl Limits {
+    fn enqueue(
+        &mut self,
+        priority: Priority,
+        method: &http::Method,
+        endpoint: &str,
+    ) -> (Reverse<Priority>, u64) {
+        let ticket = (Reverse(priority), self.next_waiter);
+        self.next_waiter += 1;
+        self.waiters
+            .insert(ticket, (method.clone(), endpoint.to_owned()));
+        ticket
+    }
+
+    /// Takes room for the waiting request if it's its turn, or returns how long to wait before trying again.
+    fn try_acquire(&mut self, ticket: (Reverse<Priority>, u64), now: Instant) -> Option<Duration> {
+        let Limits {
+            ref mut global,
+            ref mut endpoints,
+            ref mut waiters,
+            ..
+        } = *self;
+        let key = match waiters.get(&ticket) {
+            Some(key) => key.clone(),
+            None => return None,
+        };
+
+        if let Some(bucket) = global {
+            bucket.refill(now);
+        }
+        for (_, key) in waiters.range(..=ticket) {
+            if let Some(bucket) = endpoints.get_mut(key) {
+                bucket.refill(now);
+            }
+        }
+
+        // requests ahead go first: those sending to the same endpoint, and any others only waiting for the global
+        // limit.
+        let (mut ahead_global, mut ahead_endpoint) = (0, 0);
+        for (_, other) in waiters.range(..ticket) {
+            if *other == key {
+                ahead_global += 1;
+                ahead_endpoint += 1;
+            } else if endpoints
+                .get(other)
+                .is_none_or(|bucket| bucket.tokens >= 1.0)
+            {
+                ahead_global += 1;
+            }
+        }
+
+        let global_wait = global
+            .as_ref()
+            .map(|bucket| bucket.wait_for(1 + ahead_global));
+        let endpoint_wait = endpoints
+            .get(&key)
+            .map(|bucket| bucket.wait_for(1 + ahead_endpoint));
+        let wait = global_wait
+            .into_iter()
+            .chain(endpoint_wait)
+            .max()
+            .unwrap_or_default();
+        if wait > Duration::from_secs(0) {
+            return Some(wait);
+        }
+
+        waiters.remove(&ticket);
+        for bucket in global.iter_mut().chain(endpoints.get_mut(&key)) {
+            bucket.tokens -= 1.0;
+        }
+        None
+    }
+}
+
+/// Stops waiting for a turn when dropped, such as when the waiting request is abandoned.
+#[cfg(feature = "http")]
+struct Waiting<'a> {
+    limiter: &'a RateLimiter,
+    ticket: (Reverse<Priority>, u64),
+}
+
+#[cfg(feature = "http")]
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.limiter.lock().waiters.remove(&self.ticket);
+    }
+}
+
+/// A client-side rate limiter, with a global limit and per-endpoint limits.
+///
+/// When set on an [`Api`] with `Api::set_rate_limiter`, each request waits until it fits within every limit which
+/// applies to it before being sent, so bursts of calls are queued rather than rejected by the server with
+/// `429 Too Many Requests`. Waiting requests are sent in order of their [`Priority`]. Waiting uses `tokio::time`, so
+/// requests must be run on a tokio runtime.
+///
+/// Limits are applied per endpoint path, with the method: `GET` and `POST` requests to the same path have separate
+/// limits.
+///
+/// When cloned, the clone will share the same underlying limits.
+///
+/// ```
+/// use screeps_api::rate_limit::{RateLimit, RateLimiter};
+///
+/// let limiter = RateLimiter::new()
+///     .with_global_limit(RateLimit::per_minute(30))
+///     .with_endpoint_limit(http::Method::GET, "game/room-terrain", RateLimit::per_hour(360));
+///
+/// assert_eq!(limiter.global_budget().unwrap().remaining, 30);
+/// ```
+///
+/// [`Api`]: ../struct.Api.html
+/// [`Priority`]: enum.Priority.html
+#[derive(Clone, Debug, Default)]
+pub struct RateLimiter {
+    limits: Arc<Mutex<Limits>>,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter without any limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a rate limiter with the [limits documented for the official server][docs].
+    ///
+    /// [docs]: https://docs.screeps.com/auth-tokens.html#Rate-Limiting
+    pub fn official() -> Self {
+        use http::Method;
+
+        RateLimiter::new()
+            .with_global_limit(RateLimit::per_minute(120))
+            .with_endpoint_limit(Method::POST, "game/map-stats", RateLimit::per_hour(60))
+            .with_endpoint_limit(Method::GET, "game/room-terrain", RateLimit::per_hour(360))
+            .with_endpoint_limit(Method::GET, "user/code", RateLimit::per_hour(60))
+            .with_endpoint_limit(Method::POST, "user/code", RateLimit::per_day(240))
+            .with_endpoint_limit(
+                Method::POST,
+                "user/set-active-branch",
+                RateLimit::per_day(240),
+            )
+            .with_endpoint_limit(Method::GET, "user/memory", RateLimit::per_day(1440))
+            .with_endpoint_limit(Method::POST, "user/memory", RateLimit::per_day(240))
+            .with_endpoint_limit(Method::GET, "user/memory-segment", RateLimit::per_hour(360))
+            .with_endpoint_limit(Method::POST, "user/memory-segment", RateLimit::per_hour(60))
+            .with_endpoint_limit(Method::POST, "user/console", RateLimit::per_hour(360))
+            .with_endpoint_limit(
+                Method::GET,
+                "game/market/orders-index",
+                RateLimit::per_hour(60),
+            )
+            .with_endpoint_limit(Method::GET, "game/market/orders", RateLimit::per_hour(60))
+            .with_endpoint_limit(
+                Method::GET,
+                "game/market/my-orders",
+                RateLimit::per_hour(60),
+            )
+            .with_endpoint_limit(Method::GET, "game/market/stats", RateLimit::per_hour(60))
+            .with_endpoint_limit(Method::GET, "user/money-history", RateLimit::per_hour(60))
+    }
+
+    /// Sets the limit applied to all requests, and returns the limiter.
+    pub fn with_global_limit(self, limit: RateLimit) -> Self {
+        self.lock().global = Some(Bucket::new(limit, Instant::now()));
+        self
+    }
+
+    /// Sets the limit applied to requests with the given method to the given endpoint, and returns the limiter.
+    ///
+    /// The endpoint is the path relative to the API url, such as `"game/room-terrain"`.
+    pub fn with_endpoint_limit(
+        self,
+        method: http::Method,
+        endpoint: &str,
+        limit: RateLimit,
+    ) -> Self {
+        self.lock().endpoints.insert(
+            (method, endpoint.to_owned()),
+            Bucket::new(limit, Instant::now()),
+        );
+        self
+    }
+
+    /// Gets the current state of the global limit, if there is one.
+    pub fn global_budget(&self) -> Option<Budget> {
+        let now = Instant::now();
+        self.lock().global.as_mut().map(|bucket| bucket.budget(now))
+    }
+
+    /// Gets the current state of the limit for the given method and endpoint, if there is one.
+    pub fn endpoint_budget(&self, method: &http::Method, endpoint: &str) -> Option<Budget> {
+        let now = Instant::now();
+        self.lock()
+            .endpoints
+            .get_mut(&(method.clone(), endpoint.to_owned()))
+            .map(|bucket| bucket.budget(now))
+    }
+
+    /// Reserves room for one request with the given method to the given endpoint, and returns how long to wait
+    /// before sending it.
+    ///
+    /// Each call counts as a request, whether or not it has to wait: requests reserved while the limit is exhausted
+    /// are given successive slots.
+    pub fn reserve(&self, method: &http::Method, endpoint: &str) -> Duration {
+        self.reserve_at(method, endpoint, Instant::now())
+    }
+
+    fn reserve_at(&self, method: &http::Method, endpoint: &str, now: Instant) -> Duration {
+        let mut limits = self.lock();
+        let Limits {
+            ref mut global,
+            ref mut endpoints,
+            ..
+        } = *limits;
+
+        let buckets = global
+            .iter_mut()
+            .chain(endpoints.get_mut(&(method.clone(), endpoint.to_owned())));
+
+        let mut wait = Duration::from_secs(0);
+        for bucket in buckets {
+            bucket.refill(now);
+            wait = wait.max(bucket.wait());
+            bucket.tokens -= 1.0;
+        }
+
+        wait
+    }
+
+    /// Waits until a request with the given method to the given endpoint and priority can be sent, and takes room
+    /// for it.
+    ///
+    /// Unlike [`RateLimiter::reserve`], requests reserve room only once it's their turn, so requests of higher priority
+    /// can overtake ones already waiting. This is what requests made through an `Api` using this limiter wait on.
+    /// Waiting uses `tokio::time`, so this must be run on a tokio runtime.
+    ///
+    /// [`RateLimiter::reserve`]: struct.RateLimiter.html#method.reserve
+    #[cfg(feature = "http")]
+    pub async fn acquire(&self, method: &http::Method, endpoint: &str, priority: Priority) {
+        let ticket = self.lock().enqueue(priority, method, endpoint);
+        let _waiting = Waiting {
+            limiter: self,
+            ticket,
+        };
+
+        loop {
+            let wait = self.lock().try_acquire(ticket, Instant::now());
+            match wait {
+                Some(wait) => {
+                    debug!(
+                        "waiting {:?} for rate limit before requesting {} {}",
+                        wait, method, endpoint
+                    );
+                    tokio::time::sleep(wait).await;
+                }
+                None => return,
+            }
+        }
+    }
+
+    /// Updates the limit for the given method and endpoint with the state the server reported, if there is a limit
+    /// for it.
+    ///
+    /// Requests the server has already counted, such as ones made by other programs using the same account, are
+    /// taken out of the budget, so following requests wait for them too. This is done automatically for responses
+    /// to requests made through an `Api` using this limiter.
+    pub fn observe(&self, method: &http::Method, endpoint: &str, info: &RateLimitInfo) {
+        self.observe_at(method, endpoint, info, Instant::now())
+    }
+
+    fn observe_at(
+        &self,
+        method: &http::Method,
+        endpoint: &str,
+        info: &RateLimitInfo,
+        now: Instant,
+    ) {
+        let mut limits = self.lock();
+        if let Some(bucket) = limits
+            .endpoints
+            .get_mut(&(method.clone(), endpoint.to_owned()))
+        {
+            bucket.refill(now);
+            bucket.tokens = bucket.tokens.min(f64::from(info.remaining));
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Limits> {
+        self.limits.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant, UNIX_EPOCH};
+
+    use http::Method;
+
+    use http::header::{HeaderMap, HeaderValue};
+
+    use super::{Priority, RateLimit, RateLimitInfo, RateLimiter};
+
+    fn secs(duration: Duration) -> u64 {
+        duration.as_secs_f64().round() as u64
+    }
+
+    #[test]
+    fn queue_requests_over_limit() {
+        let limiter = RateLimiter::new()
+            .with_global_limit(RateLimit::per_minute(2))
+            .with_endpoint_limit(Method::GET, "game/room-terrain", RateLimit::per_hour(60));
+        let now = Instant::now();
+
+        assert_eq!(
+            limiter.reserve_at(&Method::GET, "auth/me", now),
+            Duration::from_secs(0)
+        );
+        assert_eq!(
+            limiter.reserve_at(&Method::GET, "game/room-terrain", now),
+            Duration::from_secs(0)
+        );
+        assert_eq!(limiter.global_budget().unwrap().remaining, 0);
+        assert_eq!(
+            limiter
+                .endpoint_budget(&Method::GET, "game/room-terrain")
+                .unwrap()
+                .remaining,
+            59
+        );
+
+        // the global limit regains one request every 30 seconds, and waiting requests queue up behind each other.
+        assert_eq!(secs(limiter.reserve_at(&Method::GET, "auth/me", now)), 30);
+        assert_eq!(secs(limiter.reserve_at(&Method::GET, "auth/me", now)), 60);
+
+        // the endpoint limit does not apply to other methods.
+        assert!(limiter
+            .endpoint_budget(&Method::POST, "game/room-terrain")
+            .is_none());
+    }
+
+    #[test]
+    fn send_by_priority() {
+        let limiter = RateLimiter::new().with_global_limit(RateLimit::per_minute(1));
+        let now = Instant::now();
+        let mut limits = limiter.lock();
+
+        let scan = limits.enqueue(Priority::Background, &Method::GET, "game/room-terrain");
+        let console = limits.enqueue(Priority::Interactive, &Method::POST, "user/console");
+        let write = limits.enqueue(Priority::Interactive, &Method::POST, "user/memory-segment");
+
+        // the interactive requests go first, in the order they started waiting.
+        assert_eq!(secs(limits.try_acquire(scan, now).unwrap()), 120);
+        assert_eq!(limits.try_acquire(console, now), None);
+        assert_eq!(secs(limits.try_acquire(write, now).unwrap()), 60);
+        assert_eq!(secs(limits.try_acquire(scan, now).unwrap()), 120);
+
+        let later = now + Duration::from_secs(60);
+        assert_eq!(secs(limits.try_acquire(scan, later).unwrap()), 60);
+        assert_eq!(limits.try_acquire(write, later), None);
+        assert_eq!(
+            limits.try_acquire(scan, later + Duration::from_secs(60)),
+            None
+        );
+        assert!(limits.waiters.is_empty());
+    }
+
+    #[test]
+    fn observe_reported_limits() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-RateLimit-Limit", HeaderValue::from_static("60"));
+        headers.insert("X-RateLimit-Remaining", HeaderValue::from_static("5"));
+        headers.insert("X-RateLimit-Reset", HeaderValue::from_static("1500000000"));
+
+        let info = RateLimitInfo::from_headers(&headers).unwrap();
+        assert_eq!(
+            info,
+            RateLimitInfo {
+                limit: 60,
+                remaining: 5,
+                reset: UNIX_EPOCH + Duration::from_secs(1_500_000_000),
+            }
+        );
+
+        headers.remove("X-RateLimit-Remaining");
+        assert_eq!(RateLimitInfo::from_headers(&headers), None);
+
+        let limiter = RateLimiter::new().with_endpoint_limit(
+            Method::GET,
+            "user/code",
+            RateLimit::per_hour(60),
+        );
+        let now = Instant::now();
+        limiter.observe_at(&Method::GET, "user/code", &info, now);
+
+        for _ in 0..5 {
+            assert_eq!(
+                limiter.reserve_at(&Method::GET, "user/code", now),
+                Duration::from_secs(0)
+            );
+        }
+        assert_eq!(secs(limiter.reserve_at(&Method::GET, "user/code", now)), 60);
+    }
+}
@@ -0,0 +1,128 @@
+//! Hiding auth tokens and passwords from logs, debug output and errors.
+use std::fmt;
+
+use bytes::Bytes;
+#[cfg(feature = "http")]
+use http::header::{HeaderMap, HeaderName};
+
+/// What hidden values are replaced with.
+pub(crate) const REDACTED: &str = "<redacted>";
+
+/// Headers which carry credentials.
+#[cfg(feature = "http")]
+const HEADERS: &[&str] = &[
+    "x-token",
+    "x-username",
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+];
+
+/// JSON object fields which carry credentials.
+const FIELDS: &[&str] = &["token", "password"];
+
+/// Whether the header carries credentials.
+#[cfg(feature = "http")]
+pub(crate) fn is_sensitive_header(name: &HeaderName) -> bool {
+    HEADERS.contains(&name.as_str())
+}
+
+/// Formats headers, hiding the values of any which carry credentials.
+#[cfg(feature = "http")]
+pub(crate) struct Headers<'a>(pub &'a HeaderMap);
+
+#[cfg(feature = "http")]
+impl fmt::Debug for Headers<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(name, value)| {
+                let value: &dyn fmt::Debug = if is_sensitive_header(name) {
+                    &REDACTED
+                } else {
+                    value
+                };
+                (name, value)
+            }))
+            .finish()
+    }
+}
+
+/// Hides the values of any fields carrying credentials in the JSON value, at any depth.
+///
+/// Returns whether anything was hidden.
+pub(crate) fn json(value: &mut serde_json::Value) -> bool {
+    match *value {
+        serde_json::Value::Object(ref mut map) => {
+            let mut redacted = false;
+            for (key, value) in map.iter_mut() {
+                if FIELDS.contains(&key.as_str()) && !value.is_null() {
+                    *value = serde_json::Value::String(REDACTED.to_owned());
+                    redacted = true;
+                } else {
+                    redacted |= json(value);
+                }
+            }
+            redacted
+        }
+        serde_json::Value::Array(ref mut values) => values
+            .iter_mut()
+            .fold(false, |redacted, value| json(value) | redacted),
+        _ => false,
+    }
+}
+
+/// Hides the values of any fields carrying credentials in a body, if it is JSON.
+pub(crate) fn body(body: Bytes) -> Bytes {
+    match serde_json::from_slice::<serde_json::Value>(&body) {
+        Ok(mut value) => {
+            if json(&mut value) {
+                value.to_string().into()
+            } else {
+                body
+            }
+        }
+        Err(_) => body,
+    }
+}
+
+/// Formats a body as text, hiding the values of any fields carrying credentials.
+pub(crate) struct Body<'a>(pub &'a Bytes);
+
+impl fmt::Debug for Body<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let body = body(self.0.clone());
+        match std::str::from_utf8(&body) {
+            Ok(text) => text.fmt(f),
+            Err(_) => body.fmt(f),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use http::header::{HeaderMap, HeaderValue};
+
+    use super::{body, Headers};
+
+    #[test]
+    fn hide_credentials() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Token", HeaderValue::from_static("secret"));
+        headers.insert("Content-Type", HeaderValue::from_static("application/json"));
+        let formatted = format!("{:?}", Headers(&headers));
+        assert!(!formatted.contains("secret"));
+        assert!(formatted.contains("application/json"));
+
+        let login = body(r#"{"email":"user","password":"secret"}"#.into());
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&login).unwrap(),
+            json!({ "email": "user", "password": "<redacted>" })
+        );
+
+        let nested = body(r#"{"ok":1,"users":[{"token":"secret"}]}"#.into());
+        assert!(!String::from_utf8_lossy(&nested).contains("secret"));
+
+        assert_eq!(body("not json".into()), "not json");
+    }
+}
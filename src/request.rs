@@ -0,0 +1,1305 @@
+//! Transport-agnostic descriptions of API requests.
+//!
+//! Each [`Api`] method is built from a function in this module which describes the request to make: its method,
+//! endpoint path, query parameters and body. The description can be turned into an `http::Request` for any HTTP
+//! client, and the raw response parsed with [`ParseResponse`], without going through [`Api`] at all:
+//!
+//! ```
+//! use screeps_api::request;
+//!
+//! let base_url = url::Url::parse("https://screeps.com/api/").unwrap();
+//! let request = request::shard_list();
+//!
+//! let http_request = request.to_http(&base_url, None);
+//! assert_eq!(http_request.uri(), "https://screeps.com/api/game/shards/info");
+//!
+//! // ... send `http_request` with any HTTP client, then parse the response:
+//! let body = br#"{"ok":1,"shards":[]}"#;
+//! let shards = request.parse_response(http::StatusCode::OK, body).unwrap();
+//! assert!(shards.is_empty());
+//! ```
+//!
+//! [`Api`]: ../struct.Api.html
+//! [`ParseResponse`]: trait.ParseResponse.html
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use http::header::{HeaderValue, CONTENT_TYPE};
+use url::Url;
+
+use crate::{
+    data::{ResourceType, ShardRoomName, UserInfo},
+    endpoints::*,
+    error::Error,
+    rate_limit::Priority,
+    redact,
+    server::ServerProfile,
+    Token,
+};
+
+/// How an auth token is sent with authenticated requests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum AuthMode {
+    /// Chooses based on the server: `Token` for the official servers at `screeps.com`, and `TokenAndUsername` for
+    /// any other server. See `ServerProfile::auth_mode`.
+    #[default]
+    Auto,
+    /// Sends the token in the `X-Token` header, as the official server expects.
+    Token,
+    /// Sends the token in both the `X-Token` and `X-Username` headers, as private servers expect.
+    TokenAndUsername,
+}
+
+impl AuthMode {
+    /// Resolves `Auto` to the mode used for the server with the given base URL. Other modes are returned as is.
+    pub fn resolve(self, base_url: &Url) -> AuthMode {
+        match self {
+            AuthMode::Auto => ServerProfile::detect(base_url).auth_mode(),
+            other => other,
+        }
+    }
+}
+
+/// Paths to send requests for some endpoints to, instead of their usual paths.
+///
+/// Private servers with mods, and servers behind proxies with a different API prefix, sometimes serve an endpoint at
+/// another path. Paths are relative to the client's base URL, like endpoints, and endpoints are named by their usual
+/// path, as returned by [`Request::endpoint`]. Set on a client with `Api::set_endpoint_path`.
+///
+/// ```
+/// use screeps_api::request::{self, EndpointPaths};
+///
+/// let base_url = url::Url::parse("http://localhost:21025/api/").unwrap();
+/// let paths = EndpointPaths::new().with_path("game/shards/info", "mod/shards")?;
+///
+/// let request = request::shard_list();
+/// assert_eq!(
+///     request.url_with_paths(&base_url, &paths).as_str(),
+///     "http://localhost:21025/api/mod/shards"
+/// );
+/// # Ok::<(), url::ParseError>(())
+/// ```
+///
+/// [`Request::endpoint`]: struct.Request.html#method.endpoint
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EndpointPaths {
+    paths: Arc<HashMap<String, String>>,
+}
+
+impl EndpointPaths {
+    /// Creates a set of paths which overrides no endpoints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends requests for the endpoint to the given path, replacing any previous path for it.
+    ///
+    /// Fails if the path can never be joined to a base URL, such as a scheme-relative path with an invalid host.
+    pub fn set_path<E, P>(&mut self, endpoint: E, path: P) -> Result<(), url::ParseError>
+    where
+        E: Into<String>,
+        P: Into<String>,
+    {
+        let path = path.into();
+        Url::parse("http://localhost/")
+            .expect("expected pre-set url to parse, parsing failed")
+            .join(&path)?;
+
+        Arc::make_mut(&mut self.paths).insert(endpoint.into(), path);
+        Ok(())
+    }
+
+    /// Sends requests for the endpoint to the given path, and returns the paths. See [`EndpointPaths::set_path`].
+    ///
+    /// [`EndpointPaths::set_path`]: struct.EndpointPaths.html#method.set_path
+    pub fn with_path<E, P>(mut self, endpoint: E, path: P) -> Result<Self, url::ParseError>
+    where
+        E: Into<String>,
+        P: Into<String>,
+    {
+        self.set_path(endpoint, path)?;
+        Ok(self)
+    }
+
+    /// Sends requests for the endpoint to its usual path again.
+    pub fn remove_path(&mut self, endpoint: &str) {
+        if self.paths.contains_key(endpoint) {
+            Arc::make_mut(&mut self.paths).remove(endpoint);
+        }
+    }
+
+    /// Gets the path requests for the endpoint are sent to: the path set for it, or the endpoint itself.
+    pub fn path<'a>(&'a self, endpoint: &'a str) -> &'a str {
+        self.paths.get(endpoint).map_or(endpoint, String::as_str)
+    }
+
+    /// Whether no endpoints are overridden.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+/// A function told about responses with fields the result type does not parse.
+pub(crate) type WarningHook = Arc<dyn Fn(&ParseWarning) + Send + Sync>;
+
+/// The JSON data of the latest response from each endpoint.
+type RawJsonLog = Arc<Mutex<HashMap<&'static str, serde_json::Value>>>;
+
+/// Settings for how responses are parsed.
+///
+/// Set on a client with `Api::set_strict_parsing` and `Api::set_parse_warning_hook`, or given to
+/// [`Request::parse_response_with`] when parsing responses by hand.
+///
+/// [`Request::parse_response_with`]: struct.Request.html#method.parse_response_with
+#[derive(Clone, Default)]
+pub struct ParseOptions {
+    pub(crate) strict: bool,
+    pub(crate) fast: bool,
+    pub(crate) blocking_threshold: Option<usize>,
+    pub(crate) warning_hook: Option<WarningHook>,
+    /// Where the JSON data of responses is kept, if it is.
+    pub(crate) raw_json: Option<RawJsonLog>,
+    /// The endpoint and URL of the request the parsed response answers, if known.
+    source: Option<(&'static str, Url)>,
+}
+
+impl fmt::Debug for ParseOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParseOptions")
+            .field("strict", &self.strict)
+            .field("fast", &self.fast)
+            .field("blocking_threshold", &self.blocking_threshold)
+            .field("warning_hook", &self.warning_hook.is_some())
+            .field("raw_json", &self.raw_json.is_some())
+            .finish()
+    }
+}
+
+impl ParseOptions {
+    /// Creates the default settings, which parse responses leniently.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether responses with fields the result type does not parse fail to parse, and returns the settings.
+    ///
+    /// By default, such fields are only logged as a warning. Failing instead, with `ErrorKind::UnknownFields`, lets
+    /// changes to the server's API be caught by tests.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Whether responses with fields the result type does not parse fail to parse.
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Sets whether successful responses are parsed straight into the result type, and returns the settings.
+    ///
+    /// By default, responses are first parsed into a `serde_json::Value`, and then into the result type while
+    /// tracking fields which are not parsed. Skipping both is faster for large responses polled often, but fields
+    /// which are not parsed are neither logged nor reported. Responses which fail to parse are parsed again the usual
+    /// way, to describe the error. This has no effect when parsing strictly.
+    pub fn with_fast(mut self, fast: bool) -> Self {
+        self.fast = fast;
+        self
+    }
+
+    /// Whether successful responses are parsed straight into the result type.
+    pub fn fast(&self) -> bool {
+        self.fast
+    }
+
+    /// Sets the size in bytes from which response bodies are parsed on tokio's blocking thread pool, or `None` to
+    /// always parse them where they are received, and returns the settings.
+    ///
+    /// Parsing multi-megabyte responses, such as room objects or room history, can otherwise stall other tasks on
+    /// the same runtime for a noticeable time. This only applies to requests made through an `Api`, which must then
+    /// be run on a tokio runtime, and has no effect in browsers. By default, responses are never moved to the pool.
+    pub fn with_blocking_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.blocking_threshold = threshold;
+        self
+    }
+
+    /// The size in bytes from which response bodies are parsed on tokio's blocking thread pool, if any.
+    pub fn blocking_threshold(&self) -> Option<usize> {
+        self.blocking_threshold
+    }
+
+    /// Sets the function told about responses with fields the result type does not parse, instead of logging them as
+    /// a warning, and returns the settings.
+    ///
+    /// This lets applications collect changes to the server's API into their own telemetry. The function is not
+    /// called when parsing strictly, as such responses fail instead.
+    pub fn with_warning_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&ParseWarning) + Send + Sync + 'static,
+    {
+        self.warning_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets whether the JSON data of the latest response from each endpoint is kept, and returns the settings.
+    ///
+    /// This is meant for debugging, and for reading fields the result types don't expose yet without making the
+    /// request again: see [`ParseOptions::raw_json`]. Responses are still only parsed into a `serde_json::Value` once,
+    /// but are then never parsed straight into the result type. Only responses to requests made through an `Api`
+    /// are kept, as the endpoint of responses parsed by hand is not known.
+    ///
+    /// [`ParseOptions::raw_json`]: struct.ParseOptions.html#method.raw_json
+    pub fn with_raw_json(mut self, retain: bool) -> Self {
+        self.raw_json = if retain { Some(Arc::default()) } else { None };
+        self
+    }
+
+    /// Gets the JSON data of the latest response from the given endpoint, if it was kept.
+    ///
+    /// The endpoint is the path relative to the API url, such as `"game/shards/info"`. Any auth tokens or passwords
+    /// are hidden.
+    pub fn raw_json(&self, endpoint: &str) -> Option<serde_json::Value> {
+        let log = self.raw_json.as_ref()?;
+        let log = log.lock().unwrap_or_else(PoisonError::into_inner);
+        log.get(endpoint).cloned()
+    }
+
+    /// Keeps the JSON data of a response, if enabled and its endpoint is known.
+    pub(crate) fn keep_raw_json(&self, mut json: serde_json::Value) {
+        if let (Some(log), Some((endpoint, _))) = (&self.raw_json, &self.source) {
+            redact::json(&mut json);
+            let mut log = log.lock().unwrap_or_else(PoisonError::into_inner);
+            log.insert(endpoint, json);
+        }
+    }
+
+    /// Reports the given fields the result type does not parse to the warning hook, or logs them as a warning.
+    pub(crate) fn warn(&self, result_type: &'static str, unknown_fields: Vec<String>, body: &[u8]) {
+        match self.warning_hook {
+            Some(ref hook) => hook(&ParseWarning {
+                endpoint: self.source.as_ref().map(|&(endpoint, _)| endpoint),
+                url: self.source.as_ref().map(|(_, url)| url.clone()),
+                result_type,
+                unknown_fields,
+            }),
+            None => {
+                let mut data = serde_json::from_slice(body).unwrap_or(serde_json::Value::Null);
+                redact::json(&mut data);
+                warn!(
+                    "screeps API lib didn't parse some data retrieved for {}\n\
+                     full data: {}\n\
+                     unparsed fields: {:#?}",
+                    result_type,
+                    serde_json::to_string_pretty(&data).unwrap(),
+                    unknown_fields
+                );
+            }
+        }
+    }
+
+    /// These settings, for parsing the response to a request to the given endpoint and URL.
+    #[cfg(feature = "http")]
+    pub(crate) fn for_request(&self, endpoint: &'static str, url: &Url) -> Self {
+        ParseOptions {
+            source: Some((endpoint, url.clone())),
+            ..self.clone()
+        }
+    }
+}
+
+/// A response which had fields the result type does not parse. See `ParseOptions::with_warning_hook`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ParseWarning {
+    /// The endpoint the response came from, relative to the API url, if known.
+    pub endpoint: Option<&'static str>,
+    /// The whole URL the response came from, if known.
+    pub url: Option<Url>,
+    /// The name of the type the response was parsed as.
+    pub result_type: &'static str,
+    /// The paths of the fields which were not parsed, such as `"shards[0].extra"`.
+    pub unknown_fields: Vec<String>,
+}
+
+/// A result type which can be parsed from a raw API response.
+///
+/// This is implemented for the result type of every endpoint.
+pub trait ParseResponse: Sized + Send + 'static {
+    /// Parses a response with the given HTTP status and body.
+    ///
+    /// Non-success status codes result in an error containing the response body.
+    fn parse_response(status: http::StatusCode, body: &[u8]) -> Result<Self, Error>;
+
+    /// Parses a response with the given HTTP status and body, using the given settings.
+    ///
+    /// The body is shared rather than copied by errors returned, and when parsing the results of this crate's
+    /// endpoints, large strings such as memory segments and encoded terrain are borrowed from it while parsing rather
+    /// than copied into intermediate JSON data. By default, the settings are ignored.
+    fn parse_response_with(
+        status: http::StatusCode,
+        body: &Bytes,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
+        let _ = options;
+        Self::parse_response(status, body)
+    }
+}
+
+/// The arguments of an endpoint which takes more than a few simple parameters, which describe the request to make.
+///
+/// This is implemented for `LoginArgs`, `RegistrationArgs`, `MapStatsArgs`, `RecentPvpArgs` and
+/// `SetMemorySegmentArgs`. Other endpoints are described by the functions in this module. Either way, the described
+/// request's method, path, query and body can be sent with any HTTP client, and the response parsed with this
+/// crate's result types:
+///
+/// ```
+/// use screeps_api::{request::Endpoint, RecentPvpArgs};
+///
+/// let request = RecentPvpArgs::within(100).describe();
+/// assert_eq!(*request.method(), http::Method::GET);
+/// assert_eq!(request.endpoint(), "experimental/pvp");
+/// assert_eq!(request.query(), &[("interval", "100".to_owned())]);
+///
+/// let body = br#"{"ok":1,"pvp":{}}"#;
+/// let pvp = request.parse_response(http::StatusCode::OK, body).unwrap();
+/// assert!(pvp.shards.is_empty());
+/// ```
+pub trait Endpoint {
+    /// The type the response to the request parses into.
+    type Result: ParseResponse;
+
+    /// Describes the request to make with these arguments.
+    fn describe(&self) -> Request<Self::Result>;
+}
+
+/// A description of a single API request, independent of the HTTP client used to send it.
+///
+/// `R` is the type the response to this request parses into.
+pub struct Request<R> {
+    method: http::Method,
+    endpoint: &'static str,
+    query: Vec<(&'static str, String)>,
+    body: Option<Bytes>,
+    auth: bool,
+    timeout: Option<Duration>,
+    priority: Option<Priority>,
+    _phantom: PhantomData<fn() -> R>,
+}
+
+impl<R> Clone for Request<R> {
+    fn clone(&self) -> Self {
+        Request {
+            method: self.method.clone(),
+            endpoint: self.endpoint,
+            query: self.query.clone(),
+            body: self.body.clone(),
+            auth: self.auth,
+            timeout: self.timeout,
+            priority: self.priority,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<R> fmt::Debug for Request<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Request")
+            .field("method", &self.method)
+            .field("endpoint", &self.endpoint)
+            .field("query", &self.query)
+            .field("body", &self.body.as_ref().map(redact::Body))
+            .field("auth", &self.auth)
+            .field("timeout", &self.timeout)
+            .field("priority", &self.priority)
+            .finish()
+    }
+}
+
+impl<R> Request<R> {
+    /// Creates a GET request to the given endpoint.
+    pub(crate) fn get(endpoint: &'static str) -> Self {
+        Request {
+            method: http::Method::GET,
+            endpoint,
+            query: Vec::new(),
+            body: None,
+            auth: false,
+            timeout: None,
+            priority: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Creates a POST request to the given endpoint, with the given data encoded as JSON in the body.
+    pub(crate) fn post<S: serde::Serialize>(endpoint: &'static str, body: S) -> Self {
+        let body = serde_json::to_vec(&body)
+            .expect("expected serde_json::to_vec to unfailingly succeed, but it failed.");
+
+        Request {
+            method: http::Method::POST,
+            endpoint,
+            query: Vec::new(),
+            body: Some(body.into()),
+            auth: false,
+            timeout: None,
+            priority: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Adds a query parameter.
+    pub(crate) fn param<V: Into<String>>(mut self, key: &'static str, value: V) -> Self {
+        self.query.push((key, value.into()));
+        self
+    }
+
+    /// Marks this request as requiring authentication.
+    pub(crate) fn auth(mut self) -> Self {
+        self.auth = true;
+        self
+    }
+
+    /// Sets how long to wait for the server to respond to this request, overriding the client's timeout, and
+    /// returns the request.
+    ///
+    /// Each attempt of a retried request gets the full timeout. Requests which time out fail with
+    /// `ErrorKind::Timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// How long to wait for the server to respond to this request, if set with [`Request::with_timeout`].
+    ///
+    /// [`Request::with_timeout`]: struct.Request.html#method.with_timeout
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Sets how urgently this request is sent when it has to wait for the client's rate limiter, overriding the
+    /// client's priority, and returns the request.
+    ///
+    /// Memory writes are `Priority::Interactive`, and map stats, which are usually requested in bulk, are
+    /// `Priority::Background` by default. See [`Priority`].
+    ///
+    /// [`Priority`]: ../rate_limit/enum.Priority.html
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// How urgently this request is sent, if set with [`Request::with_priority`].
+    ///
+    /// [`Request::with_priority`]: struct.Request.html#method.with_priority
+    pub fn priority(&self) -> Option<Priority> {
+        self.priority
+    }
+
+    /// The HTTP method of this request.
+    pub fn method(&self) -> &http::Method {
+        &self.method
+    }
+
+    /// The endpoint path, relative to the API's base URL.
+    pub fn endpoint(&self) -> &'static str {
+        self.endpoint
+    }
+
+    /// The query parameters of this request.
+    pub fn query(&self) -> &[(&'static str, String)] {
+        &self.query
+    }
+
+    /// The JSON body of this request, if it is a POST request.
+    pub fn body(&self) -> Option<&Bytes> {
+        self.body.as_ref()
+    }
+
+    /// Whether this request must be sent with an auth token.
+    pub fn requires_auth(&self) -> bool {
+        self.auth
+    }
+
+    /// Gets the full URL of this request for an API with the given base URL.
+    pub fn url(&self, base_url: &Url) -> Url {
+        self.url_with_paths(base_url, &EndpointPaths::default())
+    }
+
+    /// Gets the full URL of this request for an API with the given base URL, where some endpoints are at other
+    /// paths.
+    pub fn url_with_paths(&self, base_url: &Url, paths: &EndpointPaths) -> Url {
+        let mut url = base_url
+            .join(paths.path(self.endpoint))
+            .expect("expected pre-set endpoint url text to succeed, but it failed.");
+
+        if !self.query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&self.query).finish();
+        }
+
+        url
+    }
+
+    /// Builds an HTTP request for an API with the given base URL, sending the given token, if any.
+    ///
+    /// The token is sent whenever one is given, even if this request does not require authentication. It is sent
+    /// in the headers `AuthMode::Auto` chooses for the server; use [`Request::to_http_with_auth`] to choose them.
+    ///
+    /// [`Request::to_http_with_auth`]: struct.Request.html#method.to_http_with_auth
+    pub fn to_http(&self, base_url: &Url, token: Option<&Token>) -> http::Request<Bytes> {
+        self.to_http_with_auth(base_url, token, AuthMode::Auto)
+    }
+
+    /// Builds an HTTP request for an API with the given base URL, sending the given token, if any, as the given
+    /// auth mode specifies.
+    pub fn to_http_with_auth(
+        &self,
+        base_url: &Url,
+        token: Option<&Token>,
+        mode: AuthMode,
+    ) -> http::Request<Bytes> {
+        self.to_http_at(&self.url(base_url), base_url, token, mode)
+    }
+
+    /// Builds an HTTP request to the given URL of this request, for an API with the given base URL.
+    pub(crate) fn to_http_at(
+        &self,
+        url: &Url,
+        base_url: &Url,
+        token: Option<&Token>,
+        mode: AuthMode,
+    ) -> http::Request<Bytes> {
+        let mut request = http::Request::builder()
+            .method(self.method.clone())
+            .uri(url.as_str())
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        if let Some(token) = token {
+            let token = HeaderValue::from_maybe_shared(token.clone())
+                .expect("tokens should always be valid headers");
+            if mode.resolve(base_url) == AuthMode::TokenAndUsername {
+                request = request.header("X-Username", token.clone());
+            }
+            request = request.header("X-Token", token);
+        }
+
+        request
+            .body(self.body.clone().unwrap_or_default())
+            .expect("building http request should never fail")
+    }
+}
+
+impl<R: ParseResponse> Request<R> {
+    /// Parses a response to this request with the given HTTP status and body.
+    ///
+    /// This is the same as `R::parse_response`.
+    pub fn parse_response(&self, status: http::StatusCode, body: &[u8]) -> Result<R, Error> {
+        R::parse_response(status, body)
+    }
+
+    /// Parses a response to this request with the given HTTP status and body, using the given settings.
+    ///
+    /// This is the same as `R::parse_response_with`.
+    pub fn parse_response_with(
+        &self,
+        status: http::StatusCode,
+        body: &Bytes,
+        options: &ParseOptions,
+    ) -> Result<R, Error> {
+        R::parse_response_with(status, body, options)
+    }
+}
+
+/// Describes a server version request. See [`Api::version`](../struct.Api.html#method.version).
+pub fn version() -> Request<ServerVersion> {
+    Request::get("version")
+}
+
+/// Describes a login request. See [`Api::login`](../struct.Api.html#method.login).
+pub fn login<'b, U, V>(username: U, password: V) -> Request<LoggedIn>
+where
+    U: Into<Cow<'b, str>>,
+    V: Into<Cow<'b, str>>,
+{
+    LoginArgs::new(username, password).describe()
+}
+
+impl Endpoint for LoginArgs<'_> {
+    type Result = LoggedIn;
+
+    fn describe(&self) -> Request<LoggedIn> {
+        Request::post("auth/signin", self)
+    }
+}
+
+/// Describes a registration request. See [`Api::register`](../struct.Api.html#method.register).
+pub fn register(details: RegistrationArgs) -> Request<RegistrationSuccess> {
+    details.describe()
+}
+
+impl Endpoint for RegistrationArgs<'_> {
+    type Result = RegistrationSuccess;
+
+    fn describe(&self) -> Request<RegistrationSuccess> {
+        Request::post("register/submit", self)
+    }
+}
+
+/// Describes a request for the logged in user's information. See [`Api::my_info`](../struct.Api.html#method.my_info).
+pub fn my_info() -> Request<MyInfo> {
+    Request::get("auth/me").auth()
+}
+
+/// Describes a request for a page of the logged in user's credit transactions. See
+/// [`Api::money_history`](../struct.Api.html#method.money_history).
+pub fn money_history(page: u32) -> Request<MoneyHistory> {
+    Request::get("user/money-history")
+        .param("page", page.to_string())
+        .auth()
+}
+
+/// Describes a request to look up a user by username. See [`Api::find_user`](../struct.Api.html#method.find_user).
+pub fn find_user<'b, U>(username: U) -> Request<UserInfo>
+where
+    U: Into<Cow<'b, str>>,
+{
+    Request::get("user/find").param("username", username.into())
+}
+
+/// Describes a request to look up a user by ID. See
+/// [`Api::find_user_by_id`](../struct.Api.html#method.find_user_by_id).
+pub fn find_user_by_id<'b, U>(user_id: U) -> Request<UserInfo>
+where
+    U: Into<Cow<'b, str>>,
+{
+    Request::get("user/find").param("id", user_id.into())
+}
+
+/// Describes a request for the world start room. See
+/// [`Api::world_start_room`](../struct.Api.html#method.world_start_room).
+pub fn world_start_room() -> Request<WorldStartRoom> {
+    Request::get("user/world-start-room").auth()
+}
+
+/// Describes a request for the start room on a shard. See
+/// [`Api::shard_start_room`](../struct.Api.html#method.shard_start_room).
+pub fn shard_start_room<'b, U>(shard: U) -> Request<WorldStartRoom>
+where
+    U: Into<Cow<'b, str>>,
+{
+    world_start_room().param("shard", shard.into())
+}
+
+/// Describes a map stats request. See [`Api::map_stats`](../struct.Api.html#method.map_stats).
+pub fn map_stats<'a, U, V>(shard: &'a str, rooms: &'a V) -> Request<MapStats>
+where
+    U: AsRef<str>,
+    &'a V: IntoIterator<Item = U>,
+{
+    MapStatsArgs::new(shard, rooms, MapStatName::RoomOwner).describe()
+}
+
+/// Describes a map stats request for a statistic besides room owners. See
+/// [`Api::map_stats_with_stat`](../struct.Api.html#method.map_stats_with_stat).
+pub fn map_stats_with_stat<'a, U, V>(
+    shard: &'a str,
+    rooms: &'a V,
+    stat: MapStatName,
+) -> Request<MapStats>
+where
+    U: AsRef<str>,
+    &'a V: IntoIterator<Item = U>,
+{
+    MapStatsArgs::new(shard, rooms, stat).describe()
+}
+
+/// Describes map stats requests for rooms on any shards, one for each shard, paired with the shard's name. See
+/// [`Api::map_stats_on`](../struct.Api.html#method.map_stats_on).
+///
+/// Requests are in the order each shard first appears in `rooms`.
+pub fn map_stats_on<'a, V>(rooms: &'a V, stat: MapStatName) -> Vec<(&'a str, Request<MapStats>)>
+where
+    &'a V: IntoIterator<Item = &'a ShardRoomName>,
+{
+    let mut shards: Vec<(&str, Vec<String>)> = Vec::new();
+    for room in rooms {
+        match shards.iter_mut().find(|(shard, _)| *shard == room.shard) {
+            Some((_, names)) => names.push(room.room.to_string()),
+            None => shards.push((&room.shard, vec![room.room.to_string()])),
+        }
+    }
+
+    shards
+        .iter()
+        .map(|(shard, names)| {
+            (
+                *shard,
+                MapStatsArgs::<'_, Vec<String>, &String>::new(shard, names, stat.clone())
+                    .describe(),
+            )
+        })
+        .collect()
+}
+
+impl<'a, T, I> Endpoint for MapStatsArgs<'a, T, I>
+where
+    I: AsRef<str>,
+    &'a T: IntoIterator<Item = I>,
+{
+    type Result = MapStats;
+
+    fn describe(&self) -> Request<MapStats> {
+        Request::post("game/map-stats", self)
+            .auth()
+            .with_priority(Priority::Background)
+    }
+}
+
+/// Describes a room overview request. See [`Api::room_overview`](../struct.Api.html#method.room_overview).
+pub fn room_overview<'b, U, V>(
+    shard: U,
+    room_name: V,
+    request_interval: u32,
+) -> Request<RoomOverview>
+where
+    U: Into<Cow<'b, str>>,
+    V: Into<Cow<'b, str>>,
+{
+    Request::get("game/room-overview")
+        .param("shard", shard.into())
+        .param("room", room_name.into())
+        .param("interval", request_interval.to_string())
+        .auth()
+}
+
+/// Describes a room overview request for a room on a shard. See
+/// [`Api::room_overview_on`](../struct.Api.html#method.room_overview_on).
+pub fn room_overview_on(room: &ShardRoomName, request_interval: u32) -> Request<RoomOverview> {
+    room_overview(&*room.shard, room.room.to_string(), request_interval)
+}
+
+/// Describes a room terrain request. See [`Api::room_terrain`](../struct.Api.html#method.room_terrain).
+pub fn room_terrain<'b, U, V>(shard: Option<U>, room_name: V) -> Request<RoomTerrain>
+where
+    U: Into<Cow<'b, str>>,
+    V: Into<Cow<'b, str>>,
+{
+    let request = Request::get("game/room-terrain");
+    let request = match shard {
+        Some(shard) => request.param("shard", shard.into()),
+        None => request,
+    };
+
+    request
+        .param("room", room_name.into())
+        .param("encoded", true.to_string())
+}
+
+/// Describes a room terrain request for a room on a shard. See
+/// [`Api::room_terrain_on`](../struct.Api.html#method.room_terrain_on).
+pub fn room_terrain_on(room: &ShardRoomName) -> Request<RoomTerrain> {
+    room_terrain(Some(&*room.shard), room.room.to_string())
+}
+
+/// Describes a room objects request. Only available with the `sockets` feature, which has the room object types.
+/// See [`Api::room_objects`](../struct.Api.html#method.room_objects).
+#[cfg(feature = "sockets")]
+pub fn room_objects<'b, U, V>(shard: Option<U>, room_name: V) -> Request<RoomObjects>
+where
+    U: Into<Cow<'b, str>>,
+    V: Into<Cow<'b, str>>,
+{
+    let request = Request::get("game/room-objects");
+    let request = match shard {
+        Some(shard) => request.param("shard", shard.into()),
+        None => request,
+    };
+
+    request.param("room", room_name.into())
+}
+
+/// Describes a shard list request. See [`Api::shard_list`](../struct.Api.html#method.shard_list).
+pub fn shard_list() -> Request<Vec<ShardInfo>> {
+    Request::get("game/shards/info")
+}
+
+/// Describes a room status request. See [`Api::room_status`](../struct.Api.html#method.room_status).
+pub fn room_status<'b, U>(room_name: U) -> Request<RoomStatus>
+where
+    U: Into<Cow<'b, str>>,
+{
+    Request::get("game/room-status")
+        .param("room", room_name.into())
+        .auth()
+}
+
+/// Describes a recent PvP request. See [`Api::recent_pvp`](../struct.Api.html#method.recent_pvp).
+pub fn recent_pvp(details: RecentPvpArgs) -> Request<RecentPvp> {
+    details.describe()
+}
+
+impl Endpoint for RecentPvpArgs {
+    type Result = RecentPvp;
+
+    fn describe(&self) -> Request<RecentPvp> {
+        let request = Request::get("experimental/pvp");
+
+        match *self {
+            RecentPvpArgs::WithinLast { ticks } => request.param("interval", ticks.to_string()),
+            RecentPvpArgs::Since { time } => request.param("start", time.to_string()),
+        }
+    }
+}
+
+/// Describes a leaderboard season list request. See
+/// [`Api::leaderboard_season_list`](../struct.Api.html#method.leaderboard_season_list).
+pub fn leaderboard_season_list() -> Request<Vec<LeaderboardSeason>> {
+    Request::get("leaderboard/seasons").auth()
+}
+
+/// Describes a request for a user's rank in one season. See
+/// [`Api::find_season_leaderboard_rank`](../struct.Api.html#method.find_season_leaderboard_rank).
+pub fn find_season_leaderboard_rank<'b, U, V>(
+    leaderboard_type: LeaderboardType,
+    username: U,
+    season: V,
+) -> Request<FoundUserRank>
+where
+    U: Into<Cow<'b, str>>,
+    V: Into<Cow<'b, str>>,
+{
+    Request::get("leaderboard/find")
+        .auth()
+        .param("mode", leaderboard_type.api_representation())
+        .param("season", season.into())
+        .param("username", username.into())
+}
+
+/// Describes a request for a user's rank in all seasons. See
+/// [`Api::find_leaderboard_ranks`](../struct.Api.html#method.find_leaderboard_ranks).
+pub fn find_leaderboard_ranks<'b, U>(
+    leaderboard_type: LeaderboardType,
+    username: U,
+) -> Request<Vec<FoundUserRank>>
+where
+    U: Into<Cow<'b, str>>,
+{
+    Request::get("leaderboard/find")
+        .auth()
+        .param("mode", leaderboard_type.api_representation())
+        .param("username", username.into())
+}
+
+/// Describes a leaderboard page request. See
+/// [`Api::leaderboard_page`](../struct.Api.html#method.leaderboard_page).
+pub fn leaderboard_page<'b, U>(
+    leaderboard_type: LeaderboardType,
+    season: U,
+    limit: u32,
+    offset: u32,
+) -> Request<LeaderboardPage>
+where
+    U: Into<Cow<'b, str>>,
+{
+    Request::get("leaderboard/list")
+        .auth()
+        .param("mode", leaderboard_type.api_representation())
+        .param("season", season.into())
+        .param("limit", limit.to_string())
+        .param("offset", offset.to_string())
+}
+
+/// Describes a request for how many orders each resource has on the market. See
+/// [`Api::market_orders_index`](../struct.Api.html#method.market_orders_index).
+pub fn market_orders_index<'b, U>(shard: Option<U>) -> Request<MarketOrdersIndex>
+where
+    U: Into<Cow<'b, str>>,
+{
+    let request = Request::get("game/market/orders-index");
+
+    match shard {
+        Some(shard) => request.param("shard", shard.into()),
+        None => request,
+    }
+    .auth()
+}
+
+/// Describes a request for all active orders of one resource. See
+/// [`Api::market_orders`](../struct.Api.html#method.market_orders).
+pub fn market_orders<'b, U>(shard: Option<U>, resource_type: &ResourceType) -> Request<MarketOrders>
+where
+    U: Into<Cow<'b, str>>,
+{
+    let request = Request::get("game/market/orders")
+        .param("resourceType", resource_type.to_resource_string());
+
+    match shard {
+        Some(shard) => request.param("shard", shard.into()),
+        None => request,
+    }
+    .auth()
+}
+
+/// Describes a request for the logged in user's market orders. See
+/// [`Api::my_market_orders`](../struct.Api.html#method.my_market_orders).
+pub fn my_market_orders() -> Request<MyMarketOrders> {
+    Request::get("game/market/my-orders").auth()
+}
+
+/// Describes a memory segment request. See [`Api::memory_segment`](../struct.Api.html#method.memory_segment).
+pub fn memory_segment<'b, U>(shard: Option<U>, segment: u32) -> Request<MemorySegment>
+where
+    U: Into<Cow<'b, str>>,
+{
+    let request = Request::get("user/memory-segment").param("segment", segment.to_string());
+
+    match shard {
+        Some(shard) => request.param("shard", shard.into()),
+        None => request,
+    }
+    .auth()
+}
+
+/// Describes a memory segment request on the shard a room is on. See
+/// [`Api::memory_segment_on`](../struct.Api.html#method.memory_segment_on).
+pub fn memory_segment_on(room: &ShardRoomName, segment: u32) -> Request<MemorySegment> {
+    memory_segment(Some(&*room.shard), segment)
+}
+
+/// Describes a request setting a memory segment. See
+/// [`Api::set_memory_segment`](../struct.Api.html#method.set_memory_segment).
+pub fn set_memory_segment<'b, U, V>(
+    shard: Option<U>,
+    segment: u32,
+    data: V,
+) -> Request<SetMemorySegment>
+where
+    U: Into<Cow<'b, str>>,
+    V: Into<Cow<'b, str>>,
+{
+    SetMemorySegmentArgs {
+        segment,
+        shard: shard.map(Into::into),
+        data: data.into(),
+    }
+    .describe()
+}
+
+/// Describes a request setting a memory segment on the shard a room is on. See
+/// [`Api::set_memory_segment_on`](../struct.Api.html#method.set_memory_segment_on).
+pub fn set_memory_segment_on<'b, V>(
+    room: &ShardRoomName,
+    segment: u32,
+    data: V,
+) -> Request<SetMemorySegment>
+where
+    V: Into<Cow<'b, str>>,
+{
+    set_memory_segment(Some(room.shard.clone()), segment, data)
+}
+
+impl Endpoint for SetMemorySegmentArgs<'_> {
+    type Result = SetMemorySegment;
+
+    fn describe(&self) -> Request<SetMemorySegment> {
+        Request::post("user/memory-segment", self)
+            .auth()
+            .with_priority(Priority::Interactive)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::*;
+
+    #[test]
+    fn describe_requests() {
+        let base_url = Url::parse("https://screeps.com/api/").unwrap();
+
+        let request = room_terrain(Some("shard0"), "E0N0");
+        assert_eq!(*request.method(), http::Method::GET);
+        assert_eq!(request.endpoint(), "game/room-terrain");
+        assert!(!request.requires_auth());
+        assert_eq!(
+            request.url(&base_url).as_str(),
+            "https://screeps.com/api/game/room-terrain?shard=shard0&room=E0N0&encoded=true"
+        );
+
+        let request = find_user("daboross");
+        assert!(!request.requires_auth());
+        assert_eq!(
+            request.url(&base_url).as_str(),
+            "https://screeps.com/api/user/find?username=daboross"
+        );
+
+        let request = set_memory_segment(Some("shard1"), 3, "data");
+        assert_eq!(*request.method(), http::Method::POST);
+        assert!(request.requires_auth());
+        assert!(request.query().is_empty());
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(request.body().unwrap()).unwrap(),
+            json!({ "segment": 3, "shard": "shard1", "data": "data" })
+        );
+
+        let token = Token::from_static(b"token");
+        let http_request = request.to_http(&base_url, Some(&token));
+        assert_eq!(http_request.headers()["X-Token"], "token");
+        assert!(!http_request.headers().contains_key("X-Username"));
+        assert_eq!(
+            http_request.uri(),
+            "https://screeps.com/api/user/memory-segment"
+        );
+    }
+
+    #[test]
+    fn describe_from_args() {
+        let rooms = ["E0N0", "E1N1"];
+        let request = MapStatsArgs::new("shard0", &rooms, MapStatName::RoomOwner).describe();
+        assert_eq!(request.endpoint(), "game/map-stats");
+        assert!(request.requires_auth());
+        assert_eq!(request.priority(), Some(Priority::Background));
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(request.body().unwrap()).unwrap(),
+            json!({ "rooms": ["E0N0", "E1N1"], "statName": "owner0", "shard": "shard0" })
+        );
+
+        let request = map_stats_with_stat(
+            "shard0",
+            &rooms,
+            MapStatName::Stat(MapStat::EnergyHarvested, 180),
+        );
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(request.body().unwrap()).unwrap()
+                ["statName"],
+            "energyHarvested180"
+        );
+
+        let request = RecentPvpArgs::since(500).describe();
+        assert_eq!(request.query(), &[("start", "500".to_owned())]);
+        assert_eq!(
+            request.query(),
+            recent_pvp(RecentPvpArgs::since(500)).query()
+        );
+    }
+
+    #[test]
+    fn describe_for_shard_rooms() {
+        let base_url = Url::parse("https://screeps.com/api/").unwrap();
+        let room = "shard2/W5S5".parse::<ShardRoomName>().unwrap();
+
+        assert_eq!(
+            room_terrain_on(&room).url(&base_url).as_str(),
+            "https://screeps.com/api/game/room-terrain?shard=shard2&room=W5S5&encoded=true"
+        );
+        assert_eq!(
+            room_overview_on(&room, 8).url(&base_url).as_str(),
+            "https://screeps.com/api/game/room-overview?shard=shard2&room=W5S5&interval=8"
+        );
+        assert_eq!(
+            memory_segment_on(&room, 4).url(&base_url).as_str(),
+            "https://screeps.com/api/user/memory-segment?segment=4&shard=shard2"
+        );
+
+        let rooms = ["shard2/W5S5", "shard0/E1N1", "shard2/W6S5"]
+            .iter()
+            .map(|name| name.parse::<ShardRoomName>().unwrap())
+            .collect::<Vec<_>>();
+        let requests = map_stats_on(&rooms, MapStatName::RoomOwner);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].0, "shard2");
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(requests[0].1.body().unwrap()).unwrap(),
+            json!({ "rooms": ["W5S5", "W6S5"], "statName": "owner0", "shard": "shard2" })
+        );
+        assert_eq!(requests[1].0, "shard0");
+    }
+
+    #[test]
+    fn auth_headers() {
+        let official = Url::parse("https://screeps.com/api/").unwrap();
+        let private = Url::parse("http://localhost:21025/api/").unwrap();
+        let token = Token::from_static(b"token");
+        let request = my_info();
+
+        let http_request = request.to_http(&private, Some(&token));
+        assert_eq!(http_request.headers()["X-Token"], "token");
+        assert_eq!(http_request.headers()["X-Username"], "token");
+
+        let http_request =
+            request.to_http_with_auth(&official, Some(&token), AuthMode::TokenAndUsername);
+        assert_eq!(http_request.headers()["X-Username"], "token");
+
+        let http_request = request.to_http_with_auth(&private, Some(&token), AuthMode::Token);
+        assert!(!http_request.headers().contains_key("X-Username"));
+
+        assert_eq!(AuthMode::Auto.resolve(&official), AuthMode::Token);
+    }
+
+    #[test]
+    fn override_endpoint_paths() {
+        let base_url = Url::parse("http://localhost:21025/api/").unwrap();
+        let mut paths = EndpointPaths::new()
+            .with_path("game/room-terrain", "terrain")
+            .unwrap()
+            .with_path("auth/me", "/v2/auth/me")
+            .unwrap();
+
+        let request = room_terrain(None::<&str>, "E0N0");
+        assert_eq!(
+            request.url_with_paths(&base_url, &paths).as_str(),
+            "http://localhost:21025/api/terrain?room=E0N0&encoded=true"
+        );
+        assert_eq!(
+            my_info().url_with_paths(&base_url, &paths).as_str(),
+            "http://localhost:21025/v2/auth/me"
+        );
+        assert_eq!(
+            shard_list().url_with_paths(&base_url, &paths),
+            shard_list().url(&base_url)
+        );
+
+        paths.remove_path("game/room-terrain");
+        assert_eq!(paths.path("game/room-terrain"), "game/room-terrain");
+        assert!(paths.set_path("auth/me", "//[invalid").is_err());
+    }
+
+    #[test]
+    fn parse_raw_responses() {
+        let request = memory_segment(None::<&str>, 0);
+
+        let segment = request
+            .parse_response(http::StatusCode::OK, br#"{"ok":1,"data":"asdf"}"#)
+            .unwrap();
+        assert_eq!(segment.data, "asdf");
+
+        let err = request
+            .parse_response(http::StatusCode::UNAUTHORIZED, b"Unauthorized")
+            .unwrap_err();
+        match *err.kind() {
+            crate::ErrorKind::Unauthorized => (),
+            ref other => panic!("expected unauthorized error, found {:?}", other),
+        }
+        assert_eq!(err.body().unwrap(), &b"Unauthorized"[..]);
+    }
+
+    #[test]
+    fn parse_strictly() {
+        let request = memory_segment(None::<&str>, 0);
+        let body = Bytes::from_static(br#"{"ok":1,"data":"asdf","extra":true}"#);
+
+        let segment = request.parse_response(http::StatusCode::OK, &body).unwrap();
+        assert_eq!(segment.data, "asdf");
+
+        let strict = ParseOptions::new().with_strict(true);
+        let err = request
+            .parse_response_with(http::StatusCode::OK, &body, &strict)
+            .unwrap_err();
+        match *err.kind() {
+            crate::ErrorKind::UnknownFields(ref paths) => assert_eq!(*paths, ["extra"]),
+            ref other => panic!("expected unknown fields error, found {:?}", other),
+        }
+        assert_eq!(err.category(), crate::ErrorCategory::Parse);
+
+        let segment = request
+            .parse_response_with(
+                http::StatusCode::OK,
+                &Bytes::from_static(br#"{"ok":1,"data":"asdf"}"#),
+                &strict,
+            )
+            .unwrap();
+        assert_eq!(segment.data, "asdf");
+    }
+
+    #[test]
+    fn parse_fast() {
+        let request = memory_segment(None::<&str>, 0);
+        let fast = ParseOptions::new().with_fast(true);
+        let body = Bytes::from_static(br#"{"ok":1,"data":"asdf","extra":true}"#);
+
+        let segment = request
+            .parse_response_with(http::StatusCode::OK, &body, &fast)
+            .unwrap();
+        assert_eq!(segment.data, "asdf");
+
+        let err = request
+            .parse_response_with(
+                http::StatusCode::OK,
+                &Bytes::from_static(br#"{"ok":1}"#),
+                &fast,
+            )
+            .unwrap_err();
+        assert!(err.json().is_some());
+
+        let strict = fast.with_strict(true);
+        assert!(request
+            .parse_response_with(http::StatusCode::OK, &body, &strict)
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn report_unknown_fields() {
+        let warnings = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let options = {
+            let warnings = warnings.clone();
+            ParseOptions::new()
+                .with_warning_hook(move |warning| warnings.lock().unwrap().push(warning.clone()))
+        };
+        let url = Url::parse("https://screeps.com/api/user/memory-segment").unwrap();
+        let options = options.for_request("user/memory-segment", &url);
+
+        let request = memory_segment(None::<&str>, 0);
+        let body = Bytes::from_static(br#"{"ok":1,"data":"asdf","extra":true}"#);
+        request
+            .parse_response_with(http::StatusCode::OK, &body, &options)
+            .unwrap();
+
+        let warnings = warnings.lock().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].endpoint, Some("user/memory-segment"));
+        assert_eq!(warnings[0].url, Some(url));
+        assert_eq!(warnings[0].unknown_fields, ["extra"]);
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn retain_raw_json() {
+        let options = ParseOptions::new().with_raw_json(true);
+        let url = Url::parse("https://screeps.com/api/user/memory-segment").unwrap();
+
+        let request = memory_segment(None::<&str>, 0);
+        let body = Bytes::from_static(br#"{"ok":1,"data":"asdf","extra":true}"#);
+        let segment = request
+            .parse_response_with(
+                http::StatusCode::OK,
+                &body,
+                &options.for_request("user/memory-segment", &url),
+            )
+            .unwrap();
+        assert_eq!(segment.data, "asdf");
+
+        assert_eq!(
+            options.raw_json("user/memory-segment").unwrap()["extra"],
+            true
+        );
+        assert_eq!(options.raw_json("game/shards/info"), None);
+    }
+
+    #[test]
+    fn share_body_with_errors() {
+        let request = memory_segment(None::<&str>, 0);
+        let body = Bytes::from_static(b"Bad Gateway");
+
+        let err = request
+            .parse_response_with(http::StatusCode::BAD_GATEWAY, &body, &ParseOptions::new())
+            .unwrap_err();
+        assert_eq!(err.body().unwrap().as_ptr(), body.as_ptr());
+    }
+}
@@ -0,0 +1,268 @@
+//! Retrying requests which failed for transient reasons.
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+use http::header::{HeaderMap, RETRY_AFTER};
+
+use crate::{
+    backoff::{BackoffPolicy, Exponential},
+    error::{Error, ErrorKind},
+};
+
+/// How requests which fail for transient reasons are retried.
+///
+/// Only `GET` requests are retried, as they can be repeated without side effects. A request is retried when
+/// connecting to the server fails or times out, or when the server responds with `429 Too Many Requests`,
+/// `502 Bad Gateway` or `503 Service Unavailable`.
+///
+/// When the server says how long to wait with a `Retry-After` or `X-RateLimit-Reset` header, the retry waits at
+/// least that long. If that is longer than `max_delay`, as it can be for endpoints limited per hour or per day, the
+/// request isn't retried: the error is returned right away, and `Error::retry_after` says how long the server asked
+/// to wait.
+///
+/// Retries are delayed with exponential backoff: the delay starts at `initial_delay` and doubles with each retry, up
+/// to `max_delay`. With jitter enabled, each delay is randomly shortened by up to half, so many clients failing at the
+/// same time do not all retry at the same time. Other schedules can be used with [`Api::set_retry_backoff`].
+///
+/// Create with `RetryPolicy::new()` or `RetryPolicy::default()`, then customize with the `with_*` methods:
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use screeps_api::retry::RetryPolicy;
+///
+/// let policy = RetryPolicy::new()
+///     .with_max_retries(5)
+///     .with_initial_delay(Duration::from_secs(1))
+///     .with_jitter(false);
+///
+/// assert_eq!(policy.delay(2), Some(Duration::from_secs(4)));
+/// assert_eq!(policy.delay(5), None);
+/// ```
+///
+/// [`Api::set_retry_backoff`]: ../struct.Api.html#method.set_retry_backoff
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy with default settings: up to 3 retries, starting at half a second, with jitter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of times a single request is retried.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Sets the upper bound on the delay before any retry.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Sets whether delays are randomly shortened.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The maximum number of times a single request is retried.
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// The delay before the first retry.
+    pub fn initial_delay(&self) -> Duration {
+        self.initial_delay
+    }
+
+    /// The upper bound on the delay before any retry.
+    pub fn max_delay(&self) -> Duration {
+        self.max_delay
+    }
+
+    /// Whether delays are randomly shortened.
+    pub fn jitter(&self) -> bool {
+        self.jitter
+    }
+
+    /// Gets the delay before the given retry (starting at 0), or `None` if no further retries should be made.
+    pub fn delay(&self, retry: u32) -> Option<Duration> {
+        Exponential::new(self.initial_delay, self.max_delay)
+            .with_max_attempts(Some(self.max_retries))
+            .with_jitter(self.jitter)
+            .delay(retry, Duration::from_secs(0))
+    }
+}
+
+/// Gets how long the server asked clients to wait before retrying, from the `Retry-After` or `X-RateLimit-Reset`
+/// headers of a response.
+///
+/// `Retry-After` may either be a number of seconds or an HTTP date. `X-RateLimit-Reset`, sent by the official
+/// server, is the unix timestamp in seconds at which the rate limit resets. Times in the past result in a zero delay.
+///
+/// This is also available on errors from `429 Too Many Requests` and `503 Service Unavailable` responses, as
+/// `Error::retry_after`. Other responses aren't checked.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    retry_after_at(headers, SystemTime::now())
+}
+
+fn retry_after_at(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let until = |timestamp: i64| {
+        let now = now.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        Some(Duration::from_secs(
+            (timestamp.max(0) as u64).saturating_sub(now),
+        ))
+    };
+
+    if let Some(value) = headers.get(RETRY_AFTER).and_then(|v| v.to_str().ok()) {
+        let value = value.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(Duration::from_secs(seconds));
+        }
+        if let Ok(date) = time::strptime(value, "%a, %d %b %Y %H:%M:%S GMT") {
+            return until(date.to_timespec().sec);
+        }
+    }
+
+    headers
+        .get("X-RateLimit-Reset")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .and_then(until)
+}
+
+/// Whether a response with the given status may say how long to wait before retrying: only `429 Too Many Requests`
+/// and `503 Service Unavailable` do.
+pub(crate) fn may_ask_to_wait(status: http::StatusCode) -> bool {
+    status == http::StatusCode::TOO_MANY_REQUESTS || status == http::StatusCode::SERVICE_UNAVAILABLE
+}
+
+/// Whether a request which resulted in the given response or error should be retried.
+pub(crate) fn is_transient(response: &Result<http::Response<Bytes>, Error>) -> bool {
+    match *response {
+        Ok(ref response) => matches!(
+            response.status(),
+            http::StatusCode::TOO_MANY_REQUESTS
+                | http::StatusCode::BAD_GATEWAY
+                | http::StatusCode::SERVICE_UNAVAILABLE
+        ),
+        Err(ref err) => match *err.kind() {
+            ErrorKind::Hyper(ref err) => {
+                err.is_connect() || err.is_closed() || err.is_incomplete_message()
+            }
+            #[cfg(feature = "reqwest")]
+            ErrorKind::Reqwest(ref err) => err.is_connect() || err.is_timeout(),
+            ErrorKind::Io(_) | ErrorKind::Timeout(_) => true,
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use http::header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER};
+
+    use super::{is_transient, retry_after_at, RetryPolicy};
+
+    #[test]
+    fn backoff_delays() {
+        let policy = RetryPolicy::new()
+            .with_max_retries(6)
+            .with_initial_delay(Duration::from_secs(1))
+            .with_max_delay(Duration::from_secs(10))
+            .with_jitter(false);
+
+        let delays = (0..7).map(|retry| policy.delay(retry)).collect::<Vec<_>>();
+
+        assert_eq!(
+            delays,
+            vec![
+                Some(Duration::from_secs(1)),
+                Some(Duration::from_secs(2)),
+                Some(Duration::from_secs(4)),
+                Some(Duration::from_secs(8)),
+                Some(Duration::from_secs(10)),
+                Some(Duration::from_secs(10)),
+                None,
+            ]
+        );
+
+        let jittered = policy.with_jitter(true).delay(3).unwrap();
+        assert!(jittered >= Duration::from_secs(4) && jittered <= Duration::from_secs(8));
+    }
+
+    #[test]
+    fn transient_responses() {
+        let response = |status| {
+            let mut response = http::Response::new(bytes::Bytes::new());
+            *response.status_mut() = status;
+            Ok(response)
+        };
+
+        assert!(is_transient(&response(http::StatusCode::TOO_MANY_REQUESTS)));
+        assert!(is_transient(&response(
+            http::StatusCode::SERVICE_UNAVAILABLE
+        )));
+        assert!(!is_transient(&response(http::StatusCode::OK)));
+        assert!(!is_transient(&response(http::StatusCode::UNAUTHORIZED)));
+    }
+
+    #[test]
+    fn parse_retry_after() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+        let headers = |name: HeaderName, value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, HeaderValue::from_static(value));
+            headers
+        };
+
+        assert_eq!(
+            retry_after_at(&headers(RETRY_AFTER, "120"), now),
+            Some(Duration::from_secs(120))
+        );
+        // 1_500_000_000 is Fri, 14 Jul 2017 02:40:00 GMT
+        assert_eq!(
+            retry_after_at(&headers(RETRY_AFTER, "Fri, 14 Jul 2017 02:40:30 GMT"), now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            retry_after_at(
+                &headers(HeaderName::from_static("x-ratelimit-reset"), "1500000045"),
+                now
+            ),
+            Some(Duration::from_secs(45))
+        );
+        assert_eq!(
+            retry_after_at(&headers(RETRY_AFTER, "Fri, 14 Jul 2017 02:39:00 GMT"), now),
+            Some(Duration::from_secs(0))
+        );
+        assert_eq!(retry_after_at(&HeaderMap::new(), now), None);
+    }
+}
@@ -0,0 +1,198 @@
+//! Differences between the official server, the seasonal server and private servers.
+use url::Url;
+
+use crate::request::AuthMode;
+
+/// The kind of server an [`Api`] talks to.
+///
+/// Servers differ in which endpoints they have, and in how they expect the auth token to be sent. An `Api` detects
+/// the profile from its URL unless one is set with `Api::set_server_profile`, and requests to endpoints the profile
+/// does not have fail with `ErrorKind::UnsupportedEndpoint` without being sent.
+///
+/// ```
+/// use screeps_api::server::ServerProfile;
+///
+/// let url = url::Url::parse("https://screeps.com/season/api/").unwrap();
+/// assert_eq!(ServerProfile::detect(&url), ServerProfile::Season);
+///
+/// assert!(!ServerProfile::Official.supports(&http::Method::POST, "auth/signin"));
+/// assert!(ServerProfile::Private.supports(&http::Method::POST, "auth/signin"));
+/// ```
+///
+/// [`Api`]: ../struct.Api.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ServerProfile {
+    /// The official server at `https://screeps.com/api/`.
+    Official,
+    /// The official seasonal server at `https://screeps.com/season/api/`.
+    Season,
+    /// A private server, such as one run with [screeps-server].
+    ///
+    /// Logging in and registering require the [screepsmod-auth] mod.
+    ///
+    /// [screeps-server]: https://github.com/screeps/screeps
+    /// [screepsmod-auth]: https://github.com/ScreepsMods/screepsmod-auth
+    Private,
+}
+
+/// A feature which only some servers have, as detected from the server's version information.
+///
+/// See `Api::supports`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Feature {
+    /// The server has multiple named shards, and shard-specific calls need a shard name.
+    Shards,
+    /// The server runs a seasonal competition.
+    Season,
+    /// Logging in and registering with a username and password, with the screepsmod-auth mod.
+    PasswordAuth,
+}
+
+/// Endpoints only available on private servers with screepsmod-auth.
+const PASSWORD_AUTH: &[(&str, &str)] = &[("POST", "auth/signin"), ("POST", "register/submit")];
+
+/// Endpoints only available on official servers.
+const OFFICIAL_ONLY: &[(&str, &str)] = &[("GET", "game/shards/info"), ("GET", "experimental/pvp")];
+
+impl ServerProfile {
+    /// Detects the profile of the server with the given API URL: servers at `screeps.com` are official, with the
+    /// seasonal server under `/season/`, and any other server is private.
+    pub fn detect(url: &Url) -> ServerProfile {
+        let official = url
+            .host_str()
+            .is_some_and(|host| host == "screeps.com" || host.ends_with(".screeps.com"));
+
+        if !official {
+            ServerProfile::Private
+        } else if url.path().starts_with("/season/") {
+            ServerProfile::Season
+        } else {
+            ServerProfile::Official
+        }
+    }
+
+    /// The usual API URL of servers with this profile. For private servers, this is a server running locally on the
+    /// default port.
+    pub fn default_url(self) -> Url {
+        let url = match self {
+            ServerProfile::Official => crate::DEFAULT_OFFICIAL_API_URL,
+            ServerProfile::Season => "https://screeps.com/season/api/",
+            ServerProfile::Private => "http://localhost:21025/api/",
+        };
+        Url::parse(url).expect("expected pre-set url to parse, parsing failed")
+    }
+
+    /// How servers with this profile expect the auth token to be sent.
+    pub fn auth_mode(self) -> AuthMode {
+        match self {
+            ServerProfile::Official | ServerProfile::Season => AuthMode::Token,
+            ServerProfile::Private => AuthMode::TokenAndUsername,
+        }
+    }
+
+    /// Whether servers with this profile have the given endpoint, relative to the API url, for the given method.
+    pub fn supports(self, method: &http::Method, endpoint: &str) -> bool {
+        let unsupported = match self {
+            ServerProfile::Official | ServerProfile::Season => PASSWORD_AUTH,
+            ServerProfile::Private => OFFICIAL_ONLY,
+        };
+
+        !unsupported
+            .iter()
+            .any(|&(m, e)| m == method.as_str() && e == endpoint)
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use url::Url;
+
+    use super::{Feature, ServerProfile};
+    use crate::{
+        testing::{Mock, MockTransport},
+        Api, ErrorKind,
+    };
+
+    #[test]
+    fn detect_capabilities_once() {
+        let server = MockTransport::new().with_mock(Mock::get("version").with_json(json!({
+            "ok": 1,
+            "package": 204,
+            "protocol": 14,
+            "serverData": { "shards": ["shard0"] },
+        })));
+        let api = Api::new(server.clone());
+        assert_eq!(api.supports(Feature::Shards), None);
+
+        for _ in 0..2 {
+            let version = futures::executor::block_on(api.clone().capabilities()).unwrap();
+            assert_eq!(version.protocol, 14);
+        }
+        assert_eq!(server.requests().len(), 1);
+        assert_eq!(api.supports(Feature::Shards), Some(true));
+        assert_eq!(api.supports(Feature::PasswordAuth), Some(false));
+
+        let api = api.with_url("https://screeps.com/season/api/").unwrap();
+        assert_eq!(api.supports(Feature::Shards), None);
+    }
+
+    #[test]
+    fn detect_profiles() {
+        let detect = |url| ServerProfile::detect(&Url::parse(url).unwrap());
+
+        assert_eq!(detect("https://screeps.com/api/"), ServerProfile::Official);
+        assert_eq!(
+            detect("https://screeps.com/ptr/api/"),
+            ServerProfile::Official
+        );
+        assert_eq!(
+            detect("https://screeps.com/season/api/"),
+            ServerProfile::Season
+        );
+        assert_eq!(
+            detect("http://localhost:21025/api/"),
+            ServerProfile::Private
+        );
+
+        for &profile in &[
+            ServerProfile::Official,
+            ServerProfile::Season,
+            ServerProfile::Private,
+        ] {
+            assert_eq!(ServerProfile::detect(&profile.default_url()), profile);
+        }
+    }
+
+    #[test]
+    fn reject_unsupported_endpoints() {
+        let transport = MockTransport::new();
+        let api = Api::new(transport.clone());
+        let err = futures::executor::block_on(api.login("user", "password")).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnsupportedEndpoint(endpoint) => assert_eq!(endpoint, "auth/signin"),
+            ref other => panic!("expected unsupported endpoint error, found {:?}", other),
+        }
+
+        let api = Api::new_for_server(transport.clone(), ServerProfile::Private);
+        assert!(futures::executor::block_on(api.shard_list()).is_err());
+        assert!(transport.requests().is_empty());
+    }
+
+    #[test]
+    fn relogin_only_where_login_is_supported() {
+        let transport = MockTransport::new().with_mock(
+            Mock::get("user/world-start-room").with_status(http::StatusCode::UNAUTHORIZED),
+        );
+        let api = Api::new(transport.clone())
+            .with_token("expired")
+            .with_credentials("user", "password");
+
+        let err = futures::executor::block_on(api.world_start_room().unwrap()).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnsupportedEndpoint(endpoint) => assert_eq!(endpoint, "auth/signin"),
+            ref other => panic!("expected unsupported endpoint error, found {:?}", other),
+        }
+        assert_eq!(transport.requests().len(), 1);
+    }
+}
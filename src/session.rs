@@ -0,0 +1,214 @@
+//! Connecting to a server and logging in, in one call.
+use std::fmt;
+
+use crate::{
+    error::Error, redact::REDACTED, request, server::Feature, transport::HttpTransport, Api,
+    MyInfo, ServerVersion, ShardInfo, Token,
+};
+
+/// How to authenticate when connecting with [`Api::connect_and_login`].
+///
+/// [`Api::connect_and_login`]: ../struct.Api.html#method.connect_and_login
+#[derive(Clone)]
+pub enum Login {
+    /// Authenticate with an existing auth token, as on the official server.
+    Token(Token),
+    /// Log in with a username and password, as on private servers with screepsmod-auth.
+    Password {
+        /// The username, or email, to log in with.
+        username: String,
+        /// The password to log in with.
+        password: String,
+    },
+}
+
+impl Login {
+    /// Authenticate with an existing auth token.
+    pub fn token<T: Into<Token>>(token: T) -> Self {
+        Login::Token(token.into())
+    }
+
+    /// Log in with a username and password.
+    pub fn password<U, V>(username: U, password: V) -> Self
+    where
+        U: Into<String>,
+        V: Into<String>,
+    {
+        Login::Password {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+}
+
+impl fmt::Debug for Login {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Login::Token(_) => f.debug_tuple("Token").field(&REDACTED).finish(),
+            Login::Password { ref username, .. } => f
+                .debug_struct("Password")
+                .field("username", username)
+                .field("password", &REDACTED)
+                .finish(),
+        }
+    }
+}
+
+/// What is learned about a server and the logged in user when connecting with [`Api::connect_and_login`].
+///
+/// [`Api::connect_and_login`]: ../struct.Api.html#method.connect_and_login
+#[derive(Clone, Debug)]
+pub struct Session {
+    /// The server's version, and which features it has.
+    pub version: ServerVersion,
+    /// The logged in user.
+    pub user: MyInfo,
+    /// The server's shards. This is empty for servers whose version doesn't list any shards, such as most private
+    /// servers.
+    pub shards: Vec<ShardInfo>,
+}
+
+impl Session {
+    /// The id of the logged in user.
+    pub fn user_id(&self) -> &str {
+        &self.user.user_id
+    }
+}
+
+impl<T> Api<T>
+where
+    T: HttpTransport,
+{
+    /// Connects to the server and logs in, returning the ready-to-use client along with the server's version, the
+    /// logged in user and the server's shards.
+    ///
+    /// This requests the server's version, as [`Api::capabilities`] does, then authenticates: with a token, it is
+    /// stored, and with a username and password, the client logs in and keeps them to log in again whenever the
+    /// token expires, as with [`Api::set_credentials`]. Finally, the user's information is requested to check the
+    /// authentication works, and the shard list is requested if the server's version lists any shards.
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "sync")]
+    /// # fn main() {
+    /// use screeps_api::{session::Login, Api};
+    ///
+    /// let api = Api::new(hyper::Client::new()).with_url("http://localhost:21025/api/").unwrap();
+    ///
+    /// let connect = api.connect_and_login(Login::password("username", "password"));
+    /// let (api, session) = tokio::runtime::Runtime::new().unwrap().block_on(connect).unwrap();
+    /// println!("logged in as {}", session.user_id());
+    /// # }
+    /// # #[cfg(not(feature = "sync"))] fn main() {}
+    /// ```
+    ///
+    /// [`Api::capabilities`]: ../struct.Api.html#method.capabilities
+    /// [`Api::set_credentials`]: ../struct.Api.html#method.set_credentials
+    pub async fn connect_and_login(mut self, login: Login) -> Result<(Self, Session), Error> {
+        let version = self.capabilities().await?;
+
+        match login {
+            Login::Token(token) => self.set_token(token),
+            Login::Password { username, password } => {
+                self.login(&*username, &*password)
+                    .await?
+                    .return_to(&*self.token_storage());
+                self.set_credentials(username, password);
+            }
+        }
+
+        let user = self.my_info()?.await?;
+
+        let shards = if version.supports(Feature::Shards) {
+            self.execute_anonymous(request::shard_list()).await?
+        } else {
+            Vec::new()
+        };
+
+        Ok((
+            self,
+            Session {
+                version,
+                user,
+                shards,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Login;
+    use crate::{
+        testing::{Mock, MockTransport},
+        Api,
+    };
+
+    #[test]
+    fn connect_and_login() {
+        let transport = MockTransport::new()
+            .with_mock(Mock::get("version").with_json(json!({
+                "ok": 1,
+                "protocol": 14,
+                "users": 3,
+                "serverData": { "historyChunkSize": 20 },
+            })))
+            .with_mock(Mock::post("auth/signin").with_json(json!({ "ok": 1, "token": "fresh" })))
+            .with_mock(Mock::get("auth/me").with_json(json!({
+                "ok": 1,
+                "_id": "57874d42d0ae911e3bd15bbc",
+                "username": "user",
+                "password": true,
+                "cpu": 100,
+                "gcl": 0,
+                "money": 0,
+                "cpuShard": null,
+            })));
+        let api = Api::new(transport.clone())
+            .with_url("http://localhost:21025/api/")
+            .unwrap();
+
+        let (api, session) =
+            futures::executor::block_on(api.connect_and_login(Login::password("user", "pass")))
+                .unwrap();
+        assert_eq!(session.user_id(), "57874d42d0ae911e3bd15bbc");
+        assert_eq!(session.version.protocol, 14);
+        assert!(session.shards.is_empty());
+        assert_eq!(api.token_storage().get().unwrap(), "fresh");
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[2].headers()["X-Token"], "fresh");
+        assert!(!format!("{:?}", Login::password("user", "pass")).contains("\"pass\""));
+    }
+
+    #[test]
+    fn list_shards_from_version() {
+        let transport = MockTransport::new()
+            .with_mock(Mock::get("version").with_json(json!({
+                "ok": 1,
+                "protocol": 14,
+                "serverData": { "shards": ["shard0"] },
+            })))
+            .with_mock(Mock::get("auth/me").with_json(json!({
+                "ok": 1,
+                "_id": "57874d42d0ae911e3bd15bbc",
+                "username": "user",
+                "password": true,
+                "cpu": 100,
+                "gcl": 0,
+                "money": 0,
+                "cpuShard": null,
+            })))
+            .with_mock(Mock::get("game/shards/info").with_json(json!({
+                "ok": 1,
+                "shards": [{ "name": "shard0", "users": 1246, "rooms": 28858, "tick": 5726.4 }],
+            })));
+        let api = Api::new(transport.clone());
+
+        let (_, session) =
+            futures::executor::block_on(api.connect_and_login(Login::token("token"))).unwrap();
+        assert_eq!(session.shards.len(), 1);
+        assert_eq!(session.shards[0].name, "shard0");
+        assert_eq!(transport.requests().len(), 3);
+    }
+}
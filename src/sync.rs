@@ -1,18 +1,33 @@
 //! Small wrapper around the asynchronous Api struct providing synchronous access methods.
 use std::{
     borrow::Cow,
+    fmt,
+    future::Future,
     io,
     ops::{Deref, DerefMut},
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
 };
 
+use futures::{
+    future::{self, Either},
+    stream::{BoxStream, StreamExt},
+};
 use hyper::client::HttpConnector;
 use hyper::{self, Client};
 use hyper_tls::HttpsConnector;
 
+#[cfg(feature = "sockets")]
+use crate::RoomObjects;
 use crate::{
-    error::Error, Api, FoundUserRank, LeaderboardPage, LeaderboardSeason, LeaderboardType,
-    MapStats, MyInfo, RecentPvp, RecentPvpArgs, RegistrationArgs, RegistrationSuccess,
-    RoomOverview, RoomStatus, RoomTerrain, ShardInfo, Token, WorldStartRoom,
+    cancel::CancelToken,
+    error::{Error, ErrorKind},
+    request::{ParseResponse, Request},
+    transport::HttpTransport,
+    Api, FoundUserRank, LeaderboardPage, LeaderboardSeason, LeaderboardType, MapStatName, MapStats,
+    MarketOrders, MarketOrdersIndex, MoneyHistory, MyInfo, MyMarketOrders, RecentPvp,
+    RecentPvpArgs, RegistrationArgs, RegistrationSuccess, ResourceType, RoomOverview, RoomStatus,
+    RoomTerrain, ServerVersion, ShardInfo, ShardRoomName, Token, UserInfo, WorldStartRoom,
 };
 
 type TokioRuntime = tokio::runtime::Runtime;
@@ -66,57 +81,213 @@ pub use self::error::SyncError;
 
 /// API structure mirroring [`Api`], but providing utilities for synchronous connection.
 ///
-/// This structure owns both the hyper client and the tokio runtime. If this is not wanted, please
+/// This structure owns both the HTTP client and the tokio runtime. If this is not wanted, please
 /// use [`Api`] instead.
 ///
+/// Calls only need shared access, and the client is `Send` and `Sync`, so it can be used from several threads at
+/// once. Clones are cheap: they share the same runtime and interruptions, and the same token storage and other
+/// settings as [`Api`] clones do.
+///
+/// Each call blocks until it completes, unless a deadline is set with [`SyncApi::set_call_timeout`], or it is
+/// interrupted through an [`Interrupt`] handle.
+///
 /// [`Api`]: ../struct.Api.html
+/// [`SyncApi::set_call_timeout`]: #method.set_call_timeout
+/// [`Interrupt`]: struct.Interrupt.html
 #[derive(Debug)]
-pub struct SyncApi<C = HttpsConnector<HttpConnector>> {
-    runtime: TokioRuntime,
-    client: Api<C>,
+pub struct SyncApi<T = Client<HttpsConnector<HttpConnector>>> {
+    blocking: Blocking,
+    client: Api<T>,
+}
+
+impl<T> Clone for SyncApi<T> {
+    fn clone(&self) -> Self {
+        SyncApi {
+            blocking: self.blocking.clone(),
+            client: self.client.clone(),
+        }
+    }
+}
+
+/// A handle which interrupts the calls in progress on a [`SyncApi`] and its clones, from any thread.
+///
+/// Retrieved with [`SyncApi::interrupt_handle`]. Interrupted calls return immediately, failing with
+/// `ErrorKind::Cancelled`, and abandon their requests. Calls started after interrupting are unaffected, so this can
+/// be used from a Ctrl-C handler to abort a hung call without closing the client.
+///
+/// ```no_run
+/// use std::{thread, time::Duration};
+///
+/// use screeps_api::SyncApi;
+///
+/// let api = SyncApi::new().unwrap();
+///
+/// let interrupt = api.interrupt_handle();
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_secs(10));
+///     interrupt.interrupt();
+/// });
+///
+/// match api.shard_list() {
+///     Err(ref err) if err.is_cancelled() => println!("gave up"),
+///     other => println!("{:?}", other),
+/// }
+/// ```
+///
+/// [`SyncApi`]: struct.SyncApi.html
+/// [`SyncApi::interrupt_handle`]: struct.SyncApi.html#method.interrupt_handle
+#[derive(Clone, Debug, Default)]
+pub struct Interrupt {
+    /// Cancelled and replaced on each interruption, so only the calls already holding it are interrupted.
+    current: Arc<Mutex<CancelToken>>,
+}
+
+impl Interrupt {
+    /// Interrupts every call in progress.
+    pub fn interrupt(&self) {
+        let token =
+            std::mem::take(&mut *self.current.lock().unwrap_or_else(PoisonError::into_inner));
+        token.cancel();
+    }
+
+    fn token(&self) -> CancelToken {
+        self.current
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
 }
 
-impl SyncApi<HttpsConnector<HttpConnector>> {
+/// Why a call was stopped before completing.
+#[derive(Clone, Copy, Debug)]
+enum Stopped {
+    TimedOut(Duration),
+    Interrupted,
+}
+
+impl From<Stopped> for Error {
+    fn from(stopped: Stopped) -> Error {
+        match stopped {
+            Stopped::TimedOut(timeout) => ErrorKind::Timeout(timeout).into(),
+            Stopped::Interrupted => ErrorKind::Cancelled.into(),
+        }
+    }
+}
+
+/// Runs calls to completion on the runtime, unless they time out or are interrupted.
+#[derive(Clone, Debug)]
+struct Blocking {
+    runtime: Arc<TokioRuntime>,
+    timeout: Option<Duration>,
+    interrupt: Interrupt,
+}
+
+impl Blocking {
+    fn run<F: Future>(&self, call: F) -> Result<F::Output, Stopped> {
+        let timeout = self.timeout;
+        let call = async move {
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, call)
+                    .await
+                    .map_err(|_| Stopped::TimedOut(timeout)),
+                None => Ok(call.await),
+            }
+        };
+        let interrupted = self.interrupt.token().cancelled();
+
+        self.runtime.block_on(async move {
+            futures::pin_mut!(call);
+            match future::select(interrupted, call).await {
+                Either::Left(((), _)) => Err(Stopped::Interrupted),
+                Either::Right((result, _)) => result,
+            }
+        })
+    }
+
+    fn block_on<R, F>(&self, call: F) -> Result<R, Error>
+    where
+        F: Future<Output = Result<R, Error>>,
+    {
+        self.run(call)?
+    }
+}
+
+impl SyncApi<Client<HttpsConnector<HttpConnector>>> {
     /// Creates a sync API client using an Https connector.
     ///
     /// Use [`SyncApi::new_with_connector`] to set another backend, such as an HTTP only backend.
     pub fn new() -> Result<Self, SyncError> {
         Ok(Self::new_with_connector(HttpsConnector::new())?)
     }
+
+    /// Creates a sync API client using an Https connector, which authenticates with an existing auth token and never
+    /// calls the login endpoint.
+    ///
+    /// See [`Api::new_with_token`].
+    pub fn new_with_token<U: Into<Token>>(token: U) -> Result<Self, SyncError> {
+        Ok(Self::new()?.with_token(token))
+    }
 }
 
-impl<C> SyncApi<C>
+impl<C> SyncApi<Client<C>>
 where
     C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
 {
     /// Creates a sync API client using a custom connector.
     pub fn new_with_connector(connector: C) -> Result<Self, io::Error> {
-        let runtime = TokioRuntime::new()?;
-        let hyper = Client::builder().build(connector);
-        Ok(SyncApi {
-            runtime,
-            client: Api::new(hyper),
-        })
+        Self::new_with_transport(Client::builder().build(connector))
     }
 }
 
-impl<C> Deref for SyncApi<C> {
-    type Target = Api<C>;
+impl<T> SyncApi<T>
+where
+    T: HttpTransport,
+{
+    /// Creates a sync API client using the given HTTP client, such as a `reqwest::Client` when the `reqwest`
+    /// feature is enabled.
+    pub fn new_with_transport(transport: T) -> Result<Self, io::Error> {
+        Self::from_api(Api::new(transport))
+    }
+
+    /// Creates a sync API client wrapping the given async client, on a new tokio runtime.
+    ///
+    /// To configure both in one place, see [`ApiBuilder::build_sync`].
+    ///
+    /// [`ApiBuilder::build_sync`]: ../struct.ApiBuilder.html#method.build_sync
+    pub fn from_api(client: Api<T>) -> Result<Self, io::Error> {
+        Ok(Self::from_api_with_runtime(client, TokioRuntime::new()?))
+    }
+
+    /// Creates a sync API client wrapping the given async client, running requests on the given tokio runtime.
+    pub fn from_api_with_runtime(client: Api<T>, runtime: TokioRuntime) -> Self {
+        SyncApi {
+            blocking: Blocking {
+                runtime: Arc::new(runtime),
+                timeout: None,
+                interrupt: Interrupt::default(),
+            },
+            client,
+        }
+    }
+}
+
+impl<T> Deref for SyncApi<T> {
+    type Target = Api<T>;
 
     fn deref(&self) -> &Self::Target {
         &self.client
     }
 }
 
-impl<C> DerefMut for SyncApi<C> {
+impl<T> DerefMut for SyncApi<T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.client
     }
 }
 
-impl<C> SyncApi<C>
+impl<T> SyncApi<T>
 where
-    C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
+    T: HttpTransport,
 {
     /// Sets the server url this api client will use, and returns the client.
     ///
@@ -133,11 +304,52 @@ where
     ///
     /// See also [`Api::set_token`].
     #[inline]
-    pub fn with_token<T: Into<Token>>(mut self, token: T) -> Self {
+    pub fn with_token<U: Into<Token>>(mut self, token: U) -> Self {
         self.set_token(token.into());
         self
     }
 
+    /// Sets how long each call may take in total, including any retries, waiting for rate limits and logging in
+    /// again, or `None` to wait indefinitely.
+    ///
+    /// Calls which take longer fail with `ErrorKind::Timeout`, abandoning their requests. There is no deadline by
+    /// default. Timing out uses `tokio::time`, so a runtime given to [`SyncApi::from_api_with_runtime`] must have
+    /// time enabled.
+    ///
+    /// This is separate from [`Api::set_timeout`], which limits how long each request waits for a response.
+    ///
+    /// [`SyncApi::from_api_with_runtime`]: #method.from_api_with_runtime
+    /// [`Api::set_timeout`]: ../struct.Api.html#method.set_timeout
+    #[inline]
+    pub fn set_call_timeout(&mut self, timeout: Option<Duration>) {
+        self.blocking.timeout = timeout;
+    }
+
+    /// Sets how long each call may take in total, and returns the client.
+    ///
+    /// See also [`SyncApi::set_call_timeout`].
+    ///
+    /// [`SyncApi::set_call_timeout`]: #method.set_call_timeout
+    #[inline]
+    pub fn with_call_timeout(mut self, timeout: Duration) -> Self {
+        self.set_call_timeout(Some(timeout));
+        self
+    }
+
+    /// Retrieves how long each call may take in total, if limited.
+    #[inline]
+    pub fn call_timeout(&self) -> Option<Duration> {
+        self.blocking.timeout
+    }
+
+    /// Retrieves a handle which interrupts the calls in progress on this client and its clones.
+    ///
+    /// See [`Interrupt`](struct.Interrupt.html) for more information.
+    #[inline]
+    pub fn interrupt_handle(&self) -> Interrupt {
+        self.blocking.interrupt.clone()
+    }
+
     /// Logs in with the given username and password and stores the authenticated token in self.
     ///
     /// *Note:* since [the official server implemented auth tokens][blog], this method has only
@@ -147,16 +359,16 @@ where
     ///
     /// [blog]: https://blog.screeps.com/2017/12/auth-tokens/
     /// [screepsmod-auth]: https://github.com/ScreepsMods/screepsmod-auth
-    pub fn login<'b, U, V>(&mut self, username: U, password: V) -> Result<(), Error>
+    pub fn login<'b, U, V>(&self, username: U, password: V) -> Result<(), Error>
     where
         U: Into<Cow<'b, str>>,
         V: Into<Cow<'b, str>>,
     {
         let result = self
-            .runtime
+            .blocking
             .block_on(self.client.login(username, password))?;
 
-        result.return_to(&self.client.auth_token);
+        result.return_to(&*self.client.token_storage());
 
         Ok(())
     }
@@ -168,43 +380,102 @@ where
     /// works on the official server.
     ///
     /// [screepsmod-auth]: https://github.com/ScreepsMods/screepsmod-auth
-    pub fn register(&mut self, details: RegistrationArgs) -> Result<RegistrationSuccess, Error> {
-        self.runtime.block_on(self.client.register(details))
+    pub fn register(&self, details: RegistrationArgs) -> Result<RegistrationSuccess, Error> {
+        self.blocking.block_on(self.client.register(details))
     }
 
     /// Gets user information on the user currently logged in, including username and user id.
     ///
     /// See [`Api::my_info`](../struct.Api.html#method.my_info) for more information.
-    pub fn my_info(&mut self) -> Result<MyInfo, Error> {
-        self.runtime.block_on(self.client.my_info()?)
+    pub fn my_info(&self) -> Result<MyInfo, Error> {
+        self.blocking.block_on(self.client.my_info()?)
+    }
+
+    /// Gets a page of the logged in user's credit transactions.
+    ///
+    /// See [`Api::money_history`](../struct.Api.html#method.money_history) for more information.
+    pub fn money_history(&self, page: u32) -> Result<MoneyHistory, Error> {
+        self.blocking.block_on(self.client.money_history(page)?)
+    }
+
+    /// Looks up a user's public information by username.
+    ///
+    /// See [`Api::find_user`](../struct.Api.html#method.find_user) for more information.
+    pub fn find_user<'b, U>(&self, username: U) -> Result<UserInfo, Error>
+    where
+        U: Into<Cow<'b, str>>,
+    {
+        self.blocking.block_on(self.client.find_user(username))
+    }
+
+    /// Looks up a user's public information by user ID.
+    ///
+    /// See [`Api::find_user_by_id`](../struct.Api.html#method.find_user_by_id) for more information.
+    pub fn find_user_by_id<'b, U>(&self, user_id: U) -> Result<UserInfo, Error>
+    where
+        U: Into<Cow<'b, str>>,
+    {
+        self.blocking.block_on(self.client.find_user_by_id(user_id))
     }
 
     /// Gets the world shard and room name the server thinks the client should start with viewing.
     ///
     /// See [`Api::world_start_room`](../struct.Api.html#method.world_start_room) for more information.
-    pub fn world_start_room(&mut self) -> Result<WorldStartRoom, Error> {
-        self.runtime.block_on(self.client.world_start_room()?)
+    pub fn world_start_room(&self) -> Result<WorldStartRoom, Error> {
+        self.blocking.block_on(self.client.world_start_room()?)
     }
 
     /// Gets the room name the server thinks the client should start with viewing for a particular shard.
     ///
     /// See [`Api::world_start_room`](../struct.Api.html#method.world_start_room) for more information.
-    pub fn shard_start_room<'b, U>(&mut self, shard: U) -> Result<WorldStartRoom, Error>
+    pub fn shard_start_room<'b, U>(&self, shard: U) -> Result<WorldStartRoom, Error>
     where
         U: Into<Cow<'b, str>>,
     {
-        self.runtime.block_on(self.client.shard_start_room(shard)?)
+        self.blocking.block_on(self.client.shard_start_room(shard)?)
     }
 
     /// Get information on a number of rooms.
     ///
     /// See [`Api::map_stats`](../struct.Api.html#method.map_stats) for more information.
-    pub fn map_stats<'a, U, V>(&mut self, shard: &'a str, rooms: &'a V) -> Result<MapStats, Error>
+    pub fn map_stats<'a, U, V>(&self, shard: &'a str, rooms: &'a V) -> Result<MapStats, Error>
+    where
+        U: AsRef<str>,
+        &'a V: IntoIterator<Item = U>,
+    {
+        self.blocking.block_on(self.client.map_stats(shard, rooms)?)
+    }
+
+    /// Get information on a number of rooms, along with the given statistic of each room.
+    ///
+    /// See [`Api::map_stats_with_stat`](../struct.Api.html#method.map_stats_with_stat) for more information.
+    pub fn map_stats_with_stat<'a, U, V>(
+        &self,
+        shard: &'a str,
+        rooms: &'a V,
+        stat: MapStatName,
+    ) -> Result<MapStats, Error>
     where
         U: AsRef<str>,
         &'a V: IntoIterator<Item = U>,
     {
-        self.runtime.block_on(self.client.map_stats(shard, rooms)?)
+        self.blocking
+            .block_on(self.client.map_stats_with_stat(shard, rooms, stat)?)
+    }
+
+    /// Get information on a number of rooms on any shards, along with the given statistic of each room.
+    ///
+    /// See [`Api::map_stats_on`](../struct.Api.html#method.map_stats_on) for more information.
+    pub fn map_stats_on<'a, V>(
+        &self,
+        rooms: &'a V,
+        stat: MapStatName,
+    ) -> Result<Vec<(String, MapStats)>, Error>
+    where
+        &'a V: IntoIterator<Item = &'a ShardRoomName>,
+    {
+        self.blocking
+            .block_on(self.client.map_stats_on(rooms, stat)?)
     }
 
     /// Gets the overview of a room, returning totals for usually 3 intervals, 8, 180 and 1440, representing
@@ -212,7 +483,7 @@ where
     ///
     /// See [`Api::room_overview`](../struct.Api.html#method.room_overview) for more information.
     pub fn room_overview<'b, U, V>(
-        &mut self,
+        &self,
         shard: U,
         room_name: V,
         request_interval: u32,
@@ -221,17 +492,29 @@ where
         U: Into<Cow<'b, str>>,
         V: Into<Cow<'b, str>>,
     {
-        self.runtime.block_on(
+        self.blocking.block_on(
             self.client
                 .room_overview(shard, room_name, request_interval)?,
         )
     }
 
+    /// Gets the overview of a room on a shard.
+    ///
+    /// See [`Api::room_overview`](../struct.Api.html#method.room_overview) for more information.
+    pub fn room_overview_on(
+        &self,
+        room: &ShardRoomName,
+        request_interval: u32,
+    ) -> Result<RoomOverview, Error> {
+        self.blocking
+            .block_on(self.client.room_overview_on(room, request_interval)?)
+    }
+
     /// Gets the terrain of a room, returning a 2d array of 50x50 points.
     ///
     /// See [`Api::room_terrain`](../struct.Api.html#method.room_terrain) for more information.
     pub fn room_terrain<'b, U, V>(
-        &mut self,
+        &self,
         shard: Option<U>,
         room_name: V,
     ) -> Result<RoomTerrain, Error>
@@ -239,40 +522,79 @@ where
         U: Into<Cow<'b, str>>,
         V: Into<Cow<'b, str>>,
     {
-        self.runtime
+        self.blocking
             .block_on(self.client.room_terrain(shard, room_name))
     }
 
+    /// Gets the terrain of a room on a shard, returning a 2d array of 50x50 points.
+    ///
+    /// See [`Api::room_terrain`](../struct.Api.html#method.room_terrain) for more information.
+    pub fn room_terrain_on(&self, room: &ShardRoomName) -> Result<RoomTerrain, Error> {
+        self.blocking.block_on(self.client.room_terrain_on(room))
+    }
+
+    /// Gets every object in a room, along with the users who own them.
+    ///
+    /// Only available with the `sockets` feature. See [`Api::room_objects`](../struct.Api.html#method.room_objects)
+    /// for more information.
+    #[cfg(feature = "sockets")]
+    pub fn room_objects<'b, U, V>(
+        &self,
+        shard: Option<U>,
+        room_name: V,
+    ) -> Result<RoomObjects, Error>
+    where
+        U: Into<Cow<'b, str>>,
+        V: Into<Cow<'b, str>>,
+    {
+        self.blocking
+            .block_on(self.client.room_objects(shard, room_name))
+    }
+
     /// Gets a list of shards available on this server. Errors with a `404` error when connected to a
     /// non-sharded server.
     ///
     /// See [`Api::shard_list`](../struct.Api.html#method.shard_list) for more information.
-    pub fn shard_list(&mut self) -> Result<Vec<ShardInfo>, Error> {
-        self.runtime.block_on(self.client.shard_list())
+    pub fn shard_list(&self) -> Result<Vec<ShardInfo>, Error> {
+        self.blocking.block_on(self.client.shard_list())
+    }
+
+    /// Gets the server's version, and which features it has.
+    ///
+    /// See [`Api::version`](../struct.Api.html#method.version) for more information.
+    pub fn version(&self) -> Result<ServerVersion, Error> {
+        self.blocking.block_on(self.client.version())
+    }
+
+    /// Gets the server's version, and which features it has, requesting it the first time only.
+    ///
+    /// See [`Api::capabilities`](../struct.Api.html#method.capabilities) for more information.
+    pub fn capabilities(&self) -> Result<ServerVersion, Error> {
+        self.blocking.block_on(self.client.capabilities())
     }
 
     /// Gets the "status" of a room: if it is open, if it is in a novice area, if it exists.
     ///
     /// See [`Api::room_status`](../struct.Api.html#method.room_status) for more information.
-    pub fn room_status<'b, U>(&mut self, room_name: U) -> Result<RoomStatus, Error>
+    pub fn room_status<'b, U>(&self, room_name: U) -> Result<RoomStatus, Error>
     where
         U: Into<Cow<'b, str>>,
     {
-        self.runtime.block_on(self.client.room_status(room_name)?)
+        self.blocking.block_on(self.client.room_status(room_name)?)
     }
 
     /// Experimental endpoint to get all rooms in which PvP has recently occurred.
     ///
     /// See [`Api::recent_pvp`](../struct.Api.html#method.recent_pvp) for more information.
-    pub fn recent_pvp(&mut self, details: RecentPvpArgs) -> Result<RecentPvp, Error> {
-        self.runtime.block_on(self.client.recent_pvp(details))
+    pub fn recent_pvp(&self, details: RecentPvpArgs) -> Result<RecentPvp, Error> {
+        self.blocking.block_on(self.client.recent_pvp(details))
     }
 
     /// Gets a list of all past leaderboard seasons, with end dates, display names, and season ids for each season.
     ///
     /// See [`Api::leaderboard_season_list`](../struct.Api.html#method.leaderboard_season_list) for more information.
-    pub fn leaderboard_season_list(&mut self) -> Result<Vec<LeaderboardSeason>, Error> {
-        self.runtime
+    pub fn leaderboard_season_list(&self) -> Result<Vec<LeaderboardSeason>, Error> {
+        self.blocking
             .block_on(self.client.leaderboard_season_list()?)
     }
 
@@ -282,7 +604,7 @@ where
     ///
     /// [`Api::find_season_leaderboard_rank`]: ../struct.Api.html#method.find_season_leaderboard_rank
     pub fn find_season_leaderboard_rank<'b, U, V>(
-        &mut self,
+        &self,
         leaderboard_type: LeaderboardType,
         username: U,
         season: V,
@@ -291,7 +613,7 @@ where
         U: Into<Cow<'b, str>>,
         V: Into<Cow<'b, str>>,
     {
-        self.runtime
+        self.blocking
             .block_on(self.client.find_season_leaderboard_rank(
                 leaderboard_type,
                 username,
@@ -303,14 +625,14 @@ where
     ///
     /// See [`Api::leaderboard_page`](../struct.Api.html#method.find_leaderboard_ranks) for more information.
     pub fn find_leaderboard_ranks<'b, U>(
-        &mut self,
+        &self,
         leaderboard_type: LeaderboardType,
         username: U,
     ) -> Result<Vec<FoundUserRank>, Error>
     where
         U: Into<Cow<'b, str>>,
     {
-        self.runtime.block_on(
+        self.blocking.block_on(
             self.client
                 .find_leaderboard_ranks(leaderboard_type, username)?,
         )
@@ -320,7 +642,7 @@ where
     ///
     /// See [`Api::leaderboard_page`](../struct.Api.html#method.leaderboard_page) for more information.
     pub fn leaderboard_page<'b, U>(
-        &mut self,
+        &self,
         leaderboard_type: LeaderboardType,
         season: U,
         limit: u32,
@@ -329,7 +651,7 @@ where
     where
         U: Into<Cow<'b, str>>,
     {
-        self.runtime.block_on(self.client.leaderboard_page(
+        self.blocking.block_on(self.client.leaderboard_page(
             leaderboard_type,
             season,
             limit,
@@ -337,18 +659,97 @@ where
         )?)
     }
 
+    /// Sends many requests, at most `concurrency` at a time, and collects their results in the same order as the
+    /// requests.
+    ///
+    /// See [`Api::batch`](../struct.Api.html#method.batch) for more information.
+    ///
+    /// If the batch times out or is interrupted, every request fails with the same error.
+    pub fn batch<R, I>(&self, requests: I, concurrency: usize) -> Vec<Result<R, Error>>
+    where
+        R: ParseResponse,
+        I: IntoIterator<Item = Request<R>>,
+    {
+        let requests = requests.into_iter().collect::<Vec<_>>();
+        let count = requests.len();
+        match self.blocking.run(self.client.batch(requests, concurrency)) {
+            Ok(results) => results,
+            Err(stopped) => (0..count).map(|_| Err(stopped.into())).collect(),
+        }
+    }
+
+    /// Walks the leaderboard for a given season from the top, one page at a time.
+    ///
+    /// See [`Api::leaderboard_pages`](../struct.Api.html#method.leaderboard_pages) for more information.
+    pub fn leaderboard_pages<'b, U>(
+        &self,
+        leaderboard_type: LeaderboardType,
+        season: U,
+        limit: u32,
+    ) -> Result<LeaderboardPages<'_>, Error>
+    where
+        U: Into<Cow<'b, str>>,
+    {
+        let pages = self
+            .client
+            .leaderboard_pages(leaderboard_type, season, limit)?;
+        Ok(LeaderboardPages {
+            blocking: &self.blocking,
+            pages,
+        })
+    }
+
+    /// Gets how many orders each resource has on the market, on the given shard.
+    ///
+    /// See [`Api::market_orders_index`](../struct.Api.html#method.market_orders_index) for more information.
+    pub fn market_orders_index<'b, U>(&self, shard: Option<U>) -> Result<MarketOrdersIndex, Error>
+    where
+        U: Into<Cow<'b, str>>,
+    {
+        self.blocking
+            .block_on(self.client.market_orders_index(shard)?)
+    }
+
+    /// Gets all active orders buying or selling the given resource, on the given shard.
+    ///
+    /// See [`Api::market_orders`](../struct.Api.html#method.market_orders) for more information.
+    pub fn market_orders<'b, U>(
+        &self,
+        shard: Option<U>,
+        resource_type: &ResourceType,
+    ) -> Result<MarketOrders, Error>
+    where
+        U: Into<Cow<'b, str>>,
+    {
+        self.blocking
+            .block_on(self.client.market_orders(shard, resource_type)?)
+    }
+
+    /// Gets the logged in user's market orders, on all shards.
+    ///
+    /// See [`Api::my_market_orders`](../struct.Api.html#method.my_market_orders) for more information.
+    pub fn my_market_orders(&self) -> Result<MyMarketOrders, Error> {
+        self.blocking.block_on(self.client.my_market_orders()?)
+    }
+
     /// Gets a player's memory segment
-    pub fn memory_segment<'b, U>(&mut self, shard: Option<U>, segment: u32) -> Result<String, Error>
+    pub fn memory_segment<'b, U>(&self, shard: Option<U>, segment: u32) -> Result<String, Error>
     where
         U: Into<Cow<'b, str>>,
     {
-        self.runtime
+        self.blocking
             .block_on(self.client.memory_segment(shard, segment)?)
     }
 
+    /// Gets a player's memory segment on the shard a room is on
+    pub fn memory_segment_on(&self, room: &ShardRoomName, segment: u32) -> Result<String, Error> {
+        self.blocking
+            .block_on(self.client.memory_segment_on(room, segment)?)
+    }
+
     /// Sets a player's memory segment
     pub fn set_memory_segment<'b, U, V>(
-        &mut self,
+        &self,
         shard: Option<U>,
         segment: u32,
         data: V,
@@ -357,7 +758,109 @@ where
         U: Into<Cow<'b, str>>,
         V: Into<Cow<'b, str>>,
     {
-        self.runtime
+        self.blocking
             .block_on(self.client.set_memory_segment(shard, segment, data)?)
     }
+
+    /// Sets a player's memory segment on the shard a room is on
+    pub fn set_memory_segment_on<'b, V>(
+        &self,
+        room: &ShardRoomName,
+        segment: u32,
+        data: V,
+    ) -> Result<(), Error>
+    where
+        V: Into<Cow<'b, str>>,
+    {
+        self.blocking
+            .block_on(self.client.set_memory_segment_on(room, segment, data)?)
+    }
+}
+
+/// Iterator over the pages of a leaderboard, fetching each page as it is reached.
+///
+/// Created by [`SyncApi::leaderboard_pages`](struct.SyncApi.html#method.leaderboard_pages).
+pub struct LeaderboardPages<'a> {
+    blocking: &'a Blocking,
+    pages: BoxStream<'static, Result<LeaderboardPage, Error>>,
+}
+
+impl fmt::Debug for LeaderboardPages<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LeaderboardPages").finish()
+    }
+}
+
+impl Iterator for LeaderboardPages<'_> {
+    type Item = Result<LeaderboardPage, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.blocking.run(self.pages.next()) {
+            Ok(page) => page,
+            Err(stopped) => Some(Err(stopped.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use super::SyncApi;
+    use crate::testing::{Mock, MockTransport, Stalled};
+
+    #[test]
+    fn interrupt_hung_calls() {
+        // the timeout only stops the test hanging if interrupting is broken.
+        let api = SyncApi::new_with_transport(Stalled)
+            .unwrap()
+            .with_call_timeout(Duration::from_secs(60));
+
+        // the call may not have started by the first interruption, so keep interrupting until it returns.
+        let interrupt = api.interrupt_handle();
+        let returned = Arc::new(AtomicBool::new(false));
+        let interrupter = {
+            let returned = returned.clone();
+            thread::spawn(move || {
+                while !returned.load(Ordering::SeqCst) {
+                    interrupt.interrupt();
+                    thread::sleep(Duration::from_millis(10));
+                }
+            })
+        };
+        let err = api.shard_list().unwrap_err();
+        returned.store(true, Ordering::SeqCst);
+        interrupter.join().unwrap();
+        assert!(err.is_cancelled());
+
+        // later calls are not interrupted.
+        let api = api.with_call_timeout(Duration::from_millis(10));
+        let err = api.shard_list().unwrap_err();
+        assert!(err.is_timeout());
+        assert!(!err.is_cancelled());
+    }
+
+    #[test]
+    fn share_between_threads() {
+        let transport = MockTransport::new()
+            .with_mock(Mock::get("game/shards/info").with_json(json!({ "ok": 1, "shards": [] })));
+        let api = SyncApi::new_with_transport(transport).unwrap();
+
+        let threads = (0..4)
+            .map(|_| {
+                let api = api.clone();
+                thread::spawn(move || api.shard_list().unwrap())
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            assert!(thread.join().unwrap().is_empty());
+        }
+    }
 }
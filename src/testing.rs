@@ -0,0 +1,480 @@
+//! Transports serving canned or recorded responses, for testing applications without a server.
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use bytes::Bytes;
+use futures::future::{self, BoxFuture, FutureExt};
+use http::{header::HeaderName, HeaderValue, Method, StatusCode};
+
+use crate::{error::Error, redact, transport::HttpTransport};
+
+/// A canned response, and the requests it answers.
+///
+/// A mock answers requests to its endpoint which have all of its query parameters and request headers, and which use
+/// its method, if it has one. It answers with `200 OK` and an empty body unless set otherwise.
+#[derive(Clone, Debug)]
+pub struct Mock {
+    method: Option<Method>,
+    endpoint: String,
+    query: Vec<(String, String)>,
+    request_headers: Vec<(HeaderName, HeaderValue)>,
+    status: StatusCode,
+    headers: http::HeaderMap,
+    body: Bytes,
+}
+
+impl Mock {
+    /// Creates a mock answering requests with any method to the given endpoint.
+    ///
+    /// The endpoint is the path relative to the API url, such as `"game/room-terrain"`.
+    pub fn any<E: Into<String>>(endpoint: E) -> Self {
+        Mock {
+            method: None,
+            endpoint: endpoint.into(),
+            query: Vec::new(),
+            request_headers: Vec::new(),
+            status: StatusCode::OK,
+            headers: http::HeaderMap::new(),
+            body: Bytes::new(),
+        }
+    }
+
+    /// Creates a mock answering `GET` requests to the given endpoint.
+    pub fn get<E: Into<String>>(endpoint: E) -> Self {
+        Mock {
+            method: Some(Method::GET),
+            ..Mock::any(endpoint)
+        }
+    }
+
+    /// Creates a mock answering `POST` requests to the given endpoint.
+    pub fn post<E: Into<String>>(endpoint: E) -> Self {
+        Mock {
+            method: Some(Method::POST),
+            ..Mock::any(endpoint)
+        }
+    }
+
+    /// Only answers requests with the given query parameter, and returns the mock.
+    pub fn with_query<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        self.query.push((name.into(), value.into()));
+        self
+    }
+
+    /// Only answers requests with the given header, and returns the mock.
+    pub fn with_request_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.request_headers.push((name, value));
+        self
+    }
+
+    /// Sets the status code to respond with, and returns the mock.
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Adds a header to respond with, and returns the mock.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Sets the body to respond with to the given JSON, and returns the mock.
+    pub fn with_json(self, json: serde_json::Value) -> Self {
+        self.with_body(json.to_string())
+    }
+
+    /// Sets the body to respond with, and returns the mock.
+    pub fn with_body<B: Into<Bytes>>(mut self, body: B) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    fn matches(&self, request: &http::Request<Bytes>) -> bool {
+        let uri = request.uri();
+        let endpoint = format!("/{}", self.endpoint.trim_start_matches('/'));
+        if self.method.as_ref().is_some_and(|m| m != request.method())
+            || !uri.path().ends_with(&endpoint)
+        {
+            return false;
+        }
+
+        let headers = request.headers();
+        if !self
+            .request_headers
+            .iter()
+            .all(|(name, value)| headers.get_all(name).iter().any(|v| v == value))
+        {
+            return false;
+        }
+
+        let query = url::form_urlencoded::parse(uri.query().unwrap_or("").as_bytes())
+            .into_owned()
+            .collect::<Vec<_>>();
+        self.query.iter().all(|pair| query.contains(pair))
+    }
+
+    fn response(&self) -> http::Response<Bytes> {
+        let mut response = http::Response::new(self.body.clone());
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers.clone();
+        response
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    mocks: Vec<Mock>,
+    requests: Vec<http::Request<Bytes>>,
+}
+
+/// An [`HttpTransport`] which answers requests with canned responses, and records the requests it was sent.
+///
+/// Each request is answered by the first added [`Mock`] matching it. Requests no mock matches fail with a transport
+/// error.
+///
+/// When cloned, the clone will share the same mocks and recorded requests, so one clone can be given to an [`Api`]
+/// and the other kept to check what was sent.
+///
+/// ```
+/// use screeps_api::{
+///     testing::{Mock, MockTransport},
+///     Api,
+/// };
+///
+/// let transport = MockTransport::new().with_mock(Mock::get("game/shards/info").with_json(
+///     serde_json::json!({
+///         "ok": 1,
+///         "shards": [{ "name": "shard0", "rooms": 1, "users": 2, "tick": 3000 }],
+///     }),
+/// ));
+/// let api = Api::new(transport.clone());
+///
+/// let shards = futures::executor::block_on(api.shard_list()).unwrap();
+/// assert_eq!(shards[0].name, "shard0");
+/// assert_eq!(transport.requests().len(), 1);
+/// ```
+///
+/// [`HttpTransport`]: ../transport/trait.HttpTransport.html
+/// [`Mock`]: struct.Mock.html
+/// [`Api`]: ../struct.Api.html
+#[derive(Clone, Debug, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockTransport {
+    /// Creates a transport without any mocks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a mock, after any previously added ones.
+    pub fn add_mock(&self, mock: Mock) {
+        self.lock().mocks.push(mock);
+    }
+
+    /// Adds a mock, after any previously added ones, and returns the transport.
+    pub fn with_mock(self, mock: Mock) -> Self {
+        self.add_mock(mock);
+        self
+    }
+
+    /// Retrieves the requests sent so far, in order.
+    pub fn requests(&self) -> Vec<http::Request<Bytes>> {
+        self.lock()
+            .requests
+            .iter()
+            .map(|request| {
+                let mut copy = http::Request::new(request.body().clone());
+                *copy.method_mut() = request.method().clone();
+                *copy.uri_mut() = request.uri().clone();
+                *copy.headers_mut() = request.headers().clone();
+                copy
+            })
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl HttpTransport for MockTransport {
+    fn send(
+        &self,
+        request: http::Request<Bytes>,
+    ) -> BoxFuture<'static, Result<http::Response<Bytes>, Error>> {
+        let mut state = self.lock();
+        let response = match state.mocks.iter().find(|mock| mock.matches(&request)) {
+            Some(mock) => Ok(mock.response()),
+            None => Err(Error::transport(format!(
+                "no mock response for {} {}",
+                request.method(),
+                request.uri()
+            ))),
+        };
+        state.requests.push(request);
+
+        future::ready(response).boxed()
+    }
+}
+
+/// An [`HttpTransport`] which never responds, for testing timeouts and cancellation.
+///
+/// [`HttpTransport`]: ../transport/trait.HttpTransport.html
+#[cfg(test)]
+pub(crate) struct Stalled;
+
+#[cfg(test)]
+impl HttpTransport for Stalled {
+    fn send(
+        &self,
+        _: http::Request<Bytes>,
+    ) -> BoxFuture<'static, Result<http::Response<Bytes>, Error>> {
+        future::pending().boxed()
+    }
+}
+
+/// A recorded response, as stored in a fixture file.
+#[derive(serde_derive::Serialize, serde_derive::Deserialize, Debug)]
+struct Fixture {
+    method: String,
+    uri: String,
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+#[derive(Clone)]
+enum Mode {
+    Record(Arc<dyn HttpTransport>),
+    Replay,
+}
+
+/// An [`HttpTransport`] which records responses from a real server to fixture files, or replays them.
+///
+/// In record mode, every request is sent with another transport, and its response is written to a JSON file in the
+/// fixture directory. In replay mode, requests are answered from those files without any network access, so tests
+/// using them are fast and reproducible. Requests without a recorded response fail with a transport error.
+///
+/// Responses are matched by method, path, query and body, and not by server, so fixtures recorded from one server can
+/// be replayed with any API url. Auth tokens and passwords are never written: headers carrying them are left out, and
+/// `token` or `password` fields in JSON bodies are replaced with `"<redacted>"`.
+///
+/// ```no_run
+/// use screeps_api::{testing::Recorder, Api};
+///
+/// let transport = if std::env::var_os("RECORD").is_some() {
+///     Recorder::record(hyper::Client::new(), "tests/fixtures")
+/// } else {
+///     Recorder::replay("tests/fixtures")
+/// };
+/// let api = Api::new(transport);
+/// ```
+///
+/// [`HttpTransport`]: ../transport/trait.HttpTransport.html
+#[derive(Clone)]
+pub struct Recorder {
+    mode: Mode,
+    dir: PathBuf,
+}
+
+impl std::fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mode = match self.mode {
+            Mode::Record(_) => "record",
+            Mode::Replay => "replay",
+        };
+        f.debug_struct("Recorder")
+            .field("mode", &mode)
+            .field("dir", &self.dir)
+            .finish()
+    }
+}
+
+impl Recorder {
+    /// Creates a transport which sends requests with the given transport, and records the responses in the given
+    /// directory, creating it if needed.
+    pub fn record<T: HttpTransport, P: Into<PathBuf>>(transport: T, dir: P) -> Self {
+        Recorder {
+            mode: Mode::Record(Arc::new(transport)),
+            dir: dir.into(),
+        }
+    }
+
+    /// Creates a transport which answers requests with the responses recorded in the given directory.
+    pub fn replay<P: Into<PathBuf>>(dir: P) -> Self {
+        Recorder {
+            mode: Mode::Replay,
+            dir: dir.into(),
+        }
+    }
+
+    /// The directory fixtures are kept in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The fixture file for a request.
+    fn path(&self, request: &http::Request<Bytes>) -> PathBuf {
+        let uri = request.uri();
+        let endpoint = uri
+            .path()
+            .rsplit("/api/")
+            .next()
+            .unwrap_or("")
+            .replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_");
+
+        // FNV-1a, so that names stay the same across Rust versions.
+        let body = redact::body(request.body().clone());
+        let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+        let query = uri.query().unwrap_or("").as_bytes();
+        for &byte in query.iter().chain(&[0]).chain(body.iter()) {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+
+        self.dir.join(format!(
+            "{}-{}-{:016x}.json",
+            request.method().as_str().to_lowercase(),
+            endpoint,
+            hash
+        ))
+    }
+}
+
+fn write_fixture(path: &Path, fixture: &Fixture) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let contents = serde_json::to_vec_pretty(fixture)?;
+    fs::write(path, contents)
+}
+
+fn read_fixture(path: &Path) -> Result<http::Response<Bytes>, Error> {
+    let fixture: Fixture = serde_json::from_slice(&fs::read(path)?)?;
+
+    let mut response = http::Response::new(Bytes::from(fixture.body));
+    *response.status_mut() = StatusCode::from_u16(fixture.status).map_err(Error::transport)?;
+    for (name, value) in &fixture.headers {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(Error::transport)?;
+        let value = HeaderValue::from_str(value).map_err(Error::transport)?;
+        response.headers_mut().append(name, value);
+    }
+    Ok(response)
+}
+
+impl HttpTransport for Recorder {
+    fn send(
+        &self,
+        request: http::Request<Bytes>,
+    ) -> BoxFuture<'static, Result<http::Response<Bytes>, Error>> {
+        let path = self.path(&request);
+
+        let transport = match self.mode {
+            Mode::Record(ref transport) => transport.clone(),
+            Mode::Replay => {
+                let response = read_fixture(&path).map_err(|e| {
+                    Error::transport(format!(
+                        "no recorded response for {} {} in {}: {}",
+                        request.method(),
+                        request.uri(),
+                        path.display(),
+                        e
+                    ))
+                });
+                return future::ready(response).boxed();
+            }
+        };
+
+        let method = request.method().to_string();
+        let uri = request.uri().to_string();
+        async move {
+            let response = transport.send(request).await?;
+
+            let headers = response
+                .headers()
+                .iter()
+                .filter(|&(name, _)| !redact::is_sensitive_header(name))
+                .filter_map(|(name, value)| {
+                    Some((name.as_str().to_owned(), value.to_str().ok()?.to_owned()))
+                })
+                .collect();
+            let fixture = Fixture {
+                method,
+                uri,
+                status: response.status().as_u16(),
+                headers,
+                body: String::from_utf8_lossy(&redact::body(response.body().clone())).into_owned(),
+            };
+            if let Err(e) = write_fixture(&path, &fixture) {
+                warn!("failed to record response to {}: {}", path.display(), e);
+            }
+
+            Ok(response)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{Mock, MockTransport, Recorder};
+    use crate::Api;
+
+    #[test]
+    fn match_by_query() {
+        let status = |room: &str| json!({ "ok": 1, "room": { "_id": room, "status": "normal" } });
+        let transport = MockTransport::new()
+            .with_mock(
+                Mock::get("game/room-status")
+                    .with_query("room", "E1N1")
+                    .with_json(status("E1N1")),
+            )
+            .with_mock(Mock::get("game/room-status").with_json(status("W1S1")));
+        let api = Api::new(transport.clone()).with_token("token");
+
+        let matched = futures::executor::block_on(api.room_status("E1N1").unwrap()).unwrap();
+        assert_eq!(matched.room_name.unwrap().to_string(), "E1N1");
+
+        let fallback = futures::executor::block_on(api.room_status("E2N2").unwrap()).unwrap();
+        assert_eq!(fallback.room_name.unwrap().to_string(), "W1S1");
+
+        assert!(futures::executor::block_on(api.shard_list()).is_err());
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let dir =
+            std::env::temp_dir().join(format!("screeps-api-fixtures-test-{}", std::process::id()));
+        let server = MockTransport::new()
+            .with_mock(Mock::post("auth/signin").with_json(json!({ "ok": 1, "token": "secret" })));
+
+        let api = Api::new(Recorder::record(server, &dir))
+            .with_url("http://localhost:21025/api/")
+            .unwrap();
+        let logged_in = futures::executor::block_on(api.login("user", "password")).unwrap();
+        assert_eq!(logged_in.token, "secret");
+
+        for entry in fs::read_dir(&dir).unwrap() {
+            let contents = fs::read_to_string(entry.unwrap().path()).unwrap();
+            assert!(!contents.contains("secret"));
+        }
+
+        let api = Api::new(Recorder::replay(&dir))
+            .with_url("http://127.0.0.1/api/")
+            .unwrap();
+        let logged_in = futures::executor::block_on(api.login("user", "password")).unwrap();
+        assert_eq!(logged_in.token, "<redacted>");
+        assert!(futures::executor::block_on(api.login("other", "password")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
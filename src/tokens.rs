@@ -0,0 +1,441 @@
+//! Places to keep auth tokens.
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use url::Url;
+
+use crate::{redact, Token};
+
+/// Storage for the token used by a client, so that async requests can update the client's token if an updated
+/// token is returned.
+///
+/// Provided implementations keep the token in memory ([`MemoryTokenStorage`], the default), in a file
+/// ([`FileTokenStorage`]), or, with the `keyring` feature, in the operating system's keyring
+/// (`KeyringTokenStorage`). Tokens kept in a file or keyring survive restarts, and can be shared by several processes.
+///
+/// [`MemoryTokenStorage`]: struct.MemoryTokenStorage.html
+/// [`FileTokenStorage`]: struct.FileTokenStorage.html
+pub trait TokenStorage: fmt::Debug + Send + Sync + 'static {
+    /// Overwrites the previously stored token with the given token.
+    fn set(&self, token: Token);
+
+    /// Gets the current stored token.
+    fn get(&self) -> Option<Token>;
+}
+
+impl<S: TokenStorage + ?Sized> TokenStorage for Arc<S> {
+    fn set(&self, token: Token) {
+        (**self).set(token)
+    }
+
+    fn get(&self) -> Option<Token> {
+        (**self).get()
+    }
+}
+
+/// Token storage in memory.
+///
+/// When cloned, the clone will share the same underlying synchronized token storage.
+#[derive(Clone, Default)]
+pub struct MemoryTokenStorage(Arc<RwLock<Option<Token>>>);
+
+impl fmt::Debug for MemoryTokenStorage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let token = self.get().map(|_| redact::REDACTED);
+        f.debug_tuple("MemoryTokenStorage").field(&token).finish()
+    }
+}
+
+impl TokenStorage for MemoryTokenStorage {
+    fn set(&self, token: Token) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Some(token);
+    }
+
+    fn get(&self) -> Option<Token> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// Tokens for several accounts on several servers, keyed by server URL and account name.
+///
+/// Set on an [`Api`] with `Api::set_token_store`, along with the account the client acts as: the client then uses the
+/// token stored for its current URL and that account, and keeps using the right token when its URL is changed. Many
+/// clients can share one store, each for a different account or server.
+///
+/// When cloned, the clone will share the same underlying tokens. Tokens are never included in debug output.
+///
+/// ```
+/// # #[cfg(feature = "http")]
+/// # fn main() {
+/// use screeps_api::tokens::TokenStore;
+///
+/// let store = TokenStore::new();
+/// let official = url::Url::parse("https://screeps.com/api/").unwrap();
+/// store.set(&official, "alice", "alice-token".into());
+///
+/// let api = screeps_api::Api::new(hyper::Client::new()).with_token_store(store.clone(), "alice");
+/// assert_eq!(api.token_storage().get().unwrap(), "alice-token");
+///
+/// let api = api.with_url("http://localhost:21025/api/").unwrap();
+/// assert_eq!(api.token_storage().get(), None);
+/// # }
+/// # #[cfg(not(feature = "http"))] fn main() {}
+/// ```
+///
+/// [`Api`]: ../struct.Api.html
+#[derive(Clone, Default)]
+pub struct TokenStore {
+    tokens: Arc<RwLock<HashMap<(String, String), Token>>>,
+}
+
+impl fmt::Debug for TokenStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TokenStore")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl TokenStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores the token for the given account on the given server, replacing any previous one.
+    pub fn set(&self, server: &Url, account: &str, token: Token) {
+        self.tokens
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key(server, account), token);
+    }
+
+    /// Gets the token stored for the given account on the given server.
+    pub fn get(&self, server: &Url, account: &str) -> Option<Token> {
+        self.tokens
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key(server, account))
+            .cloned()
+    }
+
+    /// Removes and returns the token stored for the given account on the given server.
+    pub fn remove(&self, server: &Url, account: &str) -> Option<Token> {
+        self.tokens
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&key(server, account))
+    }
+
+    /// The number of stored tokens.
+    pub fn len(&self) -> usize {
+        self.tokens
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Whether no tokens are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets storage for the token of one account on one server, backed by this store.
+    pub fn account(&self, server: &Url, account: &str) -> AccountTokenStorage {
+        AccountTokenStorage {
+            store: self.clone(),
+            server: server.clone(),
+            account: account.to_owned(),
+        }
+    }
+}
+
+/// Servers are keyed by URL, ignoring any trailing slash.
+fn key(server: &Url, account: &str) -> (String, String) {
+    (
+        server.as_str().trim_end_matches('/').to_owned(),
+        account.to_owned(),
+    )
+}
+
+/// Token storage for one account on one server, backed by a [`TokenStore`].
+///
+/// [`TokenStore`]: struct.TokenStore.html
+#[derive(Clone, Debug)]
+pub struct AccountTokenStorage {
+    store: TokenStore,
+    server: Url,
+    account: String,
+}
+
+impl AccountTokenStorage {
+    /// The server this storage is for.
+    pub fn server(&self) -> &Url {
+        &self.server
+    }
+
+    /// The account this storage is for.
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+}
+
+impl TokenStorage for AccountTokenStorage {
+    fn set(&self, token: Token) {
+        self.store.set(&self.server, &self.account, token);
+    }
+
+    fn get(&self) -> Option<Token> {
+        self.store.get(&self.server, &self.account)
+    }
+}
+
+/// Token storage in a file.
+///
+/// The file is read each time the token is needed, and replaced each time it changes, so several processes can use
+/// the same file. Replacing it is atomic: a new file is written next to it, then renamed over it. On unix, the file
+/// is only readable by its owner (permissions `0600`).
+///
+/// Errors reading or writing the file are logged, and a missing file means there is no token.
+#[derive(Clone, Debug)]
+pub struct FileTokenStorage {
+    path: PathBuf,
+}
+
+impl FileTokenStorage {
+    /// Creates storage using the file at the given path, which does not need to exist yet.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        FileTokenStorage { path: path.into() }
+    }
+
+    /// The path of the token file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn write(&self, token: &[u8]) -> io::Result<()> {
+        // each write gets its own file, so concurrent writers in other threads or processes never share one.
+        let mut temp_name = self
+            .path
+            .file_name()
+            .map(|name| name.to_owned())
+            .unwrap_or_default();
+        temp_name.push(format!(
+            ".{}.{:016x}.tmp",
+            std::process::id(),
+            rand::random::<u64>()
+        ));
+        let temp_path = self.path.with_file_name(temp_name);
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let written = options.open(&temp_path).and_then(|mut file| {
+            use std::io::Write;
+
+            file.write_all(token)?;
+            file.sync_all()
+        });
+
+        let result = written.and_then(|()| fs::rename(&temp_path, &self.path));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result
+    }
+}
+
+impl TokenStorage for FileTokenStorage {
+    fn set(&self, token: Token) {
+        if let Err(e) = self.write(&token) {
+            warn!(
+                "failed to write auth token to {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+
+    fn get(&self) -> Option<Token> {
+        match fs::read(&self.path) {
+            Ok(contents) => {
+                let token = Token::from(contents);
+                let len = token.iter().rposition(|b| !b.is_ascii_whitespace())? + 1;
+                Some(token.slice(..len))
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => {
+                warn!(
+                    "failed to read auth token from {}: {}",
+                    self.path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Token storage in the operating system's keyring, such as the macOS Keychain, the Windows Credential Manager, or
+/// the Secret Service on Linux.
+///
+/// Errors accessing the keyring are logged, and a missing entry means there is no token.
+#[cfg(feature = "keyring")]
+#[derive(Clone, Debug)]
+pub struct KeyringTokenStorage {
+    service: String,
+    account: String,
+}
+
+#[cfg(feature = "keyring")]
+impl KeyringTokenStorage {
+    /// Creates storage using the keyring entry for the given service and account names, such as
+    /// `"screeps.com"` and a username.
+    pub fn new<S: Into<String>, A: Into<String>>(service: S, account: A) -> Self {
+        KeyringTokenStorage {
+            service: service.into(),
+            account: account.into(),
+        }
+    }
+
+    fn entry(&self) -> keyring::Entry {
+        keyring::Entry::new(&self.service, &self.account)
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl TokenStorage for KeyringTokenStorage {
+    fn set(&self, token: Token) {
+        let token = match std::str::from_utf8(&token) {
+            Ok(v) => v,
+            Err(_) => {
+                warn!("not storing non-UTF-8 auth token in keyring");
+                return;
+            }
+        };
+
+        if let Err(e) = self.entry().set_password(token) {
+            warn!("failed to store auth token in keyring: {}", e);
+        }
+    }
+
+    fn get(&self) -> Option<Token> {
+        match self.entry().get_password() {
+            Ok(token) => Some(token.into()),
+            Err(keyring::Error::NoEntry) => None,
+            Err(e) => {
+                warn!("failed to read auth token from keyring: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use url::Url;
+
+    use super::{FileTokenStorage, MemoryTokenStorage, TokenStorage, TokenStore};
+
+    #[test]
+    fn shared_memory_storage() {
+        let tokens = MemoryTokenStorage::default();
+        let clone = tokens.clone();
+
+        assert_eq!(tokens.get(), None);
+        clone.set("token".into());
+        assert_eq!(tokens.get().unwrap(), "token");
+    }
+
+    #[test]
+    fn tokens_per_server_and_account() {
+        let official = Url::parse("https://screeps.com/api/").unwrap();
+        let private = Url::parse("http://localhost:21025/api/").unwrap();
+        let store = TokenStore::new();
+
+        store
+            .account(&official, "alice")
+            .set("official-alice".into());
+        store.account(&private, "alice").set("private-alice".into());
+        store.set(&official, "bob", "official-bob".into());
+
+        assert_eq!(store.len(), 3);
+        assert_eq!(
+            store.account(&official, "alice").get().unwrap(),
+            "official-alice"
+        );
+        assert_eq!(store.get(&private, "alice").unwrap(), "private-alice");
+        assert_eq!(store.get(&official, "bob").unwrap(), "official-bob");
+        assert_eq!(store.get(&private, "bob"), None);
+
+        let without_slash = Url::parse("http://localhost:21025/api").unwrap();
+        assert_eq!(store.get(&without_slash, "alice").unwrap(), "private-alice");
+    }
+
+    #[test]
+    fn file_storage() {
+        let path =
+            std::env::temp_dir().join(format!("screeps-api-token-test-{}", std::process::id()));
+        let tokens = FileTokenStorage::new(&path);
+
+        assert_eq!(tokens.get(), None);
+        tokens.set("first".into());
+        tokens.set("second".into());
+        assert_eq!(FileTokenStorage::new(&path).get().unwrap(), "second");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::write(&path, "edited\n").unwrap();
+        assert_eq!(tokens.get().unwrap(), "edited");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn file_storage_concurrent_writes() {
+        let dir = std::env::temp_dir().join(format!(
+            "screeps-api-token-concurrent-test-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("token");
+
+        let writers = (0..8)
+            .map(|idx| {
+                let tokens = FileTokenStorage::new(&path);
+                std::thread::spawn(move || tokens.set(format!("token{}", idx).into()))
+            })
+            .collect::<Vec<_>>();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert!(FileTokenStorage::new(&path)
+            .get()
+            .unwrap()
+            .starts_with(b"token"));
+        // every temporary file was renamed into place.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1122 @@
+//! HTTP clients which can be used to send API requests.
+use std::{fmt, sync::Arc, time::Duration};
+#[cfg(feature = "sync")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures::future::{BoxFuture, FutureExt};
+use url::Url;
+
+use crate::{error::Error, redact};
+
+/// An HTTP client which [`Api`] can send requests with.
+///
+/// This is implemented for `hyper::Client`, for `reqwest::Client` when the `reqwest` feature is enabled, and in
+/// browsers by `FetchTransport` when the `wasm` feature is enabled. All three ask for gzip-compressed responses, which
+/// the official server supports, and decompress them. Other HTTP clients, or test doubles, can be used by implementing
+/// it:
+///
+/// ```
+/// use bytes::Bytes;
+/// use futures::future::{BoxFuture, FutureExt};
+/// use screeps_api::{transport::HttpTransport, Error};
+///
+/// /// Responds to every request with an empty list of shards.
+/// struct NoShards;
+///
+/// impl HttpTransport for NoShards {
+///     fn send(
+///         &self,
+///         _request: http::Request<Bytes>,
+///     ) -> BoxFuture<'static, Result<http::Response<Bytes>, Error>> {
+///         let body = Bytes::from_static(br#"{"ok":1,"shards":[]}"#);
+///         futures::future::ready(Ok(http::Response::new(body))).boxed()
+///     }
+/// }
+///
+/// let api = screeps_api::Api::new(NoShards);
+/// let shards = futures::executor::block_on(api.shard_list()).unwrap();
+/// assert!(shards.is_empty());
+/// ```
+///
+/// Errors from custom clients can be reported using [`Error::transport`]. Clients receiving bodies in chunks can report
+/// their progress to the [`BodyProgress`] in the request's extensions, if there is one.
+///
+/// [`Api`]: ../struct.Api.html
+/// [`Error::transport`]: ../error/struct.Error.html#method.transport
+/// [`BodyProgress`]: struct.BodyProgress.html
+pub trait HttpTransport: Send + Sync + 'static {
+    /// Sends a request, resolving to the response with its full body.
+    ///
+    /// The response is returned as-is: non-success status codes should not be turned into errors here. Compressed
+    /// bodies should be decompressed, though, as responses are parsed without looking at their `Content-Encoding`.
+    fn send(
+        &self,
+        request: http::Request<Bytes>,
+    ) -> BoxFuture<'static, Result<http::Response<Bytes>, Error>>;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<C> HttpTransport for hyper::Client<C>
+where
+    C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
+{
+    fn send(
+        &self,
+        request: http::Request<Bytes>,
+    ) -> BoxFuture<'static, Result<http::Response<Bytes>, Error>> {
+        use hyper::body::HttpBody;
+
+        let progress = request.extensions().get::<BodyProgress>().cloned();
+        let mut request = request.map(hyper::Body::from);
+        request
+            .headers_mut()
+            .entry(http::header::ACCEPT_ENCODING)
+            .or_insert(http::HeaderValue::from_static("gzip"));
+        let response = self.request(request);
+
+        async move {
+            let (mut parts, mut body) = response.await?.into_parts();
+
+            let mut buffer = BodyBuffer::new(&parts.headers, progress);
+            while let Some(chunk) = body.data().await {
+                buffer.push(&chunk?);
+            }
+            let body = decompress(&mut parts.headers, buffer.finish())?;
+
+            Ok(http::Response::from_parts(parts, body))
+        }
+        .boxed()
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpTransport for reqwest::Client {
+    fn send(
+        &self,
+        request: http::Request<Bytes>,
+    ) -> BoxFuture<'static, Result<http::Response<Bytes>, Error>> {
+        use std::convert::TryFrom;
+
+        let client = self.clone();
+        let progress = request.extensions().get::<BodyProgress>().cloned();
+        let request = reqwest::Request::try_from(request);
+
+        async move {
+            let mut response = client.execute(request?).await?;
+
+            let status = response.status();
+            let version = response.version();
+            let headers = response.headers().clone();
+
+            let mut buffer = BodyBuffer::new(&headers, progress);
+            while let Some(chunk) = response.chunk().await? {
+                buffer.push(&chunk);
+            }
+
+            let mut result = http::Response::new(buffer.finish());
+            *result.status_mut() = status;
+            *result.version_mut() = version;
+            *result.headers_mut() = headers;
+
+            Ok(result)
+        }
+        .boxed()
+    }
+}
+
+/// Decompresses a gzip-encoded body, removing the headers describing the encoded body. Other bodies are returned as-is.
+#[cfg(not(target_arch = "wasm32"))]
+fn decompress(headers: &mut http::HeaderMap, body: Bytes) -> Result<Bytes, Error> {
+    use std::io::Read;
+
+    let gzip = headers
+        .get(http::header::CONTENT_ENCODING)
+        .is_some_and(|encoding| encoding.as_bytes().eq_ignore_ascii_case(b"gzip"));
+    if !gzip {
+        return Ok(body);
+    }
+
+    let mut decompressed = Vec::with_capacity(body.len() * 4);
+    flate2::read::GzDecoder::new(&body[..])
+        .read_to_end(&mut decompressed)
+        .map_err(|e| Error::transport(format!("invalid gzip response body: {}", e)))?;
+
+    headers.remove(http::header::CONTENT_ENCODING);
+    headers.remove(http::header::CONTENT_LENGTH);
+    Ok(decompressed.into())
+}
+
+/// Receives how much of a response body has been received, as it is received.
+///
+/// When an `Observer` is set on a client, this is added to the extensions of each request given to the
+/// [`HttpTransport`], and the transport reports to it after each chunk of the body. The observer is then told through
+/// `Observer::on_progress`, which helps to follow very large responses, such as room history or whole leaderboards.
+///
+/// [`HttpTransport`]: trait.HttpTransport.html
+#[derive(Clone)]
+pub struct BodyProgress {
+    report: Arc<dyn Fn(u64, Option<u64>) + Send + Sync>,
+}
+
+impl fmt::Debug for BodyProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BodyProgress").finish()
+    }
+}
+
+impl BodyProgress {
+    pub(crate) fn new<F>(report: F) -> Self
+    where
+        F: Fn(u64, Option<u64>) + Send + Sync + 'static,
+    {
+        BodyProgress {
+            report: Arc::new(report),
+        }
+    }
+
+    /// Reports that `received` bytes of the body have been received so far, out of `total` if the size of the body is
+    /// known, such as from the `Content-Length` header.
+    pub fn report(&self, received: u64, total: Option<u64>) {
+        (self.report)(received, total)
+    }
+}
+
+/// The most space reserved up front for a body, however large its `Content-Length` says it is.
+#[cfg(any(not(target_arch = "wasm32"), feature = "reqwest"))]
+const MAX_RESERVED: u64 = 16 * 1024 * 1024;
+
+/// Collects a response body from its chunks, reserving space for all of it up front when its size is known.
+#[cfg(any(not(target_arch = "wasm32"), feature = "reqwest"))]
+struct BodyBuffer {
+    buffer: bytes::BytesMut,
+    total: Option<u64>,
+    progress: Option<BodyProgress>,
+}
+
+#[cfg(any(not(target_arch = "wasm32"), feature = "reqwest"))]
+impl BodyBuffer {
+    fn new(headers: &http::HeaderMap, progress: Option<BodyProgress>) -> Self {
+        let total = headers
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok());
+        let reserved = total.map_or(0, |total| total.min(MAX_RESERVED) as usize);
+
+        BodyBuffer {
+            buffer: bytes::BytesMut::with_capacity(reserved),
+            total,
+            progress,
+        }
+    }
+
+    fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+        if let Some(progress) = &self.progress {
+            progress.report(self.buffer.len() as u64, self.total);
+        }
+    }
+
+    fn finish(self) -> Bytes {
+        self.buffer.freeze()
+    }
+}
+
+/// An HTTP client using the browser's `fetch` function, for use in WebAssembly built for `wasm32-unknown-unknown`.
+///
+/// Build with `default-features = false, features = ["wasm"]`, as the `sync` feature is not available in browsers.
+/// Waiting for a [`RateLimiter`], retrying and timeouts need a tokio timer, so they can't be used with this client,
+/// and neither can an [`Observer`] or a [`ResponseCache`], which use `std::time::Instant`.
+///
+/// Browsers only let pages read the `X-Token` response header when the server lists it in
+/// `Access-Control-Expose-Headers`, so tokens returned by the server may not be seen.
+///
+/// ```no_run
+/// # #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+/// # async fn run() {
+/// use screeps_api::{transport::FetchTransport, Api};
+///
+/// let api = Api::new(FetchTransport::new());
+/// let shards = api.shard_list().await.unwrap();
+/// # }
+/// ```
+///
+/// [`RateLimiter`]: ../rate_limit/struct.RateLimiter.html
+/// [`Observer`]: ../metrics/trait.Observer.html
+/// [`ResponseCache`]: ../cache/struct.ResponseCache.html
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+#[derive(Clone, Debug, Default)]
+pub struct FetchTransport {
+    _private: (),
+}
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+impl FetchTransport {
+    /// Creates a client which sends requests with `fetch`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+impl HttpTransport for FetchTransport {
+    fn send(
+        &self,
+        request: http::Request<Bytes>,
+    ) -> BoxFuture<'static, Result<http::Response<Bytes>, Error>> {
+        // browsers are single-threaded, so the future is never actually sent between threads.
+        send_wrapper::SendWrapper::new(fetch(request)).boxed()
+    }
+}
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+async fn fetch(request: http::Request<Bytes>) -> Result<http::Response<Bytes>, Error> {
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+
+    fn js_error(err: JsValue) -> Error {
+        Error::transport(format!("fetch failed: {:?}", err))
+    }
+
+    let progress = request.extensions().get::<BodyProgress>().cloned();
+    let (parts, body) = request.into_parts();
+
+    let headers = web_sys::Headers::new().map_err(js_error)?;
+    for (name, value) in &parts.headers {
+        let value = value.to_str().map_err(Error::transport)?;
+        headers.append(name.as_str(), value).map_err(js_error)?;
+    }
+
+    let mut init = web_sys::RequestInit::new();
+    init.method(parts.method.as_str());
+    init.headers(&headers);
+    if !body.is_empty() {
+        init.body(Some(&js_sys::Uint8Array::from(&body[..])));
+    }
+    let request =
+        web_sys::Request::new_with_str_and_init(&parts.uri.to_string(), &init).map_err(js_error)?;
+
+    // `fetch` is on the window in pages, and on the global scope in workers.
+    let global = js_sys::global();
+    let promise = match global.dyn_ref::<web_sys::Window>() {
+        Some(window) => window.fetch_with_request(&request),
+        None => global
+            .unchecked_into::<web_sys::WorkerGlobalScope>()
+            .fetch_with_request(&request),
+    };
+    let response: web_sys::Response = JsFuture::from(promise)
+        .await
+        .map_err(js_error)?
+        .unchecked_into();
+
+    let buffer = JsFuture::from(response.array_buffer().map_err(js_error)?)
+        .await
+        .map_err(js_error)?;
+    let body = js_sys::Uint8Array::new(&buffer).to_vec();
+    // `fetch` only gives the whole body at once.
+    if let Some(progress) = progress {
+        progress.report(body.len() as u64, Some(body.len() as u64));
+    }
+
+    let mut result = http::Response::new(Bytes::from(body));
+    *result.status_mut() =
+        http::StatusCode::from_u16(response.status()).map_err(Error::transport)?;
+    if let Some(entries) = js_sys::try_iter(&response.headers()).map_err(js_error)? {
+        for entry in entries {
+            let entry: js_sys::Array = entry.map_err(js_error)?.unchecked_into();
+            let (name, value) = match (entry.get(0).as_string(), entry.get(1).as_string()) {
+                (Some(name), Some(value)) => (name, value),
+                _ => continue,
+            };
+            let name = http::header::HeaderName::from_bytes(name.as_bytes());
+            let value = http::HeaderValue::from_str(&value);
+            if let (Ok(name), Ok(value)) = (name, value) {
+                result.headers_mut().append(name, value);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// A proxy server to send requests through.
+///
+/// HTTP, HTTPS and SOCKS5 proxies are supported. With `reqwest`, SOCKS5 proxies need the `socks` feature.
+#[derive(Clone)]
+pub struct Proxy {
+    url: Url,
+    auth: Option<(String, String)>,
+}
+
+impl fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Proxy")
+            .field("url", &self.url.as_str())
+            .field(
+                "auth",
+                &self
+                    .auth
+                    .as_ref()
+                    .map(|(username, _)| (username, redact::REDACTED)),
+            )
+            .finish()
+    }
+}
+
+impl Proxy {
+    /// Creates a proxy from its URL, such as `"http://proxy.example.com:8080"` or `"socks5://localhost:1080"`.
+    pub fn new<U: AsRef<str>>(url: U) -> Result<Self, url::ParseError> {
+        Ok(Proxy {
+            url: Url::parse(url.as_ref())?,
+            auth: None,
+        })
+    }
+
+    /// Sets the username and password to authenticate to the proxy with, and returns the proxy.
+    pub fn with_basic_auth<U, V>(mut self, username: U, password: V) -> Self
+    where
+        U: Into<String>,
+        V: Into<String>,
+    {
+        self.auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// The URL of the proxy server.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// The username and password to authenticate to the proxy with, if any.
+    #[cfg(any(feature = "sync", feature = "sockets", feature = "reqwest"))]
+    pub(crate) fn basic_auth(&self) -> Option<(&str, &str)> {
+        self.auth
+            .as_ref()
+            .map(|(username, password)| (&**username, &**password))
+    }
+
+    #[cfg(feature = "reqwest")]
+    fn to_reqwest(&self) -> Result<reqwest::Proxy, reqwest::Error> {
+        let proxy = reqwest::Proxy::all(self.url.as_str())?;
+        Ok(match self.basic_auth() {
+            Some((username, password)) => proxy.basic_auth(username, password),
+            None => proxy,
+        })
+    }
+}
+
+/// Which HTTP versions a client uses to talk to the server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum HttpVersion {
+    /// HTTP/1.1, or HTTP/2 when negotiated by the client.
+    #[default]
+    Any,
+    /// Only HTTP/1.1.
+    Http1Only,
+    /// Only HTTP/2, without negotiating it first.
+    Http2Only,
+}
+
+/// Settings for the HTTP client requests are sent with, such as a proxy to use, which TLS certificates to trust, how
+/// long to wait for connections, or how connections are reused.
+///
+/// With the `reqwest` feature, a `reqwest::Client` with these settings can be built with
+/// [`ClientConfig::build_reqwest`]. With the `sync` feature, a `hyper::Client` with these settings can be built with
+/// [`ClientConfig::build_hyper`], or an HTTPS connector with the TLS, timeout and proxy settings with
+/// [`ClientConfig::https_connector`], for use with `SyncApi::new_with_connector`.
+///
+/// Clients making many requests can tune how connections are reused:
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use screeps_api::transport::{ClientConfig, HttpVersion};
+///
+/// let config = ClientConfig::new()
+///     .with_pool_max_idle_per_host(32)
+///     .with_pool_idle_timeout(Duration::from_secs(30))
+///     .with_http_version(HttpVersion::Http1Only);
+///
+/// let client = config.hyper_builder().build_http::<hyper::Body>();
+/// let api = screeps_api::Api::new(client);
+/// ```
+///
+/// ```
+/// # #[cfg(feature = "sync")]
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use screeps_api::transport::{ClientConfig, Proxy};
+///
+/// let proxy = Proxy::new("http://proxy.example.com:8080")?.with_basic_auth("user", "password");
+/// let client = ClientConfig::new().with_proxy(proxy).build_hyper()?;
+///
+/// let api = screeps_api::Api::new(client);
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "sync"))] fn main() {}
+/// ```
+///
+/// Private servers using self-signed certificates can be connected to by trusting their certificate:
+///
+/// ```no_run
+/// # #[cfg(feature = "sync")]
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use screeps_api::{transport::ClientConfig, SyncApi};
+///
+/// let certificate = std::fs::read("server.pem")?;
+/// let connector = ClientConfig::new()
+///     .with_root_certificate(certificate)
+///     .https_connector()?;
+///
+/// let api = SyncApi::new_with_connector(connector)?.with_url("https://screeps.example.com/api/")?;
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "sync"))] fn main() {}
+/// ```
+///
+/// [`ClientConfig::build_reqwest`]: struct.ClientConfig.html#method.build_reqwest
+/// [`ClientConfig::https_connector`]: struct.ClientConfig.html#method.https_connector
+/// [`ClientConfig::build_hyper`]: struct.ClientConfig.html#method.build_hyper
+#[derive(Clone, Debug, Default)]
+pub struct ClientConfig {
+    proxy: Option<Proxy>,
+    root_certificates: Vec<Vec<u8>>,
+    accept_invalid_certs: bool,
+    connect_timeout: Option<Duration>,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Duration>,
+    http_version: HttpVersion,
+}
+
+impl ClientConfig {
+    /// Creates settings for a client connecting directly to the server.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the proxy all requests are sent through, and returns the settings.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// The proxy all requests are sent through, if any.
+    pub fn proxy(&self) -> Option<&Proxy> {
+        self.proxy.as_ref()
+    }
+
+    /// Sets how long to wait for a connection to the server to be established, and returns the settings.
+    ///
+    /// This limits connecting alone: use `Api::set_timeout` to limit how long to wait for whole responses.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// How long to wait for a connection to the server to be established, if limited.
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// Sets how many idle connections to each server are kept open for reuse, and returns the settings.
+    ///
+    /// By default, the HTTP client's own limit is used, which is unlimited for both `hyper` and `reqwest`.
+    pub fn with_pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// How many idle connections to each server are kept open for reuse, if set.
+    pub fn pool_max_idle_per_host(&self) -> Option<usize> {
+        self.pool_max_idle_per_host
+    }
+
+    /// Sets how long idle connections are kept open for reuse, and returns the settings.
+    ///
+    /// By default, the HTTP client's own timeout is used, which is 90 seconds for both `hyper` and `reqwest`.
+    pub fn with_pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// How long idle connections are kept open for reuse, if set.
+    pub fn pool_idle_timeout(&self) -> Option<Duration> {
+        self.pool_idle_timeout
+    }
+
+    /// Sets which HTTP versions to use, and returns the settings.
+    pub fn with_http_version(mut self, version: HttpVersion) -> Self {
+        self.http_version = version;
+        self
+    }
+
+    /// Which HTTP versions are used.
+    pub fn http_version(&self) -> HttpVersion {
+        self.http_version
+    }
+
+    /// Adds a PEM-encoded certificate to trust when connecting over HTTPS, on top of the system's trusted
+    /// certificates, and returns the settings.
+    pub fn with_root_certificate<C: Into<Vec<u8>>>(mut self, pem: C) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    /// Sets whether to accept any certificate when connecting over HTTPS, including invalid and expired ones, and
+    /// returns the settings.
+    ///
+    /// This makes connections vulnerable to man-in-the-middle attacks, and should only be used for testing. Prefer
+    /// [`ClientConfig::with_root_certificate`] to connect to servers with self-signed certificates.
+    ///
+    /// [`ClientConfig::with_root_certificate`]: struct.ClientConfig.html#method.with_root_certificate
+    pub fn with_danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Whether any certificate is accepted when connecting over HTTPS.
+    pub fn danger_accept_invalid_certs(&self) -> bool {
+        self.accept_invalid_certs
+    }
+
+    /// Builds a TLS connector trusting the certificates these settings trust.
+    ///
+    /// This is what [`ClientConfig::https_connector`] connects with. Socket connections aren't made by this crate, so
+    /// websocket clients built on `native-tls`, such as the `websocket` crate, can be given this connector to connect to
+    /// the same servers over `wss`. Fails if any of the root certificates is invalid.
+    ///
+    /// [`ClientConfig::https_connector`]: struct.ClientConfig.html#method.https_connector
+    #[cfg(feature = "sync")]
+    pub fn tls_connector(&self) -> Result<native_tls::TlsConnector, Error> {
+        let mut tls = native_tls::TlsConnector::builder();
+        for pem in &self.root_certificates {
+            tls.add_root_certificate(
+                native_tls::Certificate::from_pem(pem).map_err(Error::transport)?,
+            );
+        }
+        tls.danger_accept_invalid_certs(self.accept_invalid_certs);
+        tls.build().map_err(Error::transport)
+    }
+
+    /// Builds an HTTPS connector for `hyper` with these TLS, connect timeout and proxy settings.
+    ///
+    /// Fails if the proxy's scheme isn't `http`, `https`, `socks5` or `socks5h`, or if any of the root certificates is
+    /// invalid.
+    #[cfg(feature = "sync")]
+    pub fn https_connector(&self) -> Result<hyper_tls::HttpsConnector<ProxyConnector>, Error> {
+        if let Some(ref proxy) = self.proxy {
+            match proxy.url().scheme() {
+                "http" | "https" | "socks5" | "socks5h" => (),
+                scheme => {
+                    return Err(Error::transport(format!(
+                        "unsupported proxy scheme: {}",
+                        scheme
+                    )))
+                }
+            }
+        }
+        let tls = tokio_native_tls::TlsConnector::from(self.tls_connector()?);
+
+        let mut http = hyper::client::HttpConnector::new();
+        http.enforce_http(false);
+        http.set_connect_timeout(self.connect_timeout);
+
+        let connector = ProxyConnector {
+            http,
+            proxy: self.proxy.clone(),
+            tls: tls.clone(),
+        };
+        Ok(hyper_tls::HttpsConnector::from((connector, tls)))
+    }
+
+    /// Creates a builder for a `hyper::Client` with these connection reuse and HTTP version settings.
+    ///
+    /// The TLS, connect timeout and proxy settings are part of the connector, which is given when building the client:
+    /// see [`ClientConfig::https_connector`]. HTTPS connectors from `hyper-tls` don't negotiate HTTP/2, so with
+    /// `HttpVersion::Any`, such clients use HTTP/1.1.
+    ///
+    /// [`ClientConfig::https_connector`]: struct.ClientConfig.html#method.https_connector
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn hyper_builder(&self) -> hyper::client::Builder {
+        let mut builder = hyper::Client::builder();
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder.pool_idle_timeout(timeout);
+        }
+        builder.http2_only(self.http_version == HttpVersion::Http2Only);
+        builder
+    }
+
+    /// Builds a `hyper::Client` connecting over HTTPS with these settings.
+    ///
+    /// Fails if the proxy's scheme isn't supported, or if any of the root certificates is invalid. See
+    /// [`ClientConfig::https_connector`].
+    ///
+    /// [`ClientConfig::https_connector`]: struct.ClientConfig.html#method.https_connector
+    #[cfg(feature = "sync")]
+    pub fn build_hyper(
+        &self,
+    ) -> Result<hyper::Client<hyper_tls::HttpsConnector<ProxyConnector>>, Error> {
+        Ok(self.hyper_builder().build(self.https_connector()?))
+    }
+
+    /// Builds a `reqwest::Client` with these settings.
+    ///
+    /// Without a proxy set, the client uses the proxy set in the `HTTP_PROXY` and `HTTPS_PROXY` environment
+    /// variables, if any.
+    #[cfg(feature = "reqwest")]
+    pub fn build_reqwest(&self) -> Result<reqwest::Client, Error> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(ref proxy) = self.proxy {
+            builder = builder.proxy(proxy.to_reqwest()?);
+        }
+        for pem in &self.root_certificates {
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(pem)?);
+        }
+        builder = builder.danger_accept_invalid_certs(self.accept_invalid_certs);
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        builder = match self.http_version {
+            HttpVersion::Any => builder,
+            HttpVersion::Http1Only => builder.http1_only(),
+            HttpVersion::Http2Only => builder.http2_prior_knowledge(),
+        };
+        Ok(builder.build()?)
+    }
+}
+
+/// An error connecting through a `ProxyConnector`.
+#[cfg(feature = "sync")]
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The most bytes of an HTTP proxy's response to a `CONNECT` request read before giving up on it.
+#[cfg(feature = "sync")]
+const MAX_CONNECT_RESPONSE: usize = 8 * 1024;
+
+/// The port SOCKS5 proxies listen on, when the proxy url has none.
+#[cfg(feature = "sync")]
+const DEFAULT_SOCKS_PORT: u16 = 1080;
+
+/// A `hyper` connector which connects to the server directly, or tunnels to it through a proxy.
+///
+/// Built by [`ClientConfig::https_connector`], which wraps it in an `HttpsConnector` adding TLS for `https` urls on top
+/// of the connection. Tunnels are opened with a `CONNECT` request through HTTP and HTTPS proxies, and with a SOCKS5
+/// handshake through `socks5` and `socks5h` proxies, sending the proxy's credentials if it has any.
+///
+/// [`ClientConfig::https_connector`]: struct.ClientConfig.html#method.https_connector
+#[cfg(feature = "sync")]
+#[derive(Clone)]
+pub struct ProxyConnector {
+    http: hyper::client::HttpConnector,
+    proxy: Option<Proxy>,
+    tls: tokio_native_tls::TlsConnector,
+}
+
+#[cfg(feature = "sync")]
+impl fmt::Debug for ProxyConnector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProxyConnector")
+            .field("proxy", &self.proxy)
+            .finish()
+    }
+}
+
+#[cfg(feature = "sync")]
+impl ProxyConnector {
+    /// Opens a tunnel through the proxy to the host of `dst`.
+    async fn tunnel(mut self, proxy: Proxy, dst: hyper::Uri) -> Result<ProxyStream, BoxError> {
+        use hyper::service::Service;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        use crate::tunnel::{self, Socks5Handshake};
+
+        let target = Url::parse(&dst.to_string())?;
+        let host = proxy.url().host_str().ok_or("proxy url has no host")?;
+        let port = proxy
+            .url()
+            .port_or_known_default()
+            .unwrap_or(DEFAULT_SOCKS_PORT);
+        let proxy_uri = format!("http://{}:{}", host, port).parse::<hyper::Uri>()?;
+
+        let tcp = self.http.call(proxy_uri).await?;
+        let mut stream: Box<dyn Io> = match proxy.url().scheme() {
+            "https" => Box::new(self.tls.connect(host, tcp).await?),
+            _ => Box::new(tcp),
+        };
+
+        if let Some(request) = tunnel::proxy_connect_request(&proxy, &target) {
+            stream.write_all(request.as_bytes()).await?;
+            let mut head = Vec::new();
+            while !head.ends_with(b"\r\n\r\n") {
+                if head.len() >= MAX_CONNECT_RESPONSE {
+                    return Err("proxy response to CONNECT is too long".into());
+                }
+                head.push(stream.read_u8().await?);
+            }
+            match tunnel::connect_response_status(&head) {
+                Some(200..=299) => (),
+                Some(status) => {
+                    return Err(format!("proxy refused to connect, with status {}", status).into())
+                }
+                None => return Err("malformed response from proxy to CONNECT".into()),
+            }
+        } else if let Some(handshake) = Socks5Handshake::new(&proxy, &target) {
+            stream.write_all(&handshake.greeting()).await?;
+            let mut reply = [0; 2];
+            stream.read_exact(&mut reply).await?;
+            if let Some(request) = handshake.auth_request(reply)? {
+                stream.write_all(&request).await?;
+                stream.read_exact(&mut reply).await?;
+                handshake.check_auth_reply(reply)?;
+            }
+            stream.write_all(&handshake.connect_request()).await?;
+            let mut header = [0; 5];
+            stream.read_exact(&mut header).await?;
+            let mut bound = vec![0; handshake.connect_reply_len(header)?];
+            stream.read_exact(&mut bound).await?;
+        } else {
+            return Err(format!("cannot open a tunnel to {} through {:?}", target, proxy).into());
+        }
+
+        Ok(ProxyStream { inner: stream })
+    }
+}
+
+#[cfg(feature = "sync")]
+impl hyper::service::Service<hyper::Uri> for ProxyConnector {
+    type Response = ProxyStream;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<ProxyStream, BoxError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        self.http.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, dst: hyper::Uri) -> Self::Future {
+        match self.proxy.clone() {
+            Some(proxy) => self.clone().tunnel(proxy, dst).boxed(),
+            None => {
+                let connecting = self.http.call(dst);
+                async move {
+                    Ok(ProxyStream {
+                        inner: Box::new(connecting.await?),
+                    })
+                }
+                .boxed()
+            }
+        }
+    }
+}
+
+/// A stream a `ProxyStream` can wrap.
+#[cfg(feature = "sync")]
+trait Io: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin {}
+
+#[cfg(feature = "sync")]
+impl<T> Io for T where T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin {}
+
+/// A connection made by a [`ProxyConnector`]: either directly to the server, or a tunnel to it through a proxy.
+///
+/// [`ProxyConnector`]: struct.ProxyConnector.html
+#[cfg(feature = "sync")]
+pub struct ProxyStream {
+    inner: Box<dyn Io>,
+}
+
+#[cfg(feature = "sync")]
+impl fmt::Debug for ProxyStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProxyStream").finish()
+    }
+}
+
+#[cfg(feature = "sync")]
+impl tokio::io::AsyncRead for ProxyStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+#[cfg(feature = "sync")]
+impl tokio::io::AsyncWrite for ProxyStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(feature = "sync")]
+impl hyper::client::connect::Connection for ProxyStream {
+    fn connected(&self) -> hyper::client::connect::Connected {
+        hyper::client::connect::Connected::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use bytes::Bytes;
+
+    use super::{BodyBuffer, BodyProgress, ClientConfig, HttpVersion, Proxy};
+
+    #[test]
+    fn proxy_settings() {
+        let proxy = Proxy::new("socks5://localhost:1080")
+            .unwrap()
+            .with_basic_auth("user", "secret");
+        assert_eq!(proxy.url().scheme(), "socks5");
+        assert!(!format!("{:?}", proxy).contains("secret"));
+
+        let config = ClientConfig::new().with_proxy(proxy);
+        assert_eq!(config.proxy().unwrap().url().port(), Some(1080));
+        assert!(Proxy::new("not a url").is_err());
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn hyper_client_with_proxy() {
+        let config = ClientConfig::new();
+        assert!(config.build_hyper().is_ok());
+
+        let config = config.with_proxy(Proxy::new("http://proxy.example.com:8080").unwrap());
+        assert!(config.build_hyper().is_ok());
+        let config = config.with_proxy(Proxy::new("socks5h://localhost").unwrap());
+        assert!(config.https_connector().is_ok());
+        let config = config.with_proxy(Proxy::new("ftp://proxy.example.com").unwrap());
+        assert!(config.https_connector().is_err());
+    }
+
+    /// Reads the head of an HTTP request or response.
+    #[cfg(feature = "sync")]
+    async fn read_head(stream: &mut tokio::net::TcpStream) -> Vec<u8> {
+        use tokio::io::AsyncReadExt;
+
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        head
+    }
+
+    /// Sends a request through a proxy accepting connections with `listener`, which opens the tunnel with `open` and
+    /// then answers as the server.
+    #[cfg(feature = "sync")]
+    fn get_through_proxy<F, Fut>(
+        proxy: &str,
+        listener: std::net::TcpListener,
+        open: F,
+    ) -> http::StatusCode
+    where
+        F: FnOnce(tokio::net::TcpStream) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = tokio::net::TcpStream> + Send,
+    {
+        use tokio::io::AsyncWriteExt;
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async move {
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            let server = tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut stream = open(stream).await;
+                let request = read_head(&mut stream).await;
+                assert!(request.starts_with(b"GET /api/version HTTP/1.1\r\n"));
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\n\r\n{\"ok\":1}")
+                    .await
+                    .unwrap();
+            });
+
+            let client = ClientConfig::new()
+                .with_proxy(Proxy::new(proxy).unwrap().with_basic_auth("user", "secret"))
+                .build_hyper()
+                .unwrap();
+            let uri = "http://screeps.example.com:21025/api/version"
+                .parse()
+                .unwrap();
+            let response = client.get(uri).await.unwrap();
+            server.await.unwrap();
+            response.status()
+        })
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn tunnel_through_http_proxy() {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let proxy = format!("http://{}", listener.local_addr().unwrap());
+
+        let status = get_through_proxy(&proxy, listener, |mut stream| async move {
+            let head = read_head(&mut stream).await;
+            assert_eq!(
+                head,
+                &b"CONNECT screeps.example.com:21025 HTTP/1.1\r\nHost: screeps.example.com:21025\r\n\
+                   Proxy-Authorization: Basic dXNlcjpzZWNyZXQ=\r\n\r\n"[..]
+            );
+            stream
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .await
+                .unwrap();
+            stream
+        });
+        assert_eq!(status, 200);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn tunnel_through_socks5_proxy() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let proxy = format!("socks5h://{}", listener.local_addr().unwrap());
+
+        let status = get_through_proxy(&proxy, listener, |mut stream| async move {
+            let mut greeting = [0; 4];
+            stream.read_exact(&mut greeting).await.unwrap();
+            assert_eq!(greeting, [5, 2, 0, 2]);
+            stream.write_all(&[5, 2]).await.unwrap();
+
+            let mut auth = [0; 13];
+            stream.read_exact(&mut auth).await.unwrap();
+            assert_eq!(&auth, b"\x01\x04user\x06secret");
+            stream.write_all(&[1, 0]).await.unwrap();
+
+            let mut connect = [0; 26];
+            stream.read_exact(&mut connect).await.unwrap();
+            assert_eq!(&connect, b"\x05\x01\x00\x03\x13screeps.example.com\x52\x21");
+            stream
+                .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0])
+                .await
+                .unwrap();
+            stream
+        });
+        assert_eq!(status, 200);
+    }
+
+    #[test]
+    fn collect_body_in_chunks() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(http::header::CONTENT_LENGTH, "6".parse().unwrap());
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let progress = {
+            let reports = reports.clone();
+            BodyProgress::new(move |received, total| {
+                reports.lock().unwrap().push((received, total))
+            })
+        };
+
+        let mut buffer = BodyBuffer::new(&headers, Some(progress));
+        assert!(buffer.buffer.capacity() >= 6);
+        buffer.push(b"abc");
+        buffer.push(b"def");
+        assert_eq!(buffer.finish(), "abcdef");
+        assert_eq!(*reports.lock().unwrap(), [(3, Some(6)), (6, Some(6))]);
+
+        let mut buffer = BodyBuffer::new(&http::HeaderMap::new(), None);
+        buffer.push(b"abc");
+        assert_eq!(buffer.finish(), "abc");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn decompress_gzip() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(br#"{"ok":1}"#).unwrap();
+        let compressed = Bytes::from(encoder.finish().unwrap());
+
+        let mut headers = http::HeaderMap::new();
+        headers.insert(http::header::CONTENT_ENCODING, "gzip".parse().unwrap());
+        headers.insert(http::header::CONTENT_LENGTH, compressed.len().into());
+        let body = super::decompress(&mut headers, compressed).unwrap();
+        assert_eq!(body, r#"{"ok":1}"#);
+        assert!(headers.is_empty());
+
+        let body = super::decompress(&mut http::HeaderMap::new(), Bytes::from("plain")).unwrap();
+        assert_eq!(body, "plain");
+
+        let mut headers = http::HeaderMap::new();
+        headers.insert(http::header::CONTENT_ENCODING, "gzip".parse().unwrap());
+        assert!(super::decompress(&mut headers, Bytes::from("not gzip")).is_err());
+    }
+
+    #[test]
+    fn pool_settings() {
+        let config = ClientConfig::new()
+            .with_pool_max_idle_per_host(4)
+            .with_pool_idle_timeout(Duration::from_secs(5))
+            .with_http_version(HttpVersion::Http1Only);
+        assert_eq!(config.pool_max_idle_per_host(), Some(4));
+        assert_eq!(config.pool_idle_timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(config.http_version(), HttpVersion::Http1Only);
+        assert_eq!(ClientConfig::new().http_version(), HttpVersion::Any);
+
+        #[cfg(feature = "sync")]
+        assert!(config.build_hyper().is_ok());
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn invalid_root_certificate() {
+        assert!(ClientConfig::new().https_connector().is_ok());
+        assert!(ClientConfig::new()
+            .with_danger_accept_invalid_certs(true)
+            .tls_connector()
+            .is_ok());
+        assert!(ClientConfig::new()
+            .with_root_certificate(&b"not a certificate"[..])
+            .tls_connector()
+            .is_err());
+        assert!(ClientConfig::new()
+            .with_root_certificate(&b"not a certificate"[..])
+            .https_connector()
+            .is_err());
+    }
+}
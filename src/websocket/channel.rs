@@ -1,9 +1,18 @@
 //! Websocket subscribable channel data structure.
-use std::{borrow::Cow, fmt};
+use std::{
+    borrow::Cow,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
 
-use crate::RoomName;
+use crate::{RoomName, RoomNameParseError, ShardRoomName};
 
 /// Different channels one can subscribe to.
+///
+/// Channels compare and hash by their protocol string (their `Display` output), and can be parsed back from it
+/// with `FromStr`.
+#[derive(Clone, Debug)]
 pub enum Channel<'a> {
     /// Server messages (TODO: find message here).
     ServerMessages,
@@ -136,6 +145,39 @@ impl<'a> Channel<'a> {
         }
     }
 
+    /// Creates a channel subscribing to map-view updates of a room on a shard.
+    pub fn room_map_view_on(room: &ShardRoomName) -> Self {
+        Channel::RoomMapView {
+            shard_name: Some(room.shard.clone().into()),
+            room_name: room.room,
+        }
+    }
+
+    /// Creates a channel subscribing to detailed updates of a room's contents on a shard.
+    ///
+    /// See [`Channel::room_detail`](#method.room_detail) for the limits on these subscriptions.
+    pub fn room_detail_on(room: &ShardRoomName) -> Self {
+        Channel::RoomDetail {
+            shard_name: Some(room.shard.clone().into()),
+            room_name: room.room,
+        }
+    }
+
+    /// If this channel is for updates of a room on a shard, gets the shard and room name.
+    pub fn shard_room_name(&self) -> Option<ShardRoomName> {
+        match *self {
+            Channel::RoomMapView {
+                shard_name: Some(ref shard_name),
+                room_name,
+            }
+            | Channel::RoomDetail {
+                shard_name: Some(ref shard_name),
+                room_name,
+            } => Some(ShardRoomName::new(&**shard_name, room_name)),
+            _ => None,
+        }
+    }
+
     /// Creates a channel subscribing to a user's CPU and memory.
     pub fn user_cpu<T: Into<Cow<'a, str>>>(user_id: T) -> Self {
         Channel::UserCpu {
@@ -240,3 +282,283 @@ impl<'a> fmt::Display for Channel<'a> {
         }
     }
 }
+
+impl<'a> Channel<'a> {
+    /// Whether this channel's protocol string is the given name, checked without formatting it into a new string.
+    fn is_named(&self, name: &str) -> bool {
+        struct Remaining<'s>(&'s str);
+
+        impl fmt::Write for Remaining<'_> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                if self.0.starts_with(s) {
+                    self.0 = &self.0[s.len()..];
+                    Ok(())
+                } else {
+                    Err(fmt::Error)
+                }
+            }
+        }
+
+        let mut remaining = Remaining(name);
+        fmt::write(&mut remaining, format_args!("{}", self)).is_ok() && remaining.0.is_empty()
+    }
+}
+
+impl<'a, 'b> PartialEq<Channel<'b>> for Channel<'a> {
+    /// Two channels are equal if they refer to the same server-side channel.
+    ///
+    /// A `Channel::Other` created from a channel string will equal the equivalent typed channel.
+    fn eq(&self, other: &Channel<'b>) -> bool {
+        match (self, other) {
+            (Channel::Other { channel }, other) => other.is_named(channel),
+            (this, Channel::Other { channel }) => this.is_named(channel),
+            (Channel::ServerMessages, Channel::ServerMessages) => true,
+            (Channel::UserCpu { user_id: a }, Channel::UserCpu { user_id: b })
+            | (Channel::UserMessages { user_id: a }, Channel::UserMessages { user_id: b })
+            | (Channel::UserCredits { user_id: a }, Channel::UserCredits { user_id: b })
+            | (Channel::UserConsole { user_id: a }, Channel::UserConsole { user_id: b })
+            | (
+                Channel::UserActiveBranch { user_id: a },
+                Channel::UserActiveBranch { user_id: b },
+            ) => a == b,
+            (
+                Channel::UserConversation {
+                    user_id: a,
+                    target_user_id: a_target,
+                },
+                Channel::UserConversation {
+                    user_id: b,
+                    target_user_id: b_target,
+                },
+            ) => a == b && a_target == b_target,
+            (
+                Channel::UserMemoryPath {
+                    user_id: a,
+                    path: a_path,
+                },
+                Channel::UserMemoryPath {
+                    user_id: b,
+                    path: b_path,
+                },
+            ) => a == b && a_path == b_path,
+            (
+                Channel::RoomMapView {
+                    shard_name: a_shard,
+                    room_name: a,
+                },
+                Channel::RoomMapView {
+                    shard_name: b_shard,
+                    room_name: b,
+                },
+            )
+            | (
+                Channel::RoomDetail {
+                    shard_name: a_shard,
+                    room_name: a,
+                },
+                Channel::RoomDetail {
+                    shard_name: b_shard,
+                    room_name: b,
+                },
+            ) => a == b && a_shard.as_deref() == b_shard.as_deref(),
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Eq for Channel<'a> {}
+
+impl<'a> Hash for Channel<'a> {
+    /// Hashes the channel's protocol string, so that a `Channel::Other` hashes the same as the equivalent typed
+    /// channel.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        struct HashBytes<'h, H>(&'h mut H);
+
+        impl<H: Hasher> fmt::Write for HashBytes<'_, H> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                // bytes are hashed one at a time, so the result doesn't depend on how the string is split up.
+                for byte in s.bytes() {
+                    self.0.write_u8(byte);
+                }
+                Ok(())
+            }
+        }
+
+        let _ = fmt::write(&mut HashBytes(state), format_args!("{}", self));
+        state.write_u8(0xff);
+    }
+}
+
+impl FromStr for Channel<'static> {
+    type Err = RoomNameParseError<'static>;
+
+    /// Parses a channel protocol string, such as `room:shard3/E3N3` or `user:abc/console`.
+    ///
+    /// Strings which aren't recognized are parsed as `Channel::Other`. Fails only if a room channel contains an
+    /// invalid room name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn shard_and_room(
+            rest: &str,
+        ) -> Result<(Option<Cow<'static, str>>, RoomName), RoomNameParseError<'static>> {
+            let (shard_name, room_name) = match rest.rfind('/') {
+                Some(idx) => (Some(rest[..idx].to_owned().into()), &rest[idx + 1..]),
+                None => (None, rest),
+            };
+            let room_name = RoomName::new(room_name).map_err(RoomNameParseError::into_owned)?;
+
+            Ok((shard_name, room_name))
+        }
+
+        const USER_PREFIX: &str = "user:";
+        const ROOM_PREFIX: &str = "room:";
+        const ROOM_MAP_VIEW_PREFIX: &str = "roomMap2:";
+
+        if s == "server-message" {
+            return Ok(Channel::ServerMessages);
+        }
+
+        if let Some(rest) = s.strip_prefix(ROOM_PREFIX) {
+            let (shard_name, room_name) = shard_and_room(rest)?;
+            return Ok(Channel::RoomDetail {
+                shard_name,
+                room_name,
+            });
+        }
+
+        if let Some(rest) = s.strip_prefix(ROOM_MAP_VIEW_PREFIX) {
+            let (shard_name, room_name) = shard_and_room(rest)?;
+            return Ok(Channel::RoomMapView {
+                shard_name,
+                room_name,
+            });
+        }
+
+        if let Some(rest) = s.strip_prefix(USER_PREFIX) {
+            if let Some(idx) = rest.find('/') {
+                let user_id = rest[..idx].to_owned();
+                let sub = &rest[idx + 1..];
+
+                const MEMORY_PREFIX: &str = "memory/";
+                const CONVERSATION_PREFIX: &str = "message:";
+
+                if let Some(path) = sub.strip_prefix(MEMORY_PREFIX) {
+                    return Ok(Channel::user_memory_path(user_id, path.to_owned()));
+                }
+                if let Some(target) = sub.strip_prefix(CONVERSATION_PREFIX) {
+                    return Ok(Channel::user_conversation(user_id, target.to_owned()));
+                }
+
+                let channel = match sub {
+                    "cpu" => Some(Channel::user_cpu(user_id)),
+                    "newMessage" => Some(Channel::user_messages(user_id)),
+                    "money" => Some(Channel::user_credits(user_id)),
+                    "console" => Some(Channel::user_console(user_id)),
+                    "set-active-branch" => Some(Channel::user_active_branch(user_id)),
+                    _ => None,
+                };
+
+                if let Some(channel) = channel {
+                    return Ok(channel);
+                }
+            }
+        }
+
+        Ok(Channel::other(s.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::Channel;
+    use crate::{RoomName, ShardRoomName};
+
+    #[test]
+    fn typed_channels_equal_other() {
+        assert!(Channel::user_cpu("abc") == Channel::other("user:abc/cpu"));
+        assert!(
+            Channel::user_memory_path("abc", "creeps.harvester")
+                == Channel::other("user:abc/memory/creeps.harvester")
+        );
+        assert!(
+            Channel::room_detail(RoomName::new("E3N3").unwrap(), Some("shard3"))
+                == Channel::other("room:shard3/E3N3")
+        );
+        assert!(Channel::user_cpu("abc") != Channel::user_console("abc"));
+        assert!(Channel::user_cpu("abc") != Channel::other("user:abc/cpu2"));
+        assert!(Channel::other("user:abc") != Channel::user_cpu("abc"));
+        assert!(Channel::room_detail_ps(RoomName::new("E3N3").unwrap()) != Channel::other("room:"));
+    }
+
+    #[test]
+    fn round_trip_channel_strings() {
+        let channels = [
+            Channel::server_messages(),
+            Channel::user_cpu("abc"),
+            Channel::user_messages("abc"),
+            Channel::user_conversation("abc", "def"),
+            Channel::user_credits("abc"),
+            Channel::user_memory_path("abc", "creeps.harvester"),
+            Channel::user_console("abc"),
+            Channel::user_active_branch("abc"),
+            Channel::room_map_view(RoomName::new("E3N3").unwrap(), Some("shard3")),
+            Channel::room_detail_ps(RoomName::new("W0S0").unwrap()),
+            Channel::other("something-else"),
+        ];
+
+        for channel in channels.iter() {
+            let parsed = channel.to_string().parse::<Channel>().unwrap();
+            assert_eq!(parsed, *channel);
+            assert_eq!(
+                std::mem::discriminant(&parsed),
+                std::mem::discriminant(channel)
+            );
+        }
+
+        match "room:shard3/E3N3".parse::<Channel>().unwrap() {
+            Channel::RoomDetail {
+                shard_name: Some(ref shard_name),
+                room_name,
+            } => {
+                assert_eq!(shard_name, "shard3");
+                assert_eq!(room_name, RoomName::new("E3N3").unwrap());
+            }
+            other => panic!("expected room detail channel, found {:?}", other),
+        }
+
+        assert!("room:shard3/notaroom".parse::<Channel>().is_err());
+    }
+
+    #[test]
+    fn shard_room_channels() {
+        let room = "shard3/E3N3".parse::<ShardRoomName>().unwrap();
+        assert_eq!(
+            Channel::room_detail_on(&room),
+            Channel::other("room:shard3/E3N3")
+        );
+        assert_eq!(
+            Channel::room_map_view_on(&room),
+            Channel::other("roomMap2:shard3/E3N3")
+        );
+        assert_eq!(
+            "room:shard3/E3N3"
+                .parse::<Channel>()
+                .unwrap()
+                .shard_room_name(),
+            Some(room)
+        );
+        assert_eq!(
+            Channel::room_detail_ps(RoomName::new("E3N3").unwrap()).shard_room_name(),
+            None
+        );
+    }
+
+    #[test]
+    fn channels_in_sets() {
+        let mut set = HashSet::new();
+        set.insert(Channel::user_cpu("abc"));
+        assert!(set.contains(&Channel::other("user:abc/cpu")));
+        assert!(!set.contains(&Channel::user_console("abc")));
+    }
+}
@@ -1,7 +1,7 @@
 //! Websocket command creation.
 use std::str;
 
-use super::Channel;
+use super::{Channel, PendingSubscriptions};
 
 /// Gets the raw websocket string to send for subscribing to a channel.
 ///
@@ -35,6 +35,45 @@ pub fn unsubscribe(channel: &Channel) -> String {
     sockjs_send_from_internal(&message)
 }
 
+/// Gets a single raw websocket string which subscribes to all of the given channels, and the set of subscriptions
+/// waiting for a result.
+///
+/// This sends all subscriptions in one SockJS frame, which is useful when (re)establishing many subscriptions at
+/// once. The server does not confirm subscriptions, so the result of each is found from the messages received
+/// afterwards: pass them to [`PendingSubscriptions::resolve`].
+///
+/// See [`subscribe`] for notes on subscribing to a channel multiple times.
+///
+/// [`subscribe`]: fn.subscribe.html
+/// [`PendingSubscriptions::resolve`]: ../struct.PendingSubscriptions.html#method.resolve
+pub fn subscribe_all(channels: &[Channel]) -> (String, PendingSubscriptions) {
+    let messages = channels
+        .iter()
+        .map(|channel| format!("subscribe {}", channel))
+        .collect::<Vec<_>>();
+
+    (
+        sockjs_send_all_from_internal(&messages),
+        PendingSubscriptions::new(channels),
+    )
+}
+
+/// Gets a single raw websocket string which unsubscribes from all of the given channels.
+///
+/// Like single unsubscriptions, these are neither confirmed nor reported as failing by the server, so there are no
+/// per-channel results. See [`subscribe_all`] and [`unsubscribe`].
+///
+/// [`subscribe_all`]: fn.subscribe_all.html
+/// [`unsubscribe`]: fn.unsubscribe.html
+pub fn unsubscribe_all(channels: &[Channel]) -> String {
+    let messages = channels
+        .iter()
+        .map(|channel| format!("unsubscribe {}", channel))
+        .collect::<Vec<_>>();
+
+    sockjs_send_all_from_internal(&messages)
+}
+
 /// Authenticates with the given token.
 ///
 /// After doing this, you'll be able to subscribe and unsubscribe to messages. A "auth success"
@@ -52,3 +91,32 @@ fn sockjs_send_from_internal<T: AsRef<str>>(source: &T) -> String {
     serde_json::to_string(&(source.as_ref(),))
         .expect("serializing a tuple containing a single string can't fail.")
 }
+
+fn sockjs_send_all_from_internal<T: AsRef<str>>(sources: &[T]) -> String {
+    let sources = sources.iter().map(AsRef::as_ref).collect::<Vec<&str>>();
+
+    serde_json::to_string(&sources).expect("serializing a list of strings can't fail.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{subscribe, subscribe_all, unsubscribe_all};
+    use crate::websocket::Channel;
+
+    #[test]
+    fn batch_commands() {
+        let channels = [Channel::user_cpu("abc"), Channel::user_console("abc")];
+
+        let (frame, pending) = subscribe_all(&channels);
+        assert_eq!(
+            frame,
+            r#"["subscribe user:abc/cpu","subscribe user:abc/console"]"#
+        );
+        assert!(pending.is_pending(&channels[0]) && pending.is_pending(&channels[1]));
+        assert_eq!(
+            unsubscribe_all(&channels),
+            r#"["unsubscribe user:abc/cpu","unsubscribe user:abc/console"]"#
+        );
+        assert_eq!(subscribe_all(&channels[..1]).0, subscribe(&channels[0]));
+    }
+}
@@ -0,0 +1,228 @@
+//! Configuration for socket connections.
+use std::{borrow::Cow, sync::Arc, time::Duration};
+
+use super::Channel;
+use crate::{
+    backoff::{BackoffPolicy, Exponential},
+    data::RoomName,
+};
+
+/// How a dropped socket connection should be re-established.
+///
+/// This is the default kind of [`BackoffPolicy`] given to [`SocketConfig::with_reconnect`], but any other can be used.
+///
+/// [`BackoffPolicy`]: ../backoff/trait.BackoffPolicy.html
+/// [`SocketConfig::with_reconnect`]: struct.SocketConfig.html#method.with_reconnect
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReconnectPolicy {
+    /// Never reconnect.
+    Never,
+    /// Reconnect after a fixed delay.
+    Fixed {
+        /// Delay before each reconnection attempt.
+        delay: Duration,
+        /// Maximum number of consecutive attempts, or `None` for unlimited.
+        max_attempts: Option<u32>,
+    },
+    /// Reconnect after a delay which doubles with each consecutive failed attempt.
+    Exponential {
+        /// Delay before the first attempt.
+        initial: Duration,
+        /// Upper bound on the delay.
+        max: Duration,
+        /// Maximum number of consecutive attempts, or `None` for unlimited.
+        max_attempts: Option<u32>,
+    },
+}
+
+impl BackoffPolicy for ReconnectPolicy {
+    fn delay(&self, attempt: u32, previous: Duration) -> Option<Duration> {
+        match *self {
+            ReconnectPolicy::Never => None,
+            ReconnectPolicy::Fixed {
+                delay,
+                max_attempts,
+            } => match max_attempts {
+                Some(max) if attempt >= max => None,
+                _ => Some(delay),
+            },
+            ReconnectPolicy::Exponential {
+                initial,
+                max,
+                max_attempts,
+            } => Exponential::new(initial, max)
+                .with_max_attempts(max_attempts)
+                .delay(attempt, previous),
+        }
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy::Exponential {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            max_attempts: None,
+        }
+    }
+}
+
+/// Settings for socket connections: how to reconnect them when they drop, which shard to subscribe on, and how many
+/// received messages to queue.
+///
+/// [`AccountSockets::with_config`] uses these settings for every account it manages, including when its connections
+/// are run by [`spawn`].
+///
+/// There are no ping interval, compression or frame buffer settings: those belong to the websocket client which opens
+/// the connection, and are set on it in the `connect` closure passed to `spawn`. The server keeps the connection alive
+/// itself, sending a SockJS heartbeat frame (`h`) every 25 seconds, so no pings need to be sent from this side.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use screeps_api::websocket::{ReconnectPolicy, SocketConfig};
+///
+/// let config = SocketConfig::new()
+///     .with_reconnect(ReconnectPolicy::Fixed {
+///         delay: Duration::from_secs(5),
+///         max_attempts: Some(3),
+///     })
+///     .with_shard(Some("shard3"));
+///
+/// assert_eq!(config.shard(), Some("shard3"));
+/// ```
+///
+/// [`spawn`]: fn.spawn.html
+/// [`AccountSockets::with_config`]: struct.AccountSockets.html#method.with_config
+#[derive(Clone, Debug)]
+pub struct SocketConfig {
+    reconnect: Arc<dyn BackoffPolicy>,
+    shard: Option<Cow<'static, str>>,
+    queue_capacity: usize,
+}
+
+impl Default for SocketConfig {
+    fn default() -> Self {
+        SocketConfig {
+            reconnect: Arc::new(ReconnectPolicy::default()),
+            shard: Some("shard0".into()),
+            queue_capacity: 1024,
+        }
+    }
+}
+
+impl SocketConfig {
+    /// Creates a configuration with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how to reconnect when the connection drops: a [`ReconnectPolicy`], a [`DecorrelatedJitter`] schedule, or
+    /// one of the application's own.
+    ///
+    /// The policy is asked for each delay with the number of consecutive failed attempts and the previous delay it
+    /// returned.
+    ///
+    /// [`ReconnectPolicy`]: enum.ReconnectPolicy.html
+    /// [`DecorrelatedJitter`]: ../backoff/struct.DecorrelatedJitter.html
+    pub fn with_reconnect<B: BackoffPolicy>(mut self, policy: B) -> Self {
+        self.reconnect = Arc::new(policy);
+        self
+    }
+
+    /// Sets the shard used for room channels created with this configuration, or `None` for servers without
+    /// shards.
+    pub fn with_shard<T: Into<Cow<'static, str>>>(mut self, shard: Option<T>) -> Self {
+        self.shard = shard.map(Into::into);
+        self
+    }
+
+    /// Sets how many received messages the handle returned by [`spawn`] queues, before dropping the oldest ones to make
+    /// room.
+    ///
+    /// # Panics
+    ///
+    /// `spawn` panics if this is 0.
+    ///
+    /// [`spawn`]: fn.spawn.html
+    pub fn with_queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity;
+        self
+    }
+
+    /// How to reconnect when the connection drops.
+    pub fn reconnect(&self) -> &dyn BackoffPolicy {
+        &*self.reconnect
+    }
+
+    /// The default shard for room channels.
+    pub fn shard(&self) -> Option<&str> {
+        self.shard.as_ref().map(AsRef::as_ref)
+    }
+
+    /// How many received messages are queued.
+    pub fn queue_capacity(&self) -> usize {
+        self.queue_capacity
+    }
+
+    /// Creates a room detail channel for the given room on the configured shard.
+    pub fn room_detail(&self, room_name: RoomName) -> Channel<'_> {
+        Channel::room_detail(room_name, self.shard())
+    }
+
+    /// Creates a room map view channel for the given room on the configured shard.
+    pub fn room_map_view(&self, room_name: RoomName) -> Channel<'_> {
+        Channel::room_map_view(room_name, self.shard())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{ReconnectPolicy, SocketConfig};
+    use crate::backoff::{BackoffPolicy, Exponential};
+
+    #[test]
+    fn exponential_reconnect() {
+        let policy = ReconnectPolicy::Exponential {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(10),
+            max_attempts: Some(6),
+        };
+
+        let delays = (0..7)
+            .map(|attempt| policy.delay(attempt, Duration::from_secs(0)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            delays,
+            vec![
+                Some(Duration::from_secs(1)),
+                Some(Duration::from_secs(2)),
+                Some(Duration::from_secs(4)),
+                Some(Duration::from_secs(8)),
+                Some(Duration::from_secs(10)),
+                Some(Duration::from_secs(10)),
+                None,
+            ]
+        );
+        assert_eq!(
+            ReconnectPolicy::Never.delay(0, Duration::from_secs(0)),
+            None
+        );
+
+        let config = SocketConfig::new();
+        assert_eq!(
+            config.reconnect().delay(0, Duration::from_secs(0)),
+            Some(Duration::from_secs(1))
+        );
+
+        let backoff = Exponential::new(Duration::from_secs(2), Duration::from_secs(8));
+        let config = config.with_reconnect(backoff);
+        assert_eq!(
+            config.reconnect().delay(1, Duration::from_secs(2)),
+            Some(Duration::from_secs(4))
+        );
+    }
+}
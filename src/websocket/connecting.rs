@@ -1,6 +1,4 @@
 //! Websocket url utilities.
-use std::str;
-
 use rand::{self, seq::SliceRandom, Rng};
 use url::Url;
 
@@ -94,8 +92,34 @@ pub fn default_url() -> Url {
 ///
 /// The input URL should be an API url in the format of `https://screeps.com/api/`.
 pub fn transform_url<U: AsRef<str> + ?Sized>(url: &U) -> Result<Url, UrlError> {
-    use std::fmt;
+    // we could probably just use gen_ascii_chars for the session ID, but to be safe
+    // we just use the subset that `sockjs-client` does.
+    const VALID_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz012345";
+
+    let mut rng = rand::thread_rng();
+    let server_id = rng.gen_range(0, 1000);
+    let session_id = (0..8)
+        .map(|_| *VALID_CHARS.choose(&mut rng).unwrap() as char)
+        .collect::<String>();
+
+    socket_url(url, server_id, &session_id)
+}
 
+/// Derives the SockJS websocket URL for a given screeps API URL, server id and session id.
+///
+/// The result is in the format `wss://screeps.com/socket/<server_id>/<session_id>/websocket`, using `ws` for `http`
+/// API urls and `wss` for `https` ones. Both official server urls (`https://screeps.com/api/`) and private server urls
+/// (`http://localhost:21025/` or `http://localhost:21025/api/`) are supported.
+///
+/// The server id is formatted as three digits, as `sockjs-client` does. Most users will want [`transform_url`]
+/// instead, which picks random ids.
+///
+/// [`transform_url`]: fn.transform_url.html
+pub fn socket_url<U: AsRef<str> + ?Sized>(
+    url: &U,
+    server_id: u32,
+    session_id: &str,
+) -> Result<Url, UrlError> {
     let mut url = match url.as_ref().parse::<Url>() {
         Ok(v) => v,
         Err(e) => return Err(UrlError::from_err(e, url.as_ref().to_owned())),
@@ -115,30 +139,64 @@ pub fn transform_url<U: AsRef<str> + ?Sized>(url: &U) -> Result<Url, UrlError> {
     url.set_scheme(new_scheme)
         .expect("expected `ws` and `wss` to be valid url schemes.");
 
-    // we could probably just use gen_ascii_chars for the session ID, but to be safe
-    // we just use the subset that `sockjs-client` does.
-    const VALID_CHARS: &'static [u8] = b"abcdefghijklmnopqrstuvwxyz012345";
-
-    // avoiding allocations!
-    struct GenServerAndSessionId;
+    // the socket lives next to the API, so strip a trailing `api` path segment if there is one.
+    let base_path = {
+        let path = url.path().trim_end_matches('/');
+        match path.rfind('/') {
+            Some(idx) if &path[idx + 1..] == "api" => path[..idx].to_owned(),
+            _ => path.to_owned(),
+        }
+    };
 
-    impl fmt::Display for GenServerAndSessionId {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            let mut rng = rand::thread_rng();
-            write!(f, "../socket/{:04}/", rng.gen_range(0, 1000))?;
+    url.set_path(&format!(
+        "{}/socket/{:03}/{}/websocket",
+        base_path, server_id, session_id
+    ));
+    url.set_query(None);
+    url.set_fragment(None);
 
-            for _ in 0..8 {
-                write!(f, "{}", *VALID_CHARS.choose(&mut rng).unwrap() as char)?;
-            }
-            write!(f, "/websocket")?;
+    Ok(url)
+}
 
-            Ok(())
-        }
+#[cfg(test)]
+mod tests {
+    use super::{socket_url, transform_url};
+
+    #[test]
+    fn official_server_url() {
+        assert_eq!(
+            socket_url("https://screeps.com/api/", 123, "abcdefgh")
+                .unwrap()
+                .as_str(),
+            "wss://screeps.com/socket/123/abcdefgh/websocket"
+        );
+        assert_eq!(
+            socket_url("https://screeps.com/season/api", 7, "abcdefgh")
+                .unwrap()
+                .as_str(),
+            "wss://screeps.com/season/socket/007/abcdefgh/websocket"
+        );
     }
 
-    let result = url
-        .join(&GenServerAndSessionId.to_string())
-        .expect("expected generated string known to be correct to parse successfully");
+    #[test]
+    fn private_server_url() {
+        assert_eq!(
+            socket_url("http://localhost:21025/", 5, "01234567")
+                .unwrap()
+                .as_str(),
+            "ws://localhost:21025/socket/005/01234567/websocket"
+        );
+        assert_eq!(
+            socket_url("http://localhost:21025/api/", 5, "01234567")
+                .unwrap()
+                .as_str(),
+            "ws://localhost:21025/socket/005/01234567/websocket"
+        );
+    }
 
-    Ok(result)
+    #[test]
+    fn wrong_scheme() {
+        assert!(socket_url("ftp://screeps.com/api/", 0, "abcdefgh").is_err());
+        assert!(transform_url("ftp://screeps.com/api/").is_err());
+    }
 }
@@ -0,0 +1,541 @@
+//! Running socket connections on a background task.
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    hash::Hash,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use futures::{
+    channel::mpsc,
+    future::{self, Either},
+    Sink, SinkExt, Stream, StreamExt,
+};
+
+use super::{
+    AccountMessage, AccountSockets, BoundedQueue, Channel, ConnectionStats, OverflowPolicy,
+};
+
+/// A request from a `SocketHandle` to the connection of one of its accounts.
+#[derive(Debug)]
+enum Command {
+    Subscribe(String),
+    Unsubscribe(String),
+    Send(String),
+    Shutdown,
+}
+
+/// A handle to socket connections running in the background, one per account, created with [`spawn`].
+///
+/// Subscriptions made through the handle are remembered, and made again whenever an account's connection is
+/// re-established. Dropping the handle shuts the connections down, as with [`SocketHandle::shutdown`].
+///
+/// [`spawn`]: fn.spawn.html
+/// [`SocketHandle::shutdown`]: #method.shutdown
+#[derive(Debug)]
+pub struct SocketHandle<K: Hash + Eq> {
+    commands: HashMap<K, mpsc::UnboundedSender<Command>>,
+    sockets: Arc<Mutex<AccountSockets<K>>>,
+    messages: BoundedQueue<AccountMessage<'static, K>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl<K: Hash + Eq + Clone> SocketHandle<K> {
+    /// Subscribes an account to a channel, now if connected or as soon as its connection is (re-)established
+    /// otherwise.
+    ///
+    /// Does nothing if the account is unknown.
+    pub fn subscribe(&self, account: &K, channel: &Channel) {
+        self.command(account, Command::Subscribe(channel.to_string()));
+    }
+
+    /// Unsubscribes an account from a channel.
+    ///
+    /// Does nothing if the account is unknown.
+    pub fn unsubscribe(&self, account: &K, channel: &Channel) {
+        self.command(account, Command::Unsubscribe(channel.to_string()));
+    }
+
+    /// Sends a raw websocket string, such as one created with the functions in [`commands`], on an account's
+    /// connection.
+    ///
+    /// Unlike subscriptions, this is only sent if the account is connected when it is processed: it is dropped
+    /// otherwise.
+    ///
+    /// [`commands`]: commands/index.html
+    pub fn send(&self, account: &K, frame: String) {
+        self.command(account, Command::Send(frame));
+    }
+
+    /// The queue each message received is pushed to, tagged with the account it was received for.
+    ///
+    /// When the queue is full, the oldest messages are dropped to make room. The queue is closed once every
+    /// connection has stopped, after being shut down or once the reconnect policy gives up on it.
+    pub fn messages(&self) -> BoundedQueue<AccountMessage<'static, K>> {
+        self.messages.clone()
+    }
+
+    /// Gets a snapshot of the statistics of an account's connections, which are updated as frames are received.
+    ///
+    /// Returns `None` if the account is unknown.
+    pub fn stats(&self, account: &K) -> Option<ConnectionStats> {
+        let sockets = self.sockets.lock().unwrap_or_else(PoisonError::into_inner);
+        sockets.stats(account).cloned()
+    }
+
+    /// Closes every connection and stops reconnecting them, without waiting for them to stop.
+    pub fn shutdown(&self) {
+        for commands in self.commands.values() {
+            // fails once the connection has stopped, when there is nothing left to do.
+            let _ = commands.unbounded_send(Command::Shutdown);
+        }
+    }
+
+    /// Waits for every connection to stop, after [`SocketHandle::shutdown`] or once the reconnect policy gives up on
+    /// them.
+    ///
+    /// [`SocketHandle::shutdown`]: #method.shutdown
+    pub async fn join(self) {
+        let SocketHandle { commands, task, .. } = self;
+        // keep the connections running until they stop by themselves.
+        let result = task.await;
+        drop(commands);
+
+        if let Err(err) = result {
+            if err.is_panic() {
+                std::panic::resume_unwind(err.into_panic());
+            }
+        }
+    }
+
+    fn command(&self, account: &K, command: Command) {
+        if let Some(commands) = self.commands.get(account) {
+            // fails once the connection has stopped, when there is nothing left to do.
+            let _ = commands.unbounded_send(command);
+        }
+    }
+}
+
+/// Runs a socket connection for each account in `sockets` on a tokio task, reconnecting them when they drop, and
+/// immediately returns a handle to them.
+///
+/// `connect` is called with an account's key to open each of its connections, with any websocket client: it returns
+/// the sending and receiving halves of the connection, which send and receive text frames. On each connection, the
+/// task authenticates with the account's current token and subscribes to the channels the account is subscribed to.
+/// Refreshed tokens received are written back to the account's token storage, so adding accounts with
+/// `AccountSockets::add_account_with_storage` and `Api::token_storage` shares them with each account's `Api`.
+///
+/// When a connection drops or fails to open, it is opened again after the delay given by the reconnect policy in the
+/// `SocketConfig` of `sockets`, while the other accounts stay connected. This must be called from within a tokio
+/// runtime, such as in a task, or after `Runtime::enter`.
+///
+/// ```no_run
+/// # use futures::{sink::Drain, stream::Pending};
+/// # type Frames = Pending<Result<String, std::io::Error>>;
+/// # async fn open_socket(url: &url::Url) -> Result<(Drain<String>, Frames), std::io::Error> {
+/// #     Ok((futures::sink::drain(), futures::stream::pending()))
+/// # }
+/// # async fn run() {
+/// use screeps_api::websocket::{self, AccountSockets, Channel, SocketConfig};
+///
+/// let first = screeps_api::Api::new(hyper::Client::new()).with_token("first token");
+/// let second = screeps_api::Api::new(hyper::Client::new()).with_token("second token");
+///
+/// let mut accounts = AccountSockets::with_config(SocketConfig::new());
+/// accounts.add_account_with_storage("first", first.token_storage());
+/// accounts.add_account_with_storage("second", second.token_storage());
+///
+/// let sockets = websocket::spawn(accounts, |_account| async {
+///     // open the connection with your websocket client of choice, on a new url each time.
+///     open_socket(&websocket::default_url()).await
+/// });
+/// sockets.subscribe(&"first", &Channel::ServerMessages);
+///
+/// let messages = sockets.messages();
+/// std::thread::spawn(move || {
+///     while let Some(message) = messages.pop() {
+///         println!("{}: {:?}", message.account, message.message);
+///     }
+/// });
+///
+/// // ...
+///
+/// sockets.shutdown();
+/// sockets.join().await;
+/// # }
+/// ```
+pub fn spawn<K, F, Fut, E, W, R, RE>(sockets: AccountSockets<K>, connect: F) -> SocketHandle<K>
+where
+    K: Hash + Eq + Clone + fmt::Debug + Send + 'static,
+    F: Fn(K) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(W, R), E>> + Send + 'static,
+    E: fmt::Display + Send + 'static,
+    W: Sink<String> + Send + Unpin + 'static,
+    W::Error: fmt::Display,
+    R: Stream<Item = Result<String, RE>> + Send + Unpin + 'static,
+    RE: fmt::Display + Send + 'static,
+{
+    let messages = BoundedQueue::new(
+        sockets.config().queue_capacity(),
+        OverflowPolicy::DropOldest,
+    );
+    let accounts = sockets.accounts().cloned().collect::<Vec<_>>();
+    let sockets = Arc::new(Mutex::new(sockets));
+
+    let mut commands = HashMap::with_capacity(accounts.len());
+    let mut drivers = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        let (sender, receiver) = mpsc::unbounded();
+        commands.insert(account.clone(), sender);
+        drivers.push(Driver {
+            account,
+            sockets: sockets.clone(),
+            commands: receiver,
+            messages: messages.clone(),
+        });
+    }
+
+    let task = {
+        let messages = messages.clone();
+        tokio::spawn(async move {
+            let connect = &connect;
+            future::join_all(drivers.into_iter().map(|driver| driver.run(connect))).await;
+            messages.close();
+        })
+    };
+
+    SocketHandle {
+        commands,
+        sockets,
+        messages,
+        task,
+    }
+}
+
+/// The connection of one account run by `spawn`.
+struct Driver<K: Hash + Eq> {
+    account: K,
+    sockets: Arc<Mutex<AccountSockets<K>>>,
+    commands: mpsc::UnboundedReceiver<Command>,
+    messages: BoundedQueue<AccountMessage<'static, K>>,
+}
+
+/// Something which happened on a connection.
+enum Event<T> {
+    Frame(Option<T>),
+    Command(Option<Command>),
+}
+
+impl<K: Hash + Eq + Clone + fmt::Debug> Driver<K> {
+    async fn run<F, Fut, E, W, R, RE>(mut self, connect: &F)
+    where
+        F: Fn(K) -> Fut,
+        Fut: Future<Output = Result<(W, R), E>>,
+        E: fmt::Display,
+        W: Sink<String> + Unpin,
+        W::Error: fmt::Display,
+        R: Stream<Item = Result<String, RE>> + Unpin,
+        RE: fmt::Display,
+    {
+        loop {
+            match self.until_shutdown(connect(self.account.clone())).await {
+                Some(Ok((sink, stream))) => {
+                    if !self.drive(sink, stream).await {
+                        break;
+                    }
+                }
+                Some(Err(err)) => warn!("failed to connect socket for {:?}: {}", self.account, err),
+                None => break,
+            }
+
+            let delay = self.lock().reconnect_delay(&self.account);
+            let delay = match delay {
+                Some(delay) => delay,
+                None => {
+                    warn!("giving up on reconnecting socket for {:?}", self.account);
+                    break;
+                }
+            };
+            debug!("reconnecting socket for {:?} in {:?}", self.account, delay);
+            if self
+                .until_shutdown(tokio::time::sleep(delay))
+                .await
+                .is_none()
+            {
+                break;
+            }
+        }
+    }
+
+    /// Runs an open connection until it drops, returning `true`, or until shut down, returning `false`.
+    async fn drive<W, R, RE>(&mut self, mut sink: W, mut stream: R) -> bool
+    where
+        W: Sink<String> + Unpin,
+        W::Error: fmt::Display,
+        R: Stream<Item = Result<String, RE>> + Unpin,
+        RE: fmt::Display,
+    {
+        let connect_commands = self
+            .lock()
+            .connect_commands(&self.account)
+            .unwrap_or_default();
+        for command in connect_commands {
+            if let Err(err) = sink.send(command).await {
+                warn!("failed to send on socket for {:?}: {}", self.account, err);
+                return true;
+            }
+        }
+
+        loop {
+            let event = match future::select(stream.next(), self.commands.next()).await {
+                Either::Left((frame, _)) => Event::Frame(frame),
+                Either::Right((command, _)) => Event::Command(command),
+            };
+
+            let frame = match event {
+                Event::Frame(Some(Ok(frame))) => {
+                    let parsed = self.lock().parse(&self.account, &frame);
+                    match parsed {
+                        Ok(message) => {
+                            self.messages.push(message);
+                        }
+                        Err(err) => {
+                            warn!(
+                                "failed to parse socket frame for {:?}: {}",
+                                self.account, err
+                            )
+                        }
+                    }
+                    continue;
+                }
+                Event::Frame(Some(Err(err))) => {
+                    warn!("socket connection for {:?} failed: {}", self.account, err);
+                    return true;
+                }
+                Event::Frame(None) => {
+                    debug!("socket connection for {:?} closed", self.account);
+                    return true;
+                }
+                Event::Command(Some(Command::Subscribe(channel))) => self
+                    .lock()
+                    .subscribe(&self.account, &Channel::other(channel)),
+                Event::Command(Some(Command::Unsubscribe(channel))) => self
+                    .lock()
+                    .unsubscribe(&self.account, &Channel::other(channel)),
+                Event::Command(Some(Command::Send(frame))) => Some(frame),
+                Event::Command(Some(Command::Shutdown)) | Event::Command(None) => {
+                    if let Err(err) = sink.close().await {
+                        debug!("failed to close socket for {:?}: {}", self.account, err);
+                    }
+                    return false;
+                }
+            };
+
+            if let Some(frame) = frame {
+                if let Err(err) = sink.send(frame).await {
+                    warn!("failed to send on socket for {:?}: {}", self.account, err);
+                    return true;
+                }
+            }
+        }
+    }
+
+    /// Waits for `future` while disconnected, recording subscriptions made meanwhile.
+    ///
+    /// Returns `None` if shut down first.
+    async fn until_shutdown<T, Fut: Future<Output = T>>(&mut self, future: Fut) -> Option<T> {
+        futures::pin_mut!(future);
+
+        loop {
+            let command = match future::select(future.as_mut(), self.commands.next()).await {
+                Either::Left((output, _)) => return Some(output),
+                Either::Right((command, _)) => command,
+            };
+
+            match command {
+                Some(Command::Subscribe(channel)) => {
+                    self.lock()
+                        .subscribe(&self.account, &Channel::other(channel));
+                }
+                Some(Command::Unsubscribe(channel)) => {
+                    self.lock()
+                        .unsubscribe(&self.account, &Channel::other(channel));
+                }
+                Some(Command::Send(_)) => warn!(
+                    "dropping frame sent while the socket for {:?} is disconnected",
+                    self.account
+                ),
+                Some(Command::Shutdown) | None => return None,
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, AccountSockets<K>> {
+        self.sockets.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use futures::{channel::mpsc, SinkExt, StreamExt};
+
+    use super::spawn;
+    use crate::{
+        websocket::{
+            AccountSockets, Channel, ReconnectPolicy, ScreepsMessage, SocketConfig, SockjsMessage,
+        },
+        MemoryTokenStorage, TokenStorage,
+    };
+
+    /// The socket's ends of a connection.
+    type Connection = (
+        mpsc::UnboundedSender<String>,
+        mpsc::UnboundedReceiver<Result<String, String>>,
+    );
+
+    /// The server's ends of a connection.
+    type Remote = (
+        mpsc::UnboundedReceiver<String>,
+        mpsc::UnboundedSender<Result<String, String>>,
+    );
+
+    /// Opening a connection.
+    type Connect = futures::future::Ready<Result<Connection, String>>;
+
+    /// The connections opened so far, with the accounts they were opened for.
+    type Opened = Arc<Mutex<Vec<(&'static str, Remote)>>>;
+
+    /// Opens connections which are the ends of the channels it returns, one per connection opened.
+    fn connections() -> (
+        impl Fn(&'static str) -> Connect + Send + Sync + 'static,
+        Opened,
+    ) {
+        let opened = Opened::default();
+        let connect = {
+            let opened = opened.clone();
+            move |account| {
+                let (sent, sent_rx) = mpsc::unbounded();
+                let (received_tx, received) = mpsc::unbounded();
+                opened
+                    .lock()
+                    .unwrap()
+                    .push((account, (sent_rx, received_tx)));
+                futures::future::ready(Ok((sent, received)))
+            }
+        };
+        (connect, opened)
+    }
+
+    /// Takes the oldest connection opened for the account.
+    fn take(opened: &Opened, account: &str) -> Remote {
+        let mut opened = opened.lock().unwrap();
+        let idx = opened
+            .iter()
+            .position(|&(opened_for, _)| opened_for == account)
+            .unwrap();
+        opened.remove(idx).1
+    }
+
+    #[test]
+    fn run_in_background() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let tokens = MemoryTokenStorage::default();
+            tokens.set("token-two".into());
+            let config = SocketConfig::new().with_reconnect(ReconnectPolicy::Fixed {
+                delay: Duration::from_millis(1),
+                max_attempts: Some(1),
+            });
+            let mut accounts = AccountSockets::with_config(config);
+            accounts.add_account("first", &b"token-one"[..]);
+            accounts.add_account_with_storage("second", tokens.clone());
+            let (connect, opened) = connections();
+
+            let sockets = spawn(accounts, connect);
+            sockets.subscribe(&"second", &Channel::user_cpu("57874d42d0ae911e3bd15bbc"));
+            let messages = sockets.messages();
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let (mut first_sent, _first_received) = take(&opened, "first");
+            assert_eq!(first_sent.next().await.unwrap(), r#"["auth token-one"]"#);
+            let (mut sent, mut received) = take(&opened, "second");
+            assert_eq!(sent.next().await.unwrap(), r#"["auth token-two"]"#);
+            assert_eq!(
+                sent.next().await.unwrap(),
+                r#"["subscribe user:57874d42d0ae911e3bd15bbc/cpu"]"#
+            );
+
+            received
+                .send(Ok(r#"a["auth ok new-token"]"#.to_owned()))
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let message = messages.try_pop().unwrap();
+            assert_eq!(message.account, "second");
+            match message.message {
+                SockjsMessage::Messages(ref messages) => match messages[..] {
+                    [ScreepsMessage::AuthOk { ref new_token }] => {
+                        assert_eq!(new_token, "new-token")
+                    }
+                    ref other => panic!("expected auth ok, found {:?}", other),
+                },
+                ref other => panic!("expected messages, found {:?}", other),
+            }
+            assert_eq!(tokens.get().unwrap(), "new-token");
+            assert_eq!(sockets.stats(&"second").unwrap().messages_received(), 1);
+            assert_eq!(sockets.stats(&"first").unwrap().messages_received(), 0);
+            assert!(sockets.stats(&"third").is_none());
+
+            // dropping a connection reconnects it, authenticating with the new token and subscribing again, while
+            // the other account stays connected.
+            drop(received);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let (mut sent, _received) = take(&opened, "second");
+            assert_eq!(sent.next().await.unwrap(), r#"["auth new-token"]"#);
+            assert_eq!(
+                sent.next().await.unwrap(),
+                r#"["subscribe user:57874d42d0ae911e3bd15bbc/cpu"]"#
+            );
+            assert!(opened.lock().unwrap().is_empty());
+            assert_eq!(sockets.stats(&"second").unwrap().reconnects(), 1);
+
+            sockets.shutdown();
+            sockets.join().await;
+            assert_eq!(first_sent.next().await, None);
+            assert_eq!(sent.next().await, None);
+            assert!(messages.pop().is_none());
+        });
+    }
+
+    #[test]
+    fn give_up_reconnecting() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let config = SocketConfig::new().with_reconnect(ReconnectPolicy::Never);
+            let mut accounts = AccountSockets::with_config(config);
+            accounts.add_account("account", &b"token"[..]);
+            let (connect, opened) = connections();
+
+            let sockets = spawn(accounts, connect);
+            let messages = sockets.messages();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            // closes the connection.
+            opened.lock().unwrap().clear();
+
+            sockets.join().await;
+            assert!(messages.pop().is_none());
+        });
+    }
+}
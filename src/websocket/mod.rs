@@ -1,13 +1,68 @@
-//! Handling of socket connections to screeps using ws-rs as a backend.
+//! Handling of socket connections to screeps.
+//!
+//! `screeps-api` does not open websocket connections itself: this module provides the pieces needed to talk to the
+//! server over a connection made with any websocket client. [`transform_url`] finds the url to connect to,
+//! [`commands`] creates the frames to send, and [`SockjsMessage::parse`] interprets the frames received.
+//!
+//! # TLS
+//!
+//! Since the connection is owned by the websocket client, so is its TLS configuration. Servers using `https` API urls
+//! get `wss` socket urls; to connect to a private server using a self-signed certificate or a custom root CA, build a
+//! TLS connector trusting it with `ClientConfig::tls_connector`, from the same settings as the HTTP client, and pass it
+//! to your websocket client. The `ws-debug` example does this with the `websocket` crate.
+//!
+//! # Proxies
+//!
+//! Likewise, connecting through a proxy is up to the websocket client. For clients which can run the handshake over an
+//! existing stream, the proxy set in the HTTP client's `ClientConfig` can be used for the socket too, with the `http`
+//! feature: [`proxy_connect_request`] creates the request which opens a tunnel through an HTTP or HTTPS proxy, and
+//! [`Socks5Handshake`] the messages which open one through a SOCKS5 proxy. Both send the proxy's credentials, if any,
+//! and are what the `hyper` connector built by `ClientConfig::https_connector` tunnels requests with.
+//!
+//! # Running in the background
+//!
+//! With the `http` feature, [`spawn`] runs a connection for each account of an [`AccountSockets`] on a tokio task,
+//! opened by any websocket client, and returns a [`SocketHandle`] right away. The task authenticates, resubscribes and
+//! reconnects each account as needed, following its [`SocketConfig`], while the handle subscribes accounts to channels
+//! and receives their messages, tagged with the account they were received for.
+//!
+//! [`transform_url`]: fn.transform_url.html
+//! [`spawn`]: fn.spawn.html
+//! [`AccountSockets`]: struct.AccountSockets.html
+//! [`SocketHandle`]: struct.SocketHandle.html
+//! [`SocketConfig`]: struct.SocketConfig.html
+//! [`commands`]: commands/index.html
+//! [`proxy_connect_request`]: fn.proxy_connect_request.html
+//! [`Socks5Handshake`]: struct.Socks5Handshake.html
+//! [`SockjsMessage::parse`]: enum.SockjsMessage.html#method.parse
 mod channel;
 pub mod commands;
+mod config;
 mod connecting;
+#[cfg(feature = "http")]
+mod driver;
+mod multiplex;
 mod parsing;
+mod pending;
+mod queue;
+mod recording;
+mod stats;
 mod types;
 
 pub use self::{
     channel::Channel,
-    connecting::{default_url, transform_url},
+    config::{ReconnectPolicy, SocketConfig},
+    connecting::{default_url, socket_url, transform_url, UrlError},
+    multiplex::{AccountMessage, AccountSockets},
     parsing::*,
+    pending::PendingSubscriptions,
+    queue::{BoundedQueue, OverflowPolicy},
+    recording::{RecordedFrame, Recorder, Replay, ReplayError},
+    stats::ConnectionStats,
     types::*,
 };
+
+#[cfg(feature = "http")]
+pub use self::driver::{spawn, SocketHandle};
+#[cfg(feature = "http")]
+pub use crate::tunnel::{proxy_connect_request, Socks5Error, Socks5Handshake};
@@ -0,0 +1,423 @@
+//! Managing socket state for several accounts at once.
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    hash::Hash,
+    sync::Arc,
+    time::Duration,
+};
+
+use super::{commands, Channel, ConnectionStats, ParseError, SocketConfig, SockjsMessage};
+use crate::{MemoryTokenStorage, Token, TokenStorage};
+
+/// A parsed message, tagged with the account whose socket it arrived on.
+#[derive(Clone, Debug)]
+pub struct AccountMessage<'a, K> {
+    /// The account the message was received for.
+    pub account: K,
+    /// The parsed message.
+    pub message: SockjsMessage<'a>,
+}
+
+#[derive(Clone, Debug)]
+struct AccountState {
+    tokens: Arc<dyn TokenStorage>,
+    subscriptions: Vec<String>,
+    stats: ConnectionStats,
+    /// Consecutive failed connections, and the delay before the last attempt.
+    failures: u32,
+    last_delay: Duration,
+}
+
+impl AccountState {
+    fn new(tokens: Arc<dyn TokenStorage>) -> Self {
+        AccountState {
+            tokens,
+            subscriptions: Vec::new(),
+            stats: ConnectionStats::new(),
+            failures: 0,
+            last_delay: Duration::from_secs(0),
+        }
+    }
+}
+
+/// Keeps track of the socket state for multiple accounts, for example when monitoring an alliance using several
+/// accounts at once.
+///
+/// Like the rest of the `websocket` module, this does no IO itself. Either pass it to [`spawn`], which opens and runs
+/// a socket for every account on a tokio task, or open one socket per account on whatever event loop you are using,
+/// send the commands this returns down the matching socket, and pass every incoming frame to [`AccountSockets::parse`]
+/// with the key of the account it was received on.
+///
+/// Each account is identified by a key `K` of your choosing, such as a username or a server/username pair. Each
+/// account's token is kept in a [`TokenStorage`], which can be shared with the account's [`Api`] using
+/// [`AccountSockets::add_account_with_storage`]: refreshed tokens received on the socket are then used by HTTP
+/// requests too.
+///
+/// When a socket drops, [`AccountSockets::reconnect_delay`] tells how long to wait before reconnecting it, following
+/// the reconnect policy in the [`SocketConfig`] given to [`AccountSockets::with_config`].
+///
+/// [`spawn`]: fn.spawn.html
+/// [`AccountSockets::parse`]: struct.AccountSockets.html#method.parse
+/// [`AccountSockets::reconnect_delay`]: struct.AccountSockets.html#method.reconnect_delay
+/// [`SocketConfig`]: struct.SocketConfig.html
+/// [`AccountSockets::with_config`]: struct.AccountSockets.html#method.with_config
+/// [`TokenStorage`]: ../tokens/trait.TokenStorage.html
+/// [`Api`]: ../struct.Api.html
+/// [`AccountSockets::add_account_with_storage`]: struct.AccountSockets.html#method.add_account_with_storage
+#[derive(Clone, Debug)]
+pub struct AccountSockets<K: Hash + Eq> {
+    accounts: HashMap<K, AccountState>,
+    config: SocketConfig,
+}
+
+impl<K: Hash + Eq> Default for AccountSockets<K> {
+    fn default() -> Self {
+        AccountSockets {
+            accounts: HashMap::new(),
+            config: SocketConfig::default(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone> AccountSockets<K> {
+    /// Creates a new manager with no accounts, using the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new manager with no accounts, using the given settings for every account.
+    pub fn with_config(config: SocketConfig) -> Self {
+        AccountSockets {
+            accounts: HashMap::new(),
+            config,
+        }
+    }
+
+    /// The settings used for every account.
+    pub fn config(&self) -> &SocketConfig {
+        &self.config
+    }
+
+    /// Adds an account, or replaces the token of an existing account.
+    ///
+    /// Subscriptions of an existing account are kept, and its token is stored in its existing token storage.
+    pub fn add_account<T: Into<Token>>(&mut self, account: K, token: T) {
+        match self.accounts.entry(account) {
+            Entry::Occupied(e) => e.get().tokens.set(token.into()),
+            Entry::Vacant(e) => {
+                let tokens = MemoryTokenStorage::default();
+                tokens.set(token.into());
+                e.insert(AccountState::new(Arc::new(tokens)));
+            }
+        }
+    }
+
+    /// Adds an account whose token is kept in the given storage, or replaces the token storage of an existing
+    /// account.
+    ///
+    /// Pass `Api::token_storage` to share the storage with an `Api`: the socket authenticates with the token the
+    /// `Api` last received, and refreshed tokens received on the socket are stored for the `Api` to use. Subscriptions
+    /// of an existing account are kept.
+    pub fn add_account_with_storage<S: TokenStorage>(&mut self, account: K, storage: S) {
+        let tokens: Arc<dyn TokenStorage> = Arc::new(storage);
+        match self.accounts.entry(account) {
+            Entry::Occupied(mut e) => e.get_mut().tokens = tokens,
+            Entry::Vacant(e) => {
+                e.insert(AccountState::new(tokens));
+            }
+        }
+    }
+
+    /// Removes an account, returning its current token if it was known.
+    pub fn remove_account(&mut self, account: &K) -> Option<Token> {
+        self.accounts
+            .remove(account)
+            .and_then(|state| state.tokens.get())
+    }
+
+    /// Iterates over the keys of all known accounts.
+    pub fn accounts(&self) -> impl Iterator<Item = &K> + '_ {
+        self.accounts.keys()
+    }
+
+    /// Gets the token currently stored for an account.
+    pub fn token(&self, account: &K) -> Option<Token> {
+        self.accounts.get(account)?.tokens.get()
+    }
+
+    /// Gets the connection statistics for an account.
+    pub fn stats(&self, account: &K) -> Option<&ConnectionStats> {
+        self.accounts.get(account).map(|state| &state.stats)
+    }
+
+    /// Gets the raw websocket strings to send when an account's socket has (re)connected: authentication with the
+    /// account's current token, if it has one, then a subscription to each channel the account is subscribed to.
+    ///
+    /// Returns `None` if the account is unknown.
+    pub fn connect_commands(&mut self, account: &K) -> Option<Vec<String>> {
+        let state = self.accounts.get_mut(account)?;
+
+        if state.stats.frames_received() > 0 {
+            state.stats.record_reconnect();
+        }
+
+        let mut result = Vec::with_capacity(state.subscriptions.len() + 1);
+        result.extend(
+            state
+                .tokens
+                .get()
+                .map(|token| commands::authenticate(&token)),
+        );
+        result.extend(
+            state
+                .subscriptions
+                .iter()
+                .map(|channel| commands::subscribe(&Channel::other(channel.as_str()))),
+        );
+
+        Some(result)
+    }
+
+    /// Records that an account's socket dropped or failed to connect, and gets how long to wait before connecting it
+    /// again.
+    ///
+    /// The delay comes from the configured reconnect policy, given the number of consecutive failures: this count is
+    /// reset once a frame is received on the new connection. Returns `None` if the policy gives up, or if the account
+    /// is unknown.
+    pub fn reconnect_delay(&mut self, account: &K) -> Option<Duration> {
+        let state = self.accounts.get_mut(account)?;
+
+        let delay = self
+            .config
+            .reconnect()
+            .delay(state.failures, state.last_delay)?;
+        state.failures += 1;
+        state.last_delay = delay;
+
+        Some(delay)
+    }
+
+    /// Records a subscription for the given account, and gets the raw websocket string to send on its socket.
+    ///
+    /// Returns `None` if the account is unknown.
+    pub fn subscribe(&mut self, account: &K, channel: &Channel) -> Option<String> {
+        let state = self.accounts.get_mut(account)?;
+
+        let name = channel.to_string();
+        if !state.subscriptions.contains(&name) {
+            state.subscriptions.push(name);
+        }
+
+        Some(commands::subscribe(channel))
+    }
+
+    /// Removes a subscription for the given account, and gets the raw websocket string to send on its socket.
+    ///
+    /// Returns `None` if the account is unknown.
+    pub fn unsubscribe(&mut self, account: &K, channel: &Channel) -> Option<String> {
+        let state = self.accounts.get_mut(account)?;
+
+        let name = channel.to_string();
+        state.subscriptions.retain(|existing| *existing != name);
+
+        Some(commands::unsubscribe(channel))
+    }
+
+    /// Iterates over the channels an account is subscribed to.
+    pub fn subscriptions(&self, account: &K) -> impl Iterator<Item = Channel<'_>> + '_ {
+        self.accounts
+            .get(account)
+            .into_iter()
+            .flat_map(|state| state.subscriptions.iter())
+            .map(|channel| Channel::other(channel.as_str()))
+    }
+
+    /// Finds the account's room detail subscriptions which have not received an actual update in the last `max_age`
+    /// ticks, as of `game_time`.
+    ///
+    /// Only 2 room detail subscriptions per account receive updates each tick. Rotating the subscriptions this
+    /// returns out makes room for others.
+    pub fn starved_rooms(&self, account: &K, game_time: u32, max_age: u32) -> Vec<Channel<'_>> {
+        let state = match self.accounts.get(account) {
+            Some(state) => state,
+            None => return Vec::new(),
+        };
+
+        state
+            .subscriptions
+            .iter()
+            .filter(|channel| channel.starts_with("room:"))
+            .map(|channel| Channel::other(channel.as_str()))
+            .filter(|channel| match state.stats.room_last_update(channel) {
+                Some(last) => game_time.saturating_sub(last) > max_age,
+                None => true,
+            })
+            .collect()
+    }
+
+    /// Parses a frame received on the given account's socket, tagging the result with that account.
+    ///
+    /// Refreshed tokens received on an account's socket are written to that account's token storage. Frames for
+    /// unknown accounts are still parsed, but are not recorded in any statistics.
+    pub fn parse(
+        &mut self,
+        account: &K,
+        frame: &str,
+    ) -> Result<AccountMessage<'static, K>, ParseError> {
+        let message = SockjsMessage::parse(frame)?;
+
+        if let Some(state) = self.accounts.get_mut(account) {
+            state.failures = 0;
+            state.last_delay = Duration::from_secs(0);
+            state.stats.record_frame(frame, &message);
+            message.store_token(&*state.tokens);
+        }
+
+        Ok(AccountMessage {
+            account: account.clone(),
+            message,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::AccountSockets;
+    use crate::{
+        websocket::{Channel, ReconnectPolicy, ScreepsMessage, SocketConfig, SockjsMessage},
+        MemoryTokenStorage, RoomName, TokenStorage,
+    };
+
+    #[test]
+    fn tag_messages_with_account() {
+        let mut sockets = AccountSockets::new();
+        sockets.add_account("first", &b"token-one"[..]);
+        sockets.add_account("second", &b"token-two"[..]);
+
+        sockets
+            .subscribe(&"second", &Channel::user_cpu("57874d42d0ae911e3bd15bbc"))
+            .unwrap();
+
+        let commands = sockets.connect_commands(&"second").unwrap();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0], r#"["auth token-two"]"#);
+        assert_eq!(
+            commands[1],
+            r#"["subscribe user:57874d42d0ae911e3bd15bbc/cpu"]"#
+        );
+
+        let frame =
+            r#"a["[\"user:57874d42d0ae911e3bd15bbc/cpu\",{\"cpu\":32,\"memory\":126435}]"]"#;
+        let tagged = sockets.parse(&"second", frame).unwrap();
+        assert_eq!(tagged.account, "second");
+        match tagged.message {
+            SockjsMessage::Messages(ref messages) => match messages[0] {
+                ScreepsMessage::ChannelUpdate { ref update } => {
+                    assert!(update.is_from(&Channel::user_cpu("57874d42d0ae911e3bd15bbc")))
+                }
+                ref other => panic!("expected channel update, found {:?}", other),
+            },
+            ref other => panic!("expected messages, found {:?}", other),
+        }
+
+        assert_eq!(sockets.stats(&"second").unwrap().messages_received(), 1);
+        assert_eq!(sockets.stats(&"first").unwrap().messages_received(), 0);
+
+        sockets
+            .parse(&"first", r#"a["auth ok token-three"]"#)
+            .unwrap();
+        assert_eq!(sockets.token(&"first").unwrap(), "token-three");
+    }
+
+    #[test]
+    fn write_back_refreshed_tokens() {
+        let tokens = MemoryTokenStorage::default();
+        tokens.set("old-token".into());
+
+        let mut sockets = AccountSockets::new();
+        sockets.add_account_with_storage("account", tokens.clone());
+        assert_eq!(
+            sockets.connect_commands(&"account").unwrap(),
+            [r#"["auth old-token"]"#]
+        );
+
+        sockets
+            .parse(&"account", r#"a["time 1474379899001"]"#)
+            .unwrap();
+        assert_eq!(tokens.get().unwrap(), "old-token");
+
+        sockets
+            .parse(&"account", r#"a["auth ok new-token"]"#)
+            .unwrap();
+        assert_eq!(tokens.get().unwrap(), "new-token");
+        assert_eq!(sockets.token(&"account").unwrap(), "new-token");
+
+        // replacing the token of an account with shared storage replaces it for everything sharing it.
+        sockets.add_account("account", &b"newer-token"[..]);
+        assert_eq!(tokens.get().unwrap(), "newer-token");
+    }
+
+    #[test]
+    fn find_starved_rooms() {
+        let mut sockets = AccountSockets::new();
+        sockets.add_account("account", &b"token"[..]);
+
+        let e1n1 = Channel::room_detail(RoomName::new("E1N1").unwrap(), Some("shard0"));
+        let e2n2 = Channel::room_detail(RoomName::new("E2N2").unwrap(), Some("shard0"));
+        sockets.subscribe(&"account", &e1n1).unwrap();
+        sockets.subscribe(&"account", &e2n2).unwrap();
+
+        sockets
+            .parse(
+                &"account",
+                r#"a["[\"room:shard0/E1N1\",{\"gameTime\":100,\"info\":{},\"objects\":{}}]"]"#,
+            )
+            .unwrap();
+        sockets
+            .parse(
+                &"account",
+                r#"a["[\"err@room:shard0/E2N2\",\"subscribe limit reached\"]"]"#,
+            )
+            .unwrap();
+
+        let stats = sockets.stats(&"account").unwrap();
+        assert_eq!(stats.room_last_update(&e1n1), Some(100));
+        assert_eq!(stats.room_last_update(&e2n2), None);
+        assert_eq!(stats.room_skipped(&e2n2), 1);
+
+        assert_eq!(sockets.starved_rooms(&"account", 105, 10), vec![e2n2]);
+    }
+
+    #[test]
+    fn reconnect_with_policy() {
+        let config = SocketConfig::new().with_reconnect(ReconnectPolicy::Exponential {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            max_attempts: Some(3),
+        });
+        let mut sockets = AccountSockets::with_config(config);
+        sockets.add_account("account", &b"token"[..]);
+
+        assert_eq!(
+            sockets.reconnect_delay(&"account"),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            sockets.reconnect_delay(&"account"),
+            Some(Duration::from_secs(2))
+        );
+
+        // a frame on the new connection resets the count.
+        sockets.parse(&"account", "o").unwrap();
+        assert_eq!(
+            sockets.reconnect_delay(&"account"),
+            Some(Duration::from_secs(1))
+        );
+        sockets.reconnect_delay(&"account").unwrap();
+        sockets.reconnect_delay(&"account").unwrap();
+        assert_eq!(sockets.reconnect_delay(&"account"), None);
+        assert_eq!(sockets.reconnect_delay(&"unknown"), None);
+    }
+}
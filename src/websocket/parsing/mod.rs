@@ -6,8 +6,8 @@ use serde::{
     Deserialize, Deserializer,
 };
 
-use crate::websocket::types::ChannelUpdate;
-use crate::Token;
+use crate::websocket::{types::ChannelUpdate, Channel};
+use crate::{Token, TokenStorage};
 
 mod error;
 
@@ -49,8 +49,32 @@ pub enum SockjsMessage<'a> {
 }
 
 impl<'a> SockjsMessage<'a> {
+    /// If this contains a successful authentication message, stores the refreshed token it carries in the given
+    /// token storage.
+    ///
+    /// Sharing the `TokenStorage` of an [`Api`] keeps HTTP requests using the newest token. Returns true if a token
+    /// was stored. [`AccountSockets::parse`] does this for every frame it parses.
+    ///
+    /// [`Api`]: ../struct.Api.html
+    /// [`AccountSockets::parse`]: struct.AccountSockets.html#method.parse
+    pub fn store_token(&self, tokens: &dyn TokenStorage) -> bool {
+        match *self {
+            SockjsMessage::Message(ref message) => message.store_token(tokens),
+            SockjsMessage::Messages(ref messages) => {
+                messages.iter().fold(false, |stored, message| {
+                    message.store_token(tokens) || stored
+                })
+            }
+            _ => false,
+        }
+    }
+}
+
+impl SockjsMessage<'static> {
     /// Parses an incoming raw websockets messages on a Screeps SockJS socket into some result.
-    pub fn parse<T: AsRef<str> + ?Sized>(message_generic: &'a T) -> Result<Self, ParseError> {
+    ///
+    /// The result does not borrow from the message, so it can be kept or sent elsewhere once the message is dropped.
+    pub fn parse<T: AsRef<str> + ?Sized>(message_generic: &T) -> Result<Self, ParseError> {
         let message = message_generic.as_ref();
 
         let first = match message.chars().next() {
@@ -68,7 +92,7 @@ impl<'a> SockjsMessage<'a> {
                 match serde_json::from_str::<(i64, &str)>(rest) {
                     Ok((code, reason)) => SockjsMessage::Close {
                         code: code,
-                        reason: reason.into(),
+                        reason: reason.to_owned().into(),
                     },
                     Err(e) => {
                         return Err(ParseError::serde(
@@ -200,16 +224,109 @@ pub enum ScreepsMessage<'a> {
         /// The update.
         update: ChannelUpdate<'a>,
     },
+    /// An error reported by the server, either for a specific channel or for the connection as a whole.
+    ///
+    /// This is distinct from a parse failure: the message was understood, and it says something went wrong.
+    Error {
+        /// The channel this error relates to, if any.
+        channel: Option<Channel<'a>>,
+        /// The error message.
+        error: Cow<'a, str>,
+    },
     /// Another kind of message.
     Other(Cow<'a, str>),
 }
 
+impl<'a> ScreepsMessage<'a> {
+    /// If this message is an update on a subscribed channel, gets the channel it was sent on.
+    pub fn channel(&self) -> Option<Channel<'_>> {
+        match *self {
+            ScreepsMessage::ChannelUpdate { ref update } => Some(update.channel()),
+            _ => None,
+        }
+    }
+
+    /// If this is a successful authentication message, stores the refreshed token it carries in the given token
+    /// storage. Returns true if a token was stored.
+    pub fn store_token(&self, tokens: &dyn TokenStorage) -> bool {
+        match *self {
+            ScreepsMessage::AuthOk { ref new_token } => {
+                tokens.set(new_token.clone());
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
 const AUTH_PREFIX: &str = "auth ";
 const TIME_PREFIX: &str = "time ";
 const PROTOCOL_PREFIX: &str = "protocol ";
 const PACKAGE_PREFIX: &str = "package ";
 const AUTH_OK: &str = "ok ";
 const AUTH_FAILED: &str = "failed";
+const ERROR_PREFIXES: [&str; 2] = ["error:", "error "];
+const CHANNEL_ERROR_PREFIX: &str = "err@";
+
+/// Parses server error payloads which aren't channel updates: `error <message>` strings and `{"error": <message>}`
+/// objects.
+fn parse_error_message(message: &str) -> Option<ScreepsMessage<'static>> {
+    #[derive(serde_derive::Deserialize)]
+    struct ErrorObject {
+        error: serde_json::Value,
+    }
+
+    if let Some(rest) = ERROR_PREFIXES
+        .iter()
+        .find_map(|prefix| message.strip_prefix(prefix))
+    {
+        let rest = rest.trim_start();
+        return Some(ScreepsMessage::Error {
+            channel: None,
+            error: rest.to_owned().into(),
+        });
+    }
+
+    if message.starts_with('{') {
+        if let Ok(ErrorObject { error }) = serde_json::from_str::<ErrorObject>(message) {
+            return Some(ScreepsMessage::Error {
+                channel: None,
+                error: error_string(error).into(),
+            });
+        }
+    }
+
+    None
+}
+
+/// Turns `["err@<channel>", <message>]` channel errors which weren't handled more specifically into errors.
+fn channel_error(update: ChannelUpdate<'static>) -> ScreepsMessage<'static> {
+    match update {
+        ChannelUpdate::Other { channel, update } => {
+            if let Some(rest) = channel.strip_prefix(CHANNEL_ERROR_PREFIX) {
+                let channel = rest
+                    .parse()
+                    .unwrap_or_else(|_| Channel::other(rest.to_owned()));
+                ScreepsMessage::Error {
+                    channel: Some(channel),
+                    error: error_string(update).into(),
+                }
+            } else {
+                ScreepsMessage::ChannelUpdate {
+                    update: ChannelUpdate::Other { channel, update },
+                }
+            }
+        }
+        update => ScreepsMessage::ChannelUpdate { update },
+    }
+}
+
+fn error_string(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
 
 impl ScreepsMessage<'static> {
     /// Parses the internal message from a SockJS message into a meaningful type.
@@ -275,8 +392,12 @@ impl ScreepsMessage<'static> {
                 }
             }
 
+            if let Some(error) = parse_error_message(message) {
+                return error;
+            }
+
             match from_str_with_warning(message, "screeps typed channel update") {
-                Ok(update) => return ScreepsMessage::ChannelUpdate { update: update },
+                Ok(update) => return channel_error(update),
                 // let failures just result in an 'other' message.
                 Err(e) => warn!("error parsing update message: {}", e),
             }
@@ -287,3 +408,78 @@ impl ScreepsMessage<'static> {
         ScreepsMessage::Other(message.as_ref().to_owned().into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ScreepsMessage, SockjsMessage};
+    use crate::{
+        websocket::{Channel, ChannelUpdate},
+        MemoryTokenStorage, TokenStorage,
+    };
+
+    #[test]
+    fn store_refreshed_token() {
+        let tokens = MemoryTokenStorage::default();
+        tokens.set("old-token".into());
+
+        let message = SockjsMessage::parse(r#"a["time 1474379899001"]"#).unwrap();
+        assert!(!message.store_token(&tokens));
+        assert_eq!(tokens.get().unwrap(), "old-token");
+
+        let message = SockjsMessage::parse(r#"a["auth ok new-token"]"#).unwrap();
+        assert!(message.store_token(&tokens));
+        assert_eq!(tokens.get().unwrap(), "new-token");
+    }
+
+    #[test]
+    fn parse_server_errors() {
+        let cases = [
+            (
+                "error: subscribe limit reached",
+                None,
+                "subscribe limit reached",
+            ),
+            ("error invalid token", None, "invalid token"),
+            (r#"{"error":"invalid token"}"#, None, "invalid token"),
+            (
+                r#"["err@room:shard0/E1N1","invalid room"]"#,
+                Some("room:shard0/E1N1"),
+                "invalid room",
+            ),
+            (
+                r#"["err@user:abc/memory/a.b","invalid path"]"#,
+                Some("user:abc/memory/a.b"),
+                "invalid path",
+            ),
+        ];
+
+        for &(raw, expected_channel, expected_error) in cases.iter() {
+            match ScreepsMessage::parse(raw) {
+                ScreepsMessage::Error { channel, error } => {
+                    assert_eq!(channel.map(|c| c.to_string()).as_deref(), expected_channel);
+                    assert_eq!(error, expected_error);
+                }
+                other => panic!("expected error parsing {}, found {:?}", raw, other),
+            }
+        }
+
+        match ScreepsMessage::parse(r#"["err@room:shard0/E1N1","subscribe limit reached"]"#) {
+            ScreepsMessage::ChannelUpdate {
+                update: ChannelUpdate::NoRoomDetail { .. },
+            } => (),
+            other => panic!("expected no room detail update, found {:?}", other),
+        }
+
+        match ScreepsMessage::parse(r#"["err@room:shard0/E1N1","invalid room"]"#) {
+            ScreepsMessage::Error {
+                channel: Some(Channel::RoomDetail { room_name, .. }),
+                ..
+            } => assert_eq!(room_name.to_string(), "E1N1"),
+            other => panic!("expected room detail error, found {:?}", other),
+        }
+
+        if let ScreepsMessage::Error { .. } = ScreepsMessage::parse("errorless message") {
+            panic!("expected only `error:` and `error ` to be errors");
+        }
+    }
+}
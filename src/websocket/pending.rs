@@ -0,0 +1,171 @@
+//! Finding out which of a batch of subscriptions succeeded.
+use std::collections::HashSet;
+
+use super::{Channel, ScreepsMessage, SockjsMessage};
+
+/// Subscriptions sent together with [`commands::subscribe_all`], waiting for a sign from the server of whether they
+/// succeeded.
+///
+/// The server does not confirm subscriptions. Instead, a subscription has succeeded once the first update arrives on
+/// its channel, and has failed if the server sends an error for its channel. Pass every parsed message to
+/// [`PendingSubscriptions::resolve`] to collect these results.
+///
+/// Channels which only update when something happens, such as new messages or memory path changes, stay pending
+/// until then: a subscription which is still pending has not failed.
+///
+/// ```
+/// use screeps_api::websocket::{commands, Channel, SockjsMessage};
+///
+/// let channels = [Channel::user_cpu("abc"), Channel::user_console("abc")];
+/// let (frame, mut pending) = commands::subscribe_all(&channels);
+/// // ... send `frame` to the server, then pass it each message received:
+///
+/// let message = SockjsMessage::parse(r#"a["[\"user:abc/cpu\",{\"cpu\":32,\"memory\":126435}]"]"#)?;
+/// let results = pending.resolve(&message);
+/// assert_eq!(results, [(Channel::user_cpu("abc"), Ok(()))]);
+///
+/// let message = SockjsMessage::parse(r#"a["[\"err@user:abc/console\",\"not authorized\"]"]"#)?;
+/// let results = pending.resolve(&message);
+/// assert_eq!(results, [(Channel::user_console("abc"), Err("not authorized".to_owned()))]);
+/// assert!(pending.is_empty());
+/// # Ok::<(), screeps_api::websocket::ParseError>(())
+/// ```
+///
+/// [`commands::subscribe_all`]: commands/fn.subscribe_all.html
+/// [`PendingSubscriptions::resolve`]: struct.PendingSubscriptions.html#method.resolve
+#[derive(Clone, Debug, Default)]
+pub struct PendingSubscriptions {
+    channels: HashSet<Channel<'static>>,
+}
+
+impl PendingSubscriptions {
+    /// Creates a set waiting on the given channels.
+    pub fn new(channels: &[Channel]) -> Self {
+        PendingSubscriptions {
+            channels: channels.iter().map(owned).collect(),
+        }
+    }
+
+    /// Resolves the subscriptions the given message shows the result of, removing them from this set.
+    ///
+    /// Returns each channel resolved, with `Ok(())` if an update arrived on it, or the server's error message if an
+    /// error did. Room detail subscriptions which won't receive an update this tick due to the 2 room limit have
+    /// succeeded: they will receive updates on later ticks.
+    pub fn resolve(
+        &mut self,
+        message: &SockjsMessage,
+    ) -> Vec<(Channel<'static>, Result<(), String>)> {
+        match *message {
+            SockjsMessage::Message(ref message) => self.resolve_one(message).into_iter().collect(),
+            SockjsMessage::Messages(ref messages) => messages
+                .iter()
+                .filter_map(|message| self.resolve_one(message))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn resolve_one(
+        &mut self,
+        message: &ScreepsMessage,
+    ) -> Option<(Channel<'static>, Result<(), String>)> {
+        if self.channels.is_empty() {
+            return None;
+        }
+
+        let (channel, result) = match *message {
+            ScreepsMessage::ChannelUpdate { ref update } => (owned(&update.channel()), Ok(())),
+            ScreepsMessage::Error {
+                channel: Some(ref channel),
+                ref error,
+            } => (owned(channel), Err(error.clone().into_owned())),
+            _ => return None,
+        };
+
+        self.channels
+            .take(&channel)
+            .map(|channel| (channel, result))
+    }
+
+    /// Whether the subscription to the given channel is still waiting for a result.
+    pub fn is_pending(&self, channel: &Channel) -> bool {
+        self.channels.contains(channel)
+    }
+
+    /// Iterates over the channels still waiting for a result.
+    pub fn pending(&self) -> impl Iterator<Item = &Channel<'static>> + '_ {
+        self.channels.iter()
+    }
+
+    /// The number of channels still waiting for a result.
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Whether every subscription has been resolved.
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+}
+
+/// Copies a channel, parsing it back from its protocol string.
+fn owned(channel: &Channel) -> Channel<'static> {
+    let name = channel.to_string();
+    name.parse().unwrap_or_else(|_| Channel::other(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PendingSubscriptions;
+    use crate::{
+        websocket::{Channel, SockjsMessage},
+        RoomName,
+    };
+
+    #[test]
+    fn resolve_room_subscriptions() {
+        let e1n1 = Channel::room_detail(RoomName::new("E1N1").unwrap(), Some("shard0"));
+        let e2n2 = Channel::room_detail(RoomName::new("E2N2").unwrap(), Some("shard0"));
+        let mut pending = PendingSubscriptions::new(&[e1n1.clone(), e2n2.clone()]);
+        assert_eq!(pending.len(), 2);
+
+        let message =
+            SockjsMessage::parse(r#"a["[\"err@room:shard0/E2N2\",\"subscribe limit reached\"]"]"#)
+                .unwrap();
+        assert_eq!(pending.resolve(&message), [(e2n2.clone(), Ok(()))]);
+        assert!(!pending.is_pending(&e2n2));
+
+        // updates on other channels, and repeated updates, resolve nothing.
+        assert!(pending.resolve(&message).is_empty());
+        let message =
+            SockjsMessage::parse(r#"a["[\"user:abc/cpu\",{\"cpu\":32,\"memory\":1}]"]"#).unwrap();
+        assert!(pending.resolve(&message).is_empty());
+
+        assert!(pending.is_pending(&e1n1));
+        assert_eq!(pending.pending().collect::<Vec<_>>(), [&e1n1]);
+    }
+
+    #[test]
+    fn resolve_room_errors() {
+        let e1n1 = Channel::room_detail(RoomName::new("E1N1").unwrap(), Some("shard0"));
+        let e2n2 = Channel::room_detail(RoomName::new("E2N2").unwrap(), Some("shard0"));
+        let mut pending = PendingSubscriptions::new(&[e1n1.clone(), e2n2.clone()]);
+
+        let message =
+            SockjsMessage::parse(r#"a["[\"err@room:shard0/E1N1\",\"invalid room\"]"]"#).unwrap();
+        assert_eq!(
+            pending.resolve(&message),
+            [(e1n1, Err("invalid room".to_owned()))]
+        );
+
+        let message = SockjsMessage::parse(
+            r#"a["[\"err@room:shard0/E2N2\",\"room \\\"E2N2\\\" not found\"]"]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            pending.resolve(&message),
+            [(e2n2, Err("room \"E2N2\" not found".to_owned()))]
+        );
+        assert!(pending.is_empty());
+    }
+}
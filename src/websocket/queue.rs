@@ -0,0 +1,237 @@
+//! Bounded queue for passing socket data from a reader to a handler.
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex, PoisonError},
+};
+
+/// What a [`BoundedQueue`] does when an item is pushed while it is full.
+///
+/// [`BoundedQueue`]: struct.BoundedQueue.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Discard the oldest queued item to make room.
+    DropOldest,
+    /// Block the pushing thread until there is room.
+    Block,
+}
+
+#[derive(Debug)]
+struct State<T> {
+    items: VecDeque<T>,
+    dropped: u64,
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: usize,
+    policy: OverflowPolicy,
+}
+
+/// A bounded, thread-safe queue to sit between a websocket reader and a handler.
+///
+/// Room updates can arrive faster than a slow handler processes them. Pushing each frame (or parsed message) into
+/// this queue from the reader, and popping from it in the handler, keeps memory use bounded: once `capacity` items are
+/// queued, new items either replace the oldest ones or wait for room, depending on the [`OverflowPolicy`].
+///
+/// Clones share the same queue.
+///
+/// [`OverflowPolicy`]: enum.OverflowPolicy.html
+#[derive(Debug)]
+pub struct BoundedQueue<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Clone for BoundedQueue<T> {
+    fn clone(&self) -> Self {
+        BoundedQueue {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> BoundedQueue<T> {
+    /// Creates a new queue holding at most `capacity` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        assert!(capacity > 0, "expected queue capacity to be at least 1");
+
+        BoundedQueue {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    items: VecDeque::with_capacity(capacity),
+                    dropped: 0,
+                    closed: false,
+                }),
+                not_empty: Condvar::new(),
+                not_full: Condvar::new(),
+                capacity,
+                policy,
+            }),
+        }
+    }
+
+    /// Adds an item to the queue.
+    ///
+    /// With `OverflowPolicy::DropOldest`, returns the item dropped to make room, if any. With
+    /// `OverflowPolicy::Block`, waits for room and always returns `None`.
+    ///
+    /// If the queue has been closed, the item is returned immediately without being queued.
+    pub fn push(&self, item: T) -> Option<T> {
+        let shared = &*self.shared;
+        let mut state = shared.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if state.closed {
+            return Some(item);
+        }
+
+        let mut dropped = None;
+        if state.items.len() >= shared.capacity {
+            match shared.policy {
+                OverflowPolicy::DropOldest => {
+                    dropped = state.items.pop_front();
+                    state.dropped += 1;
+                }
+                OverflowPolicy::Block => {
+                    while state.items.len() >= shared.capacity && !state.closed {
+                        state = shared
+                            .not_full
+                            .wait(state)
+                            .unwrap_or_else(PoisonError::into_inner);
+                    }
+                    if state.closed {
+                        return Some(item);
+                    }
+                }
+            }
+        }
+
+        state.items.push_back(item);
+        shared.not_empty.notify_one();
+
+        dropped
+    }
+
+    /// Takes the oldest item from the queue, waiting for one if it is empty.
+    ///
+    /// Returns `None` once the queue has been closed and all remaining items have been taken.
+    pub fn pop(&self) -> Option<T> {
+        let shared = &*self.shared;
+        let mut state = shared.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        loop {
+            if let Some(item) = state.items.pop_front() {
+                shared.not_full.notify_one();
+                return Some(item);
+            }
+            if state.closed {
+                return None;
+            }
+            state = shared
+                .not_empty
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Takes the oldest item from the queue if there is one, without waiting.
+    pub fn try_pop(&self) -> Option<T> {
+        let shared = &*self.shared;
+        let item = shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .items
+            .pop_front();
+
+        if item.is_some() {
+            shared.not_full.notify_one();
+        }
+
+        item
+    }
+
+    /// Closes the queue: further pushes are rejected, and `pop` returns `None` once the queue is empty.
+    pub fn close(&self) {
+        let shared = &*self.shared;
+        shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .closed = true;
+        shared.not_empty.notify_all();
+        shared.not_full.notify_all();
+    }
+
+    /// The number of items currently queued.
+    pub fn len(&self) -> usize {
+        self.shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .items
+            .len()
+    }
+
+    /// Whether the queue is currently empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The total number of items dropped to make room for newer ones.
+    pub fn dropped(&self) -> u64 {
+        self.shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::{BoundedQueue, OverflowPolicy};
+
+    #[test]
+    fn drop_oldest() {
+        let queue = BoundedQueue::new(2, OverflowPolicy::DropOldest);
+
+        assert_eq!(queue.push(1), None);
+        assert_eq!(queue.push(2), None);
+        assert_eq!(queue.push(3), Some(1));
+
+        assert_eq!(queue.dropped(), 1);
+        assert_eq!(queue.try_pop(), Some(2));
+        assert_eq!(queue.try_pop(), Some(3));
+        assert_eq!(queue.try_pop(), None);
+    }
+
+    #[test]
+    fn block_until_room() {
+        let queue = BoundedQueue::new(1, OverflowPolicy::Block);
+
+        let producer = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                for i in 0..10 {
+                    assert_eq!(queue.push(i), None);
+                }
+                queue.close();
+            })
+        };
+
+        let received = std::iter::from_fn(|| queue.pop()).collect::<Vec<_>>();
+        producer.join().unwrap();
+
+        assert_eq!(received, (0..10).collect::<Vec<_>>());
+        assert_eq!(queue.dropped(), 0);
+    }
+}
@@ -0,0 +1,218 @@
+//! Recording raw websocket traffic, and replaying it back through the parser.
+//!
+//! Recordings are stored one frame per line, each line being a JSON array of the millisecond offset since the
+//! recording started and the raw frame text: `[1532, "a[\"...\"]"]`.
+use std::{
+    error, fmt,
+    io::{self, BufRead, Write},
+    time::{Duration, Instant},
+};
+
+use super::{ParseError, SockjsMessage};
+
+/// Writes raw incoming websocket frames, with timestamps, to some output.
+///
+/// Feed this every text frame received from the server, before or after parsing it. The resulting output can be
+/// read back with [`Replay`].
+///
+/// [`Replay`]: struct.Replay.html
+#[derive(Debug)]
+pub struct Recorder<W> {
+    output: W,
+    start: Instant,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Creates a recorder writing to the given output. Frame timestamps are relative to this call.
+    pub fn new(output: W) -> Self {
+        Recorder {
+            output,
+            start: Instant::now(),
+        }
+    }
+
+    /// Records a single raw frame, timestamped with the time elapsed since this recorder was created.
+    pub fn record(&mut self, frame: &str) -> io::Result<()> {
+        let elapsed = self.start.elapsed();
+        self.record_at(elapsed, frame)
+    }
+
+    /// Records a single raw frame with an explicit offset from the start of the recording.
+    pub fn record_at(&mut self, offset: Duration, frame: &str) -> io::Result<()> {
+        let millis = offset.as_secs() * 1000 + u64::from(offset.subsec_millis());
+        serde_json::to_writer(&mut self.output, &(millis, frame))?;
+        self.output.write_all(b"\n")
+    }
+
+    /// Flushes the underlying output.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+
+    /// Retrieves the underlying output.
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+}
+
+/// A single frame read back from a recording.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedFrame {
+    /// Time since the start of the recording at which this frame was received.
+    pub offset: Duration,
+    /// The raw frame text, exactly as received from the server.
+    pub frame: String,
+}
+
+impl RecordedFrame {
+    /// Parses this frame as if it had just been received from the server.
+    pub fn parse(&self) -> Result<SockjsMessage<'_>, ParseError> {
+        SockjsMessage::parse(&self.frame)
+    }
+}
+
+/// Reads frames back from a recording made with [`Recorder`].
+///
+/// This is an iterator over all recorded frames, and can also drive a handler with each parsed message via
+/// [`Replay::drive`].
+///
+/// [`Recorder`]: struct.Recorder.html
+/// [`Replay::drive`]: struct.Replay.html#method.drive
+#[derive(Debug)]
+pub struct Replay<R> {
+    input: R,
+    line: String,
+}
+
+impl<R: BufRead> Replay<R> {
+    /// Creates a replay reading from the given input.
+    pub fn new(input: R) -> Self {
+        Replay {
+            input,
+            line: String::new(),
+        }
+    }
+
+    /// Parses every recorded frame in order, passing each resulting message to the given handler along with the
+    /// offset it was originally received at.
+    ///
+    /// Stops at the first frame which fails to read or parse.
+    pub fn drive<F>(self, mut handler: F) -> Result<(), ReplayError>
+    where
+        F: FnMut(Duration, SockjsMessage<'_>),
+    {
+        for frame in self {
+            let frame = frame?;
+            let message = frame.parse().map_err(ReplayError::Parse)?;
+            handler(frame.offset, message);
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for Replay<R> {
+    type Item = Result<RecordedFrame, ReplayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            match self.input.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => (),
+                Err(e) => return Some(Err(ReplayError::Io(e))),
+            }
+
+            let line = self.line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            return Some(
+                serde_json::from_str::<(u64, String)>(line)
+                    .map(|(millis, frame)| RecordedFrame {
+                        offset: Duration::from_millis(millis),
+                        frame,
+                    })
+                    .map_err(ReplayError::Format),
+            );
+        }
+    }
+}
+
+/// Error reading back a recording.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// Error reading from the underlying input.
+    Io(io::Error),
+    /// A line in the recording was not a valid recorded frame.
+    Format(serde_json::Error),
+    /// A recorded frame failed to parse as a SockJS message.
+    Parse(ParseError),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReplayError::Io(ref e) => write!(f, "error reading recording: {}", e),
+            ReplayError::Format(ref e) => write!(f, "malformed recorded frame: {}", e),
+            ReplayError::Parse(ref e) => write!(f, "error parsing recorded frame: {}", e),
+        }
+    }
+}
+
+impl error::Error for ReplayError {
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            ReplayError::Io(ref e) => Some(e),
+            ReplayError::Format(ref e) => Some(e),
+            ReplayError::Parse(ref e) => Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Recorder, Replay};
+    use crate::websocket::SockjsMessage;
+
+    #[test]
+    fn record_and_replay() {
+        let mut recorder = Recorder::new(Vec::new());
+        recorder.record_at(Duration::from_millis(0), "o").unwrap();
+        recorder
+            .record_at(Duration::from_millis(1500), "a[\"time 1474379899001\"]")
+            .unwrap();
+        recorder
+            .record_at(Duration::from_millis(1600), "h")
+            .unwrap();
+
+        let data = recorder.into_inner();
+
+        let mut seen = Vec::new();
+        Replay::new(&data[..])
+            .drive(|offset, message| seen.push((offset, kind(&message))))
+            .unwrap();
+
+        assert_eq!(
+            seen,
+            vec![
+                (Duration::from_millis(0), "open"),
+                (Duration::from_millis(1500), "messages"),
+                (Duration::from_millis(1600), "heartbeat"),
+            ]
+        );
+    }
+
+    fn kind(message: &SockjsMessage) -> &'static str {
+        match *message {
+            SockjsMessage::Open => "open",
+            SockjsMessage::Heartbeat => "heartbeat",
+            SockjsMessage::Close { .. } => "close",
+            SockjsMessage::Message(_) => "message",
+            SockjsMessage::Messages(_) => "messages",
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Per-connection statistics tracking.
+use std::{collections::HashMap, time::Instant};
+
+use super::{Channel, ChannelUpdate, ScreepsMessage, SockjsMessage};
+
+/// Counters describing the health of a single websocket connection.
+///
+/// `screeps-api` does not own the connection, so these statistics are only as accurate as what they are fed: call
+/// [`ConnectionStats::record_frame`] with every incoming text frame, and [`ConnectionStats::record_reconnect`] each
+/// time the connection is re-established. Connections run by [`spawn`] are recorded this way, and their statistics are
+/// available from [`SocketHandle::stats`].
+///
+/// [`spawn`]: fn.spawn.html
+/// [`SocketHandle::stats`]: struct.SocketHandle.html#method.stats
+/// [`ConnectionStats::record_frame`]: struct.ConnectionStats.html#method.record_frame
+/// [`ConnectionStats::record_reconnect`]: struct.ConnectionStats.html#method.record_reconnect
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionStats {
+    frames_received: u64,
+    bytes_received: u64,
+    messages_received: u64,
+    reconnects: u32,
+    last_heartbeat: Option<Instant>,
+    last_frame: Option<Instant>,
+    last_game_time: Option<u32>,
+    per_channel: HashMap<String, u64>,
+    rooms: HashMap<String, RoomActivity>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct RoomActivity {
+    last_update: Option<u32>,
+    skipped: u64,
+}
+
+impl ConnectionStats {
+    /// Creates a new set of zeroed statistics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a single raw frame, and the result of parsing it.
+    pub fn record_frame(&mut self, raw: &str, parsed: &SockjsMessage) {
+        let now = Instant::now();
+
+        self.frames_received += 1;
+        self.bytes_received += raw.len() as u64;
+        self.last_frame = Some(now);
+
+        match *parsed {
+            SockjsMessage::Heartbeat => self.last_heartbeat = Some(now),
+            SockjsMessage::Message(ref message) => self.record_message(message),
+            SockjsMessage::Messages(ref messages) => {
+                for message in messages {
+                    self.record_message(message);
+                }
+            }
+            SockjsMessage::Open | SockjsMessage::Close { .. } => (),
+        }
+    }
+
+    fn record_message(&mut self, message: &ScreepsMessage) {
+        self.messages_received += 1;
+
+        if let ScreepsMessage::ChannelUpdate { ref update } = *message {
+            if let Some(time) = update.game_time() {
+                self.last_game_time = Some(self.last_game_time.map_or(time, |last| last.max(time)));
+            }
+            match *update {
+                ChannelUpdate::RoomDetail { .. } => {
+                    let last_game_time = self.last_game_time;
+                    let room = self.rooms.entry(update.channel().to_string()).or_default();
+                    room.last_update = update.game_time().or(last_game_time);
+                }
+                ChannelUpdate::NoRoomDetail { .. } => {
+                    self.rooms
+                        .entry(update.channel().to_string())
+                        .or_default()
+                        .skipped += 1;
+                }
+                _ => (),
+            }
+            *self
+                .per_channel
+                .entry(update.channel().to_string())
+                .or_insert(0) += 1;
+        }
+    }
+
+    /// Records that the connection was re-established.
+    pub fn record_reconnect(&mut self) {
+        self.reconnects += 1;
+    }
+
+    /// The total number of raw frames received.
+    pub fn frames_received(&self) -> u64 {
+        self.frames_received
+    }
+
+    /// The total number of bytes of raw frame text received.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// The total number of screeps messages received, including those batched together into one frame.
+    pub fn messages_received(&self) -> u64 {
+        self.messages_received
+    }
+
+    /// The number of times the connection has been re-established.
+    pub fn reconnects(&self) -> u32 {
+        self.reconnects
+    }
+
+    /// The time the last SockJS heartbeat was received, if any.
+    pub fn last_heartbeat(&self) -> Option<Instant> {
+        self.last_heartbeat
+    }
+
+    /// The time the last frame of any kind was received, if any.
+    pub fn last_frame(&self) -> Option<Instant> {
+        self.last_frame
+    }
+
+    /// The latest game tick seen in any room update, if any.
+    pub fn last_game_time(&self) -> Option<u32> {
+        self.last_game_time
+    }
+
+    /// The number of updates received on the given channel.
+    pub fn channel_updates(&self, channel: &Channel) -> u64 {
+        self.per_channel
+            .get(&channel.to_string())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// The game tick at which the given room detail channel last received an actual update, rather than a
+    /// notification that it was skipped due to the room subscription limit.
+    pub fn room_last_update(&self, channel: &Channel) -> Option<u32> {
+        self.rooms
+            .get(&channel.to_string())
+            .and_then(|room| room.last_update)
+    }
+
+    /// The number of ticks the given room detail channel was skipped due to the room subscription limit.
+    pub fn room_skipped(&self, channel: &Channel) -> u64 {
+        self.rooms
+            .get(&channel.to_string())
+            .map_or(0, |room| room.skipped)
+    }
+
+    /// Iterates over all channels which have received updates, and the number of updates received on each.
+    pub fn channels(&self) -> impl Iterator<Item = (&str, u64)> + '_ {
+        self.per_channel.iter().map(|(k, v)| (k.as_str(), *v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConnectionStats;
+    use crate::websocket::{Channel, SockjsMessage};
+
+    #[test]
+    fn count_frames_and_channels() {
+        let mut stats = ConnectionStats::new();
+
+        let frames = [
+            "o",
+            "h",
+            r#"a["[\"user:57874d42d0ae911e3bd15bbc/cpu\",{\"cpu\":32,\"memory\":126435}]"]"#,
+            r#"a["[\"user:57874d42d0ae911e3bd15bbc/cpu\",{\"cpu\":30,\"memory\":126435}]","time 1474379899001"]"#,
+            r#"a["[\"roomMap2:shard0/E1N1\",{\"gameTime\":23469491,\"w\":[[1,2]]}]"]"#,
+        ];
+
+        for frame in frames.iter() {
+            let parsed = SockjsMessage::parse(frame).unwrap();
+            stats.record_frame(frame, &parsed);
+        }
+        stats.record_reconnect();
+
+        assert_eq!(stats.frames_received(), 5);
+        assert_eq!(stats.messages_received(), 4);
+        assert_eq!(stats.last_game_time(), Some(23469491));
+        assert_eq!(stats.reconnects(), 1);
+        assert!(stats.last_heartbeat().is_some());
+        assert_eq!(
+            stats.channel_updates(&Channel::user_cpu("57874d42d0ae911e3bd15bbc")),
+            2
+        );
+    }
+}
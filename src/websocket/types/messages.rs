@@ -41,11 +41,24 @@ pub struct Message {
     /// The other user involved in this conversation: the one who isn't the user who received this update.
     #[serde(rename = "respondent")]
     pub respondent_id: String,
+    /// The date this message was sent, in the format like 2017-03-04T05:38:04.012Z, if sent.
+    #[serde(default)]
+    pub date: Option<String>,
     /// Phantom data in order to allow adding any additional fields in the future.
     #[serde(skip)]
     _non_exhaustive: (),
 }
 
+#[cfg(feature = "chrono")]
+impl Message {
+    /// The date this message was sent, or `None` if it wasn't sent or isn't in the expected format.
+    pub fn datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.date
+            .as_ref()
+            .and_then(|date| crate::decoders::parse_iso_datetime(date))
+    }
+}
+
 /// Update for a newly received message.
 #[derive(serde_derive::Deserialize, Clone, Debug)]
 pub struct MessageUpdate {
@@ -87,3 +100,31 @@ pub enum ConversationUpdate {
         message: MessageUnreadUpdate,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Message, MessageDirectionType};
+
+    #[test]
+    fn parse_message() {
+        let message: Message = serde_json::from_value(json!({
+            "_id": "58bb2a1c4a4b1c6b3a8e1d9f",
+            "outMessage": "58bb2a1c4a4b1c6b3a8e1d9e",
+            "text": "Hello!",
+            "type": "in",
+            "unread": true,
+            "user": "57874d42d0ae911e3bd15bbc",
+            "respondent": "57c7df771d90a0c561977377",
+            "date": "2017-03-04T20:55:56.412Z"
+        }))
+        .unwrap();
+
+        assert_eq!(message.direction, MessageDirectionType::Incoming);
+        assert_eq!(message.date.as_deref(), Some("2017-03-04T20:55:56.412Z"));
+        #[cfg(feature = "chrono")]
+        assert_eq!(
+            message.datetime().map(|date| date.timestamp_millis()),
+            Some(1488660956412)
+        );
+    }
+}
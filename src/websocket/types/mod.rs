@@ -7,7 +7,7 @@ use serde::{
 };
 
 use crate::websocket::Channel;
-use crate::RoomName;
+use crate::{Credits, RoomName, ShardRoomName};
 
 mod messages;
 mod room;
@@ -76,7 +76,7 @@ pub enum ChannelUpdate<'a> {
         /// The user ID this credit update is for.
         user_id: Cow<'a, str>,
         /// The number of credits.
-        update: f64,
+        update: Credits,
     },
     /// An update on a new message received by a user. Sent each time a user receives a message.
     UserMessage {
@@ -119,6 +119,14 @@ impl<'a> ChannelUpdate<'a> {
             _ => None,
         }
     }
+    /// If this update is directly associated with a room on a shard, gets the shard and room name.
+    pub fn shard_room_name(&self) -> Option<ShardRoomName> {
+        match (self.shard_name(), self.room_name()) {
+            (Some(shard), Some(&room)) => Some(ShardRoomName::new(shard, room)),
+            _ => None,
+        }
+    }
+
     /// If this update is directly associated with a room, gets the room name.
     pub fn room_name(&self) -> Option<&RoomName> {
         match *self {
@@ -129,6 +137,15 @@ impl<'a> ChannelUpdate<'a> {
         }
     }
 
+    /// If this update is a room update which includes the game time it was created at, gets that game time.
+    pub fn game_time(&self) -> Option<u32> {
+        match *self {
+            ChannelUpdate::RoomMapView { ref update, .. } => update.game_time,
+            ChannelUpdate::RoomDetail { ref update, .. } => update.game_time,
+            _ => None,
+        }
+    }
+
     /// If this update is directly associated with a subscribed user id, gets the user id.
     ///
     /// The user_id is *always* the user id of the subscribed user, never another associated id.
@@ -184,6 +201,14 @@ impl<'a> ChannelUpdate<'a> {
             ChannelUpdate::Other { ref channel, .. } => Channel::other(channel.as_ref()),
         }
     }
+
+    /// Checks whether this update was sent on the given channel.
+    ///
+    /// This can be used to match updates against the channels used to subscribe, and works even for channels which
+    /// are only parsed as `ChannelUpdate::Other`.
+    pub fn is_from(&self, channel: &Channel) -> bool {
+        self.channel() == *channel
+    }
 }
 
 struct ChannelUpdateVisitor<'a> {
@@ -211,7 +236,7 @@ impl<'de> Visitor<'de> for ChannelUpdateVisitor<'de> {
     {
         const ROOM_MAP_VIEW_PREFIX: &str = "roomMap2:";
         const ROOM_PREFIX: &str = "room:";
-        const ROOM_ERR_PREFIX: &str = "err@room:"; // TODO: generic error handling with this `err@` format.
+        const ROOM_ERR_PREFIX: &str = "err@room:";
         const USER_PREFIX: &str = "user:";
         const USER_CPU: &str = "cpu";
         const USER_CONSOLE: &str = "console";
@@ -286,6 +311,18 @@ impl<'de> Visitor<'de> for ChannelUpdateVisitor<'de> {
                     .ok_or_else(|| de::Error::invalid_length(2, &self))?,
             });
         } else if channel.starts_with(ROOM_ERR_PREFIX) {
+            // the server sends this when more rooms are subscribed to than it will send updates for this tick. Every
+            // other `err@<channel>` is left as an `Other` update, which `ScreepsMessage::parse` turns into an error.
+            let update: serde_json::Value = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+            if update != "subscribe limit reached" {
+                return Ok(ChannelUpdate::Other {
+                    channel: channel.to_owned().into(),
+                    update,
+                });
+            }
+
             let room_name_and_shard = &channel[ROOM_ERR_PREFIX.len()..];
 
             let (shard_name, room_name) = {
@@ -293,7 +330,12 @@ impl<'de> Visitor<'de> for ChannelUpdateVisitor<'de> {
                 match (split.next(), split.next()) {
                     (Some(shard), Some(room)) => (Some(shard), room),
                     (Some(room), None) => (None, room),
-                    _ => finish_other!(),
+                    _ => {
+                        return Ok(ChannelUpdate::Other {
+                            channel: channel.to_owned().into(),
+                            update,
+                        })
+                    }
                 }
             };
 
@@ -304,24 +346,10 @@ impl<'de> Visitor<'de> for ChannelUpdateVisitor<'de> {
                 )
             })?;
 
-            let err_message = seq
-                .next_element::<&str>()?
-                .ok_or_else(|| de::Error::invalid_length(2, &self))?;
-
-            // TODO: This is currently just a patch in for a common error message, but we don't handle any other
-            // errors that are reported as `err@<rest of channel name>`.
-            //
-            // We should:
-            // A. find out if the server actually ever sends other error messages, or if this is hardcoded on the
-            //    other side too.
-            // B. add handling of all err@ messages into a variant which can then just parse the channel name into
-            //    a `Channel`.
-            if err_message == "subscribe limit reached" {
-                return Ok(ChannelUpdate::NoRoomDetail {
-                    room_name: room_name,
-                    shard_name: shard_name.map(ToOwned::to_owned),
-                });
-            }
+            return Ok(ChannelUpdate::NoRoomDetail {
+                room_name,
+                shard_name: shard_name.map(ToOwned::to_owned),
+            });
         } else if channel.starts_with(USER_PREFIX) {
             let user_id_and_part = &channel[USER_PREFIX.len()..];
 
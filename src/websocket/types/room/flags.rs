@@ -1,7 +1,10 @@
 //! Types for user flags which can appear in rooms.
-use std::fmt;
+use std::{convert::TryFrom, error, fmt};
 
-use serde::de::{Deserializer, Error, Unexpected, Visitor};
+use serde::de::{Deserialize, Deserializer, Error, Unexpected, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::{Position, RoomName, RoomXY};
 
 /// Single flag.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -18,6 +21,28 @@ pub struct Flag {
     pub y: u32,
 }
 
+impl Flag {
+    /// Gets the position of the flag within its room, or `None` if the server sent a position outside of the room.
+    pub fn xy(&self) -> Option<RoomXY> {
+        RoomXY::try_from((self.x, self.y)).ok()
+    }
+
+    /// Gets the position of the flag, given the room it is in.
+    ///
+    /// Room updates only include flags in the room they are for, so this is usually the room the update came from.
+    pub fn position(&self, room: RoomName) -> Option<Position> {
+        self.xy().map(|xy| Position::new(room, xy))
+    }
+
+    /// Formats this flag as the server sends it in room updates, such as `Flag1~1~10~25~25`.
+    pub fn to_flag_string(&self) -> String {
+        format!(
+            "{}~{}~{}~{}~{}",
+            self.name, self.primary_color as u8, self.secondary_color as u8, self.x, self.y
+        )
+    }
+}
+
 /// All possible colors a flag can have.
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
@@ -50,7 +75,29 @@ pub enum FlagColor {
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct FlagColorError;
 
+impl error::Error for FlagColorError {}
+
+impl fmt::Display for FlagColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("expected a flag color code between 1 and 10")
+    }
+}
+
 impl FlagColor {
+    /// Every flag color, in order of color code.
+    pub const ALL: [FlagColor; 10] = [
+        FlagColor::Red,
+        FlagColor::Purple,
+        FlagColor::Blue,
+        FlagColor::Cyan,
+        FlagColor::Green,
+        FlagColor::Yellow,
+        FlagColor::Orange,
+        FlagColor::Brown,
+        FlagColor::Grey,
+        FlagColor::White,
+    ];
+
     /// Converts an integer color code into a flag color.
     #[inline]
     pub fn from(v: u8) -> Result<Self, FlagColorError> {
@@ -71,25 +118,72 @@ impl FlagColor {
 
     #[inline]
     fn from_serde<E: Error>(v: u8) -> Result<Self, E> {
-        match v {
-            1 => Ok(FlagColor::Red),
-            2 => Ok(FlagColor::Purple),
-            3 => Ok(FlagColor::Blue),
-            4 => Ok(FlagColor::Cyan),
-            5 => Ok(FlagColor::Green),
-            6 => Ok(FlagColor::Yellow),
-            7 => Ok(FlagColor::Orange),
-            8 => Ok(FlagColor::Brown),
-            9 => Ok(FlagColor::Grey),
-            10 => Ok(FlagColor::White),
-            other => Err(E::invalid_value(
-                Unexpected::Unsigned(other as u64),
+        FlagColor::from(v).map_err(|_| {
+            E::invalid_value(
+                Unexpected::Unsigned(v as u64),
                 &"an integer between 1 and 10",
-            )),
+            )
+        })
+    }
+
+    /// The lowercase name of this color, such as `"red"`.
+    pub fn to_color_string(self) -> &'static str {
+        match self {
+            FlagColor::Red => "red",
+            FlagColor::Purple => "purple",
+            FlagColor::Blue => "blue",
+            FlagColor::Cyan => "cyan",
+            FlagColor::Green => "green",
+            FlagColor::Yellow => "yellow",
+            FlagColor::Orange => "orange",
+            FlagColor::Brown => "brown",
+            FlagColor::Grey => "grey",
+            FlagColor::White => "white",
         }
     }
 }
 
+impl TryFrom<u8> for FlagColor {
+    type Error = FlagColorError;
+
+    #[inline]
+    fn try_from(v: u8) -> Result<Self, FlagColorError> {
+        FlagColor::from(v)
+    }
+}
+
+impl From<FlagColor> for u8 {
+    #[inline]
+    fn from(color: FlagColor) -> u8 {
+        color as u8
+    }
+}
+
+impl fmt::Display for FlagColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.to_color_string())
+    }
+}
+
+impl Serialize for FlagColor {
+    /// Serializes this color as its color code, as the game stores it.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl<'de> Deserialize<'de> for FlagColor {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        FlagColor::from_serde(u8::deserialize(deserializer)?)
+    }
+}
+
 struct FlagStringVisitor;
 
 impl<'de> Visitor<'de> for FlagStringVisitor {
@@ -181,3 +275,56 @@ where
 {
     deserializer.deserialize_option(FlagStringVisitor)
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::{deserialize_flags, Flag, FlagColor};
+    use crate::{Position, RoomName, RoomXY};
+
+    fn parse(flags: serde_json::Value) -> Vec<Flag> {
+        deserialize_flags(flags).unwrap()
+    }
+
+    #[test]
+    fn parse_flag_string() {
+        let flags = parse(json!("Flag1~1~10~14~7|remote mining~3~3~0~49"));
+
+        assert_eq!(
+            flags,
+            vec![
+                Flag {
+                    name: "Flag1".to_owned(),
+                    primary_color: FlagColor::Red,
+                    secondary_color: FlagColor::White,
+                    x: 14,
+                    y: 7,
+                },
+                Flag {
+                    name: "remote mining".to_owned(),
+                    primary_color: FlagColor::Blue,
+                    secondary_color: FlagColor::Blue,
+                    x: 0,
+                    y: 49,
+                },
+            ]
+        );
+        assert_eq!(flags[0].to_flag_string(), "Flag1~1~10~14~7");
+
+        let room = RoomName::new("E4S61").unwrap();
+        assert_eq!(
+            flags[1].position(room),
+            Some(Position::new(room, RoomXY::new(0, 49).unwrap()))
+        );
+
+        assert_eq!(parse(json!("")), vec![]);
+        assert_eq!(parse(json!(null)), vec![]);
+        assert!(deserialize_flags(json!("Flag1~1~11~14~7")).is_err());
+
+        assert_eq!(FlagColor::deserialize(json!(9)).unwrap(), FlagColor::Grey);
+        assert_eq!(serde_json::to_value(FlagColor::Cyan).unwrap(), json!(4));
+        assert_eq!(FlagColor::Orange.to_string(), "orange");
+        assert_eq!(FlagColor::ALL[4], FlagColor::Green);
+    }
+}
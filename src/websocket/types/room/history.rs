@@ -0,0 +1,205 @@
+//! Parsing room history chunks.
+use std::collections::HashMap;
+
+use super::{objects::RoomObject, ObjectDiff};
+use crate::data::RoomName;
+
+/// A chunk of room history: every object in a room at the start of the chunk, and the changes in each following tick.
+///
+/// The server saves history in chunks of a fixed number of ticks, given by `ServerVersion::history_chunk_size`, and
+/// serves each chunk as JSON from `room-history/{shard}/{room}/{base tick}.json`. Changes in each tick use the same
+/// format as socket room updates, so the same [`ObjectDiff`]s apply to both.
+///
+/// [`ObjectDiff`]: enum.ObjectDiff.html
+#[derive(Clone, Debug)]
+pub struct RoomHistoryChunk {
+    /// The room this is the history of.
+    pub room_name: RoomName,
+    /// The real date/time when this chunk was saved, in milliseconds since the unix epoch.
+    pub timestamp: Option<u64>,
+    /// The game tick this chunk starts at.
+    pub base_tick: u32,
+    /// Every object in the room at `base_tick`, keyed by ID.
+    pub base: HashMap<String, RoomObject>,
+    /// The changes in each tick after `base_tick`, in order. Ticks where nothing changed may be left out.
+    pub ticks: Vec<RoomHistoryTick>,
+    /// Phantom data in order to allow adding any additional fields in the future.
+    _non_exhaustive: (),
+}
+
+/// The changes to a room's objects in one tick of room history.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoomHistoryTick {
+    /// The game tick of these changes.
+    pub tick: u32,
+    /// The value sent for each object which changed this tick, keyed by ID.
+    ///
+    /// See [`RoomHistoryTick::object_diffs`] for what each change was.
+    ///
+    /// [`RoomHistoryTick::object_diffs`]: struct.RoomHistoryTick.html#method.object_diffs
+    pub objects: HashMap<String, serde_json::Value>,
+}
+
+impl RoomHistoryTick {
+    /// Iterates over the changes to each object in this tick, along with the object's ID.
+    pub fn object_diffs(&self) -> impl Iterator<Item = (&str, ObjectDiff<'_>)> {
+        self.objects
+            .iter()
+            .map(|(id, value)| (&**id, ObjectDiff::from_value(value)))
+    }
+
+    /// Applies these changes to the objects known in the room, keyed by ID.
+    pub fn apply_to(
+        &self,
+        objects: &mut HashMap<String, RoomObject>,
+    ) -> Result<(), serde_json::Error> {
+        for (id, diff) in self.object_diffs() {
+            diff.apply_to(id, objects)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl RoomHistoryChunk {
+    /// Parses a room history chunk from the JSON the server serves it as.
+    pub fn from_json(json: &[u8]) -> Result<Self, serde_json::Error> {
+        Self::from_value(serde_json::from_slice(json)?)
+    }
+
+    /// Parses a room history chunk from an already-parsed JSON value.
+    pub fn from_value(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        use serde::de::Error;
+
+        let RawChunk {
+            timestamp,
+            room,
+            base: base_tick,
+            ticks: raw_ticks,
+        } = serde_json::from_value(value)?;
+
+        let mut base = None;
+        let mut ticks = Vec::with_capacity(raw_ticks.len());
+        for (tick, objects) in raw_ticks {
+            let tick: u32 = tick.parse().map_err(|_| {
+                serde_json::Error::custom(format_args!("expected tick number, found {:?}", tick))
+            })?;
+            let objects = objects.unwrap_or_default();
+
+            if tick == base_tick {
+                base = Some(
+                    objects
+                        .into_iter()
+                        .filter(|(_, value)| !value.is_null())
+                        .map(|(id, value)| Ok((id, RoomObject::from_value(value)?)))
+                        .collect::<Result<HashMap<_, _>, serde_json::Error>>()?,
+                );
+            } else if tick > base_tick {
+                ticks.push(RoomHistoryTick {
+                    tick,
+                    objects: objects.into_iter().collect(),
+                });
+            }
+        }
+        ticks.sort_by_key(|tick| tick.tick);
+
+        let base = base.ok_or_else(|| {
+            serde_json::Error::custom(format_args!(
+                "expected base tick {} in room history",
+                base_tick
+            ))
+        })?;
+
+        Ok(RoomHistoryChunk {
+            room_name: room,
+            timestamp,
+            base_tick,
+            base,
+            ticks,
+            _non_exhaustive: (),
+        })
+    }
+
+    /// Gets every object in the room at the given tick, by applying the changes up to and including that tick to the
+    /// base snapshot.
+    ///
+    /// Ticks before `base_tick` give the base snapshot.
+    pub fn objects_at(&self, tick: u32) -> Result<HashMap<String, RoomObject>, serde_json::Error> {
+        let mut objects = self.base.clone();
+        for changes in self.ticks.iter().take_while(|changes| changes.tick <= tick) {
+            changes.apply_to(&mut objects)?;
+        }
+
+        Ok(objects)
+    }
+}
+
+/// Room history chunk raw format.
+#[derive(serde_derive::Deserialize)]
+struct RawChunk {
+    #[serde(default)]
+    timestamp: Option<u64>,
+    room: RoomName,
+    base: u32,
+    ticks: HashMap<String, Option<serde_json::Map<String, serde_json::Value>>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ObjectDiff, RoomHistoryChunk};
+
+    #[test]
+    fn parse_room_history_chunk() {
+        let json = json!({
+            "timestamp": 1534962337516i64,
+            "room": "E15N52",
+            "base": 29910000,
+            "ticks": {
+                "29910000": {
+                    "57ef9e5486f108ae6e60f7a4": {
+                        "_id": "57ef9e5486f108ae6e60f7a4",
+                        "energy": 1200,
+                        "energyCapacity": 3000,
+                        "invaderHarvested": 0,
+                        "nextRegenerationTime": 29910120,
+                        "room": "E15N52",
+                        "ticksToRegeneration": 300,
+                        "type": "source",
+                        "x": 35,
+                        "y": 7
+                    }
+                },
+                "29910002": {
+                    "57ef9e5486f108ae6e60f7a4": null
+                },
+                "29910001": {
+                    "57ef9e5486f108ae6e60f7a4": {
+                        "energy": 1190
+                    }
+                }
+            }
+        });
+
+        let chunk = RoomHistoryChunk::from_value(json).unwrap();
+
+        assert_eq!(chunk.room_name.to_string(), "E15N52");
+        assert_eq!(chunk.base.len(), 1);
+        assert_eq!(chunk.ticks[0].tick, 29910001);
+        let diff = |tick: usize| chunk.ticks[tick].object_diffs().next().unwrap().1;
+        assert_eq!(diff(1), ObjectDiff::Delete);
+        match diff(0) {
+            ObjectDiff::Update(value) => assert_eq!(value["energy"], 1190),
+            ref other => panic!("expected update, found {:?}", other),
+        }
+
+        let objects = chunk.objects_at(29910001).unwrap();
+        match objects["57ef9e5486f108ae6e60f7a4"] {
+            crate::websocket::objects::RoomObject::Source(ref source) => {
+                assert_eq!(source.energy, 1190)
+            }
+            ref other => panic!("expected source, found {:?}", other),
+        }
+        assert!(chunk.objects_at(29910002).unwrap().is_empty());
+        assert_eq!(chunk.objects_at(0).unwrap().len(), 1);
+    }
+}
@@ -3,7 +3,7 @@
 //! This is made significantly more complicated by the fact that all updates besides the initial one are "partial" -
 //! they only contain changes, and each update to a specific room object will not contain the object's type, as it
 //! will not have changed.
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap};
 
 use crate::data::Badge;
 
@@ -12,9 +12,13 @@ pub mod flags;
 pub mod room_object_macros;
 #[macro_use]
 pub mod resources;
+mod history;
 pub mod objects;
 
 use self::flags::{deserialize_flags, Flag};
+use self::objects::RoomObject;
+
+pub use self::history::*;
 
 /// Update for detailed room information.
 #[derive(serde_derive::Deserialize, Clone, Debug)]
@@ -30,7 +34,11 @@ pub struct RoomUpdate {
     /// updates by their nature are incremental - and this includes the "type"
     /// field.
     ///
-    /// These values can be applied as updates to the `RoomObject` type.
+    /// These values can be applied as updates to the [`RoomObject`] type, most easily with
+    /// [`RoomUpdate::apply_to`].
+    ///
+    /// [`RoomObject`]: objects/enum.RoomObject.html
+    /// [`RoomUpdate::apply_to`]: #method.apply_to
     #[serde(with = "tuple_vec_map")]
     pub objects: Vec<(String, serde_json::Value)>,
     /// All of the subscribed user's flags in this room.
@@ -46,15 +54,107 @@ pub struct RoomUpdate {
     ///
     /// TODO: parse this further.
     pub visual: Option<String>,
-    /// Detailed information on all users that have things in this room.
+    /// Detailed information on all users that have things in this room, keyed by user ID.
     ///
-    /// Parse using `RoomUserInfo`.
-    pub users: Option<HashMap<String, serde_json::Value>>,
+    /// Like objects, users are only sent when they are new or have changed, so this should be merged into
+    /// previously received users rather than replacing them.
+    pub users: Option<HashMap<String, RoomUserInfo>>,
     /// Phantom data in order to allow adding any additional fields in the future.
     #[serde(skip)]
     _phantom: (),
 }
 
+impl RoomUpdate {
+    /// Gets the information sent in this update for the given user ID, if any.
+    pub fn user(&self, user_id: &str) -> Option<&RoomUserInfo> {
+        self.users.as_ref().and_then(|users| users.get(user_id))
+    }
+
+    /// Iterates over the changes to each object in this update, along with the object's ID.
+    pub fn object_diffs(&self) -> impl Iterator<Item = (&str, ObjectDiff<'_>)> {
+        self.objects
+            .iter()
+            .map(|(id, value)| (&**id, ObjectDiff::from_value(value)))
+    }
+
+    /// Applies the objects in this update to the objects known in the room, keyed by ID.
+    ///
+    /// New objects are added, objects which were already known are updated, and removed objects are removed. Starting
+    /// with an empty map, applying each update received for a room keeps the map up to date with the room.
+    pub fn apply_to(
+        &self,
+        objects: &mut HashMap<String, RoomObject>,
+    ) -> Result<(), serde_json::Error> {
+        for (id, diff) in self.object_diffs() {
+            diff.apply_to(id, objects)?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the username of the given user ID, if it was sent in this update.
+    pub fn username(&self, user_id: &str) -> Option<&str> {
+        self.user(user_id)
+            .and_then(|info| info.username.as_ref())
+            .map(String::as_str)
+    }
+}
+
+/// A change to a single room object, as sent in room updates and in each tick of room history.
+///
+/// Both send the changes in a tick as a map from object ID to either a JSON object or `null`. Objects sent whole,
+/// including their `type`, were created, objects sent with only the fields which changed were updated, and objects
+/// sent as `null` were deleted.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ObjectDiff<'a> {
+    /// The object was added to the room. Holds the whole object.
+    Create(&'a serde_json::Value),
+    /// The object changed. Holds only the fields which changed, with removed fields set to `null`.
+    Update(&'a serde_json::Value),
+    /// The object was removed from the room.
+    Delete,
+}
+
+impl<'a> ObjectDiff<'a> {
+    /// Interprets the value sent for an object in a room update or room history tick.
+    pub fn from_value(value: &'a serde_json::Value) -> Self {
+        if value.is_null() {
+            ObjectDiff::Delete
+        } else if value.get("type").is_some() {
+            ObjectDiff::Create(value)
+        } else {
+            ObjectDiff::Update(value)
+        }
+    }
+
+    /// Applies this change to the object with the given ID, in the objects known in the room.
+    ///
+    /// Objects which are created while already known are updated with all of their fields, so an object resent
+    /// whole is never lost. Updates to objects which aren't known error, as they can't be parsed without a `type`.
+    pub fn apply_to(
+        &self,
+        id: &str,
+        objects: &mut HashMap<String, RoomObject>,
+    ) -> Result<(), serde_json::Error> {
+        let value = match *self {
+            ObjectDiff::Delete => {
+                objects.remove(id);
+                return Ok(());
+            }
+            ObjectDiff::Create(value) | ObjectDiff::Update(value) => value,
+        };
+
+        match objects.entry(id.to_owned()) {
+            Entry::Occupied(entry) => entry.into_mut().update(value.clone())?,
+            Entry::Vacant(entry) => {
+                entry.insert(RoomObject::from_value(value.clone())?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// "info" struct to go with room update.
 ///
 /// TODO: find all variants and parse into enum.
@@ -69,7 +169,7 @@ pub struct RoomUpdateInfo {
 
 with_update_struct! {
     /// Information on a user which is packaged with a room update.
-    #[derive(serde_derive::Deserialize, Clone, Hash, Debug, PartialEq)]
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Clone, Hash, Debug, PartialEq)]
     pub struct RoomUserInfo {
         /// User ID
         #[serde(rename = "_id")]
@@ -87,3 +187,56 @@ with_update_struct! {
     #[derive(serde_derive::Deserialize, Clone, Hash, Debug, PartialEq)]
     pub struct RoomUserInfoUpdate { ... }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::RoomUpdate;
+    use crate::data::BadgeType;
+
+    #[test]
+    fn parse_room_update_users() {
+        let json = json!({
+            "gameTime": 23469491,
+            "info": {
+                "mode": "world"
+            },
+            "objects": {},
+            "users": {
+                "57874d42d0ae911e3bd15bbc": {
+                    "_id": "57874d42d0ae911e3bd15bbc",
+                    "badge": {
+                        "color1": "#260d0d",
+                        "color2": "#6b2e41",
+                        "color3": "#ffe56d",
+                        "flip": false,
+                        "param": -100,
+                        "type": 21
+                    },
+                    "username": "daboross"
+                },
+                "2": {
+                    "_id": "2",
+                    "username": "Invader"
+                }
+            }
+        });
+
+        let update = RoomUpdate::deserialize(json).unwrap();
+
+        assert_eq!(update.game_time, Some(23469491));
+        assert_eq!(
+            update.username("57874d42d0ae911e3bd15bbc"),
+            Some("daboross")
+        );
+        assert_eq!(update.username("2"), Some("Invader"));
+        assert_eq!(update.username("3"), None);
+
+        let badge = update
+            .user("57874d42d0ae911e3bd15bbc")
+            .and_then(|info| info.badge.as_ref())
+            .unwrap();
+        assert_eq!(badge.badge_type, BadgeType::Fixed(21));
+    }
+}
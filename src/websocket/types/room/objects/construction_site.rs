@@ -4,7 +4,7 @@ use crate::data::RoomName;
 /// Type of structure (not general room object).
 ///
 /// Currently only used when decoding ConstructionSites.
-#[derive(Clone, Debug, PartialEq, Eq, serde_derive::Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, serde_derive::Deserialize, serde_derive::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum StructureType {
     /// StructureSpawn structure type
@@ -45,6 +45,10 @@ pub enum StructureType {
     Container,
     /// StructureNuker structure type
     Nuker,
+    /// StructureFactory structure type
+    Factory,
+    /// StructureInvaderCore structure type
+    InvaderCore,
 }
 
 basic_updatable!(StructureType);
@@ -72,6 +76,22 @@ with_base_fields_and_update_struct! {
     pub struct ConstructionSiteUpdate { ... }
 }
 
+impl ConstructionSite {
+    /// The amount of progress still needed before the structure is built.
+    pub fn progress_remaining(&self) -> u32 {
+        self.progress_total.saturating_sub(self.progress)
+    }
+
+    /// The fraction of the required progress which has been made, from 0 to 1.
+    pub fn progress_fraction(&self) -> f64 {
+        if self.progress_total == 0 {
+            0.0
+        } else {
+            f64::from(self.progress) / f64::from(self.progress_total)
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use serde::Deserialize;
@@ -112,4 +132,35 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn parse_factory_site_and_update() {
+        let json = json!({
+            "_id": "5f1a2b3c4d5e6f7a8b9c0d1e",
+            "progress": 0,
+            "progressTotal": 100000,
+            "room": "E9S32",
+            "structureType": "factory",
+            "type": "constructionSite",
+            "user": "59cec9e20dd629146b767d96",
+            "x": 20,
+            "y": 21,
+        });
+
+        let mut obj = ConstructionSite::deserialize(json).unwrap();
+
+        assert_eq!(obj.structure_type, StructureType::Factory);
+        assert_eq!(obj.name, None);
+        assert_eq!(obj.progress_remaining(), 100000);
+
+        obj.update(
+            serde_json::from_value(json!({
+                "progress": 25000,
+            }))
+            .unwrap(),
+        );
+
+        assert_eq!(obj.progress_remaining(), 75000);
+        assert_eq!(obj.progress_fraction(), 0.25);
+    }
 }
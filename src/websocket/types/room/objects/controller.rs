@@ -1,9 +1,12 @@
 //! `StructureController` data description.
 use crate::{
-    data::{RoomName, RoomSign},
+    data::{ControllerInfo, RoomName, RoomSign},
     decoders::optional_timespec_seconds,
+    websocket::room_object_macros::Updatable,
 };
 
+pub use crate::data::ControllerReservation;
+
 implement_update_for! {
     RoomSign;
 
@@ -25,21 +28,50 @@ implement_update_for! {
     }
 }
 
-with_update_struct! {
-    /// A struct describing a room's reservation.
-    #[derive(serde_derive::Deserialize, Clone, Debug, PartialEq)]
+implement_update_for! {
+    ControllerReservation;
+
+    /// The update structure for a controller reservation.
+    #[derive(serde_derive::Deserialize, Clone, Debug)]
     #[serde(rename_all = "camelCase")]
-    pub struct ControllerReservation {
-        /// The user ID of the user reserving this controller.
-        pub user: String,
-        /// The game time when this reservation will end if not extended.
-        pub end_time: u32,
+    pub struct ControllerReservationUpdate {
+        priv user: Option<String>,
+        priv end_time: Option<u32>,
     }
+}
 
-    /// The update structure for a controller reservation.
+impl ControllerReservation {
+    /// Updates this reservation with all values present in the given update.
+    pub fn update(&mut self, update: ControllerReservationUpdate) {
+        <Self as crate::websocket::room_object_macros::Updatable>::apply_update(self, update);
+    }
+}
+
+implement_update_for! {
+    ControllerInfo;
+
+    /// The update structure for a controller's state.
     #[derive(serde_derive::Deserialize, Clone, Debug)]
     #[serde(rename_all = "camelCase")]
-    pub struct ControllerReservationUpdate { ... }
+    pub struct ControllerInfoUpdate {
+        priv level: Option<u16>,
+        (null_is_default)
+        priv progress: Option<u64>,
+        (null_is_default)
+        priv progress_total: Option<u64>,
+        priv downgrade_time: Option<Option<u64>>,
+        priv reservation: Option<<Option<ControllerReservation> as Updatable>::Update>,
+        priv safe_mode: Option<Option<u32>>,
+        (null_is_default)
+        priv safe_mode_available: Option<u32>,
+        (null_is_default)
+        priv safe_mode_cooldown: Option<u32>,
+        priv sign: Option<<Option<RoomSign> as Updatable>::Update>,
+        priv upgrade_blocked: Option<Option<u32>>,
+        priv user: Option<Option<String>>,
+        (null_is_default)
+        priv is_power_enabled: Option<bool>,
+    }
 }
 
 with_structure_fields_and_update_struct! {
@@ -47,57 +79,18 @@ with_structure_fields_and_update_struct! {
     #[derive(Clone, Debug, PartialEq)]
     #[serde(rename_all = "camelCase")]
     pub struct StructureController {
-        /// The number of upgrade points the controller has.
-        #[serde(default)]
-        pub progress: u64,
-        /// The number of upgrade points needed before the next level is reached.
-        #[serde(default)]
-        pub progress_total: u64,
-        /// The current controller level (1-8 inclusive).
-        pub level: u16,
-        /// Controller reservation.
-        pub reservation: Option<ControllerReservation>,
-        /// Game time at which the current safemode will end, if any.
-        pub safe_mode: Option<u32>,
-        /// How many more safemodes are available.
-        #[serde(default)]
-        pub safe_mode_available: u32,
-        /// The game time that must be reached before safe mode can be used on the controller.
-        ///
-        /// May be in the past, in which safe mode may be used immediately.
-        #[serde(default, with = "crate::decoders::null_as_default")]
-        pub safe_mode_cooldown: u32,
-        /// The number of game ticks without an upgrade needed before the controller downgrades.
-        ///
-        /// None if unowned.
-        pub downgrade_time: Option<u64>,
-        /// The room sign.
-        pub sign: Option<RoomSign>,
-        /// The number of ticks until upgrading is no longer blocked.
-        pub upgrade_blocked: Option<u32>,
-        /// ID of the user who owns the controller, and thus the room.
-        pub user: Option<String>,
+        /// The controller's state: its level, progress, reservation, safe mode and sign.
+        #[serde(flatten)]
+        pub info: ControllerInfo,
     }
 
     /// The update structure for a controller object.
     #[derive(Clone, Debug)]
     #[serde(rename_all = "camelCase")]
     pub struct StructureControllerUpdate {
-        (null_is_default)
-        - progress: u64,
-        (null_is_default)
-        - progress_total: u64,
-        - level: u16,
-        - reservation: Option<ControllerReservation>,
-        - safe_mode: Option<u32>,
-        (null_is_default)
-        - safe_mode_available: u32,
-        (null_is_default)
-        - safe_mode_cooldown: u32,
-        - downgrade_time: Option<u64>,
-        - sign: Option<RoomSign>,
-        - upgrade_blocked: Option<u32>,
-        - user: Option<String>,
+        #[serde(flatten)]
+        (no_extra_meta)
+        - info: ControllerInfo,
     }
 }
 
@@ -108,21 +101,12 @@ impl StructureController {
     ///
     /// See also [`StructureController::progress_required_at_level`].
     pub fn progress_required(&self) -> Option<u32> {
-        Self::progress_required_at_level(self.level)
+        self.info.progress_required()
     }
 
     /// Progress required for a level.
     pub fn progress_required_at_level(level: u16) -> Option<u32> {
-        match level {
-            1 => Some(200),
-            2 => Some(45000),
-            3 => Some(135000),
-            4 => Some(405000),
-            5 => Some(1215000),
-            6 => Some(3645000),
-            7 => Some(10935000),
-            _ => None,
-        }
+        ControllerInfo::progress_required_at_level(level)
     }
 
     /// The total downgrade time for this controller's level (100% dependent on `level`)
@@ -131,22 +115,12 @@ impl StructureController {
     ///
     /// See also [`StructureController::total_downgrade_time_at_level`]
     pub fn total_downgrade_time(&self) -> Option<u32> {
-        Self::total_downgrade_time_at_level(self.level)
+        self.info.total_downgrade_time()
     }
 
     /// Total downgrade time for a level (game ticks since the last upgrade that this controller will loose a level).
     pub fn total_downgrade_time_at_level(level: u16) -> Option<u32> {
-        match level {
-            1 => Some(20000),
-            2 => Some(5000),
-            3 => Some(10000),
-            4 => Some(20000),
-            5 => Some(40000),
-            6 => Some(60000),
-            7 => Some(100000),
-            8 => Some(150000),
-            _ => None,
-        }
+        ControllerInfo::total_downgrade_time_at_level(level)
     }
 }
 
@@ -155,7 +129,7 @@ mod test {
     use serde::Deserialize;
     use {serde_json, time};
 
-    use crate::data::{RoomName, RoomSign};
+    use crate::data::{ControllerInfo, RoomName, RoomSign};
 
     use super::{ControllerReservation, StructureController};
 
@@ -198,22 +172,25 @@ mod test {
                 y: 39,
                 hits: 0,
                 hits_max: 0,
-                level: 8,
-                progress: 0,
-                progress_total: 0,
-                reservation: None,
-                safe_mode: None,
-                safe_mode_available: 7,
-                safe_mode_cooldown: 17083195,
-                downgrade_time: Some(20020430),
-                sign: Some(RoomSign {
-                    text: "◯".to_owned(),
-                    game_time_set: 19869070,
-                    time_set: time::Timespec::new(1498254694977, 0),
-                    user_id: "57874d42d0ae911e3bd15bbc".to_owned(),
-                }),
-                upgrade_blocked: None,
-                user: Some("57874d42d0ae911e3bd15bbc".to_owned()),
+                info: ControllerInfo {
+                    level: 8,
+                    progress: 0,
+                    progress_total: 0,
+                    reservation: None,
+                    safe_mode: None,
+                    safe_mode_available: 7,
+                    safe_mode_cooldown: 17083195,
+                    downgrade_time: Some(20020430),
+                    sign: Some(RoomSign {
+                        text: "◯".to_owned(),
+                        game_time_set: 19869070,
+                        time_set: time::Timespec::new(1498254694977, 0),
+                        user_id: "57874d42d0ae911e3bd15bbc".to_owned(),
+                    }),
+                    upgrade_blocked: None,
+                    user: Some("57874d42d0ae911e3bd15bbc".to_owned()),
+                    is_power_enabled: false,
+                },
             }
         );
 
@@ -233,22 +210,25 @@ mod test {
                 y: 39,
                 hits: 0,
                 hits_max: 0,
-                level: 8,
-                progress: 0,
-                progress_total: 0,
-                reservation: None,
-                safe_mode: None,
-                safe_mode_available: 8,
-                safe_mode_cooldown: 17083195,
-                downgrade_time: Some(20020430),
-                sign: Some(RoomSign {
-                    text: "◯".to_owned(),
-                    game_time_set: 19869070,
-                    time_set: time::Timespec::new(1498254694977, 0),
-                    user_id: "57874d42d0ae911e3bd15bbc".to_owned(),
-                }),
-                upgrade_blocked: None,
-                user: Some("57874d42d0ae911e3bd15bbc".to_owned()),
+                info: ControllerInfo {
+                    level: 8,
+                    progress: 0,
+                    progress_total: 0,
+                    reservation: None,
+                    safe_mode: None,
+                    safe_mode_available: 8,
+                    safe_mode_cooldown: 17083195,
+                    downgrade_time: Some(20020430),
+                    sign: Some(RoomSign {
+                        text: "◯".to_owned(),
+                        game_time_set: 19869070,
+                        time_set: time::Timespec::new(1498254694977, 0),
+                        user_id: "57874d42d0ae911e3bd15bbc".to_owned(),
+                    }),
+                    upgrade_blocked: None,
+                    user: Some("57874d42d0ae911e3bd15bbc".to_owned()),
+                    is_power_enabled: false,
+                },
             }
         );
     }
@@ -287,22 +267,25 @@ mod test {
                 room: RoomName::new("W12S55").unwrap(),
                 x: 22,
                 y: 37,
-                downgrade_time: None,
                 hits: 0,
                 hits_max: 0,
-                level: 0,
-                progress: 0,
-                progress_total: 0,
-                reservation: Some(ControllerReservation {
-                    user: "57874d42d0ae911e3bd15bbc".to_owned(),
-                    end_time: 20158024,
-                }),
-                safe_mode: None,
-                safe_mode_available: 0,
-                safe_mode_cooldown: 16611615,
-                upgrade_blocked: None,
-                user: None,
-                sign: None,
+                info: ControllerInfo {
+                    downgrade_time: None,
+                    level: 0,
+                    progress: 0,
+                    progress_total: 0,
+                    reservation: Some(ControllerReservation {
+                        user: "57874d42d0ae911e3bd15bbc".to_owned(),
+                        end_time: 20158024,
+                    }),
+                    safe_mode: None,
+                    safe_mode_available: 0,
+                    safe_mode_cooldown: 16611615,
+                    upgrade_blocked: None,
+                    user: None,
+                    sign: None,
+                    is_power_enabled: false,
+                },
             }
         );
 
@@ -322,22 +305,25 @@ mod test {
                 room: RoomName::new("W12S55").unwrap(),
                 x: 22,
                 y: 37,
-                downgrade_time: None,
                 hits: 0,
                 hits_max: 0,
-                level: 0,
-                progress: 0,
-                progress_total: 0,
-                reservation: Some(ControllerReservation {
-                    user: "57874d42d0ae911e3bd15bbc".to_owned(),
-                    end_time: 20158029,
-                }),
-                safe_mode: None,
-                safe_mode_available: 0,
-                safe_mode_cooldown: 16611615,
-                upgrade_blocked: None,
-                user: None,
-                sign: None,
+                info: ControllerInfo {
+                    downgrade_time: None,
+                    level: 0,
+                    progress: 0,
+                    progress_total: 0,
+                    reservation: Some(ControllerReservation {
+                        user: "57874d42d0ae911e3bd15bbc".to_owned(),
+                        end_time: 20158029,
+                    }),
+                    safe_mode: None,
+                    safe_mode_available: 0,
+                    safe_mode_cooldown: 16611615,
+                    upgrade_blocked: None,
+                    user: None,
+                    sign: None,
+                    is_power_enabled: false,
+                },
             }
         );
     }
@@ -351,22 +337,25 @@ mod test {
             y: 39,
             hits: 0,
             hits_max: 0,
-            level: 8,
-            progress: 0,
-            progress_total: 0,
-            reservation: None,
-            safe_mode: None,
-            safe_mode_available: 7,
-            safe_mode_cooldown: 17083195,
-            downgrade_time: Some(20020430),
-            sign: Some(RoomSign {
-                text: "◯".to_owned(),
-                game_time_set: 19869070,
-                time_set: time::Timespec::new(1498254694977, 0),
-                user_id: "57874d42d0ae911e3bd15bbc".to_owned(),
-            }),
-            upgrade_blocked: None,
-            user: Some("57874d42d0ae911e3bd15bbc".to_owned()),
+            info: ControllerInfo {
+                level: 8,
+                progress: 0,
+                progress_total: 0,
+                reservation: None,
+                safe_mode: None,
+                safe_mode_available: 7,
+                safe_mode_cooldown: 17083195,
+                downgrade_time: Some(20020430),
+                sign: Some(RoomSign {
+                    text: "◯".to_owned(),
+                    game_time_set: 19869070,
+                    time_set: time::Timespec::new(1498254694977, 0),
+                    user_id: "57874d42d0ae911e3bd15bbc".to_owned(),
+                }),
+                upgrade_blocked: None,
+                user: Some("57874d42d0ae911e3bd15bbc".to_owned()),
+                is_power_enabled: false,
+            },
         };
 
         obj.update(
@@ -385,17 +374,20 @@ mod test {
                 y: 39,
                 hits: 0,
                 hits_max: 0,
-                level: 8,
-                progress: 0,
-                progress_total: 0,
-                reservation: None,
-                safe_mode: None,
-                safe_mode_available: 7,
-                safe_mode_cooldown: 17083195,
-                downgrade_time: Some(20020430),
-                sign: None,
-                upgrade_blocked: None,
-                user: Some("57874d42d0ae911e3bd15bbc".to_owned()),
+                info: ControllerInfo {
+                    level: 8,
+                    progress: 0,
+                    progress_total: 0,
+                    reservation: None,
+                    safe_mode: None,
+                    safe_mode_available: 7,
+                    safe_mode_cooldown: 17083195,
+                    downgrade_time: Some(20020430),
+                    sign: None,
+                    upgrade_blocked: None,
+                    user: Some("57874d42d0ae911e3bd15bbc".to_owned()),
+                    is_power_enabled: false,
+                },
             },
             "signal failure text"
         );
@@ -424,19 +416,22 @@ mod test {
                 room: RoomName::new("E6S31").unwrap(),
                 x: 35,
                 y: 27,
-                downgrade_time: None,
                 hits: 0,
                 hits_max: 0,
-                level: 0,
-                progress: 0,
-                progress_total: 0,
-                reservation: None,
-                safe_mode: None,
-                safe_mode_available: 0,
-                safe_mode_cooldown: 0,
-                upgrade_blocked: None,
-                user: None,
-                sign: None,
+                info: ControllerInfo {
+                    downgrade_time: None,
+                    level: 0,
+                    progress: 0,
+                    progress_total: 0,
+                    reservation: None,
+                    safe_mode: None,
+                    safe_mode_available: 0,
+                    safe_mode_cooldown: 0,
+                    upgrade_blocked: None,
+                    user: None,
+                    sign: None,
+                    is_power_enabled: false,
+                },
             }
         );
     }
@@ -479,9 +474,47 @@ mod test {
                 room: RoomName::new("E7S27").unwrap(),
                 x: 17,
                 y: 18,
-                downgrade_time: Some(7131716),
                 hits: 0,
                 hits_max: 0,
+                info: ControllerInfo {
+                    downgrade_time: Some(7131716),
+                    level: 6,
+                    progress: 2722264,
+                    progress_total: 0,
+                    reservation: None,
+                    safe_mode: Some(5990844),
+                    safe_mode_available: 4,
+                    safe_mode_cooldown: 0,
+                    upgrade_blocked: None,
+                    user: Some("5cad043ff77d0b62a38318e7".to_owned()),
+                    sign: Some(RoomSign {
+                        text: "Territory of Metyrio".to_owned(),
+                        game_time_set: 508258,
+                        time_set: time::Timespec::new(1540160091380, 0),
+                        user_id: "583e2a4c445866cb4ad3117e".to_owned(),
+                    }),
+                    is_power_enabled: false,
+                },
+            }
+        );
+        assert_eq!(obj.info.progress, 2722264);
+        assert_eq!(
+            obj.info,
+            serde_json::from_value::<ControllerInfo>(json).unwrap()
+        );
+    }
+
+    #[test]
+    fn handle_functionally_full_update() {
+        let mut obj = StructureController {
+            id: "5bbcad579099fc012e637271".to_owned(),
+            room: RoomName::new("E7S27").unwrap(),
+            x: 17,
+            y: 18,
+            hits: 0,
+            hits_max: 0,
+            info: ControllerInfo {
+                downgrade_time: Some(7131716),
                 level: 6,
                 progress: 2722264,
                 progress_total: 0,
@@ -497,35 +530,8 @@ mod test {
                     time_set: time::Timespec::new(1540160091380, 0),
                     user_id: "583e2a4c445866cb4ad3117e".to_owned(),
                 }),
-            }
-        );
-    }
-
-    #[test]
-    fn handle_functionally_full_update() {
-        let mut obj = StructureController {
-            id: "5bbcad579099fc012e637271".to_owned(),
-            room: RoomName::new("E7S27").unwrap(),
-            x: 17,
-            y: 18,
-            downgrade_time: Some(7131716),
-            hits: 0,
-            hits_max: 0,
-            level: 6,
-            progress: 2722264,
-            progress_total: 0,
-            reservation: None,
-            safe_mode: Some(5990844),
-            safe_mode_available: 4,
-            safe_mode_cooldown: 0,
-            upgrade_blocked: None,
-            user: Some("5cad043ff77d0b62a38318e7".to_owned()),
-            sign: Some(RoomSign {
-                text: "Territory of Metyrio".to_owned(),
-                game_time_set: 508258,
-                time_set: time::Timespec::new(1540160091380, 0),
-                user_id: "583e2a4c445866cb4ad3117e".to_owned(),
-            }),
+                is_power_enabled: false,
+            },
         };
 
         obj.update(
@@ -2,13 +2,11 @@
 use super::super::resources::ResourceType;
 use super::super::resources::Store;
 use super::ActionLogTarget;
-use crate::data::RoomName;
+use crate::data::{BodyPart, Boost, RoomName};
 
 with_update_struct! {
     /// A struct describing a creep part.
-    ///
-    /// TODO: parse creep part boosts.
-    #[derive(serde_derive::Deserialize, Clone, Debug, PartialEq)]
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Clone, Debug, PartialEq)]
     #[serde(rename_all = "camelCase")]
     pub struct CreepPart {
         /// Part health, out of 100.
@@ -16,8 +14,8 @@ with_update_struct! {
         /// Part type.
         #[serde(rename = "type")]
         pub part_type: CreepPartType,
-        /// Part boost, if any.
-        pub boost: Option<ResourceType>,
+        /// Part boost, if any: the compound the part was boosted with.
+        pub boost: Option<Boost>,
     }
 
     /// The update structure for a `CreepPart`.
@@ -26,34 +24,25 @@ with_update_struct! {
     pub struct CreepPartUpdate { ... }
 }
 
-#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[serde(rename_all = "snake_case")]
 /// A type of creep part.
-pub enum CreepPartType {
-    /// A move part - allows creeps to move, or move faster (max speed at 1 move part per other part).
-    Move,
-    /// A work part - allows creeps to spend energy doing things, mine sources and minerals, and damage
-    /// structures.
-    Work,
-    /// A carry part - allows creeps to carry energy and other resources.
-    Carry,
-    /// An attack part - allows creeps to damage both other creeps, and structures.
-    Attack,
-    /// A heal part - allows creeps to heal creeps (other and self).
-    Heal,
-    /// A ranged attack part - allows creeps to attack other creeps and structures from a small distance.
-    RangedAttack,
-    /// A tough part - a cheap part which has no additional functionality.
-    Tough,
-    /// A claim part - a part which allows a creep to reserve or claim a room controller.
-    Claim,
-}
+///
+/// This is the same as [`BodyPart`], which is also used outside of room objects.
+///
+/// [`BodyPart`]: ../../enum.BodyPart.html
+pub type CreepPartType = BodyPart;
+
+basic_updatable!(BodyPart, Boost);
 
-basic_updatable!(CreepPartType);
+impl CreepPart {
+    /// Whether this part still has hits left. Parts with no hits have no effect.
+    pub fn is_active(&self) -> bool {
+        self.hits > 0
+    }
+}
 
 with_update_struct! {
     /// A struct describing a creep's message conveyed with `say`.
-    #[derive(serde_derive::Deserialize, Default, Clone, Debug, PartialEq)]
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Default, Clone, Debug, PartialEq)]
     #[serde(rename_all = "camelCase")]
     pub struct CreepMessage {
         /// If true, the message is visible to all players.
@@ -70,7 +59,7 @@ with_update_struct! {
 
 with_update_struct! {
     /// A struct describing a creep's actions.
-    #[derive(serde_derive::Deserialize, Default, Clone, Debug, PartialEq)]
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Default, Clone, Debug, PartialEq)]
     #[serde(rename_all = "camelCase")]
     pub struct CreepActions {
         /// The location this creep harvested last tick.
@@ -155,7 +144,7 @@ with_base_fields_and_update_struct! {
         - hits_max: i32,
         - user: String,
         - spawning: bool,
-        #[serde(rename = "energyCapacity")]
+        #[serde(rename = "storeCapacity")]
         - capacity: i32,
         - notify_when_attacked: bool,
         - name: String,
@@ -172,6 +161,36 @@ impl Creep {
     pub fn carry_contents(&self) -> impl Iterator<Item = (ResourceType, i32)> + '_ {
         self.store.iter()
     }
+
+    /// The number of ticks this creep has left to live at the given game time, or `None` if it is still spawning.
+    pub fn ticks_to_live(&self, game_time: u32) -> Option<u32> {
+        self.age_time
+            .map(|age_time| age_time.saturating_sub(game_time))
+    }
+
+    /// The number of parts of the given type in this creep's body, including parts with no hits left.
+    pub fn part_count(&self, part_type: CreepPartType) -> usize {
+        self.body
+            .iter()
+            .filter(|part| part.part_type == part_type)
+            .count()
+    }
+
+    /// The number of parts of the given type in this creep's body which still have hits left, and so have an
+    /// effect.
+    pub fn active_part_count(&self, part_type: CreepPartType) -> usize {
+        self.body
+            .iter()
+            .filter(|part| part.part_type == part_type && part.is_active())
+            .count()
+    }
+
+    /// Iterates over the parts of this creep's body which are boosted, along with the compound boosting them.
+    pub fn boosted_parts(&self) -> impl Iterator<Item = (&CreepPart, &Boost)> + '_ {
+        self.body
+            .iter()
+            .filter_map(|part| part.boost.as_ref().map(|boost| (part, boost)))
+    }
 }
 
 #[cfg(test)]
@@ -180,7 +199,8 @@ mod test {
 
     use crate::data::RoomName;
 
-    use super::{ActionLogTarget, Creep, CreepActions, CreepPartType};
+    use super::{ActionLogTarget, Creep, CreepActions, CreepPart, CreepPartType};
+    use crate::data::Boost;
 
     #[test]
     fn parse_creep() {
@@ -326,4 +346,62 @@ mod test {
 
         assert_eq!(obj.store, store! { Energy: 13 });
     }
+
+    #[test]
+    fn parse_damaged_boosted_creep() {
+        let json = json!({
+            "_id": "5e117142fadd09a383ffdc9a",
+            "ageTime": 23470000,
+            "body": [
+                { "hits": 0, "type": "tough", "boost": "XGHO2" },
+                { "hits": 40, "type": "attack", "boost": "UH" },
+                { "hits": 100, "type": "attack" },
+                { "hits": 100, "type": "move" }
+            ],
+            "fatigue": 2,
+            "hits": 240,
+            "hitsMax": 400,
+            "name": "defender",
+            "room": "E44S19",
+            "store": {},
+            "storeCapacity": 0,
+            "type": "creep",
+            "user": "2",
+            "x": 10,
+            "y": 20
+        });
+
+        let mut creep = Creep::deserialize(json).unwrap();
+
+        assert_eq!(creep.user, "2");
+        assert_eq!(creep.fatigue, 2);
+        assert_eq!(creep.ticks_to_live(23469491), Some(509));
+        assert_eq!(creep.part_count(CreepPartType::Tough), 1);
+        assert_eq!(creep.active_part_count(CreepPartType::Tough), 0);
+        assert_eq!(creep.active_part_count(CreepPartType::Attack), 2);
+        assert_eq!(
+            creep
+                .boosted_parts()
+                .map(|(part, boost)| (part.part_type, boost.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (CreepPartType::Tough, Boost::CatalyzedGhodiumAlkalide),
+                (CreepPartType::Attack, Boost::UtriumHydride),
+            ]
+        );
+
+        creep.update(
+            serde_json::from_value(json!({
+                "body": { "1": { "hits": 0 } },
+                "storeCapacity": 50,
+            }))
+            .unwrap(),
+        );
+        assert_eq!(creep.active_part_count(CreepPartType::Attack), 1);
+
+        let part = CreepPart::deserialize(json!({ "hits": 100, "type": "work", "boost": "XQH2O" }))
+            .unwrap();
+        assert_eq!(part.boost, Some(Boost::Other("XQH2O".to_owned())));
+        assert_eq!(creep.capacity, 50);
+    }
 }
@@ -0,0 +1,106 @@
+//! `Deposit` data description.
+use super::super::resources::ResourceType;
+use crate::data::RoomName;
+
+with_base_fields_and_update_struct! {
+    /// A deposit, an object in highway rooms creeps can harvest for commodity resources.
+    ///
+    /// Each harvest makes the next cooldown longer, and deposits disappear when not harvested for a while.
+    #[derive(Clone, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Deposit {
+        /// The type of resource this deposit has: silicon, metal, biomass or mist.
+        pub deposit_type: ResourceType,
+        /// The total amount of resources harvested from this deposit.
+        #[serde(default)]
+        pub harvested: u32,
+        /// The length of the cooldown started by the last harvest, in game ticks.
+        #[serde(default)]
+        pub last_cooldown: u32,
+        /// The game time at which the deposit can next be harvested, if it has been harvested.
+        pub cooldown_time: Option<u32>,
+        /// The game time at which the deposit will disappear, unless harvested before then.
+        pub decay_time: u32,
+    }
+
+    /// The update structure for a deposit object.
+    #[derive(Clone, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DepositUpdate {
+        - deposit_type: ResourceType,
+        - harvested: u32,
+        - last_cooldown: u32,
+        - cooldown_time: Option<u32>,
+        - decay_time: u32,
+    }
+}
+
+impl Deposit {
+    /// The number of game ticks until this deposit can next be harvested, given the current game time.
+    pub fn cooldown(&self, game_time: u32) -> u32 {
+        self.cooldown_time
+            .map_or(0, |time| time.saturating_sub(game_time))
+    }
+
+    /// The number of game ticks until this deposit disappears, given the current game time.
+    pub fn ticks_to_decay(&self, game_time: u32) -> u32 {
+        self.decay_time.saturating_sub(game_time)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Deserialize;
+
+    use crate::data::RoomName;
+
+    use super::{Deposit, ResourceType};
+
+    #[test]
+    fn parse_deposit_and_update() {
+        let json = json!({
+            "_id": "5e5b7b1f0a1b2c3d4e5f6a7b",
+            "cooldownTime": 20000105,
+            "decayTime": 20049950,
+            "depositType": "mist",
+            "harvested": 1204,
+            "lastCooldown": 12,
+            "room": "W10N5",
+            "type": "deposit",
+            "x": 12,
+            "y": 40,
+        });
+
+        let mut obj = Deposit::deserialize(json).unwrap();
+
+        assert_eq!(
+            obj,
+            Deposit {
+                id: "5e5b7b1f0a1b2c3d4e5f6a7b".to_owned(),
+                room: RoomName::new("W10N5").unwrap(),
+                x: 12,
+                y: 40,
+                deposit_type: ResourceType::Mist,
+                harvested: 1204,
+                last_cooldown: 12,
+                cooldown_time: Some(20000105),
+                decay_time: 20049950,
+            }
+        );
+        assert_eq!(obj.cooldown(20000100), 5);
+        assert_eq!(obj.cooldown(20000200), 0);
+        assert_eq!(obj.ticks_to_decay(20000000), 49950);
+
+        obj.update(
+            serde_json::from_value(json!({
+                "harvested": 1210,
+                "lastCooldown": 13,
+                "cooldownTime": 20000118,
+            }))
+            .unwrap(),
+        );
+
+        assert_eq!(obj.harvested, 1210);
+        assert_eq!(obj.cooldown(20000105), 13);
+    }
+}
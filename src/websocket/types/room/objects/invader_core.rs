@@ -0,0 +1,141 @@
+//! `StructureInvaderCore` data description.
+use super::super::resources::ResourceType;
+use super::{ObjectEffect, EFFECT_COLLAPSE_TIMER, EFFECT_INVULNERABILITY};
+use crate::data::RoomName;
+
+with_structure_fields_and_update_struct! {
+    /// An invader core - the center of an NPC stronghold in a sector's center rooms, or a lesser core which
+    /// reserves rooms nearby.
+    #[derive(Clone, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StructureInvaderCore {
+        /// The user ID of the owner of this structure: always the invader NPC.
+        pub user: String,
+        /// The level of the stronghold, from 0 for lesser cores to 5.
+        pub level: u8,
+        /// The name of the stronghold layout, such as `"bunker3"`.
+        pub template_name: Option<String>,
+        /// The ID shared by all structures of this stronghold.
+        pub stronghold_id: Option<String>,
+        /// The type of deposit which will be left behind when the stronghold is destroyed.
+        pub deposit_type: Option<ResourceType>,
+        /// The game time at which this core finishes deploying its stronghold, if it is still deploying.
+        pub deploy_time: Option<u32>,
+        /// Effects currently applied to this core, including its collapse timer.
+        #[serde(default)]
+        pub effects: Vec<ObjectEffect>,
+    }
+
+    /// The update structure for an invader core.
+    #[derive(Clone, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StructureInvaderCoreUpdate {
+        - user: String,
+        - level: u8,
+        - template_name: Option<String>,
+        - stronghold_id: Option<String>,
+        - deposit_type: Option<ResourceType>,
+        - deploy_time: Option<u32>,
+        - effects: Vec<ObjectEffect>,
+    }
+}
+
+impl StructureInvaderCore {
+    /// Finds the effect with the given ID on this core.
+    pub fn effect(&self, effect: u32) -> Option<&ObjectEffect> {
+        self.effects.iter().find(|e| e.effect == effect)
+    }
+
+    /// The game time at which this stronghold collapses, if it has a collapse timer.
+    pub fn collapse_time(&self) -> Option<u32> {
+        self.effect(EFFECT_COLLAPSE_TIMER).map(|e| e.end_time)
+    }
+
+    /// The number of game ticks until this stronghold collapses, given the current game time.
+    pub fn ticks_to_collapse(&self, game_time: u32) -> Option<u32> {
+        self.collapse_time()
+            .map(|time| time.saturating_sub(game_time))
+    }
+
+    /// The number of game ticks until this core finishes deploying, given the current game time.
+    ///
+    /// Returns `None` if this core is not deploying.
+    pub fn ticks_to_deploy(&self, game_time: u32) -> Option<u32> {
+        self.deploy_time.map(|time| time.saturating_sub(game_time))
+    }
+
+    /// Whether this core can't currently be damaged, given the current game time.
+    pub fn is_invulnerable(&self, game_time: u32) -> bool {
+        self.effect(EFFECT_INVULNERABILITY)
+            .is_some_and(|e| e.end_time > game_time)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Deserialize;
+
+    use crate::data::Power;
+
+    use super::{ResourceType, StructureInvaderCore};
+
+    #[test]
+    fn parse_invader_core_and_update() {
+        let json = json!({
+            "_id": "5f2a3b4c5d6e7f8a9b0c1d2e",
+            "depositType": "silicon",
+            "effects": [
+                {
+                    "duration": 75000,
+                    "effect": 1002,
+                    "endTime": 20075000,
+                    "power": 1002
+                },
+                {
+                    "duration": 5000,
+                    "effect": 1001,
+                    "endTime": 20005000,
+                    "power": 1001
+                }
+            ],
+            "hits": 100000,
+            "hitsMax": 100000,
+            "level": 3,
+            "room": "W35N35",
+            "strongholdId": "abc123",
+            "templateName": "bunker3",
+            "type": "invaderCore",
+            "user": "2",
+            "x": 25,
+            "y": 25
+        });
+
+        let mut obj = StructureInvaderCore::deserialize(json).unwrap();
+
+        assert_eq!(obj.level, 3);
+        assert_eq!(obj.deposit_type, Some(ResourceType::Silicon));
+        assert_eq!(obj.ticks_to_collapse(20000000), Some(75000));
+        assert!(obj.is_invulnerable(20000000));
+        assert!(!obj.is_invulnerable(20005000));
+        assert_eq!(obj.ticks_to_deploy(20000000), None);
+        assert_eq!(obj.effects[0].power(), None);
+
+        obj.update(
+            serde_json::from_value(json!({
+                "effects": {
+                    "1": {
+                        "effect": 6,
+                        "level": 2,
+                        "endTime": 20000010,
+                        "duration": 1000
+                    }
+                }
+            }))
+            .unwrap(),
+        );
+
+        assert!(!obj.is_invulnerable(20000000));
+        assert_eq!(obj.effects[1].power(), Some(Power::OperateExtension));
+        assert_eq!(obj.collapse_time(), Some(20075000));
+    }
+}
@@ -51,7 +51,7 @@ with_structure_fields_and_update_struct! {
 
 with_update_struct! {
     /// A struct describing the source labs for a lab performing a mineral reaction.
-    #[derive(serde_derive::Deserialize, Clone, Debug, PartialEq)]
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Clone, Debug, PartialEq)]
     pub struct LabActionTarget {
         /// The x position of the first source lab.
         pub x1: u32,
@@ -70,7 +70,7 @@ with_update_struct! {
 
 with_update_struct! {
     /// A struct describing a lab's actions.
-    #[derive(serde_derive::Deserialize, Clone, Debug, PartialEq)]
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Clone, Debug, PartialEq)]
     #[serde(rename_all = "camelCase")]
     pub struct StructureLabActions {
         /// The two source labs that provided minerals for the reaction that was run last tick.
@@ -1,21 +1,23 @@
 //! `Mineral` data description.
 use super::super::resources::ResourceType;
-use crate::data::RoomName;
+use crate::data::{Density, RoomName};
+
+basic_updatable!(Density);
 
 with_base_fields_and_update_struct! {
     /// A mineral, an object creeps can mine for a non-energy resource.
     #[derive(Clone, Debug, PartialEq)]
     #[serde(rename_all = "camelCase")]
     pub struct Mineral {
-        /// The 'density' value, dictating how much of the resource is added when the mineral regenerates.
+        /// The mineral's density, dictating how much of the resource is added when the mineral regenerates.
         ///
         /// Changes each regeneration.
-        pub density: u8,
+        pub density: Density,
         /// The current amount of the resource in the mineral.
         pub mineral_amount: f64,
         /// The type of resource this mineral has.
         pub mineral_type: ResourceType,
-        /// The number of game ticks until the mineral next regenerates
+        /// The game time at which the mineral next regenerates
         /// (or None if the mineral still has any resources left).
         pub next_regeneration_time: Option<u32>,
     }
@@ -26,11 +28,26 @@ with_base_fields_and_update_struct! {
     pub struct MineralUpdate { ... }
 }
 
+impl Mineral {
+    /// Whether all of this mineral's resources have been mined, and it is waiting to regenerate.
+    pub fn is_depleted(&self) -> bool {
+        self.mineral_amount <= 0.0
+    }
+
+    /// The number of game ticks until this mineral regenerates, given the current game time.
+    ///
+    /// Returns `None` if the mineral still has any resources left.
+    pub fn ticks_to_regeneration(&self, game_time: u32) -> Option<u32> {
+        self.next_regeneration_time
+            .map(|time| time.saturating_sub(game_time))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use serde::Deserialize;
 
-    use crate::data::RoomName;
+    use crate::data::{Density, RoomName};
 
     use super::{Mineral, ResourceType};
 
@@ -57,11 +74,36 @@ mod test {
                 room: RoomName::new("E4S61").unwrap(),
                 x: 14,
                 y: 21,
-                density: 3,
+                density: Density::High,
                 mineral_amount: 65590.0,
                 mineral_type: ResourceType::Hydrogen,
                 next_regeneration_time: None,
             }
         );
+        assert!(!obj.is_depleted());
+        assert_eq!(obj.ticks_to_regeneration(19894026), None);
+        assert_eq!(obj.density.amount(), 70000);
+    }
+
+    #[test]
+    fn parse_depleted_mineral() {
+        let json = json!({
+            "_id": "57efa010195b160f02c752d6",
+            "density": 2,
+            "mineralAmount": 0,
+            "mineralType": "X",
+            "nextRegenerationTime": 19944026,
+            "room": "E4S61",
+            "type": "mineral",
+            "x": 14,
+            "y": 21,
+        });
+
+        let obj = Mineral::deserialize(json).unwrap();
+
+        assert_eq!(obj.mineral_type, ResourceType::Catalyst);
+        assert_eq!(obj.density, Density::Moderate);
+        assert!(obj.is_depleted());
+        assert_eq!(obj.ticks_to_regeneration(19894026), Some(50000));
     }
 }
@@ -5,14 +5,24 @@
 //!
 //! Reading the source code is definitely possible. But there may be some investment in reading
 //! each of the macros defined and used here, and it will be much easier to just read the documentation.
-use crate::RoomName;
+use std::convert::TryFrom;
+
+use serde::{
+    de::{Deserialize, Deserializer, Error as _},
+    ser::{Serialize, Serializer},
+};
+
+use super::resources::Store;
+use crate::{ControllerInfo, RoomName, RoomXY};
 
 mod construction_site;
 mod container;
 mod controller;
 mod creep;
+mod deposit;
 mod extension;
 mod extractor;
+mod invader_core;
 mod keeper_lair;
 mod lab;
 mod link;
@@ -21,10 +31,12 @@ mod nuker;
 mod observer;
 mod portal;
 mod power_bank;
+mod power_creep;
 mod power_spawn;
 mod rampart;
 mod resource;
 mod road;
+mod ruin;
 mod shared;
 mod source;
 mod spawn;
@@ -35,10 +47,11 @@ mod tower;
 mod wall;
 
 pub use self::{
-    construction_site::*, container::*, controller::*, creep::*, extension::*, extractor::*,
-    keeper_lair::*, lab::*, link::*, mineral::*, nuker::*, observer::*, portal::*, power_bank::*,
-    power_spawn::*, rampart::*, resource::*, road::*, shared::*, source::*, spawn::*, storage::*,
-    terminal::*, tombstone::*, tower::*, wall::*,
+    construction_site::*, container::*, controller::*, creep::*, deposit::*, extension::*,
+    extractor::*, invader_core::*, keeper_lair::*, lab::*, link::*, mineral::*, nuker::*,
+    observer::*, portal::*, power_bank::*, power_creep::*, power_spawn::*, rampart::*, resource::*,
+    road::*, ruin::*, shared::*, source::*, spawn::*, storage::*, terminal::*, tombstone::*,
+    tower::*, wall::*,
 };
 
 /// Enum describing all known room objects.
@@ -49,6 +62,8 @@ pub enum KnownRoomObject {
     Source(Source),
     /// Mineral object.
     Mineral(Mineral),
+    /// Deposit object.
+    Deposit(Deposit),
     /// Spawn owned structure.
     Spawn(StructureSpawn),
     /// Extension owned structure.
@@ -64,6 +79,8 @@ pub enum KnownRoomObject {
     Rampart(StructureRampart),
     /// Keeper Lair NPC structure.
     KeeperLair(StructureKeeperLair),
+    /// Invader core NPC structure.
+    InvaderCore(StructureInvaderCore),
     /// Controller permanent structure.
     Controller(StructureController),
     /// Portal naturally occurring structure.
@@ -90,8 +107,12 @@ pub enum KnownRoomObject {
     Nuker(StructureNuker),
     /// Tombstone structure
     Tombstone(Tombstone),
+    /// Ruin structure
+    Ruin(Ruin),
     /// Creep
     Creep(Creep),
+    /// Power creep
+    PowerCreep(PowerCreep),
     /// Resource
     #[serde(rename = "energy")]
     Resource(Resource),
@@ -119,9 +140,9 @@ macro_rules! match_obj_variants {
     ) => (
         match_many_variants!(
             $src,
-            (Source, Mineral, Spawn, Extension, Extractor, Wall, Road, Rampart, KeeperLair, Controller, Portal,
-            Link, Storage, Tower, Observer, PowerBank, PowerSpawn, Lab, Terminal, Container, Nuker, Tombstone, Creep,
-            Resource, ConstructionSite)
+            (Source, Mineral, Deposit, Spawn, Extension, Extractor, Wall, Road, Rampart, KeeperLair, InvaderCore,
+            Controller, Portal, Link, Storage, Tower, Observer, PowerBank, PowerSpawn, Lab, Terminal, Container, Nuker,
+            Tombstone, Ruin, Creep, PowerCreep, Resource, ConstructionSite)
             ($name) => $code
         )
     )
@@ -147,6 +168,11 @@ impl KnownRoomObject {
         match_obj_variants!(self, v => v.y)
     }
 
+    /// Get this object's position within its room, or `None` if the server sent a position outside of the room.
+    pub fn xy(&self) -> Option<RoomXY> {
+        RoomXY::try_from((self.x(), self.y())).ok()
+    }
+
     /// Get this object's id
     pub fn id(&self) -> &str {
         match_obj_variants!(self, v => &v.id)
@@ -158,14 +184,417 @@ impl KnownRoomObject {
     }
 }
 
+/// A room object, with typed variants for the major kinds of objects, and everything else kept as JSON.
+///
+/// This can be parsed from the objects of a [`RoomObjects`] result, and built up from socket room updates with
+/// [`RoomUpdate::apply_to`]. Unlike [`KnownRoomObject`], objects of types which aren't known parse successfully, as
+/// `Raw` objects, so new or rare object types don't stop the rest of a room from being read. Objects of known types
+/// which don't have the expected shape are kept as `Raw` objects too.
+///
+/// ```
+/// use screeps_api::websocket::objects::RoomObject;
+///
+/// let object: RoomObject = serde_json::from_value(serde_json::json!({
+///     "_id": "5f0b7b6e0a1b2c3d4e5f6a7b",
+///     "type": "factory",
+///     "room": "W1N1",
+///     "x": 20,
+///     "y": 30,
+///     "level": 2,
+/// }))
+/// .unwrap();
+///
+/// assert_eq!(object.object_type(), "factory");
+/// match object {
+///     RoomObject::Raw(raw) => assert_eq!(raw.data["level"], 2),
+///     other => panic!("expected a raw object, found {:?}", other),
+/// }
+/// ```
+///
+/// [`RoomObjects`]: ../../struct.RoomObjects.html
+/// [`RoomUpdate::apply_to`]: ../struct.RoomUpdate.html#method.apply_to
+/// [`KnownRoomObject`]: enum.KnownRoomObject.html
+#[derive(Clone, Debug)]
+pub enum RoomObject {
+    /// Spawn owned structure.
+    Spawn(StructureSpawn),
+    /// Extension owned structure.
+    Extension(StructureExtension),
+    /// Tower owned structure.
+    Tower(StructureTower),
+    /// Storage owned structure.
+    Storage(StructureStorage),
+    /// Terminal owned structure.
+    Terminal(StructureTerminal),
+    /// Source object.
+    Source(Source),
+    /// Mineral object.
+    Mineral(Mineral),
+    /// Deposit object.
+    Deposit(Deposit),
+    /// Controller permanent structure.
+    Controller(StructureController),
+    /// Invader core NPC structure.
+    InvaderCore(StructureInvaderCore),
+    /// Creep
+    Creep(Creep),
+    /// Power creep
+    PowerCreep(PowerCreep),
+    /// Tombstone structure
+    Tombstone(Tombstone),
+    /// Ruin structure
+    Ruin(Ruin),
+    /// Construction site
+    ConstructionSite(ConstructionSite),
+    /// Any other object.
+    Raw(RawRoomObject),
+}
+
+/// A room object of a type without its own [`RoomObject`] variant, with the fields all objects have parsed and
+/// the rest kept as JSON.
+///
+/// [`RoomObject`]: enum.RoomObject.html
+#[derive(serde_derive::Deserialize, serde_derive::Serialize, Clone, Debug, PartialEq)]
+pub struct RawRoomObject {
+    /// Unique 'id' identifier for all game objects on a server.
+    #[serde(rename = "_id")]
+    pub id: String,
+    /// The object's type, such as `"factory"`.
+    #[serde(rename = "type")]
+    pub object_type: String,
+    /// Room object is in.
+    pub room: RoomName,
+    /// X position within the room (0-50).
+    #[serde(with = "crate::decoders::u32_or_str_containing")]
+    pub x: u32,
+    /// Y position within the room (0-50).
+    #[serde(with = "crate::decoders::u32_or_str_containing")]
+    pub y: u32,
+    /// All other fields of the object.
+    #[serde(flatten)]
+    pub data: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RawRoomObject {
+    /// Update this object with a JSON update: fields present in the update replace existing ones, and null fields
+    /// are removed.
+    pub fn update(&mut self, input: serde_json::Value) -> Result<(), serde_json::Error> {
+        let fields: serde_json::Map<String, serde_json::Value> = serde_json::from_value(input)?;
+
+        for (key, value) in fields {
+            match &*key {
+                "_id" | "type" | "room" => (),
+                "x" => self.x = crate::decoders::u32_or_str_containing::deserialize(value)?,
+                "y" => self.y = crate::decoders::u32_or_str_containing::deserialize(value)?,
+                _ if value.is_null() => {
+                    self.data.remove(&key);
+                }
+                _ => {
+                    self.data.insert(key, value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! match_room_object {
+    ($src:ident, $name:ident => $code:expr) => {
+        match $src {
+            RoomObject::Spawn($name) => $code,
+            RoomObject::Extension($name) => $code,
+            RoomObject::Tower($name) => $code,
+            RoomObject::Storage($name) => $code,
+            RoomObject::Terminal($name) => $code,
+            RoomObject::Source($name) => $code,
+            RoomObject::Mineral($name) => $code,
+            RoomObject::Deposit($name) => $code,
+            RoomObject::Controller($name) => $code,
+            RoomObject::InvaderCore($name) => $code,
+            RoomObject::Creep($name) => $code,
+            RoomObject::PowerCreep($name) => $code,
+            RoomObject::Tombstone($name) => $code,
+            RoomObject::Ruin($name) => $code,
+            RoomObject::ConstructionSite($name) => $code,
+            RoomObject::Raw($name) => $code,
+        }
+    };
+}
+
+impl RoomObject {
+    /// Parses a room object from its JSON, as a `Raw` object if its type doesn't have its own variant, or if it
+    /// doesn't have the shape its type's variant expects.
+    ///
+    /// Only fails if the object is missing a field all objects have, such as its id or position.
+    pub fn from_value(input: serde_json::Value) -> Result<Self, serde_json::Error> {
+        let object_type = input.get("type").and_then(serde_json::Value::as_str);
+
+        let typed = match object_type {
+            Some("spawn") => StructureSpawn::deserialize(&input).map(RoomObject::Spawn),
+            Some("extension") => StructureExtension::deserialize(&input).map(RoomObject::Extension),
+            Some("tower") => StructureTower::deserialize(&input).map(RoomObject::Tower),
+            Some("storage") => StructureStorage::deserialize(&input).map(RoomObject::Storage),
+            Some("terminal") => StructureTerminal::deserialize(&input).map(RoomObject::Terminal),
+            Some("source") => Source::deserialize(&input).map(RoomObject::Source),
+            Some("mineral") => Mineral::deserialize(&input).map(RoomObject::Mineral),
+            Some("deposit") => Deposit::deserialize(&input).map(RoomObject::Deposit),
+            Some("controller") => {
+                StructureController::deserialize(&input).map(RoomObject::Controller)
+            }
+            Some("invaderCore") => {
+                StructureInvaderCore::deserialize(&input).map(RoomObject::InvaderCore)
+            }
+            Some("creep") => Creep::deserialize(&input).map(RoomObject::Creep),
+            Some("powerCreep") => PowerCreep::deserialize(&input).map(RoomObject::PowerCreep),
+            Some("tombstone") => Tombstone::deserialize(&input).map(RoomObject::Tombstone),
+            Some("ruin") => Ruin::deserialize(&input).map(RoomObject::Ruin),
+            Some("constructionSite") => {
+                ConstructionSite::deserialize(&input).map(RoomObject::ConstructionSite)
+            }
+            _ => return Ok(RoomObject::Raw(serde_json::from_value(input)?)),
+        };
+
+        match typed {
+            Ok(mut object) => {
+                object.read_store_capacity(&input)?;
+                Ok(object)
+            }
+            Err(_) => Ok(RoomObject::Raw(serde_json::from_value(input)?)),
+        }
+    }
+
+    /// Update this room object with a JSON update string.
+    pub fn update(&mut self, input: serde_json::Value) -> Result<(), serde_json::Error> {
+        self.read_store_capacity(&input)?;
+
+        match self {
+            RoomObject::Raw(raw) => raw.update(input)?,
+            RoomObject::Spawn(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::Extension(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::Tower(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::Storage(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::Terminal(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::Source(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::Mineral(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::Deposit(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::Controller(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::InvaderCore(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::Creep(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::PowerCreep(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::Tombstone(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::Ruin(v) => v.update(serde_json::from_value(input)?),
+            RoomObject::ConstructionSite(v) => v.update(serde_json::from_value(input)?),
+        }
+
+        Ok(())
+    }
+
+    /// Get this object's type, as the server names it, such as `"spawn"` or `"constructionSite"`.
+    pub fn object_type(&self) -> &str {
+        match self {
+            RoomObject::Spawn(_) => "spawn",
+            RoomObject::Extension(_) => "extension",
+            RoomObject::Tower(_) => "tower",
+            RoomObject::Storage(_) => "storage",
+            RoomObject::Terminal(_) => "terminal",
+            RoomObject::Source(_) => "source",
+            RoomObject::Mineral(_) => "mineral",
+            RoomObject::Deposit(_) => "deposit",
+            RoomObject::Controller(_) => "controller",
+            RoomObject::InvaderCore(_) => "invaderCore",
+            RoomObject::Creep(_) => "creep",
+            RoomObject::PowerCreep(_) => "powerCreep",
+            RoomObject::Tombstone(_) => "tombstone",
+            RoomObject::Ruin(_) => "ruin",
+            RoomObject::ConstructionSite(_) => "constructionSite",
+            RoomObject::Raw(raw) => &raw.object_type,
+        }
+    }
+
+    /// Get this object's x position
+    pub fn x(&self) -> u32 {
+        match_room_object!(self, v => v.x)
+    }
+
+    /// Get this object's y position
+    pub fn y(&self) -> u32 {
+        match_room_object!(self, v => v.y)
+    }
+
+    /// Get this object's position within its room, or `None` if the server sent a position outside of the room.
+    pub fn xy(&self) -> Option<RoomXY> {
+        RoomXY::try_from((self.x(), self.y())).ok()
+    }
+
+    /// Get this object's id
+    pub fn id(&self) -> &str {
+        match_room_object!(self, v => &v.id)
+    }
+
+    /// Get this object's room name
+    pub fn room(&self) -> RoomName {
+        match_room_object!(self, v => v.room)
+    }
+
+    /// Get the resources this object holds, if it is a typed object with a store.
+    ///
+    /// The store's capacity is filled in from the object's `storeCapacity` and `storeCapacityResource` fields.
+    pub fn store(&self) -> Option<&Store> {
+        match self {
+            RoomObject::Storage(v) => Some(&v.store),
+            RoomObject::Terminal(v) => Some(&v.store),
+            RoomObject::Creep(v) => Some(&v.store),
+            RoomObject::PowerCreep(v) => Some(&v.store),
+            RoomObject::Tombstone(v) => Some(&v.store),
+            RoomObject::Ruin(v) => Some(&v.store),
+            _ => None,
+        }
+    }
+
+    fn read_store_capacity(&mut self, input: &serde_json::Value) -> Result<(), serde_json::Error> {
+        let store = match self {
+            RoomObject::Storage(v) => &mut v.store,
+            RoomObject::Terminal(v) => &mut v.store,
+            RoomObject::Creep(v) => &mut v.store,
+            RoomObject::PowerCreep(v) => &mut v.store,
+            RoomObject::Tombstone(v) => &mut v.store,
+            RoomObject::Ruin(v) => &mut v.store,
+            _ => return Ok(()),
+        };
+
+        match input.as_object() {
+            Some(fields) => store.read_capacity(fields),
+            None => Ok(()),
+        }
+    }
+
+    /// Get this object's controller state, if it is a controller.
+    pub fn controller_info(&self) -> Option<&ControllerInfo> {
+        match self {
+            RoomObject::Controller(controller) => Some(&controller.info),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RoomObject {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let input = serde_json::Value::deserialize(deserializer)?;
+        RoomObject::from_value(input).map_err(D::Error::custom)
+    }
+}
+
+/// A typed room object's fields, along with the type they're parsed from.
+#[derive(serde_derive::Serialize)]
+struct TaggedRoomObject<'a, T> {
+    #[serde(rename = "type")]
+    object_type: &'a str,
+    #[serde(flatten)]
+    object: &'a T,
+}
+
+impl Serialize for RoomObject {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let object_type = self.object_type();
+        match self {
+            RoomObject::Raw(raw) => raw.serialize(serializer),
+            RoomObject::Spawn(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::Extension(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::Tower(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::Storage(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::Terminal(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::Source(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::Mineral(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::Deposit(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::Controller(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::InvaderCore(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::Creep(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::PowerCreep(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::Tombstone(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::Ruin(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+            RoomObject::ConstructionSite(object) => TaggedRoomObject {
+                object_type,
+                object,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::hash_map::Entry::*;
     use std::collections::HashMap;
 
+    use serde::Deserialize;
     use serde_json;
 
-    use super::KnownRoomObject;
+    use super::{KnownRoomObject, RoomObject};
+    use crate::websocket::resources::ResourceType;
+    use crate::websocket::RoomUpdate;
 
     #[test]
     fn parse_a_room_update_chain() {
@@ -219,4 +648,103 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn build_room_objects_from_updates() {
+        let json: serde_json::Value = serde_json::from_slice(include_bytes!("test-stream.json"))
+            .expect("expected saved json file to parse successfully.");
+
+        let mut objects = HashMap::<String, RoomObject>::new();
+        for update in json.as_array().unwrap() {
+            let update = RoomUpdate::deserialize(json!({ "info": {}, "objects": update })).unwrap();
+            update.apply_to(&mut objects).unwrap();
+        }
+        assert!(!objects.is_empty());
+        assert!(objects
+            .values()
+            .any(|object| !matches!(object, RoomObject::Raw(_))));
+
+        let mut raw = RoomObject::from_value(json!({
+            "_id": "5f0b7b6e0a1b2c3d4e5f6a7b",
+            "type": "factory",
+            "room": "W1N1",
+            "x": 20,
+            "y": 30,
+            "level": 2,
+            "cooldown": 5,
+        }))
+        .unwrap();
+        raw.update(json!({ "x": 21, "cooldown": null, "store": { "energy": 100 } }))
+            .unwrap();
+        assert_eq!(raw.id(), "5f0b7b6e0a1b2c3d4e5f6a7b");
+        assert_eq!(raw.x(), 21);
+        match raw {
+            RoomObject::Raw(raw) => {
+                assert_eq!(raw.object_type, "factory");
+                assert_eq!(
+                    serde_json::Value::Object(raw.data),
+                    json!({ "level": 2, "store": { "energy": 100 } })
+                );
+            }
+            other => panic!("expected a raw object, found {:?}", other),
+        }
+
+        let source = RoomObject::from_value(json!({
+            "_id": "5f0b7b6e0a1b2c3d4e5f6a7c",
+            "type": "source",
+            "room": "W1N1",
+            "x": 5,
+            "y": 6,
+            "energy": 3000,
+            "energyCapacity": 3000,
+            "ticksToRegeneration": 300,
+            "nextRegenerationTime": 1000,
+            "invaderHarvested": 0,
+        }));
+        match source {
+            Ok(RoomObject::Source(source)) => assert_eq!(source.energy, 3000),
+            other => panic!("expected a source, found {:?}", other),
+        }
+
+        let mut terminal = RoomObject::from_value(json!({
+            "_id": "5f0b7b6e0a1b2c3d4e5f6a7e",
+            "type": "terminal",
+            "room": "W1N1",
+            "x": 9,
+            "y": 10,
+            "hits": 3000,
+            "hitsMax": 3000,
+            "user": "57874d42d0ae911e3bd15bbc",
+            "storeCapacity": 300000,
+            "cooldownTime": 0,
+            "notifyWhenAttacked": true,
+            "store": { "energy": 1000, "H": 500 },
+        }))
+        .unwrap();
+        let store = terminal.store().unwrap();
+        assert_eq!(store.capacity(&ResourceType::Energy), Some(300000));
+        assert_eq!(store.free_capacity(&ResourceType::Energy), Some(298500));
+
+        terminal
+            .update(json!({ "store": { "energy": 500 }, "storeCapacity": 100000 }))
+            .unwrap();
+        let store = terminal.store().unwrap();
+        assert_eq!(store.free_capacity(&ResourceType::Hydrogen), Some(99000));
+
+        let spawn = RoomObject::from_value(json!({
+            "_id": "5f0b7b6e0a1b2c3d4e5f6a7d",
+            "type": "spawn",
+            "room": "W1N1",
+            "x": 7,
+            "y": 8,
+            "name": "Spawn1",
+        }));
+        match spawn {
+            Ok(RoomObject::Raw(raw)) => {
+                assert_eq!(raw.object_type, "spawn");
+                assert_eq!(raw.data["name"], "Spawn1");
+            }
+            other => panic!("expected a raw object, found {:?}", other),
+        }
+    }
 }
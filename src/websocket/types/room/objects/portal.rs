@@ -6,7 +6,7 @@ use crate::{
 
 with_update_struct! {
     /// The destination for a portal structure.
-    #[derive(serde_derive::Deserialize, Clone, Debug, PartialEq)]
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Clone, Debug, PartialEq)]
     pub struct PortalDestination {
         /// The room name the other side of this portal ends at.
         pub room: RoomName,
@@ -0,0 +1,161 @@
+//! `PowerCreep` data description.
+use std::collections::HashMap;
+
+use super::super::resources::ResourceType;
+use super::super::resources::Store;
+use crate::data::{Power, PowerClass, PowerInfo, RoomName};
+
+basic_updatable!(PowerClass);
+
+implement_update_for! {
+    PowerInfo;
+
+    /// The update structure for a power creep's knowledge of one power.
+    #[derive(serde_derive::Deserialize, Clone, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PowerInfoUpdate {
+        priv level: Option<u8>,
+        priv cooldown_time: Option<Option<u32>>,
+    }
+}
+
+with_base_fields_and_update_struct! {
+    /// A power creep object, spawned into a room.
+    #[derive(Clone, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PowerCreep {
+        /// The user ID of the owner of this power creep.
+        pub user: String,
+        /// The name of this power creep, unique per player.
+        pub name: String,
+        /// The class of this power creep.
+        pub class_name: PowerClass,
+        /// The level of this power creep: the number of powers levels it has learned.
+        pub level: u32,
+        /// The current number of hit-points this power creep has.
+        pub hits: i32,
+        /// The maximum number of hit-points this power creep has.
+        pub hits_max: i32,
+        /// The powers this power creep has learned, with their levels and cooldowns.
+        #[serde(default)]
+        pub powers: HashMap<Power, PowerInfo>,
+        /// The game time at which this power creep will die if not renewed before then.
+        pub age_time: Option<u32>,
+        /// Whether or not an attack on this power creep will send an email to the owner automatically.
+        #[serde(default)]
+        pub notify_when_attacked: bool,
+        /// The total amount of resources this power creep can carry.
+        #[serde(rename = "storeCapacity")]
+        pub capacity: i32,
+        /// The resources and amounts of each resource this power creep holds.
+        pub store: Store,
+    }
+
+    /// The update structure for a `PowerCreep`.
+    #[derive(Clone, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PowerCreepUpdate {
+        - user: String,
+        - name: String,
+        - class_name: PowerClass,
+        - level: u32,
+        - hits: i32,
+        - hits_max: i32,
+        - powers: HashMap<Power, PowerInfo>,
+        - age_time: Option<u32>,
+        - notify_when_attacked: bool,
+        #[serde(rename = "storeCapacity")]
+        - capacity: i32,
+        - store: Store,
+    }
+}
+
+impl PowerCreep {
+    /// The amount of ops this power creep holds, which most powers use up.
+    pub fn ops(&self) -> i32 {
+        self.store.get(ResourceType::Ops)
+    }
+
+    /// The level this power creep has learned the given power to, or `None` if it hasn't learned it.
+    pub fn power_level(&self, power: Power) -> Option<u8> {
+        self.powers.get(&power).map(|info| info.level)
+    }
+
+    /// The number of game ticks this power creep has left to live, given the current game time.
+    ///
+    /// Returns `None` if the server didn't send when the power creep will die.
+    pub fn ticks_to_live(&self, game_time: u32) -> Option<u32> {
+        self.age_time.map(|time| time.saturating_sub(game_time))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Deserialize;
+
+    use crate::data::{Power, PowerClass};
+
+    use super::PowerCreep;
+
+    #[test]
+    fn parse_power_creep_and_update() {
+        let json = json!({
+            "_id": "5e8a1b2c3d4e5f6a7b8c9d0e",
+            "ageTime": 20005000,
+            "className": "operator",
+            "hits": 1300,
+            "hitsMax": 1300,
+            "level": 12,
+            "name": "Operator1",
+            "notifyWhenAttacked": true,
+            "powers": {
+                "1": {
+                    "cooldownTime": 20000050,
+                    "level": 5
+                },
+                "6": {
+                    "level": 4
+                }
+            },
+            "room": "W41N48",
+            "store": {
+                "ops": 312
+            },
+            "storeCapacity": 1300,
+            "type": "powerCreep",
+            "user": "57874d42d0ae911e3bd15bbc",
+            "x": 21,
+            "y": 17
+        });
+
+        let mut obj = PowerCreep::deserialize(json).unwrap();
+
+        assert_eq!(obj.class_name, PowerClass::Operator);
+        assert_eq!(obj.ops(), 312);
+        assert_eq!(obj.power_level(Power::GenerateOps), Some(5));
+        assert_eq!(obj.power_level(Power::Shield), None);
+        assert_eq!(obj.powers[&Power::GenerateOps].cooldown(20000000), 50);
+        assert_eq!(obj.ticks_to_live(20000000), Some(5000));
+
+        obj.update(
+            serde_json::from_value(json!({
+                "powers": {
+                    "6": {
+                        "cooldownTime": 20000051
+                    }
+                },
+                "store": {
+                    "ops": 302
+                }
+            }))
+            .unwrap(),
+        );
+
+        assert_eq!(obj.ops(), 302);
+        assert_eq!(obj.powers[&Power::OperateExtension].level, 4);
+        assert_eq!(
+            obj.powers[&Power::OperateExtension].cooldown_time,
+            Some(20000051)
+        );
+    }
+}
@@ -1,10 +1,7 @@
 //! `Resource` data description.
 use std::fmt;
 
-use serde::de::{
-    value::Error as ValueError, Deserialize, Deserializer, Error, IgnoredAny, IntoDeserializer,
-    MapAccess, Visitor,
-};
+use serde::de::{Deserialize, Deserializer, Error, IgnoredAny, MapAccess, Visitor};
 
 use super::super::resources::ResourceType;
 use crate::data::RoomName;
@@ -75,14 +72,10 @@ impl<'de> Deserialize<'de> for FieldName {
                     "x" => Ok(FieldName::X),
                     "y" => Ok(FieldName::Y),
                     "resourceType" => Ok(FieldName::ResourceType),
-                    other => {
-                        match ResourceType::deserialize(
-                            IntoDeserializer::<ValueError>::into_deserializer(other),
-                        ) {
-                            Ok(resource_type) => Ok(FieldName::Other(resource_type)),
-                            Err(_) => Ok(FieldName::Ignored),
-                        }
-                    }
+                    other => match ResourceType::known(other) {
+                        Some(resource_type) => Ok(FieldName::Other(resource_type)),
+                        None => Ok(FieldName::Ignored),
+                    },
                 }
             }
 
@@ -97,14 +90,10 @@ impl<'de> Deserialize<'de> for FieldName {
                     b"y" => Ok(FieldName::Y),
                     b"resourceType" => Ok(FieldName::ResourceType),
                     other => match ::std::str::from_utf8(other) {
-                        Ok(other_str) => {
-                            match ResourceType::deserialize(
-                                IntoDeserializer::<ValueError>::into_deserializer(other_str),
-                            ) {
-                                Ok(resource_type) => Ok(FieldName::Other(resource_type)),
-                                Err(_) => Ok(FieldName::Ignored),
-                            }
-                        }
+                        Ok(other_str) => match ResourceType::known(other_str) {
+                            Some(resource_type) => Ok(FieldName::Other(resource_type)),
+                            None => Ok(FieldName::Ignored),
+                        },
                         Err(_) => Ok(FieldName::Ignored),
                     },
                 }
@@ -0,0 +1,115 @@
+//! `Ruin` data description.
+use super::super::resources::ResourceType;
+use super::super::resources::Store;
+use crate::data::RoomName;
+
+with_update_struct! {
+    /// A struct describing the structure a ruin was left by.
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Clone, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RuinStructure {
+        /// The ID of the destroyed structure.
+        pub id: String,
+        /// The type of the destroyed structure, such as `"spawn"`.
+        #[serde(rename = "type")]
+        pub structure_type: String,
+        /// The user ID of the owner of the destroyed structure, if it was owned.
+        pub user: Option<String>,
+        /// The maximum number of hit-points the destroyed structure had.
+        pub hits_max: Option<i32>,
+    }
+
+    /// The update structure for a `RuinStructure`.
+    #[derive(serde_derive::Deserialize, Clone, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RuinStructureUpdate { ... }
+}
+
+with_base_fields_and_update_struct! {
+    /// A ruin - remnants of a destroyed structure, holding the resources it held.
+    #[derive(Clone, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Ruin {
+        /// The structure which was destroyed.
+        pub structure: RuinStructure,
+        /// The time at which the structure was destroyed.
+        pub destroy_time: u32,
+        /// The time at which this ruin will decay.
+        pub decay_time: u32,
+        /// The resources and amounts of each resource some game object holds.
+        pub store: Store,
+    }
+
+    /// The update structure for a `Ruin`.
+    #[derive(Clone, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RuinUpdate { ... }
+}
+
+impl Ruin {
+    /// Iterates over this ruin's resources.
+    pub fn resources(&self) -> impl Iterator<Item = (ResourceType, i32)> + '_ {
+        self.store.iter()
+    }
+
+    /// The number of game ticks until this ruin decays, given the current game time.
+    pub fn ticks_to_decay(&self, game_time: u32) -> u32 {
+        self.decay_time.saturating_sub(game_time)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Deserialize;
+
+    use super::Ruin;
+
+    #[test]
+    fn parse_ruin_and_update() {
+        let json = json!({
+            "_id": "5f0e1d2c3b4a5f6e7d8c9b0a",
+            "decayTime": 38470500,
+            "destroyTime": 38470000,
+            "room": "W41N48",
+            "store": {
+                "energy": 300,
+                "XGHO2": 15
+            },
+            "structure": {
+                "hits": 0,
+                "hitsMax": 15000,
+                "id": "5e8a1b2c3d4e5f6a7b8c9d0f",
+                "type": "spawn",
+                "user": "57874d42d0ae911e3bd15bbc"
+            },
+            "type": "ruin",
+            "x": 25,
+            "y": 25
+        });
+
+        let mut obj = Ruin::deserialize(json).unwrap();
+
+        assert_eq!(obj.structure.structure_type, "spawn");
+        assert_eq!(
+            obj.structure.user.as_deref(),
+            Some("57874d42d0ae911e3bd15bbc")
+        );
+        assert_eq!(obj.destroy_time, 38470000);
+        assert_eq!(obj.ticks_to_decay(38470400), 100);
+        assert_eq!(
+            obj.store,
+            store! { Energy: 300, CatalyzedGhodiumAlkalide: 15 }
+        );
+
+        obj.update(
+            serde_json::from_value(json!({
+                "store": {
+                    "energy": 0
+                }
+            }))
+            .unwrap(),
+        );
+
+        assert_eq!(obj.store, store! { CatalyzedGhodiumAlkalide: 15 });
+    }
+}
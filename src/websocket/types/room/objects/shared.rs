@@ -1,8 +1,11 @@
 //! Structures which are shared and exist as sub-field of multiple structures.
+use std::convert::TryFrom;
+
+use crate::data::Power;
 
 with_update_struct! {
     /// A struct describing the destination of various actions within action logs.
-    #[derive(serde_derive::Deserialize, Clone, Debug, PartialEq)]
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Clone, Debug, PartialEq)]
     pub struct ActionLogTarget {
         /// The in-room x position of this target.
         pub x: u32,
@@ -14,3 +17,51 @@ with_update_struct! {
     #[derive(serde_derive::Deserialize, Clone, Debug)]
     pub struct ActionLogTargetUpdate { ... }
 }
+
+/// The effect ID of the invulnerability effect, which stops an invader core being damaged while it deploys.
+pub const EFFECT_INVULNERABILITY: u32 = 1001;
+/// The effect ID of the collapse timer effect, after which a stronghold collapses.
+pub const EFFECT_COLLAPSE_TIMER: u32 = 1002;
+
+with_update_struct! {
+    /// A struct describing an effect applied to an object, either by a power or by the game itself.
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Clone, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ObjectEffect {
+        /// The effect ID: a power ID for effects from powers, or a natural effect such as
+        /// [`EFFECT_COLLAPSE_TIMER`].
+        ///
+        /// [`EFFECT_COLLAPSE_TIMER`]: constant.EFFECT_COLLAPSE_TIMER.html
+        pub effect: u32,
+        /// The level of the power, for effects from powers.
+        #[serde(default)]
+        pub level: Option<u8>,
+        /// The game time at which this effect ends.
+        pub end_time: u32,
+        /// The total length of this effect, in game ticks.
+        #[serde(default)]
+        pub duration: u32,
+    }
+
+    /// The update structure for an `ObjectEffect`.
+    #[derive(serde_derive::Deserialize, Clone, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ObjectEffectUpdate {
+        - effect: u32,
+        - level: Option<u8>,
+        - end_time: u32,
+        - duration: u32,
+    }
+}
+
+impl ObjectEffect {
+    /// The power which caused this effect, if it was caused by a power.
+    pub fn power(&self) -> Option<Power> {
+        u8::try_from(self.effect).ok().and_then(Power::from_id)
+    }
+
+    /// The number of game ticks until this effect ends, given the current game time.
+    pub fn ticks_remaining(&self, game_time: u32) -> u32 {
+        self.end_time.saturating_sub(game_time)
+    }
+}
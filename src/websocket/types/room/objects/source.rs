@@ -34,6 +34,35 @@ with_base_fields_and_update_struct! {
     }
 }
 
+impl Source {
+    /// Whether all of this source's energy has been harvested, and it is waiting to regenerate.
+    pub fn is_depleted(&self) -> bool {
+        self.energy <= 0
+    }
+
+    /// The amount of energy harvested from this source since it last regenerated.
+    pub fn energy_harvested(&self) -> i32 {
+        (self.energy_capacity - self.energy).max(0)
+    }
+
+    /// The number of game ticks until this source regenerates, given the current game time.
+    ///
+    /// Returns `None` when waiting on a creep to first harvest it.
+    pub fn regenerates_in(&self, game_time: u32) -> Option<u32> {
+        self.next_regeneration_time
+            .map(|time| time.saturating_sub(game_time))
+    }
+
+    /// The energy which must be harvested each tick to fully harvest this source before it regenerates.
+    pub fn full_harvest_rate(&self) -> f64 {
+        if self.ticks_to_regeneration == 0 {
+            0.0
+        } else {
+            f64::from(self.energy_capacity) / f64::from(self.ticks_to_regeneration)
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use serde::Deserialize;
@@ -74,6 +103,9 @@ mod test {
                 ticks_to_regeneration: 300,
             }
         );
+        assert_eq!(obj.energy_harvested(), 2740);
+        assert_eq!(obj.regenerates_in(19894000), Some(26));
+        assert_eq!(obj.full_harvest_rate(), 10.0);
 
         obj.update(
             serde_json::from_value(json!({
@@ -98,6 +130,7 @@ mod test {
                 ticks_to_regeneration: 300,
             }
         );
+        assert!(obj.is_depleted());
     }
 
     #[test]
@@ -3,7 +3,7 @@ use crate::data::RoomName;
 
 with_update_struct! {
     /// A struct describing a creep currently spawning (used as part of the update for a StructureSpawn).
-    #[derive(serde_derive::Deserialize, Clone, Debug, PartialEq)]
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Clone, Debug, PartialEq)]
     #[serde(rename_all = "camelCase")]
     pub struct SpawningCreep {
         /// The name of this creep, unique per player.
@@ -6,35 +6,68 @@ use crate::data::RoomName;
 use super::creep::CreepPartType;
 
 with_base_fields_and_update_struct! {
-    /// A tomstone - remnants of a dead creep
+    /// A tomstone - remnants of a dead creep or power creep
+    ///
+    /// The `creep_*` fields describe a dead creep, and are empty for power creeps, which have their own fields.
     #[derive(Clone, Debug, PartialEq)]
     #[serde(rename_all = "camelCase")]
     pub struct Tombstone {
         /// The user ID of the owner of the creep
         pub user: String,
         /// The body of the creep who died
+        #[serde(default)]
         pub creep_body: Vec<CreepPartType>,
         /// The ID of the creep who died
+        #[serde(default)]
         pub creep_id: String,
         /// The name of the creep who died
+        #[serde(default)]
         pub creep_name: String,
         /// What the creep was saying when they died
         pub creep_saying: Option<String>,
         /// How many ticks-to-live did the creeps have when it died
+        #[serde(default)]
         pub creep_ticks_to_live: i32,
+        /// The ID of the power creep who died, if this is a power creep's tombstone
+        pub power_creep_id: Option<String>,
+        /// The name of the power creep who died, if this is a power creep's tombstone
+        pub power_creep_name: Option<String>,
+        /// The class of the power creep who died, such as `"operator"`
+        pub power_creep_class_name: Option<String>,
+        /// The level of the power creep who died
+        pub power_creep_level: Option<u32>,
+        /// How many ticks-to-live did the power creep have when it died
+        pub power_creep_ticks_to_live: Option<i32>,
+        /// What the power creep was saying when they died
+        pub power_creep_saying: Option<String>,
         /// The time of death of the creep
         pub death_time: u32,
         /// The time at which this structure will decay
         pub decay_time: u32,
         /// The resources and amounts of each resource some game object holds.
         pub store: Store,
-        // TODO: what does the tombstone of a power creep look like?
     }
 
     /// The update structure for a `Tombstone`.
     #[derive(Clone, Debug)]
     #[serde(rename_all = "camelCase")]
-    pub struct TombstoneUpdate { ... }
+    pub struct TombstoneUpdate {
+        - user: String,
+        - creep_body: Vec<CreepPartType>,
+        - creep_id: String,
+        - creep_name: String,
+        - creep_saying: Option<String>,
+        - creep_ticks_to_live: i32,
+        - power_creep_id: Option<String>,
+        - power_creep_name: Option<String>,
+        - power_creep_class_name: Option<String>,
+        - power_creep_level: Option<u32>,
+        - power_creep_ticks_to_live: Option<i32>,
+        - power_creep_saying: Option<String>,
+        - death_time: u32,
+        - decay_time: u32,
+        - store: Store,
+    }
 }
 
 impl Tombstone {
@@ -42,13 +75,23 @@ impl Tombstone {
     pub fn resources(&self) -> impl Iterator<Item = (ResourceType, i32)> + '_ {
         self.store.iter()
     }
+
+    /// Whether this is the tombstone of a power creep, rather than a regular creep.
+    pub fn is_power_creep(&self) -> bool {
+        self.power_creep_id.is_some()
+    }
+
+    /// The number of game ticks until this tombstone decays, given the current game time.
+    pub fn ticks_to_decay(&self, game_time: u32) -> u32 {
+        self.decay_time.saturating_sub(game_time)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use serde::Deserialize;
 
-    use super::{Tombstone};
+    use super::Tombstone;
 
     #[test]
     fn parse_simple_tombstone() {
@@ -139,5 +182,38 @@ mod test {
         assert_eq!(obj.creep_id, "5f021a8c0a816c111ff81006");
         assert_eq!(obj.creep_name, "W31N48_transport_20200");
         assert_eq!(obj.store, store! { Energy: 1000 });
+        assert!(!obj.is_power_creep());
+        assert_eq!(obj.ticks_to_decay(38443700), 58);
+    }
+
+    #[test]
+    fn parse_power_creep_tombstone() {
+        let json = json!({
+            "_id": "5f0d2c4e8a1b2c3d4e5f6a7b",
+            "deathTime": 38460012,
+            "decayTime": 38460512,
+            "powerCreepClassName": "operator",
+            "powerCreepId": "5e8a1b2c3d4e5f6a7b8c9d0e",
+            "powerCreepLevel": 12,
+            "powerCreepName": "Operator1",
+            "powerCreepSaying": null,
+            "powerCreepTicksToLive": 1204,
+            "room": "W41N48",
+            "store": {
+                "ops": 200
+            },
+            "type": "tombstone",
+            "user": "57874d42d0ae911e3bd15bbc",
+            "x": 30,
+            "y": 14
+        });
+
+        let obj = Tombstone::deserialize(json).unwrap();
+
+        assert!(obj.is_power_creep());
+        assert!(obj.creep_body.is_empty());
+        assert_eq!(obj.power_creep_name.as_deref(), Some("Operator1"));
+        assert_eq!(obj.power_creep_level, Some(12));
+        assert_eq!(obj.store, store! { Ops: 200 });
     }
 }
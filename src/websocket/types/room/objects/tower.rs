@@ -40,7 +40,7 @@ with_structure_fields_and_update_struct! {
 
 with_update_struct! {
     /// A struct describing a tower's actions.
-    #[derive(serde_derive::Deserialize, Clone, Debug, PartialEq)]
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Clone, Debug, PartialEq)]
     #[serde(rename_all = "camelCase")]
     pub struct StructureTowerActions {
         /// Where this tower attacked last tick.
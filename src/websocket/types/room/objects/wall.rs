@@ -6,7 +6,7 @@ use crate::{
 
 with_update_struct! {
     /// Describes the decay of a decaying wall.
-    #[derive(serde_derive::Deserialize, Clone, Debug, PartialEq)]
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize, Clone, Debug, PartialEq)]
     #[serde(rename_all = "camelCase")]
     pub struct WallDecayTime {
         /// Unix timestamp of when this wall will decay.
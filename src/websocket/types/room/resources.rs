@@ -3,220 +3,170 @@ use std::{cmp, collections::HashMap, fmt};
 
 use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
 
+pub use crate::data::ResourceType;
 use crate::websocket::room_object_macros::Updatable;
 
-/// All possible resource identifiers in the game.
-#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum ResourceType {
-    /// RESOURCE_ENERGY: "energy",
-    #[serde(rename = "energy")]
-    Energy,
-    /// RESOURCE_POWER: "power",
-    #[serde(rename = "power")]
-    Power,
-    /// RESOURCE_HYDROGEN: "H",
-    #[serde(rename = "H")]
-    Hydrogen,
-    /// RESOURCE_OXYGEN: "O",
-    #[serde(rename = "O")]
-    Oxygen,
-    /// RESOURCE_UTRIUM: "U",
-    #[serde(rename = "U")]
-    Utrium,
-    /// RESOURCE_LEMERGIUM: "L",
-    #[serde(rename = "L")]
-    Lemergium,
-    /// RESOURCE_KEANIUM: "K",
-    #[serde(rename = "K")]
-    Keanium,
-    /// RESOURCE_ZYNTHIUM: "Z",
-    #[serde(rename = "Z")]
-    Zynthium,
-    /// RESOURCE_CATALYST: "X",
-    #[serde(rename = "X")]
-    Catalyst,
-    /// RESOURCE_GHODIUM: "G",
-    #[serde(rename = "G")]
-    Ghodium,
-    /// RESOURCE_HYDROXIDE: "OH",
-    #[serde(rename = "OH")]
-    Hydroxide,
-    /// RESOURCE_ZYNTHIUM_KEANITE: "ZK",
-    #[serde(rename = "ZK")]
-    ZynthiumKeanite,
-    /// RESOURCE_UTRIUM_LEMERGITE: "UL",
-    #[serde(rename = "UL")]
-    UtriumLemergite,
-    /// RESOURCE_UTRIUM_HYDRIDE: "UH",
-    #[serde(rename = "UH")]
-    UtriumHydride,
-    /// RESOURCE_UTRIUM_OXIDE: "UO",
-    #[serde(rename = "UO")]
-    UtriumOxide,
-    /// RESOURCE_KEANIUM_HYDRIDE: "KH",
-    #[serde(rename = "KH")]
-    KeaniumHydride,
-    /// RESOURCE_KEANIUM_OXIDE: "KO",
-    #[serde(rename = "KO")]
-    KeaniumOxide,
-    /// RESOURCE_LEMERGIUM_HYDRIDE: "LH",
-    #[serde(rename = "LH")]
-    LemergiumHydride,
-    /// RESOURCE_LEMERGIUM_OXIDE: "LO",
-    #[serde(rename = "LO")]
-    LemergiumOxide,
-    /// RESOURCE_ZYNTHIUM_HYDRIDE: "ZH",
-    #[serde(rename = "ZH")]
-    ZynthiumHydride,
-    /// RESOURCE_ZYNTHIUM_OXIDE: "ZO",
-    #[serde(rename = "ZO")]
-    ZynthiumOxide,
-    /// RESOURCE_GHODIUM_HYDRIDE: "GH",
-    #[serde(rename = "GH")]
-    GhodiumHydride,
-    /// RESOURCE_GHODIUM_OXIDE: "GO",
-    #[serde(rename = "GO")]
-    GhodiumOxide,
-    /// RESOURCE_UTRIUM_ACID: "UH2O",
-    #[serde(rename = "UH2O")]
-    UtriumAcid,
-    /// RESOURCE_UTRIUM_ALKALIDE: "UHO2",
-    #[serde(rename = "UHO2")]
-    UtriumAlkalide,
-    /// RESOURCE_KEANIUM_ACID: "KH2O",
-    #[serde(rename = "KH2O")]
-    KeaniumAcid,
-    /// RESOURCE_KEANIUM_ALKALIDE: "KHO2",
-    #[serde(rename = "KHO2")]
-    KeaniumAlkalide,
-    /// RESOURCE_LEMERGIUM_ACID: "LH2O",
-    #[serde(rename = "LH2O")]
-    LemergiumAcid,
-    /// RESOURCE_LEMERGIUM_ALKALIDE: "LHO2",
-    #[serde(rename = "LHO2")]
-    LemergiumAlkalide,
-    /// RESOURCE_ZYNTHIUM_ACID: "ZH2O",
-    #[serde(rename = "ZH2O")]
-    ZynthiumAcid,
-    /// RESOURCE_ZYNTHIUM_ALKALIDE: "ZHO2",
-    #[serde(rename = "ZHO2")]
-    ZynthiumAlkalide,
-    /// RESOURCE_GHODIUM_ACID: "GH2O",
-    #[serde(rename = "GH2O")]
-    GhodiumAcid,
-    /// RESOURCE_GHODIUM_ALKALIDE: "GHO2",
-    #[serde(rename = "GHO2")]
-    GhodiumAlkalide,
-    /// RESOURCE_CATALYZED_UTRIUM_ACID: "XUH2O",
-    #[serde(rename = "XUH2O")]
-    CatalyzedUtriumAcid,
-    /// RESOURCE_CATALYZED_UTRIUM_ALKALIDE: "XUHO2",
-    #[serde(rename = "XUHO2")]
-    CatalyzedUtriumAlkalide,
-    /// RESOURCE_CATALYZED_KEANIUM_ACID: "XKH2O",
-    #[serde(rename = "XKH2O")]
-    CatalyzedKeaniumAcid,
-    /// RESOURCE_CATALYZED_KEANIUM_ALKALIDE: "XKHO2",
-    #[serde(rename = "XKHO2")]
-    CatalyzedKeaniumAlkalide,
-    /// RESOURCE_CATALYZED_LEMERGIUM_ACID: "XLH2O",
-    #[serde(rename = "XLH2O")]
-    CatalyzedLemergiumAcid,
-    /// RESOURCE_CATALYZED_LEMERGIUM_ALKALIDE: "XLHO2",
-    #[serde(rename = "XLHO2")]
-    CatalyzedLemergiumAlkalide,
-    /// RESOURCE_CATALYZED_ZYNTHIUM_ACID: "XZH2O",
-    #[serde(rename = "XZH2O")]
-    CatalyzedZynthiumAcid,
-    /// RESOURCE_CATALYZED_ZYNTHIUM_ALKALIDE: "XZHO2",
-    #[serde(rename = "XZHO2")]
-    CatalyzedZynthiumAlkalide,
-    /// RESOURCE_CATALYZED_GHODIUM_ACID: "XGH2O",
-    #[serde(rename = "XGH2O")]
-    CatalyzedGhodiumAcid,
-    /// RESOURCE_CATALYZED_GHODIUM_ALKALIDE: "XGHO2",
-    #[serde(rename = "XGHO2")]
-    CatalyzedGhodiumAlkalide,
-}
-
 basic_updatable!(ResourceType);
 
-impl ResourceType {
-    // created by replacing:
-    // `s#/// [A-Z_]+: "(\w+)",\n            (\w+),#ResourceType::$2 => "$1",#g`
-    // (original is the definition for the enum)
+/// The resources and amounts of each resource some game object holds, and how much it can hold.
+///
+/// Deserializing a store from an object's `store` field gives only its contents. Stores of objects parsed as a
+/// [`RoomObject`] also know their capacity, from the object's `storeCapacity` and `storeCapacityResource` fields. To
+/// parse a store along with its capacity from any object, use [`Store::from_object`], which understands both the
+/// current format and the older format the server still sends for some objects, with a field for each resource,
+/// such as `energy` and `energyCapacity`.
+///
+/// [`RoomObject`]: ../objects/enum.RoomObject.html
+/// [`Store::from_object`]: #method.from_object
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Store {
+    contents: HashMap<ResourceType, i32>,
+    #[serde(skip)]
+    capacity: Option<i32>,
+    #[serde(skip)]
+    resource_capacity: HashMap<ResourceType, i32>,
+}
 
-    /// Finds the in-game resource type string for this resource type.
-    ///
-    /// Example:
+/// Fields holding the capacity for a single resource, in the older format.
+const LEGACY_CAPACITIES: &[(&str, ResourceType)] = &[
+    ("energyCapacity", ResourceType::Energy),
+    ("powerCapacity", ResourceType::Power),
+    ("ghodiumCapacity", ResourceType::Ghodium),
+];
+
+impl Store {
+    /// Parses the store of a room object from the object's JSON, in either format.
     ///
     /// ```
-    /// # use screeps_api::websocket::resources::ResourceType;
-    /// assert_eq!(ResourceType::Utrium.to_resource_string(), "U")
+    /// use screeps_api::websocket::resources::{ResourceType, Store};
+    ///
+    /// let modern = Store::from_object(&serde_json::json!({
+    ///     "type": "extension",
+    ///     "store": { "energy": 20 },
+    ///     "storeCapacityResource": { "energy": 50 },
+    /// }))
+    /// .unwrap();
+    /// let legacy = Store::from_object(&serde_json::json!({
+    ///     "type": "extension",
+    ///     "energy": 20,
+    ///     "energyCapacity": 50,
+    /// }))
+    /// .unwrap();
+    ///
+    /// assert_eq!(modern, legacy);
+    /// assert_eq!(modern.free_capacity(&ResourceType::Energy), Some(30));
     /// ```
-    pub fn to_resource_string(&self) -> &'static str {
-        match *self {
-            ResourceType::Energy => "energy",
-            ResourceType::Power => "power",
-            ResourceType::Hydrogen => "H",
-            ResourceType::Oxygen => "O",
-            ResourceType::Utrium => "U",
-            ResourceType::Lemergium => "L",
-            ResourceType::Keanium => "K",
-            ResourceType::Zynthium => "Z",
-            ResourceType::Catalyst => "X",
-            ResourceType::Ghodium => "G",
-            ResourceType::Hydroxide => "OH",
-            ResourceType::ZynthiumKeanite => "ZK",
-            ResourceType::UtriumLemergite => "UL",
-            ResourceType::UtriumHydride => "UH",
-            ResourceType::UtriumOxide => "UO",
-            ResourceType::KeaniumHydride => "KH",
-            ResourceType::KeaniumOxide => "KO",
-            ResourceType::LemergiumHydride => "LH",
-            ResourceType::LemergiumOxide => "LO",
-            ResourceType::ZynthiumHydride => "ZH",
-            ResourceType::ZynthiumOxide => "ZO",
-            ResourceType::GhodiumHydride => "GH",
-            ResourceType::GhodiumOxide => "GO",
-            ResourceType::UtriumAcid => "UH2O",
-            ResourceType::UtriumAlkalide => "UHO2",
-            ResourceType::KeaniumAcid => "KH2O",
-            ResourceType::KeaniumAlkalide => "KHO2",
-            ResourceType::LemergiumAcid => "LH2O",
-            ResourceType::LemergiumAlkalide => "LHO2",
-            ResourceType::ZynthiumAcid => "ZH2O",
-            ResourceType::ZynthiumAlkalide => "ZHO2",
-            ResourceType::GhodiumAcid => "GH2O",
-            ResourceType::GhodiumAlkalide => "GHO2",
-            ResourceType::CatalyzedUtriumAcid => "XUH2O",
-            ResourceType::CatalyzedUtriumAlkalide => "XUHO2",
-            ResourceType::CatalyzedKeaniumAcid => "XKH2O",
-            ResourceType::CatalyzedKeaniumAlkalide => "XKHO2",
-            ResourceType::CatalyzedLemergiumAcid => "XLH2O",
-            ResourceType::CatalyzedLemergiumAlkalide => "XLHO2",
-            ResourceType::CatalyzedZynthiumAcid => "XZH2O",
-            ResourceType::CatalyzedZynthiumAlkalide => "XZHO2",
-            ResourceType::CatalyzedGhodiumAcid => "XGH2O",
-            ResourceType::CatalyzedGhodiumAlkalide => "XGHO2",
-        }
+    pub fn from_object(object: &serde_json::Value) -> Result<Store, serde_json::Error> {
+        let fields = match object.as_object() {
+            Some(fields) => fields,
+            None => return Ok(Store::default()),
+        };
+        let int = |value: &serde_json::Value| value.as_i64().map(|v| v as i32);
+
+        let mut store = match fields.get("store") {
+            Some(contents) => Store::deserialize(contents)?,
+            None => {
+                // the older format has a field for each resource, except for lab minerals.
+                let mut store = Store::default();
+                for (key, value) in fields {
+                    if let (Some(resource), Some(amount)) = (ResourceType::known(key), int(value)) {
+                        store.insert(resource, amount);
+                    }
+                }
+                let mineral_type = fields
+                    .get("mineralType")
+                    .and_then(serde_json::Value::as_str)
+                    .map(ResourceType::from_resource_string);
+                if let Some(ref mineral_type) = mineral_type {
+                    if let Some(amount) = fields.get("mineralAmount").and_then(int) {
+                        store.insert(mineral_type.clone(), amount);
+                    }
+                    if let Some(capacity) = fields.get("mineralCapacity").and_then(int) {
+                        store
+                            .resource_capacity
+                            .insert(mineral_type.clone(), capacity);
+                    }
+                }
+                for (field, resource) in LEGACY_CAPACITIES {
+                    if let Some(capacity) = fields.get(*field).and_then(int) {
+                        store.resource_capacity.insert(resource.clone(), capacity);
+                    }
+                }
+                store
+            }
+        };
+
+        store.read_capacity(fields)?;
+
+        Ok(store)
     }
-}
 
-/// The resources and amounts of each resource some game object holds.
-#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
-#[serde(transparent)]
-pub struct Store(pub HashMap<ResourceType, i32>);
+    /// Reads this store's capacity from the `storeCapacity` and `storeCapacityResource` fields of its object, or of
+    /// an update to its object. Capacities which aren't sent are kept, and ones sent as `null` are removed.
+    pub(crate) fn read_capacity(
+        &mut self,
+        fields: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(), serde_json::Error> {
+        if let Some(capacity) = fields.get("storeCapacity") {
+            self.capacity = capacity.as_i64().map(|v| v as i32);
+        }
+        if let Some(capacities) = fields.get("storeCapacityResource") {
+            for (resource, capacity) in
+                HashMap::<ResourceType, Option<i32>>::deserialize(capacities)?
+            {
+                match capacity {
+                    Some(capacity) => self.resource_capacity.insert(resource, capacity),
+                    None => self.resource_capacity.remove(&resource),
+                };
+            }
+        }
+
+        Ok(())
+    }
 
-impl Store {
     /// Iterate over the contents of this store.
     pub fn iter(&self) -> impl Iterator<Item = (ResourceType, i32)> + '_ {
-        self.0.iter().map(|(k, v)| (*k, *v))
+        self.contents.iter().map(|(k, v)| (k.clone(), *v))
     }
 
     /// Get the amount of a specific resource in this store.
     pub fn get(&self, resource: ResourceType) -> i32 {
-        self.0.get(&resource).copied().unwrap_or(0)
+        self.contents.get(&resource).copied().unwrap_or(0)
+    }
+
+    /// Sets the amount of a resource in this store, removing it if the amount is 0.
+    pub fn insert(&mut self, resource: ResourceType, amount: i32) {
+        if amount == 0 {
+            self.contents.remove(&resource);
+        } else {
+            self.contents.insert(resource, amount);
+        }
+    }
+
+    /// The total amount of all resources in this store.
+    pub fn used_capacity(&self) -> i32 {
+        self.contents.values().sum()
+    }
+
+    /// How much of the given resource this store can hold in total, or `None` if this isn't known or the store can't
+    /// hold it.
+    pub fn capacity(&self, resource: &ResourceType) -> Option<i32> {
+        self.resource_capacity
+            .get(resource)
+            .copied()
+            .or(self.capacity)
+    }
+
+    /// How much more of the given resource this store can hold, or `None` if this isn't known or the store can't
+    /// hold it.
+    pub fn free_capacity(&self, resource: &ResourceType) -> Option<i32> {
+        match self.resource_capacity.get(resource) {
+            Some(&capacity) => Some(capacity - self.get(resource.clone())),
+            None => self
+                .capacity
+                .map(|capacity| capacity - self.used_capacity()),
+        }
     }
 }
 
@@ -245,7 +195,10 @@ impl<'de> Visitor<'de> for StoreVisitor {
             }
         }
 
-        Ok(Store(values))
+        Ok(Store {
+            contents: values,
+            ..Store::default()
+        })
     }
 }
 
@@ -301,21 +254,20 @@ impl<'de> Deserialize<'de> for StoreUpdate {
 }
 
 impl Updatable for Store {
-    type Update = Store;
+    type Update = StoreUpdate;
     fn apply_update(&mut self, update: Self::Update) {
         for (key, value) in update.0 {
-            if value == 0 {
-                self.0.remove(&key);
-            } else {
-                self.0.insert(key, value);
-            }
+            self.insert(key, value);
         }
     }
 
     fn create_from_update(update: Self::Update) -> Option<Self> {
         let mut values = update.0;
         values.retain(|_k, v| *v != 0);
-        Some(Store(values))
+        Some(Store {
+            contents: values,
+            ..Store::default()
+        })
     }
 }
 
@@ -327,7 +279,7 @@ macro_rules! store {
             let mut store = crate::websocket::types::room::resources::Store::default();
 
             $(
-                store.0.insert(crate::websocket::types::room::resources::ResourceType::$name, $val);
+                store.insert(crate::websocket::types::room::resources::ResourceType::$name, $val);
             )*
 
             store
@@ -348,3 +300,47 @@ macro_rules! store {
 //     last_line = line
 // print(last_line.strip() + '|None')
 // ```
+
+#[cfg(test)]
+mod tests {
+    use super::{ResourceType, Store, StoreUpdate};
+    use crate::websocket::room_object_macros::Updatable;
+
+    #[test]
+    fn parse_stores_in_both_formats() {
+        let storage = Store::from_object(&json!({
+            "type": "storage",
+            "store": { "energy": 1000, "H": 500, "XGHO2": null },
+            "storeCapacity": 1000000,
+        }))
+        .unwrap();
+        assert_eq!(storage.get(ResourceType::Hydrogen), 500);
+        assert_eq!(storage.used_capacity(), 1500);
+        assert_eq!(storage.free_capacity(&ResourceType::Oxygen), Some(998500));
+
+        let lab = Store::from_object(&json!({
+            "type": "lab",
+            "energy": 1500,
+            "energyCapacity": 2000,
+            "mineralType": "UH",
+            "mineralAmount": 600,
+            "mineralCapacity": 3000,
+        }))
+        .unwrap();
+        assert_eq!(lab.get(ResourceType::UtriumHydride), 600);
+        assert_eq!(lab.free_capacity(&ResourceType::Energy), Some(500));
+        assert_eq!(lab.capacity(&ResourceType::UtriumHydride), Some(3000));
+        assert_eq!(lab.capacity(&ResourceType::Oxygen), None);
+
+        let mut store = storage;
+        let update: StoreUpdate = serde_json::from_value(json!({ "energy": 0, "O": 10 })).unwrap();
+        store.apply_update(update);
+        assert_eq!(store.get(ResourceType::Energy), 0);
+        assert_eq!(
+            store.iter().collect::<std::collections::HashMap<_, _>>(),
+            vec![(ResourceType::Hydrogen, 500), (ResourceType::Oxygen, 10)]
+                .into_iter()
+                .collect()
+        );
+    }
+}
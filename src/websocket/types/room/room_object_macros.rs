@@ -1,4 +1,9 @@
 //! Module containing macros which simplify making "updateable" structures.
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    hash::Hash,
+};
+
 use time::Timespec;
 
 use crate::data::{Badge, RoomName};
@@ -271,6 +276,41 @@ where
     }
 }
 
+impl<K, V> Updatable for HashMap<K, V>
+where
+    K: Eq + Hash,
+    V: Updatable,
+{
+    /// Updates for some entries in the map, with `None` (null) removing the entry.
+    type Update = HashMap<K, Option<V::Update>>;
+
+    fn apply_update(&mut self, update: Self::Update) {
+        for (key, value_update) in update {
+            match value_update {
+                Some(value_update) => match self.entry(key) {
+                    Entry::Occupied(mut entry) => entry.get_mut().apply_update(value_update),
+                    Entry::Vacant(entry) => {
+                        if let Some(value) = V::create_from_update(value_update) {
+                            entry.insert(value);
+                        }
+                    }
+                },
+                None => {
+                    self.remove(&key);
+                }
+            }
+        }
+    }
+
+    fn create_from_update(update: Self::Update) -> Option<Self> {
+        update
+            .into_iter()
+            .filter_map(|(key, value_update)| value_update.map(|v| (key, v)))
+            .map(|(key, value_update)| V::create_from_update(value_update).map(|v| (key, v)))
+            .collect()
+    }
+}
+
 /// Mostly an implementation detail of `with_update_struct`, but can be used independently to
 /// implement Updatable on external structures.
 ///
@@ -579,7 +619,7 @@ macro_rules! with_update_struct {
 }
 
 /// This macro creates the struct described within the invocation, but with an additional 4 fields common to all
-/// room objects, and with `#[derive(serde_derive::Deserialize)]`. The structure definition is then passed on to `with_update_struct`.
+/// room objects, and with `#[derive(serde_derive::Deserialize, serde_derive::Serialize)]`. The structure definition is then passed on to `with_update_struct`.
 macro_rules! with_base_fields_and_update_struct {
     (
         $( #[$struct_attr:meta] )*
@@ -633,7 +673,7 @@ macro_rules! with_base_fields_and_update_struct {
     ) => (
         with_update_struct! {
             $( #[$struct_attr] )*
-            #[derive(serde_derive::Deserialize)]
+            #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
             pub struct $name {
                 /// Unique 'id' identifier for all game objects on a server.
                 #[serde(rename = "_id")]
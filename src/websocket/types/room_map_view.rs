@@ -34,6 +34,8 @@ pub struct RoomMapViewUpdate {
     /// This is a Vec of (user_id, owned_objects_of_that_user). The game does not provide
     /// more information on what type of object (creep or building, or type of building).
     pub users_objects: Vec<(String, Vec<(u32, u32)>)>,
+    /// The game time when this update was created, if the server sent it.
+    pub game_time: Option<u32>,
     /// Phantom data in order to allow adding any additional fields in the future.
     _non_exhaustive: (),
 }
@@ -163,11 +165,18 @@ impl<'de> Visitor<'de> for RoomMapViewUpdateVisitor {
         let mut minerals = None;
         let mut controllers = None;
         let mut keeper_lairs = None;
+        let mut game_time = None;
         // there are 8 expected keys, any extra are user ids
         let mut users_objects =
             Vec::with_capacity(cmp::max(cmp::min(access.size_hint().unwrap_or(0), 4069), 8) - 8);
 
-        while let Some((key, value)) = access.next_entry_seed(PhantomData, StrOrU32TupleVecSeed)? {
+        while let Some(key) = access.next_key::<&str>()? {
+            if key == "gameTime" {
+                game_time = access.next_value()?;
+                continue;
+            }
+
+            let value = access.next_value_seed(StrOrU32TupleVecSeed)?;
             match key {
                 "w" => walls = Some(value),
                 "r" => roads = Some(value),
@@ -190,7 +199,8 @@ impl<'de> Visitor<'de> for RoomMapViewUpdateVisitor {
             minerals: minerals.unwrap_or_default(),
             controllers: controllers.unwrap_or_default(),
             keeper_lairs: keeper_lairs.unwrap_or_default(),
-            users_objects: users_objects,
+            users_objects,
+            game_time,
             _non_exhaustive: (),
         };
 
@@ -239,4 +249,19 @@ mod test {
         }"#,
         ).expect("expected edge case parsing to succeed");
     }
+
+    #[test]
+    fn game_time() {
+        let update: RoomMapViewUpdate = serde_json::from_str(
+            r#"{"w":[[31,18]],"r":[],"pb":[],"p":[],"s":[[26,38]],"c":[[30,15]],"m":[[15,7]],"k":[],
+            "gameTime":23469491,"54d8d6bf9facf3600349ba3d":[[28,26]]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(update.game_time, Some(23469491));
+        assert_eq!(
+            update.users_objects,
+            vec![("54d8d6bf9facf3600349ba3d".to_owned(), vec![(28, 26)])]
+        );
+    }
 }
@@ -18,14 +18,14 @@ fn logged_in() -> SyncApi {
 
 #[test]
 fn test_auth_my_info() {
-    let mut api = logged_in();
+    let api = logged_in();
 
     let _ = api.my_info().unwrap();
 }
 
 #[test]
 fn test_auth_map_stats() {
-    let mut api = logged_in();
+    let api = logged_in();
 
     let result = api
         .map_stats(
@@ -46,7 +46,7 @@ fn test_auth_map_stats() {
 
 #[test]
 fn test_auth_world_start() {
-    let mut api = logged_in();
+    let api = logged_in();
 
     let start = api.world_start_room().unwrap();
 
@@ -59,7 +59,7 @@ fn test_auth_world_start() {
 
 #[test]
 fn test_auth_token_reretrieval() {
-    let mut api = logged_in();
+    let api = logged_in();
 
     api.my_info().unwrap();
 
@@ -70,7 +70,7 @@ fn test_auth_token_reretrieval() {
 
 #[test]
 fn test_auth_room_overview() {
-    let mut api = logged_in();
+    let api = logged_in();
 
     for &interval in &[8u32, 180u32, 1440u32] {
         // At the time of writing, a room owned by a user who does not have a custom badge.
@@ -86,14 +86,14 @@ fn test_auth_room_overview() {
 
 #[test]
 fn test_auth_leaderboard_seasons() {
-    let mut api = logged_in();
+    let api = logged_in();
 
     api.leaderboard_season_list().unwrap();
 }
 
 #[test]
 fn test_auth_retrieve_single_rank() {
-    let mut api = logged_in();
+    let api = logged_in();
 
     api.find_season_leaderboard_rank(
         screeps_api::LeaderboardType::GlobalControl,
@@ -129,7 +129,7 @@ fn test_auth_retrieve_single_rank() {
 
 #[test]
 fn test_auth_retrieve_all_ranks() {
-    let mut api = logged_in();
+    let api = logged_in();
 
     let result = api
         .find_leaderboard_ranks(screeps_api::LeaderboardType::GlobalControl, "daboross")
@@ -139,7 +139,7 @@ fn test_auth_retrieve_all_ranks() {
 
 #[test]
 fn test_auth_retrieve_leaderboard() {
-    let mut api = logged_in();
+    let api = logged_in();
 
     let result = api
         .leaderboard_page(
@@ -168,7 +168,7 @@ fn test_auth_retrieve_leaderboard() {
 /// This is to ensure that the documentation stays up to date if this ever changes.
 #[test]
 fn test_auth_leaderboard_limit_parameter_error() {
-    let mut api = logged_in();
+    let api = logged_in();
 
     match api.leaderboard_page(
         screeps_api::LeaderboardType::GlobalControl,
@@ -197,7 +197,7 @@ fn test_auth_leaderboard_limit_parameter_error() {
 #[test]
 #[cfg(feature = "destructive-tests")]
 fn test_memory_segment() {
-    let mut api = logged_in();
+    let api = logged_in();
 
     let orig = api.memory_segment(Some("shard0"), 1).unwrap();
 
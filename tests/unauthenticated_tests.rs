@@ -4,7 +4,7 @@ use screeps_api::SyncApi;
 
 #[test]
 fn test_room_terrain() {
-    let mut api = SyncApi::new().unwrap();
+    let api = SyncApi::new().unwrap();
 
     api.room_terrain(Some("shard0"), "W0N0").unwrap();
     api.room_terrain(Some("shard0"), "W3N9").unwrap();
@@ -12,7 +12,7 @@ fn test_room_terrain() {
 
 #[test]
 fn test_shard_list() {
-    let mut api = SyncApi::new().unwrap();
+    let api = SyncApi::new().unwrap();
 
     let list = api.shard_list().unwrap();
     assert!(list.len() >= 2);
@@ -20,7 +20,7 @@ fn test_shard_list() {
 
 #[test]
 fn test_room_terrain_invalid_room() {
-    let mut api = SyncApi::new().unwrap();
+    let api = SyncApi::new().unwrap();
 
     match api.room_terrain(Some("shard0"), "asdffdsa") {
         Err(err) => match *err.kind() {
@@ -33,7 +33,7 @@ fn test_room_terrain_invalid_room() {
 
 #[test]
 fn test_room_terrain_invalid_shard() {
-    let mut api = SyncApi::new().unwrap();
+    let api = SyncApi::new().unwrap();
 
     match api.room_terrain(Some("sharasdfd0"), "asdffdsa") {
         Err(err) => match *err.kind() {
@@ -46,7 +46,7 @@ fn test_room_terrain_invalid_shard() {
 
 #[test]
 fn test_recent_pvp() {
-    let mut api = SyncApi::new().unwrap();
+    let api = SyncApi::new().unwrap();
 
     let pvp_results_a = api
         .recent_pvp(screeps_api::RecentPvpArgs::within(15))